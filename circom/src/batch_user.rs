@@ -0,0 +1,92 @@
+use super::input_user::Input;
+use ansi_term::Colour;
+use program_structure::program_archive::ProgramArchive;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+
+// A main component of a batch compilation and the directory of its outputs.
+// The batch file is a json array of entries of the form
+// {"main": "Multiplier(3)", "public": ["a"], "output": "build/multiplier3"}
+#[derive(Clone)]
+pub struct BatchEntry {
+    pub main: String,
+    pub public: Option<Vec<String>>,
+    pub output: PathBuf,
+}
+
+fn error(message: String) {
+    eprintln!("{}", Colour::Red.paint(message));
+}
+
+pub fn read_batch(file: &Path) -> Result<Vec<BatchEntry>, ()> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|_err| error(format!("could not read the batch file {}", file.display())))?;
+    let parsed = json::parse(&contents)
+        .map_err(|err| error(format!("invalid batch file {}: {}", file.display(), err)))?;
+    if !parsed.is_array() || parsed.is_empty() {
+        error(format!("the batch file {} must be a non empty array", file.display()));
+        return Result::Err(());
+    }
+    let mut entries = Vec::new();
+    for (index, entry) in parsed.members().enumerate() {
+        let (main, output) = match (entry["main"].as_str(), entry["output"].as_str()) {
+            (Some(main), Some(output)) => (main.to_string(), PathBuf::from(output)),
+            _ => {
+                error(format!("entry {} of the batch file needs a main and an output", index));
+                return Result::Err(());
+            }
+        };
+        let public = if entry["public"].is_null() {
+            None
+        } else if entry["public"].members().all(|signal| signal.is_string()) {
+            Some(entry["public"].members().map(|signal| signal.to_string()).collect())
+        } else {
+            error(format!("the public signals of entry {} of the batch file must be a list of names", index));
+            return Result::Err(());
+        };
+        entries.push(BatchEntry { main, public, output });
+    }
+    Result::Ok(entries)
+}
+
+// Builds every main component of the batch from the same parsed and checked
+// program. A failed build does not stop the following ones
+pub fn build_batch(user_input: &Input, program_archive: ProgramArchive) -> Result<(), ()> {
+    let mut results = Vec::new();
+    for (index, entry) in user_input.batch().iter().enumerate() {
+        println!("{} {}", Colour::Green.paint("Building"), entry.main);
+        let result = if std::fs::create_dir_all(&entry.output).is_err() {
+            error(format!("could not create the output directory {}", entry.output.display()));
+            Result::Err(())
+        } else {
+            let mut program = program_archive.clone();
+            program.select_main_component(index);
+            let entry_input = user_input.with_output_path(&entry.output);
+            // A panic of the compiler in one entry is a failed build too
+            std::panic::catch_unwind(AssertUnwindSafe(|| crate::build(&entry_input, program)))
+                .unwrap_or_else(|_panic| {
+                    error(format!("the compiler panicked while building {}", entry.main));
+                    Result::Err(())
+                })
+        };
+        results.push(result);
+    }
+
+    println!("{}", Colour::Green.paint("Batch summary:"));
+    for (entry, result) in user_input.batch().iter().zip(&results) {
+        match result {
+            Result::Ok(constraints) => println!(
+                "    {}: {} constraints, written in {}",
+                entry.main,
+                constraints,
+                entry.output.display()
+            ),
+            Result::Err(()) => println!("    {}: {}", entry.main, Colour::Red.paint("failed")),
+        }
+    }
+    if results.iter().all(|result| result.is_ok()) {
+        Result::Ok(())
+    } else {
+        Result::Err(())
+    }
+}
@@ -17,6 +17,12 @@ pub struct CompilerConfig {
     pub wat_flag: bool,
     pub wasm_flag: bool,
     pub c_flag: bool,
+    pub trace_flag: bool,
+    pub portable_flag: bool,
+    pub lib_flag: bool,
+    pub embed_dat_flag: bool,
+    pub c_profile_flag: bool,
+    pub source_map_flag: bool,
     pub debug_output: bool,
     pub produce_input_log: bool,
     pub vcp: VCP,
@@ -25,113 +31,71 @@ pub struct CompilerConfig {
 pub fn compile(config: CompilerConfig) -> Result<(), ()> {
     let circuit = compiler_interface::run_compiler(
         config.vcp,
-        Config { debug_output: config.debug_output, produce_input_log: config.produce_input_log, wat_flag: config.wat_flag },
+        Config {
+            debug_output: config.debug_output,
+            produce_input_log: config.produce_input_log,
+            wat_flag: config.wat_flag,
+            trace_flag: config.trace_flag,
+            portable_flag: config.portable_flag,
+            lib_flag: config.lib_flag,
+            embed_dat_flag: config.embed_dat_flag,
+            c_profile_flag: config.c_profile_flag,
+            source_map_flag: config.source_map_flag,
+        },
     )?;
 
     if config.c_flag {
         compiler_interface::write_c(&circuit, &config.c_folder, &config.c_run_name, &config.c_file, &config.dat_file)?;
+        if config.embed_dat_flag {
+            println!("{} {}", Colour::Green.paint("Written successfully:"), config.c_file);
+        } else {
+            println!(
+                "{} {} and {}",
+                Colour::Green.paint("Written successfully:"),
+                config.c_file,
+                config.dat_file
+            );
+        }
+        let mut c_files = vec!["main.cpp", "circom.hpp", "calcwit.hpp", "calcwit.cpp", "fr.hpp", "fr.cpp"];
+        if circuit.c_producer.needs_fr_asm() {
+            c_files.push("fr.asm");
+        }
+        if config.lib_flag {
+            c_files.push("circom_witness.h");
+            c_files.push("circom_witness.cpp");
+        }
         println!(
-            "{} {} and {}",
-            Colour::Green.paint("Written successfully:"),
-            config.c_file,
-            config.dat_file
-        );
-        println!(
-            "{} {}/{}, {}, {}, {}, {}, {}, {} and {}",
+            "{} {}/{} and {}",
             Colour::Green.paint("Written successfully:"),
-	    &config.c_folder,
-            "main.cpp".to_string(),
-            "circom.hpp".to_string(),
-            "calcwit.hpp".to_string(),
-            "calcwit.cpp".to_string(),
-            "fr.hpp".to_string(),
-            "fr.cpp".to_string(),
-            "fr.asm".to_string(),
+            &config.c_folder,
+            c_files.join(", "),
             "Makefile".to_string()
         );
     }
 
-    match (config.wat_flag, config.wasm_flag) {
-        (true, true) => {
-            compiler_interface::write_wasm(&circuit, &config.js_folder, &config.wasm_name, &config.wat_file)?;
+    if config.wat_flag || config.wasm_flag {
+        let wasm_file = if config.wasm_flag { Some(config.wasm_file.as_str()) } else { None };
+        let wat_file = if config.wat_flag { Some(config.wat_file.as_str()) } else { None };
+        let result = compiler_interface::write_wasm(&circuit, &config.js_folder, &config.wasm_name, wasm_file, wat_file);
+        if let Result::Err(error) = result {
+            let report = Report::error(
+                format!("Error writing the circuit to wasm: {}", error),
+                ReportCode::ErrorWasmEncoding,
+            );
+            Report::print_reports(&[report], &FileLibrary::new());
+            return Err(());
+        }
+        if config.wat_flag {
             println!("{} {}", Colour::Green.paint("Written successfully:"), config.wat_file);
-            let result = wat_to_wasm(&config.wat_file, &config.wasm_file);
-            match result {
-                Result::Err(report) => {
-                    Report::print_reports(&[report], &FileLibrary::new());
-                    return Err(());
-                }
-                Result::Ok(()) => {
-                    println!("{} {}", Colour::Green.paint("Written successfully:"), config.wasm_file);
-                }
-            }
         }
-        (false, true) => {
-            compiler_interface::write_wasm(&circuit,  &config.js_folder, &config.wasm_name, &config.wat_file)?;
-            let result = wat_to_wasm(&config.wat_file, &config.wasm_file);
-            std::fs::remove_file(&config.wat_file).unwrap();
-            match result {
-                Result::Err(report) => {
-                    Report::print_reports(&[report], &FileLibrary::new());
-                    return Err(());
-                }
-                Result::Ok(()) => {
-                    println!("{} {}", Colour::Green.paint("Written successfully:"), config.wasm_file);
-                }
+        if config.wasm_flag {
+            println!("{} {}", Colour::Green.paint("Written successfully:"), config.wasm_file);
+            if config.source_map_flag {
+                println!("{} {}.map", Colour::Green.paint("Written successfully:"), config.wasm_file);
             }
         }
-        (true, false) => {
-            compiler_interface::write_wasm(&circuit,  &config.js_folder, &config.wasm_name, &config.wat_file)?;
-            println!("{} {}", Colour::Green.paint("Written successfully:"), config.wat_file);
-        }
-        (false, false) => {}
     }
 
     Ok(())
 }
 
-
-fn wat_to_wasm(wat_file: &str, wasm_file: &str) -> Result<(), Report> {
-    use std::fs::read_to_string;
-    use std::fs::File;
-    use std::io::BufWriter;
-    use std::io::Write;
-    use wast::Wat;
-    use wast::parser::{self, ParseBuffer};
-
-    let wat_contents = read_to_string(wat_file).unwrap();
-    let buf = ParseBuffer::new(&wat_contents).unwrap();
-    let result_wasm_contents = parser::parse::<Wat>(&buf);
-    match result_wasm_contents {
-        Result::Err(error) => {
-            Result::Err(Report::error(
-                format!("Error translating the circuit from wat to wasm.\n\nException encountered when parsing WAT: {}", error),
-                ReportCode::ErrorWat2Wasm,
-            ))
-        }
-        Result::Ok(mut wat) => {
-            let wasm_contents = wat.module.encode();
-            match wasm_contents {
-                Result::Err(error) => {
-                    Result::Err(Report::error(
-                        format!("Error translating the circuit from wat to wasm.\n\nException encountered when encoding WASM: {}", error),
-                        ReportCode::ErrorWat2Wasm,
-                    ))
-                }
-                Result::Ok(wasm_contents) => {
-                    let file = File::create(wasm_file).unwrap();
-                    let mut writer = BufWriter::new(file);
-                    writer.write_all(&wasm_contents).map_err(|_err| Report::error(
-                        format!("Error writing the circuit. Exception generated: {}", _err),
-                        ReportCode::ErrorWat2Wasm,
-                    ))?;
-                    writer.flush().map_err(|_err| Report::error(
-                        format!("Error writing the circuit. Exception generated: {}", _err),
-                        ReportCode::ErrorWat2Wasm,
-                    ))?;
-                    Ok(())
-                }
-            }
-        }
-    }
-}
@@ -0,0 +1,718 @@
+use ansi_term::Colour;
+use clap::ArgMatches;
+use constraint_writers::r1cs_reader::{self, BigInt, LinearCombination, R1CSConstraint, R1CSFile};
+use constraint_writers::sym_reader;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// circom diff compares the constraint systems of two builds. With the sym
+// files, signals are identified by name, so constraints that only changed
+// their wire numbers are identical, and the differences are attributed to the
+// component paths and template instances of the circuit. With the sym json
+// files, they are also attributed to the templates.
+
+const LISTED: usize = 5;
+
+struct Build {
+    file: R1CSFile,
+    // wire -> name of the signal in the innermost component
+    names: HashMap<usize, String>,
+    // name of the signal in the innermost component -> wire
+    wires: HashMap<String, usize>,
+    // signal name -> node id of its template instance
+    signals: BTreeMap<String, i64>,
+    // node id -> name of the template
+    templates: HashMap<i64, String>,
+}
+
+impl Build {
+    fn signal_name(&self, wire: usize) -> String {
+        match self.names.get(&wire) {
+            Some(name) => name.clone(),
+            None if wire == 0 => "one".to_string(),
+            None => format!("w{}", wire),
+        }
+    }
+
+    // Deepest component that contains every signal of the constraint
+    fn component(&self, constraint: &R1CSConstraint) -> String {
+        let mut prefix: Option<Vec<&str>> = None;
+        for (wire, _) in constraint.iter().flatten() {
+            let name = match self.names.get(wire) {
+                Some(name) => name,
+                None => continue,
+            };
+            let path: Vec<&str> = component_of(name).split('.').collect();
+            prefix = Some(match prefix {
+                None => path,
+                Some(prefix) => prefix.iter().zip(&path).take_while(|(l, r)| l == r).map(|(l, _)| *l).collect(),
+            });
+        }
+        match prefix {
+            Some(prefix) if !prefix.is_empty() => prefix.join("."),
+            _ => "main".to_string(),
+        }
+    }
+
+    fn coefficient(&self, factor: &BigInt) -> String {
+        // Field elements close to the prime are shown as negative numbers
+        let negated = &self.file.header.field - factor;
+        if negated < *factor {
+            format!("-{}", negated)
+        } else {
+            factor.to_string()
+        }
+    }
+
+    fn linear_combination(&self, linear_combination: &LinearCombination) -> Vec<(String, String)> {
+        let mut terms: Vec<(String, String)> = linear_combination
+            .iter()
+            .map(|(wire, factor)| (self.signal_name(*wire), self.coefficient(factor)))
+            .collect();
+        terms.sort();
+        terms
+    }
+
+    // Key of the constraint with its signals identified by name
+    fn named_key(&self, constraint: &R1CSConstraint) -> String {
+        let mut key = self.component(constraint);
+        for linear_combination in constraint {
+            key.push('|');
+            for (name, factor) in self.linear_combination(linear_combination) {
+                key.push_str(&format!("{}*{};", factor, name));
+            }
+        }
+        key
+    }
+
+    // Key of the constraint ignoring which signals it uses. Constraints with
+    // the same key are candidates to be equal up to a renaming of signals
+    fn shape_key(&self, constraint: &R1CSConstraint) -> String {
+        let mut key = self.component(constraint);
+        for linear_combination in constraint {
+            key.push('|');
+            let mut factors: Vec<String> =
+                linear_combination.iter().map(|(_, factor)| self.coefficient(factor)).collect();
+            factors.sort();
+            key.push_str(&factors.join(";"));
+        }
+        key
+    }
+
+    fn display(&self, constraint: &R1CSConstraint) -> String {
+        let lc = |linear_combination: &LinearCombination| {
+            let terms: Vec<String> = self
+                .linear_combination(linear_combination)
+                .into_iter()
+                .map(|(name, factor)| if factor == "1" { name } else { format!("{}*{}", factor, name) })
+                .collect();
+            if terms.is_empty() { "0".to_string() } else { terms.join(" + ") }
+        };
+        format!("({}) * ({}) = {}", lc(&constraint[0]), lc(&constraint[1]), lc(&constraint[2]))
+    }
+
+    fn template(&self, node_id: i64) -> Option<&str> {
+        self.templates.get(&node_id).map(|name| name.as_str())
+    }
+
+    // Component paths of each template instance
+    fn instances(&self) -> BTreeMap<i64, BTreeSet<String>> {
+        let mut instances: BTreeMap<i64, BTreeSet<String>> = BTreeMap::new();
+        for (name, node_id) in &self.signals {
+            instances.entry(*node_id).or_default().insert(component_of(name).to_string());
+        }
+        instances
+    }
+}
+
+fn component_of(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(position) => &name[..position],
+        None => "main",
+    }
+}
+
+fn depth(name: &str) -> usize {
+    name.matches('.').count()
+}
+
+fn error(message: String) {
+    eprintln!("{}", Colour::Red.paint(message));
+}
+
+fn load(r1cs: &str, sym: Option<&str>, sym_json: Option<&str>) -> Result<Build, ()> {
+    let file = r1cs_reader::read_r1cs(r1cs).map_err(|_err| error(format!("could not read {}", r1cs)))?;
+    let mut names = HashMap::new();
+    let mut signals = BTreeMap::new();
+    if let Some(sym) = sym {
+        let elems = sym_reader::read_sym(sym).map_err(|_err| error(format!("could not read {}", sym)))?;
+        for elem in elems {
+            // Signals of a wire are named after the innermost component
+            if elem.witness >= 0 {
+                let name = names.entry(elem.witness as usize).or_insert_with(|| elem.symbol.clone());
+                if depth(&elem.symbol) > depth(name) {
+                    *name = elem.symbol.clone();
+                }
+            }
+            signals.insert(elem.symbol, elem.node_id);
+        }
+    }
+    let wires = names.iter().map(|(wire, name)| (name.clone(), *wire)).collect();
+    let templates = match sym_json {
+        Some(sym_json) => sym_reader::read_sym_json_templates(sym_json)
+            .map_err(|_err| error(format!("could not read {}", sym_json)))?,
+        None => HashMap::new(),
+    };
+    Result::Ok(Build { file, names, wires, signals, templates })
+}
+
+// Wire of the other build that has the same name. The constant wire is always
+// paired with itself
+fn counterpart(from: &Build, to: &Build, wire: usize) -> Option<usize> {
+    if wire == 0 {
+        Some(0)
+    } else {
+        from.names.get(&wire).and_then(|name| to.wires.get(name)).cloned()
+    }
+}
+
+// Bijection between the wires of both builds found while pairing the
+// constraints of a component
+#[derive(Default)]
+struct Renaming {
+    forward: HashMap<usize, usize>,
+    backward: HashMap<usize, usize>,
+}
+
+impl Renaming {
+    fn image(&self, wire: usize, pairs: &[(usize, usize)]) -> Option<usize> {
+        let tentative = pairs.iter().find(|(old_wire, _)| *old_wire == wire).map(|(_, new_wire)| *new_wire);
+        self.forward.get(&wire).cloned().or(tentative)
+    }
+
+    fn preimage(&self, wire: usize, pairs: &[(usize, usize)]) -> Option<usize> {
+        let tentative = pairs.iter().find(|(_, new_wire)| *new_wire == wire).map(|(old_wire, _)| *old_wire);
+        self.backward.get(&wire).cloned().or(tentative)
+    }
+
+    // Old wire that a wire of the new constraint must come from, if it is known
+    fn anchor(&self, old: &Build, new: &Build, wire: usize) -> Option<usize> {
+        self.backward.get(&wire).cloned().or_else(|| counterpart(new, old, wire))
+    }
+
+    // Extends the bijection so that it maps the old constraint to the new one.
+    // Signals named the same in both builds can only be paired with each
+    // other. The terms are paired greedily, so a renaming may be missed, but
+    // two constraints are never paired if they use the signals differently
+    fn extend(&mut self, old: &Build, new: &Build, old_constraint: &R1CSConstraint, new_constraint: &R1CSConstraint) -> bool {
+        let mut pairs = Vec::new();
+        for (old_lc, new_lc) in old_constraint.iter().zip(new_constraint.iter()) {
+            let mut used = vec![false; new_lc.len()];
+            for (old_wire, factor) in old_lc {
+                let image = self.image(*old_wire, &pairs);
+                let accepts = |new_wire: usize| match image {
+                    Some(image) => image == new_wire,
+                    None => {
+                        self.preimage(new_wire, &pairs).is_none()
+                            && counterpart(old, new, *old_wire).is_none_or(|wire| wire == new_wire)
+                            && counterpart(new, old, new_wire).is_none_or(|wire| wire == *old_wire)
+                    }
+                };
+                let position = new_lc
+                    .iter()
+                    .enumerate()
+                    .position(|(i, (new_wire, new_factor))| !used[i] && new_factor == factor && accepts(*new_wire));
+                match position {
+                    Some(position) => {
+                        used[position] = true;
+                        if image.is_none() {
+                            pairs.push((*old_wire, new_lc[position].0));
+                        }
+                    }
+                    None => return false,
+                }
+            }
+        }
+        for (old_wire, new_wire) in pairs {
+            self.forward.insert(old_wire, new_wire);
+            self.backward.insert(new_wire, old_wire);
+        }
+        true
+    }
+}
+
+#[derive(Default)]
+struct ComponentDiff {
+    constraints: (usize, usize),
+    identical: usize,
+    // pairs (old, new) of constraints equal up to a renaming of signals
+    renamed: Vec<(usize, usize)>,
+    removed: Vec<usize>,
+    added: Vec<usize>,
+    removed_signals: Vec<String>,
+    added_signals: Vec<String>,
+}
+
+impl ComponentDiff {
+    fn changed(&self) -> bool {
+        !self.removed.is_empty()
+            || !self.added.is_empty()
+            || !self.renamed.is_empty()
+            || !self.removed_signals.is_empty()
+            || !self.added_signals.is_empty()
+    }
+}
+
+// Matches the constraints of both builds with the same signals. Returns the
+// component of each match and leaves in the lists the constraints without a match
+fn match_named(old: &Build, new: &Build, removed: &mut Vec<usize>, added: &mut Vec<usize>) -> Vec<String> {
+    let mut pending: HashMap<String, Vec<usize>> = HashMap::new();
+    for index in removed.iter() {
+        pending.entry(old.named_key(&old.file.constraints[*index])).or_default().push(*index);
+    }
+    let mut matched = Vec::new();
+    let mut unmatched = Vec::new();
+    for index in added.iter() {
+        let constraint = &new.file.constraints[*index];
+        let candidates = pending.get_mut(&new.named_key(constraint));
+        if let Some(old_index) = candidates.and_then(|c| c.pop()) {
+            matched.push(old_index);
+        } else {
+            unmatched.push(*index);
+        }
+    }
+    let matched_set: BTreeSet<usize> = matched.iter().cloned().collect();
+    removed.retain(|index| !matched_set.contains(index));
+    *added = unmatched;
+    matched.iter().map(|index| old.component(&old.file.constraints[*index])).collect()
+}
+
+// Matches the constraints of both builds that are equal up to a renaming of
+// signals. The renaming must be the same for all the constraints of a
+// component. Returns the pairs (old, new) and leaves in the lists the
+// constraints without a match
+fn match_renamed(old: &Build, new: &Build, removed: &mut Vec<usize>, added: &mut Vec<usize>) -> Vec<(usize, usize)> {
+    let mut pending: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    let mut uses: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in removed.iter() {
+        let constraint = &old.file.constraints[*index];
+        pending.entry(old.shape_key(constraint)).or_default().insert(*index);
+        for (wire, _) in constraint.iter().flatten() {
+            uses.entry(*wire).or_default().push(*index);
+        }
+    }
+    let mut renamings: HashMap<String, Renaming> = HashMap::new();
+    let mut matched = Vec::new();
+    let mut unmatched = Vec::new();
+    for index in added.iter() {
+        let constraint = &new.file.constraints[*index];
+        let candidates = match pending.get_mut(&new.shape_key(constraint)) {
+            Some(candidates) => candidates,
+            None => {
+                unmatched.push(*index);
+                continue;
+            }
+        };
+        let renaming = renamings.entry(new.component(constraint)).or_default();
+        // A signal already paired leaves as candidates the constraints that use its old wire
+        let anchor = constraint
+            .iter()
+            .flatten()
+            .filter(|(wire, _)| *wire != 0)
+            .find_map(|(wire, _)| renaming.anchor(old, new, *wire));
+        let mut pairs = |old_index: &&usize| renaming.extend(old, new, &old.file.constraints[**old_index], constraint);
+        let found = match anchor {
+            Some(wire) => uses.get(&wire).into_iter().flatten().filter(|c| candidates.contains(c)).find(&mut pairs),
+            None => candidates.iter().find(&mut pairs),
+        };
+        match found.cloned() {
+            Some(old_index) => {
+                candidates.remove(&old_index);
+                matched.push((old_index, *index));
+            }
+            None => unmatched.push(*index),
+        }
+    }
+    let matched_set: BTreeSet<usize> = matched.iter().map(|(old_index, _)| *old_index).collect();
+    removed.retain(|index| !matched_set.contains(index));
+    *added = unmatched;
+    matched
+}
+
+pub fn diff(matches: &ArgMatches) -> Result<(), ()> {
+    let r1cs: Vec<&str> = matches.values_of("r1cs").unwrap().collect();
+    let sym: Option<Vec<&str>> = matches.values_of("sym").map(|values| values.collect());
+    let sym_json: Option<Vec<&str>> = matches.values_of("sym_json").map(|values| values.collect());
+    let (old_sym, new_sym) = match &sym {
+        Some(sym) => (Some(sym[0]), Some(sym[1])),
+        None => (None, None),
+    };
+    let (old_sym_json, new_sym_json) = match &sym_json {
+        Some(sym_json) => (Some(sym_json[0]), Some(sym_json[1])),
+        None => (None, None),
+    };
+    let old = load(r1cs[0], old_sym, old_sym_json)?;
+    let new = load(r1cs[1], new_sym, new_sym_json)?;
+    if old.file.header.field != new.file.header.field {
+        error("the constraint systems are defined over different fields".to_string());
+        return Result::Err(());
+    }
+
+    let components = compare(&old, &new);
+    let identical: usize = components.values().map(|c| c.identical).sum();
+    let renamed: usize = components.values().map(|c| c.renamed.len()).sum();
+    let removed: usize = components.values().map(|c| c.removed.len()).sum();
+    let added: usize = components.values().map(|c| c.added.len()).sum();
+    println!(
+        "{}: {} in {}, {} in {}",
+        Colour::Green.paint("constraints"),
+        old.file.constraints.len(),
+        r1cs[0],
+        new.file.constraints.len(),
+        r1cs[1]
+    );
+    println!(
+        "    {} identical, {} equal up to renaming of signals, {} removed, {} added",
+        identical, renamed, removed, added
+    );
+    let (old_header, new_header) = (&old.file.header, &new.file.header);
+    let counts = [
+        ("wires", old_header.total_wires, new_header.total_wires),
+        ("public outputs", old_header.public_outputs, new_header.public_outputs),
+        ("public inputs", old_header.public_inputs, new_header.public_inputs),
+        ("private inputs", old_header.private_inputs, new_header.private_inputs),
+        ("labels", old_header.number_of_labels, new_header.number_of_labels),
+    ];
+    for (name, old_count, new_count) in counts.iter() {
+        if old_count != new_count {
+            println!("{}: {} -> {}", Colour::Green.paint(*name), old_count, new_count);
+        }
+    }
+
+    let changed: Vec<_> = components.iter().filter(|(_, diff)| diff.changed()).collect();
+    if !changed.is_empty() {
+        println!("{}", Colour::Green.paint("components with changes:"));
+    }
+    for (path, diff) in changed {
+        println!(
+            "  {}: {} -> {} constraints ({} removed, {} added, {} renamed)",
+            path,
+            diff.constraints.0,
+            diff.constraints.1,
+            diff.removed.len(),
+            diff.added.len(),
+            diff.renamed.len()
+        );
+        for index in diff.removed.iter().take(LISTED) {
+            println!("      - {}", old.display(&old.file.constraints[*index]));
+        }
+        for index in diff.added.iter().take(LISTED) {
+            println!("      + {}", new.display(&new.file.constraints[*index]));
+        }
+        for (old_index, new_index) in diff.renamed.iter().take(LISTED) {
+            println!(
+                "      ~ {} => {}",
+                old.display(&old.file.constraints[*old_index]),
+                new.display(&new.file.constraints[*new_index])
+            );
+        }
+        let hidden = diff.removed.len().saturating_sub(LISTED)
+            + diff.added.len().saturating_sub(LISTED)
+            + diff.renamed.len().saturating_sub(LISTED);
+        if hidden > 0 {
+            println!("      ... and {} more constraints", hidden);
+        }
+        for name in diff.removed_signals.iter().take(LISTED) {
+            println!("      - signal {}", name);
+        }
+        for name in diff.added_signals.iter().take(LISTED) {
+            println!("      + signal {}", name);
+        }
+        let hidden = diff.removed_signals.len().saturating_sub(LISTED)
+            + diff.added_signals.len().saturating_sub(LISTED);
+        if hidden > 0 {
+            println!("      ... and {} more signals", hidden);
+        }
+    }
+    print_templates(&template_changes(&old, &new, &components));
+    print_instances(&old, &new, &components);
+
+    if removed == 0 && added == 0 && renamed == 0 {
+        println!("{}", Colour::Green.paint("No differences in the constraints"));
+    }
+    Result::Ok(())
+}
+
+// Attributes the differences between both builds to the components
+fn compare(old: &Build, new: &Build) -> BTreeMap<String, ComponentDiff> {
+    let mut components: BTreeMap<String, ComponentDiff> = BTreeMap::new();
+    for constraint in &old.file.constraints {
+        components.entry(old.component(constraint)).or_default().constraints.0 += 1;
+    }
+    for constraint in &new.file.constraints {
+        components.entry(new.component(constraint)).or_default().constraints.1 += 1;
+    }
+    let mut removed: Vec<usize> = (0..old.file.constraints.len()).collect();
+    let mut added: Vec<usize> = (0..new.file.constraints.len()).collect();
+    for component in match_named(old, new, &mut removed, &mut added) {
+        components.entry(component).or_default().identical += 1;
+    }
+    for (old_index, new_index) in match_renamed(old, new, &mut removed, &mut added) {
+        components.entry(old.component(&old.file.constraints[old_index])).or_default().renamed.push((old_index, new_index));
+    }
+    for index in &removed {
+        components.entry(old.component(&old.file.constraints[*index])).or_default().removed.push(*index);
+    }
+    for index in &added {
+        components.entry(new.component(&new.file.constraints[*index])).or_default().added.push(*index);
+    }
+    for name in old.signals.keys().filter(|name| !new.signals.contains_key(*name)) {
+        components.entry(component_of(name).to_string()).or_default().removed_signals.push(name.clone());
+    }
+    for name in new.signals.keys().filter(|name| !old.signals.contains_key(*name)) {
+        components.entry(component_of(name).to_string()).or_default().added_signals.push(name.clone());
+    }
+    components
+}
+
+#[derive(Default, PartialEq, Debug)]
+struct TemplateDiff {
+    components: (usize, usize),
+    constraints: (usize, usize),
+    removed: usize,
+    added: usize,
+    renamed: usize,
+    removed_signals: usize,
+    added_signals: usize,
+}
+
+impl TemplateDiff {
+    fn changed(&self) -> bool {
+        self.components.0 != self.components.1
+            || self.constraints.0 != self.constraints.1
+            || self.removed + self.added + self.renamed + self.removed_signals + self.added_signals > 0
+    }
+}
+
+// Adds up the differences of the components of every template. Removed
+// constraints and signals count for the template of the old build, and added
+// ones for the template of the new build
+fn template_changes(
+    old: &Build,
+    new: &Build,
+    components: &BTreeMap<String, ComponentDiff>,
+) -> BTreeMap<String, TemplateDiff> {
+    let mut templates: BTreeMap<String, TemplateDiff> = BTreeMap::new();
+    for (node_id, paths) in old.instances() {
+        if let Some(template) = old.template(node_id) {
+            let template = templates.entry(template.to_string()).or_default();
+            template.components.0 += paths.len();
+            for diff in paths.iter().filter_map(|path| components.get(path)) {
+                template.constraints.0 += diff.constraints.0;
+                template.removed += diff.removed.len();
+                template.renamed += diff.renamed.len();
+                template.removed_signals += diff.removed_signals.len();
+            }
+        }
+    }
+    for (node_id, paths) in new.instances() {
+        if let Some(template) = new.template(node_id) {
+            let template = templates.entry(template.to_string()).or_default();
+            template.components.1 += paths.len();
+            for diff in paths.iter().filter_map(|path| components.get(path)) {
+                template.constraints.1 += diff.constraints.1;
+                template.added += diff.added.len();
+                template.added_signals += diff.added_signals.len();
+            }
+        }
+    }
+    templates
+}
+
+fn print_templates(templates: &BTreeMap<String, TemplateDiff>) {
+    let changed: Vec<_> = templates.iter().filter(|(_, diff)| diff.changed()).collect();
+    if !changed.is_empty() {
+        println!("{}", Colour::Green.paint("templates with changes:"));
+    }
+    for (name, diff) in changed {
+        println!(
+            "  {}: {} -> {} components, {} -> {} constraints ({} removed, {} added, {} renamed), {} removed and {} added signals",
+            name,
+            diff.components.0,
+            diff.components.1,
+            diff.constraints.0,
+            diff.constraints.1,
+            diff.removed,
+            diff.added,
+            diff.renamed,
+            diff.removed_signals,
+            diff.added_signals
+        );
+    }
+}
+
+// Component paths of a template instance, and its template when it is known
+fn describe_instance(paths: &BTreeSet<String>, template: Option<&str>) -> String {
+    let mut listed: Vec<&str> = paths.iter().take(LISTED).map(|path| path.as_str()).collect();
+    if paths.len() > LISTED {
+        listed.push("...");
+    }
+    match template {
+        Some(template) => format!("{} ({})", listed.join(", "), template),
+        None => listed.join(", "),
+    }
+}
+
+// Template instances are aligned through the component paths they share
+fn print_instances(old: &Build, new: &Build, components: &BTreeMap<String, ComponentDiff>) {
+    let old_instances = old.instances();
+    let new_instances = new.instances();
+    let mut lines = Vec::new();
+    for (node_id, paths) in &new_instances {
+        let old_paths = old_instances.values().find(|old_paths| !old_paths.is_disjoint(paths));
+        let count = |paths: &BTreeSet<String>, side: fn(&ComponentDiff) -> usize| -> usize {
+            paths.iter().filter_map(|path| components.get(path)).map(side).sum()
+        };
+        let new_constraints = count(paths, |diff| diff.constraints.1);
+        let old_constraints = old_paths.map_or(0, |old_paths| count(old_paths, |diff| diff.constraints.0));
+        let old_count = old_paths.map_or(0, |old_paths| old_paths.len());
+        if old_constraints != new_constraints || old_count != paths.len() {
+            lines.push(format!(
+                "  {}: {} -> {} instances, {} -> {} constraints",
+                describe_instance(paths, new.template(*node_id)),
+                old_count,
+                paths.len(),
+                old_constraints,
+                new_constraints
+            ));
+        }
+    }
+    for (node_id, paths) in &old_instances {
+        if new_instances.values().all(|new_paths| new_paths.is_disjoint(paths)) {
+            let constraints: usize =
+                paths.iter().filter_map(|path| components.get(path)).map(|diff| diff.constraints.0).sum();
+            lines.push(format!(
+                "  {}: {} -> 0 instances, {} -> 0 constraints",
+                describe_instance(paths, old.template(*node_id)),
+                paths.len(),
+                constraints
+            ));
+        }
+    }
+    if !lines.is_empty() {
+        println!("{}", Colour::Green.paint("template instances with changes:"));
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constraint_writers::r1cs_writer::HeaderData;
+
+    fn constraint(a: &[usize], b: &[usize], c: &[usize]) -> R1CSConstraint {
+        let lc = |wires: &[usize]| wires.iter().map(|wire| (*wire, BigInt::from(1))).collect();
+        [lc(a), lc(b), lc(c)]
+    }
+
+    fn build(constraints: Vec<R1CSConstraint>, signals: &[(usize, &str)]) -> Build {
+        let header = HeaderData {
+            field: BigInt::from(101),
+            total_wires: signals.len() + 1,
+            public_outputs: 0,
+            public_inputs: 0,
+            private_inputs: 0,
+            number_of_labels: signals.len(),
+            number_of_constraints: constraints.len(),
+        };
+        let names: HashMap<usize, String> = signals.iter().map(|(wire, name)| (*wire, name.to_string())).collect();
+        let wires = names.iter().map(|(wire, name)| (name.clone(), *wire)).collect();
+        let signals = signals.iter().map(|(_, name)| (name.to_string(), 0)).collect();
+        Build { file: R1CSFile { header, constraints }, names, wires, signals, templates: HashMap::new() }
+    }
+
+    #[test]
+    fn constraints_are_matched_by_signal_name() {
+        let old = build(vec![constraint(&[2], &[3], &[1])], &[(1, "main.c"), (2, "main.a"), (3, "main.b")]);
+        // The same constraint with the signals in other wires
+        let new = build(vec![constraint(&[1], &[3], &[2])], &[(2, "main.c"), (1, "main.a"), (3, "main.b")]);
+        let components = compare(&old, &new);
+        assert_eq!(components["main"].identical, 1);
+        assert!(!components["main"].changed());
+    }
+
+    #[test]
+    fn differences_are_attributed_to_components() {
+        let old_signals = [(1, "main.c"), (2, "main.m.a"), (3, "main.m.b"), (4, "main.m.c")];
+        let old = build(vec![constraint(&[2], &[3], &[4]), constraint(&[4], &[4], &[1])], &old_signals);
+        let new_signals = [(1, "main.c"), (2, "main.m.a"), (3, "main.m.c"), (4, "main.n.d")];
+        let new = build(vec![constraint(&[2], &[2], &[3]), constraint(&[3], &[3], &[1])], &new_signals);
+        let components = compare(&old, &new);
+        assert_eq!(components["main"].identical, 1);
+        assert!(!components["main"].changed());
+        let component = &components["main.m"];
+        assert_eq!((component.removed.clone(), component.added.clone()), (vec![0], vec![0]));
+        assert_eq!(component.removed_signals, vec!["main.m.b".to_string()]);
+        assert_eq!(components["main.n"].added_signals, vec!["main.n.d".to_string()]);
+    }
+
+    #[test]
+    fn differences_are_added_up_by_template() {
+        let mut old = build(
+            vec![constraint(&[2], &[3], &[1]), constraint(&[4], &[5], &[1])],
+            &[(1, "main.c"), (2, "main.m[0].a"), (3, "main.m[0].b"), (4, "main.m[1].a"), (5, "main.m[1].b")],
+        );
+        let mut new = build(vec![constraint(&[2], &[3], &[1])], &[(1, "main.c"), (2, "main.m[0].a"), (3, "main.m[0].b")]);
+        let instantiate = |build: &mut Build| {
+            for (name, node_id) in build.signals.iter_mut() {
+                *node_id = if name.starts_with("main.m") { 1 } else { 0 };
+            }
+            build.templates.insert(0, "Main".to_string());
+            build.templates.insert(1, "Multiplier".to_string());
+        };
+        instantiate(&mut old);
+        instantiate(&mut new);
+        let components = compare(&old, &new);
+        let templates = template_changes(&old, &new, &components);
+        let expected = TemplateDiff {
+            components: (2, 1),
+            constraints: (0, 0),
+            removed_signals: 2,
+            ..TemplateDiff::default()
+        };
+        assert_eq!(templates["Multiplier"], expected);
+        assert_eq!(templates["Main"].constraints, (2, 1));
+        assert_eq!(templates["Main"].removed, 1);
+    }
+
+    fn renamed(components: &BTreeMap<String, ComponentDiff>) -> usize {
+        components.values().map(|diff| diff.renamed.len()).sum()
+    }
+
+    #[test]
+    fn renaming_needs_a_bijection() {
+        // c <== a*b becomes c <== a*a
+        let old = build(vec![constraint(&[2], &[3], &[1])], &[]);
+        let new = build(vec![constraint(&[2], &[2], &[1])], &[]);
+        let components = compare(&old, &new);
+        assert_eq!(renamed(&components), 0);
+        assert_eq!(components["main"].removed, vec![0]);
+        assert_eq!(components["main"].added, vec![0]);
+        assert!(components["main"].changed());
+    }
+
+    #[test]
+    fn renaming_is_consistent_in_a_component() {
+        let signals = [(1, "main.c"), (2, "main.x"), (3, "main.y")];
+        let old = build(vec![constraint(&[2], &[3], &[1]), constraint(&[2], &[2], &[3])], &signals);
+        // main.x is renamed to main.w in the first constraint, and to main.v in the second
+        let signals = [(1, "main.c"), (2, "main.w"), (3, "main.y"), (4, "main.v")];
+        let new = build(vec![constraint(&[2], &[3], &[1]), constraint(&[4], &[4], &[3])], &signals);
+        let components = compare(&old, &new);
+        assert_eq!(components["main"].renamed, vec![(0, 0)]);
+        assert_eq!(components["main"].removed, vec![1]);
+        assert_eq!(components["main"].added, vec![1]);
+        assert!(components["main"].changed());
+    }
+}
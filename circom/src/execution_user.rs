@@ -1,5 +1,6 @@
 use ansi_term::Colour;
 use compiler::hir::very_concrete_program::VCP;
+use constraint_generation::SignalAssertMode;
 use constraint_writers::debug_writer::DebugWriter;
 use constraint_writers::ConstraintExporter;
 use program_structure::program_archive::ProgramArchive;
@@ -8,15 +9,24 @@ use program_structure::program_archive::ProgramArchive;
 pub struct ExecutionConfig {
     pub r1cs: String,
     pub sym: String,
+    pub sym_json: String,
+    pub origins: String,
     pub json_constraints: String,
+    pub json_substitutions: String,
     pub no_rounds: usize,
     pub flag_s: bool,
     pub flag_f: bool,
     pub flag_p: bool,
     pub flag_old_heuristics:bool,
     pub flag_verbose: bool,
+    pub log_compile_time: bool,
+    pub signal_asserts: SignalAssertMode,
     pub inspect_constraints_flag: bool,
+    pub max_exec_steps: Option<usize>,
+    pub max_recursion_depth: usize,
     pub sym_flag: bool,
+    pub sym_json_flag: bool,
+    pub origins_flag: bool,
     pub r1cs_flag: bool,
     pub json_substitution_flag: bool,
     pub json_constraint_flag: bool,
@@ -26,31 +36,46 @@ pub struct ExecutionConfig {
 pub fn execute_project(
     program_archive: ProgramArchive,
     config: ExecutionConfig,
-) -> Result<VCP, ()> {
+) -> Result<(VCP, usize), ()> {
     use constraint_generation::{build_circuit, BuildConfig};
     let debug = DebugWriter::new(config.json_constraints).unwrap();
     let build_config = BuildConfig {
         no_rounds: config.no_rounds,
         flag_json_sub: config.json_substitution_flag,
+        json_substitutions: config.json_substitutions.clone(),
+        flag_origins: config.origins_flag,
         flag_s: config.flag_s,
         flag_f: config.flag_f,
         flag_p: config.flag_p,
         flag_verbose: config.flag_verbose,
+        log_compile_time: config.log_compile_time,
+        signal_asserts: config.signal_asserts,
         inspect_constraints: config.inspect_constraints_flag,
+        max_exec_steps: config.max_exec_steps,
+        max_recursion_depth: config.max_recursion_depth,
         flag_old_heuristics: config.flag_old_heuristics,
         prime : config.prime,
     };
     let (exporter, vcp) = build_circuit(program_archive, build_config)?;
+    if config.json_substitution_flag && !config.flag_f {
+        println!("{} {}", Colour::Green.paint("Substitutions written in:"), config.json_substitutions);
+    }
     if config.r1cs_flag {
         generate_output_r1cs(&config.r1cs, exporter.as_ref())?;
     }
     if config.sym_flag {
         generate_output_sym(&config.sym, exporter.as_ref())?;
     }
+    if config.sym_json_flag {
+        generate_output_sym_json(&config.sym_json, exporter.as_ref())?;
+    }
+    if config.origins_flag {
+        generate_output_origins(&config.origins, exporter.as_ref())?;
+    }
     if config.json_constraint_flag {
         generate_json_constraints(&debug, exporter.as_ref())?;
     }
-    Result::Ok(vcp)
+    Result::Ok((vcp, exporter.number_of_constraints()))
 }
 
 fn generate_output_r1cs(file: &str, exporter: &dyn ConstraintExporter) -> Result<(), ()> {
@@ -73,6 +98,26 @@ fn generate_output_sym(file: &str, exporter: &dyn ConstraintExporter) -> Result<
     }
 }
 
+fn generate_output_sym_json(file: &str, exporter: &dyn ConstraintExporter) -> Result<(), ()> {
+    if let Result::Ok(()) = exporter.sym_json(file) {
+        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+        Result::Ok(())
+    } else {
+        eprintln!("{}", Colour::Red.paint("Could not write the output in the given path"));
+        Result::Err(())
+    }
+}
+
+fn generate_output_origins(file: &str, exporter: &dyn ConstraintExporter) -> Result<(), ()> {
+    if let Result::Ok(()) = exporter.origins(file) {
+        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+        Result::Ok(())
+    } else {
+        eprintln!("{}", Colour::Red.paint("Could not write the output in the given path"));
+        Result::Err(())
+    }
+}
+
 fn generate_json_constraints(
     debug: &DebugWriter,
     exporter: &dyn ConstraintExporter,
@@ -1,9 +1,14 @@
+use super::batch_user::{self, BatchEntry};
 use std::path::PathBuf;
+use clap::ArgMatches;
+use constraint_generation::SignalAssertMode;
 
+#[derive(Clone)]
 pub struct Input {
     pub input_program: PathBuf,
     pub out_r1cs: PathBuf,
     pub out_json_constraints: PathBuf,
+    pub out_json_substitutions: PathBuf,
     pub out_wat_code: PathBuf,
     pub out_wasm_code: PathBuf,
     pub out_wasm_name: String,
@@ -13,12 +18,26 @@ pub struct Input {
     pub out_c_code: PathBuf,
     pub out_c_dat: PathBuf,
     pub out_sym: PathBuf,
+    pub out_sym_json: PathBuf,
+    pub out_origins: PathBuf,
     //pub field: &'static str,
     pub c_flag: bool,
     pub wasm_flag: bool,
     pub wat_flag: bool,
+    pub trace_flag: bool,
+    pub portable_flag: bool,
+    pub lib_flag: bool,
+    pub embed_dat_flag: bool,
+    pub c_profile_flag: bool,
+    pub source_map_flag: bool,
+    pub update_lock_flag: bool,
+    pub main_call: Option<String>,
+    pub public_signals: Option<Vec<String>>,
+    pub batch: Vec<BatchEntry>,
     pub r1cs_flag: bool,
     pub sym_flag: bool,
+    pub sym_json_flag: bool,
+    pub origins_flag: bool,
     pub json_constraint_flag: bool,
     pub json_substitution_flag: bool,
     pub main_inputs_flag: bool,
@@ -29,7 +48,11 @@ pub struct Input {
     pub flag_old_heuristics: bool,
     pub inspect_constraints_flag: bool,
     pub no_rounds: usize,
+    pub max_exec_steps: Option<usize>,
+    pub max_recursion_depth: usize,
     pub flag_verbose: bool,
+    pub log_compile_time_flag: bool,
+    pub signal_asserts: SignalAssertMode,
     pub prime: String,
 }
 
@@ -41,19 +64,20 @@ const CPP: &'static str = "cpp";
 const JS: &'static str = "js";
 const DAT: &'static str = "dat";
 const SYM: &'static str = "sym";
+const SYM_JSON: &'static str = "sym.json";
+const ORIGINS: &'static str = "origins";
 const JSON: &'static str = "json";
 
 
 impl Input {
-    pub fn new() -> Result<Input, ()> {
+    pub fn new(matches: &ArgMatches) -> Result<Input, ()> {
         use input_processing::SimplificationStyle;
-        let matches = input_processing::view();
-        let input = input_processing::get_input(&matches)?;
+        let input = input_processing::get_input(matches)?;
         let file_name = input.file_stem().unwrap().to_str().unwrap().to_string();
-        let output_path = input_processing::get_output_path(&matches)?;
+        let output_path = input_processing::get_output_path(matches)?;
         let output_c_path = Input::build_folder(&output_path, &file_name, CPP);
         let output_js_path = Input::build_folder(&output_path, &file_name, JS);
-        let o_style = input_processing::get_simplification_style(&matches)?;
+        let o_style = input_processing::get_simplification_style(matches)?;
         Result::Ok(Input {
             //field: P_BN128,
             input_program: input,
@@ -67,31 +91,80 @@ impl Input {
             out_c_code: Input::build_output(&output_c_path, &file_name, CPP),
             out_c_dat: Input::build_output(&output_c_path, &file_name, DAT),
             out_sym: Input::build_output(&output_path, &file_name, SYM),
+            out_sym_json: Input::build_output(&output_path, &file_name, SYM_JSON),
+            out_origins: Input::build_output(&output_path, &file_name, ORIGINS),
             out_json_constraints: Input::build_output(
                 &output_path,
                 &format!("{}_constraints", file_name),
                 JSON,
             ),
-            wat_flag:input_processing::get_wat(&matches),
-            wasm_flag: input_processing::get_wasm(&matches),
-            c_flag: input_processing::get_c(&matches),
-            r1cs_flag: input_processing::get_r1cs(&matches),
-            sym_flag: input_processing::get_sym(&matches),
-            main_inputs_flag: input_processing::get_main_inputs_log(&matches),
-            json_constraint_flag: input_processing::get_json_constraints(&matches),
-            json_substitution_flag: input_processing::get_json_substitutions(&matches),
-            print_ir_flag: input_processing::get_ir(&matches),
+            out_json_substitutions: Input::build_output(
+                &output_path,
+                &format!("{}_substitutions", file_name),
+                JSON,
+            ),
+            wat_flag:input_processing::get_wat(matches),
+            wasm_flag: input_processing::get_wasm(matches),
+            trace_flag: input_processing::get_trace(matches),
+            portable_flag: input_processing::get_portable(matches),
+            lib_flag: input_processing::get_lib(matches),
+            embed_dat_flag: input_processing::get_embed_dat(matches),
+            c_profile_flag: input_processing::get_c_profile(matches),
+            source_map_flag: input_processing::get_source_map(matches),
+            update_lock_flag: input_processing::get_update_lock(matches),
+            main_call: input_processing::get_main_call(matches),
+            public_signals: input_processing::get_public_signals(matches),
+            batch: match input_processing::get_batch_file(matches) {
+                Some(file) => batch_user::read_batch(&file)?,
+                None => Vec::new(),
+            },
+            c_flag: input_processing::get_c(matches),
+            r1cs_flag: input_processing::get_r1cs(matches),
+            sym_flag: input_processing::get_sym(matches),
+            sym_json_flag: input_processing::get_sym_json(matches),
+            origins_flag: input_processing::get_origins(matches),
+            main_inputs_flag: input_processing::get_main_inputs_log(matches),
+            json_constraint_flag: input_processing::get_json_constraints(matches),
+            json_substitution_flag: input_processing::get_json_substitutions(matches),
+            print_ir_flag: input_processing::get_ir(matches),
             no_rounds: if let SimplificationStyle::O2(r) = o_style { r } else { 0 },
+            max_exec_steps: input_processing::get_max_exec_steps(matches)?,
+            max_recursion_depth: input_processing::get_max_recursion_depth(matches)?,
             fast_flag: o_style == SimplificationStyle::O0,
             reduced_simplification_flag: o_style == SimplificationStyle::O1,
-            parallel_simplification_flag: input_processing::get_parallel_simplification(&matches),
-            inspect_constraints_flag: input_processing::get_inspect_constraints(&matches),
-            flag_old_heuristics: input_processing::get_flag_old_heuristics(&matches),
-            flag_verbose: input_processing::get_flag_verbose(&matches), 
-            prime: input_processing::get_prime(&matches)?,
+            parallel_simplification_flag: input_processing::get_parallel_simplification(matches),
+            inspect_constraints_flag: input_processing::get_inspect_constraints(matches),
+            flag_old_heuristics: input_processing::get_flag_old_heuristics(matches),
+            flag_verbose: input_processing::get_flag_verbose(matches), 
+            log_compile_time_flag: input_processing::get_log_compile_time(matches),
+            signal_asserts: input_processing::get_signal_asserts(matches)?,
+            prime: input_processing::get_prime(matches)?,
         })
     }
 
+    // The same input with the outputs written in another directory
+    pub fn with_output_path(&self, output_path: &PathBuf) -> Input {
+        let file_name = self.out_wasm_name.clone();
+        let output_c_path = Input::build_folder(output_path, &file_name, CPP);
+        let output_js_path = Input::build_folder(output_path, &file_name, JS);
+        let mut input = self.clone();
+        input.out_r1cs = Input::build_output(output_path, &file_name, R1CS);
+        input.out_wat_code = Input::build_output(&output_js_path, &file_name, WAT);
+        input.out_wasm_code = Input::build_output(&output_js_path, &file_name, WASM);
+        input.out_c_code = Input::build_output(&output_c_path, &file_name, CPP);
+        input.out_c_dat = Input::build_output(&output_c_path, &file_name, DAT);
+        input.out_sym = Input::build_output(output_path, &file_name, SYM);
+        input.out_sym_json = Input::build_output(output_path, &file_name, SYM_JSON);
+        input.out_origins = Input::build_output(output_path, &file_name, ORIGINS);
+        input.out_json_constraints =
+            Input::build_output(output_path, &format!("{}_constraints", file_name), JSON);
+        input.out_json_substitutions =
+            Input::build_output(output_path, &format!("{}_substitutions", file_name), JSON);
+        input.out_js_folder = output_js_path;
+        input.out_c_folder = output_c_path;
+        input
+    }
+
     fn build_folder(output_path: &PathBuf, filename: &str, ext: &str) -> PathBuf {
         let mut file = output_path.clone();
 	    let folder_name = format!("{}_{}",filename,ext);
@@ -114,6 +187,12 @@ impl Input {
     pub fn sym_file(&self) -> &str {
         self.out_sym.to_str().unwrap()
     }
+    pub fn sym_json_file(&self) -> &str {
+        self.out_sym_json.to_str().unwrap()
+    }
+    pub fn origins_file(&self) -> &str {
+        self.out_origins.to_str().unwrap()
+    }
     pub fn wat_file(&self) -> &str {
         self.out_wat_code.to_str().unwrap()
     }
@@ -143,6 +222,9 @@ impl Input {
     pub fn json_constraints_file(&self) -> &str {
         self.out_json_constraints.to_str().unwrap()
     }
+    pub fn json_substitutions_file(&self) -> &str {
+        self.out_json_substitutions.to_str().unwrap()
+    }
     pub fn wasm_flag(&self) -> bool {
         self.wasm_flag
     }
@@ -152,6 +234,36 @@ impl Input {
     pub fn c_flag(&self) -> bool {
         self.c_flag
     }
+    pub fn trace_flag(&self) -> bool {
+        self.trace_flag
+    }
+    pub fn portable_flag(&self) -> bool {
+        self.portable_flag
+    }
+    pub fn lib_flag(&self) -> bool {
+        self.lib_flag
+    }
+    pub fn embed_dat_flag(&self) -> bool {
+        self.embed_dat_flag
+    }
+    pub fn c_profile_flag(&self) -> bool {
+        self.c_profile_flag
+    }
+    pub fn source_map_flag(&self) -> bool {
+        self.source_map_flag
+    }
+    pub fn update_lock_flag(&self) -> bool {
+        self.update_lock_flag
+    }
+    pub fn main_call(&self) -> Option<String> {
+        self.main_call.clone()
+    }
+    pub fn public_signals(&self) -> Option<Vec<String>> {
+        self.public_signals.clone()
+    }
+    pub fn batch(&self) -> &[BatchEntry] {
+        &self.batch
+    }
     pub fn unsimplified_flag(&self) -> bool {
         self.fast_flag
     }
@@ -170,6 +282,12 @@ impl Input {
     pub fn sym_flag(&self) -> bool {
         self.sym_flag
     }
+    pub fn sym_json_flag(&self) -> bool {
+        self.sym_json_flag
+    }
+    pub fn origins_flag(&self) -> bool {
+        self.origins_flag
+    }
     pub fn print_ir_flag(&self) -> bool {
         self.print_ir_flag
     }
@@ -179,6 +297,12 @@ impl Input {
     pub fn flag_verbose(&self) -> bool {
         self.flag_verbose
     }
+    pub fn log_compile_time_flag(&self) -> bool {
+        self.log_compile_time_flag
+    }
+    pub fn signal_asserts(&self) -> SignalAssertMode {
+        self.signal_asserts
+    }
     pub fn reduced_simplification_flag(&self) -> bool {
         self.reduced_simplification_flag
     }
@@ -191,13 +315,20 @@ impl Input {
     pub fn no_rounds(&self) -> usize {
         self.no_rounds
     }
+    pub fn max_exec_steps(&self) -> Option<usize> {
+        self.max_exec_steps
+    }
+    pub fn max_recursion_depth(&self) -> usize {
+        self.max_recursion_depth
+    }
     pub fn prime(&self) -> String{
         self.prime.clone()
     }
 }
-mod input_processing {
+pub mod input_processing {
     use ansi_term::Colour;
-    use clap::{App, Arg, ArgMatches};
+    use clap::{App, Arg, ArgMatches, SubCommand};
+    use constraint_generation::SignalAssertMode;
     use std::path::{Path, PathBuf};
     use crate::VERSION;
 
@@ -254,6 +385,14 @@ mod input_processing {
         matches.is_present("print_sym")
     }
 
+    pub fn get_sym_json(matches: &ArgMatches) -> bool {
+        matches.is_present("print_sym_json")
+    }
+
+    pub fn get_origins(matches: &ArgMatches) -> bool {
+        matches.is_present("print_origins")
+    }
+
     pub fn get_r1cs(matches: &ArgMatches) -> bool {
         matches.is_present("print_r1cs")
     }
@@ -270,6 +409,51 @@ mod input_processing {
         matches.is_present("print_c")
     }
 
+    pub fn get_trace(matches: &ArgMatches) -> bool {
+        matches.is_present("trace")
+    }
+
+    pub fn get_portable(matches: &ArgMatches) -> bool {
+        matches.is_present("portable")
+    }
+
+    pub fn get_lib(matches: &ArgMatches) -> bool {
+        matches.is_present("lib")
+    }
+
+    pub fn get_embed_dat(matches: &ArgMatches) -> bool {
+        matches.is_present("embed_dat")
+    }
+
+    pub fn get_c_profile(matches: &ArgMatches) -> bool {
+        matches.is_present("c_profile")
+    }
+
+    pub fn get_source_map(matches: &ArgMatches) -> bool {
+        matches.is_present("source_map")
+    }
+
+    pub fn get_update_lock(matches: &ArgMatches) -> bool {
+        matches.is_present("update_lock")
+    }
+
+    pub fn get_main_call(matches: &ArgMatches) -> Option<String> {
+        matches.value_of("main").map(|call| call.to_string())
+    }
+
+    pub fn get_public_signals(matches: &ArgMatches) -> Option<Vec<String>> {
+        matches.value_of("public").map(|list| {
+            list.split(',')
+                .map(|signal| signal.trim().to_string())
+                .filter(|signal| !signal.is_empty())
+                .collect()
+        })
+    }
+
+    pub fn get_batch_file(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.value_of("batch").map(PathBuf::from)
+    }
+
     pub fn get_main_inputs_log(matches: &ArgMatches) -> bool {
         matches.is_present("main_inputs_log")
     }
@@ -289,6 +473,36 @@ mod input_processing {
         matches.is_present("flag_verbose")
     }
 
+    pub fn get_log_compile_time(matches: &ArgMatches) -> bool {
+        matches.is_present("log_compile_time")
+    }
+
+    pub fn get_signal_asserts(matches: &ArgMatches) -> Result<SignalAssertMode, ()> {
+        match matches.value_of("signal_asserts").unwrap() {
+            "ignore" => Result::Ok(SignalAssertMode::Ignore),
+            "warn" => Result::Ok(SignalAssertMode::Warn),
+            "error" => Result::Ok(SignalAssertMode::Error),
+            "constrain" => Result::Ok(SignalAssertMode::Constrain),
+            _ => Result::Err(eprintln!("{}", Colour::Red.paint("invalid mode for the signal dependent asserts"))),
+        }
+    }
+
+    // Without the option the number of statements is not bounded
+    pub fn get_max_exec_steps(matches: &ArgMatches) -> Result<Option<usize>, ()> {
+        match matches.value_of("max_exec_steps").map(|steps| steps.parse::<usize>()) {
+            None => Ok(None),
+            Some(Ok(steps)) if steps > 0 => Ok(Some(steps)),
+            _ => Result::Err(eprintln!("{}", Colour::Red.paint("invalid maximum number of execution steps"))),
+        }
+    }
+
+    pub fn get_max_recursion_depth(matches: &ArgMatches) -> Result<usize, ()> {
+        match matches.value_of("max_recursion_depth").unwrap().parse::<usize>() {
+            Ok(depth) if depth > 0 => Ok(depth),
+            _ => Result::Err(eprintln!("{}", Colour::Red.paint("invalid maximum recursion depth"))),
+        }
+    }
+
     pub fn get_flag_old_heuristics(matches: &ArgMatches) -> bool {
         matches.is_present("flag_old_heuristics")
     }
@@ -376,8 +590,7 @@ mod input_processing {
                 Arg::with_name("print_json_sub")
                     .long("jsons")
                     .takes_value(false)
-                    .hidden(true)
-                    .help("outputs the substitution in json format"),
+                    .help("outputs the substitutions applied by the simplification in json format"),
             )
             .arg(
                 Arg::with_name("print_sym")
@@ -385,6 +598,18 @@ mod input_processing {
                     .takes_value(false)
                     .help("outputs witness in sym format"),
             )
+            .arg(
+                Arg::with_name("print_sym_json")
+                    .long("sym-json")
+                    .takes_value(false)
+                    .help("Outputs the component tree with the witness of its signals in json format"),
+            )
+            .arg(
+                Arg::with_name("print_origins")
+                    .long("origins")
+                    .takes_value(false)
+                    .help("outputs the source location of each constraint in origins format"),
+            )
             .arg(
                 Arg::with_name("print_r1cs")
                     .long("r1cs")
@@ -410,6 +635,67 @@ mod input_processing {
                     .takes_value(false)
                     .help("Compiles the circuit to c"),
             )
+            .arg(
+                Arg::with_name("trace")
+                    .long("trace")
+                    .takes_value(false)
+                    .help("Instruments the witness generators to write an execution trace"),
+            )
+            .arg(
+                Arg::with_name("portable")
+                    .long("portable")
+                    .takes_value(false)
+                    .help("Uses portable C++ field arithmetic in the c witness generator, without assembly nor GMP"),
+            )
+            .arg(
+                Arg::with_name("lib")
+                    .long("lib")
+                    .takes_value(false)
+                    .help("Adds to the c witness generator the sources of a static and a shared library with a C API"),
+            )
+            .arg(
+                Arg::with_name("embed_dat")
+                    .long("embed-dat")
+                    .takes_value(false)
+                    .help("Embeds the circuit data in the c witness generator instead of writing the .dat file"),
+            )
+            .arg(
+                Arg::with_name("c_profile")
+                    .long("c-profile")
+                    .takes_value(false)
+                    .help("Instruments the c witness generator to report the calls, time and field operations of each template and function"),
+            )
+            .arg(
+                Arg::with_name("source_map")
+                    .long("source-map")
+                    .takes_value(false)
+                    .help("Maps the code of the witness generators to the circom sources with #line directives in c and a source map of the wasm module"),
+            )
+            .arg(
+                Arg::with_name("update_lock")
+                    .long("update-lock")
+                    .takes_value(false)
+                    .help("Records the current content of the dependencies of circom.toml in circom.lock"),
+            )
+            .arg(
+                Arg::with_name("main")
+                    .long("main")
+                    .takes_value(true)
+                    .help("Template call used as main component instead of the one in the input file, e.g. 'Multiplier(3)'"),
+            )
+            .arg(
+                Arg::with_name("public")
+                    .long("public")
+                    .takes_value(true)
+                    .help("Comma separated list of the public input signals of the main component"),
+            )
+            .arg(
+                Arg::with_name("batch")
+                    .long("batch")
+                    .takes_value(true)
+                    .conflicts_with_all(&["main", "public"])
+                    .help("Json file with the main components to build, each one in its own output directory"),
+            )
             .arg(
                 Arg::with_name("parallel_simplification")
                     .long("parallel")
@@ -430,6 +716,33 @@ mod input_processing {
                     .takes_value(false)
                     .help("Shows logs during compilation"),
             )
+            .arg(
+                Arg::with_name("log_compile_time")
+                    .long("log-compile-time")
+                    .takes_value(false)
+                    .help("Prints the logs whose arguments are known while generating the constraints"),
+            )
+            .arg(
+                Arg::with_name("signal_asserts")
+                    .long("signal-asserts")
+                    .takes_value(true)
+                    .possible_values(&["ignore", "warn", "error", "constrain"])
+                    .default_value("ignore")
+                    .help("Treatment of the asserts that depend on signals, which the constraints do not enforce"),
+            )
+            .arg(
+                Arg::with_name("max_exec_steps")
+                    .long("max-exec-steps")
+                    .takes_value(true)
+                    .help("Maximum number of statements executed while generating the constraints, unbounded by default"),
+            )
+            .arg(
+                Arg::with_name("max_recursion_depth")
+                    .long("max-recursion-depth")
+                    .takes_value(true)
+                    .default_value("1000")
+                    .help("Maximum depth of nested function and template calls while generating the constraints"),
+            )
             .arg(
                 Arg::with_name("flag_old_heuristics")
                     .long("use_old_simplification_heuristics")
@@ -444,6 +757,32 @@ mod input_processing {
                     .default_value("bn128")
                     .help("To choose the prime number to use to generate the circuit. Receives the name of the curve (bn128, bls12381, goldilocks)"),
             )
+            .subcommand(
+                SubCommand::with_name("diff")
+                    .about("Compares the constraints of two builds of a circuit")
+                    .arg(
+                        Arg::with_name("r1cs")
+                            .multiple(true)
+                            .number_of_values(2)
+                            .required(true)
+                            .help("The r1cs files of both builds"),
+                    )
+                    .arg(
+                        Arg::with_name("sym")
+                            .long("sym")
+                            .number_of_values(2)
+                            .value_names(&["a.sym", "b.sym"])
+                            .help("The sym files of both builds, to compare signals and components by name"),
+                    )
+                    .arg(
+                        Arg::with_name("sym_json")
+                            .long("sym-json")
+                            .number_of_values(2)
+                            .value_names(&["a.sym.json", "b.sym.json"])
+                            .requires("sym")
+                            .help("The sym json files of both builds, to attribute the differences to templates"),
+                    ),
+            )
             .get_matches()
     }
 }
@@ -1,4 +1,6 @@
+mod batch_user;
 mod compilation_user;
+mod diff_user;
 mod execution_user;
 mod input_user;
 mod parser_user;
@@ -9,8 +11,30 @@ const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
 use ansi_term::Colour;
 use input_user::Input;
+use program_structure::program_archive::ProgramArchive;
+
+// Function calls and component instantiations are executed recursively, so the
+// compiler runs in a thread whose stack fits the maximum recursion depth
+const STACK_PER_CALL: usize = 256 * 1024;
+const MIN_STACK_SIZE: usize = 64 * 1024 * 1024;
+
 fn main() {
-    let result = start();
+    let matches = input_user::input_processing::view();
+    if let Some(diff_matches) = matches.subcommand_matches("diff") {
+        if diff_user::diff(diff_matches).is_err() {
+            eprintln!("{}", Colour::Red.paint("previous errors were found"));
+        }
+        return;
+    }
+    let result = Input::new(&matches).and_then(|user_input| {
+        let stack_size = std::cmp::max(MIN_STACK_SIZE, user_input.max_recursion_depth().saturating_mul(STACK_PER_CALL));
+        std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn(move || start(user_input))
+            .map_err(|_err| eprintln!("{}", Colour::Red.paint("could not start the compiler thread")))?
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    });
     if result.is_err() {
         eprintln!("{}", Colour::Red.paint("previous errors were found"));
     } else {
@@ -18,12 +42,22 @@ fn main() {
     }
 }
 
-fn start() -> Result<(), ()> {
-    use compilation_user::CompilerConfig;
-    use execution_user::ExecutionConfig;
-    let user_input = Input::new()?;
+fn start(user_input: Input) -> Result<(), ()> {
     let mut program_archive = parser_user::parse_project(&user_input)?;
     type_analysis_user::analyse_project(&mut program_archive)?;
+    if user_input.batch().is_empty() {
+        build(&user_input, program_archive)?;
+        Result::Ok(())
+    } else {
+        batch_user::build_batch(&user_input, program_archive)
+    }
+}
+
+// Generates the constraints and the witness generators of the selected main
+// component. Returns the number of constraints of the circuit
+fn build(user_input: &Input, program_archive: ProgramArchive) -> Result<usize, ()> {
+    use compilation_user::CompilerConfig;
+    use execution_user::ExecutionConfig;
 
     let config = ExecutionConfig {
         no_rounds: user_input.no_rounds(),
@@ -32,23 +66,38 @@ fn start() -> Result<(), ()> {
         flag_f: user_input.unsimplified_flag(),
         flag_old_heuristics: user_input.flag_old_heuristics(),
         flag_verbose: user_input.flag_verbose(),
+        log_compile_time: user_input.log_compile_time_flag(),
+        signal_asserts: user_input.signal_asserts(),
         inspect_constraints_flag: user_input.inspect_constraints_flag(),
+        max_exec_steps: user_input.max_exec_steps(),
+        max_recursion_depth: user_input.max_recursion_depth(),
         r1cs_flag: user_input.r1cs_flag(),
         json_constraint_flag: user_input.json_constraints_flag(),
         json_substitution_flag: user_input.json_substitutions_flag(),
         sym_flag: user_input.sym_flag(),
+        sym_json_flag: user_input.sym_json_flag(),
+        origins_flag: user_input.origins_flag(),
         sym: user_input.sym_file().to_string(),
+        sym_json: user_input.sym_json_file().to_string(),
+        origins: user_input.origins_file().to_string(),
         r1cs: user_input.r1cs_file().to_string(),
         json_constraints: user_input.json_constraints_file().to_string(),
+        json_substitutions: user_input.json_substitutions_file().to_string(),
         prime: user_input.prime(),        
     };
-    let circuit = execution_user::execute_project(program_archive, config)?;
+    let (circuit, constraints) = execution_user::execute_project(program_archive, config)?;
     let compilation_config = CompilerConfig {
         vcp: circuit,
         debug_output: user_input.print_ir_flag(),
         c_flag: user_input.c_flag(),
         wasm_flag: user_input.wasm_flag(),
         wat_flag: user_input.wat_flag(),
+        trace_flag: user_input.trace_flag(),
+        portable_flag: user_input.portable_flag(),
+        lib_flag: user_input.lib_flag(),
+        embed_dat_flag: user_input.embed_dat_flag(),
+        c_profile_flag: user_input.c_profile_flag(),
+        source_map_flag: user_input.source_map_flag(),
 	    js_folder: user_input.js_folder().to_string(),
 	    wasm_name: user_input.wasm_name().to_string(),
 	    c_folder: user_input.c_folder().to_string(),
@@ -60,5 +109,5 @@ fn start() -> Result<(), ()> {
         produce_input_log: user_input.main_inputs_flag(),
     };
     compilation_user::compile(compilation_config)?;
-    Result::Ok(())
+    Result::Ok(constraints)
 }
@@ -1,4 +1,5 @@
 use super::input_user::Input;
+use parser::MainOverride;
 use program_structure::error_definition::Report;
 use program_structure::program_archive::ProgramArchive;
 use crate::VERSION;
@@ -6,7 +7,16 @@ use crate::VERSION;
 
 pub fn parse_project(input_info: &Input) -> Result<ProgramArchive, ()> {
     let initial_file = input_info.input_file().to_string();
-    let result_program_archive = parser::run_parser(initial_file, VERSION);
+    let main_overrides: Vec<MainOverride> = if input_info.batch().is_empty() {
+        vec![MainOverride { call: input_info.main_call(), public: input_info.public_signals() }]
+    } else {
+        input_info.batch().iter().map(|entry| MainOverride {
+            call: Some(entry.main.clone()),
+            public: entry.public.clone(),
+        }).collect()
+    };
+    let result_program_archive =
+        parser::run_parser(initial_file, VERSION, input_info.update_lock_flag(), &main_overrides);
     match result_program_archive {
         Result::Err((file_library, report_collection)) => {
             Report::print_reports(&report_collection, &file_library);
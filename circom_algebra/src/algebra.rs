@@ -877,6 +877,7 @@ where
 {
     pub(crate) from: C,
     pub(crate) to: HashMap<C, BigInt>,
+    pub(crate) origins: Vec<usize>,
 }
 impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
     // Substitution public utils
@@ -886,15 +887,15 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
             Number { value } => {
                 let mut to = HashMap::new();
                 to.insert(ArithmeticExpression::constant_coefficient(), value);
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             Signal { symbol } => {
                 let mut to = HashMap::new();
                 to.insert(symbol, BigInt::from(1));
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             Linear { coefficients: to } if !to.contains_key(&from) => {
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             _ => Option::None,
         }
@@ -923,10 +924,13 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
     {
         let from = symbol_correspondence.get(&substitution.from).unwrap().clone();
         let to = apply_raw_correspondence(&substitution.to, symbol_correspondence);
-        Substitution { to, from }
+        Substitution { to, from, origins: substitution.origins.clone() }
     }
 
     pub fn apply_substitution(src: &mut Substitution<C>, change: &Substitution<C>, field: &BigInt) {
+        if src.to.contains_key(&change.from) {
+            merge_raw_origins(&mut src.origins, &change.origins);
+        }
         raw_substitution(&mut src.to, change, field);
     }
 
@@ -983,6 +987,10 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
         &self.to
     }
 
+    pub fn origins(&self) -> &Vec<usize> {
+        &self.origins
+    }
+
     pub fn take_cloned_signals(&self) -> HashSet<C> {
         let cq: C = ArithmeticExpression::constant_coefficient();
         let mut signals = HashSet::new();
@@ -1029,7 +1037,7 @@ impl Substitution<usize> {
         debug_assert_ne!(self.from, constant);
         let from = self.from + offset;
         let to = apply_raw_offset(&self.to, offset);
-        Substitution { from, to }
+        Substitution { from, to, origins: self.origins.clone() }
     }
 }
 
@@ -1045,11 +1053,13 @@ where
     pub(crate) a: HashMap<C, BigInt>,
     pub(crate) b: HashMap<C, BigInt>,
     pub(crate) c: HashMap<C, BigInt>,
+    // ids of the source constraints this one was derived from
+    pub(crate) origins: Vec<usize>,
 }
 
 impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
     fn new(a: HashMap<C, BigInt>, b: HashMap<C, BigInt>, c: HashMap<C, BigInt>) -> Constraint<C> {
-        Constraint { a, b, c }
+        Constraint { a, b, c, origins: Vec::new() }
     }
 
     pub fn empty() -> Constraint<C> {
@@ -1083,7 +1093,9 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         let a = apply_raw_correspondence(&constraint.a, symbol_correspondence);
         let b = apply_raw_correspondence(&constraint.b, symbol_correspondence);
         let c = apply_raw_correspondence(&constraint.c, symbol_correspondence);
-        Constraint::new(a, b, c)
+        let mut new = Constraint::new(a, b, c);
+        new.origins = constraint.origins.clone();
+        new
     }
 
     // Constraint simplifications
@@ -1100,7 +1112,7 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         debug_assert!(Constraint::is_linear(&constraint));
         debug_assert!(constraint.c.contains_key(signal));
         let raw_expression = Constraint::clear_signal(constraint.c, &signal, field);
-        Substitution { from: signal.clone(), to: raw_expression }
+        Substitution { from: signal.clone(), to: raw_expression, origins: constraint.origins }
     }
 
     pub fn clear_signal_from_linear_not_normalized(
@@ -1111,7 +1123,7 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         debug_assert!(Constraint::is_linear(&constraint));
         debug_assert!(constraint.c.contains_key(signal));
         let (coefficient, raw_expression) = Constraint::clear_signal_not_normalized(constraint.c, &signal, field);
-        (coefficient, Substitution {from: signal.clone(), to: raw_expression})
+        (coefficient, Substitution {from: signal.clone(), to: raw_expression, origins: constraint.origins})
     }
 
     pub fn take_cloned_signals(&self) -> HashSet<C> {
@@ -1179,6 +1191,10 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         substitution: &Substitution<C>,
         field: &BigInt,
     ) {
+        let from = &substitution.from;
+        if constraint.a.contains_key(from) || constraint.b.contains_key(from) || constraint.c.contains_key(from) {
+            constraint.merge_origins(&substitution.origins);
+        }
         raw_substitution(&mut constraint.a, substitution, field);
         raw_substitution(&mut constraint.b, substitution, field);
         raw_substitution(&mut constraint.c, substitution, field);
@@ -1216,6 +1232,18 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         &self.c
     }
 
+    pub fn origins(&self) -> &Vec<usize> {
+        &self.origins
+    }
+
+    pub fn set_origins(&mut self, origins: Vec<usize>) {
+        self.origins = origins;
+    }
+
+    pub fn merge_origins(&mut self, origins: &[usize]) {
+        merge_raw_origins(&mut self.origins, origins);
+    }
+
     pub fn is_equality(&self, field: &BigInt) -> bool {
         signal_equals_signal(&self.a, &self.b, &self.c, field)
     }
@@ -1257,13 +1285,22 @@ impl Constraint<usize> {
         let a = apply_raw_offset(&self.a, offset);
         let b = apply_raw_offset(&self.b, offset);
         let c = apply_raw_offset(&self.c, offset);
-        Constraint::new(a, b, c)
+        let mut new = Constraint::new(a, b, c);
+        new.origins = self.origins.clone();
+        new
+    }
+    pub fn apply_origin_offset(&mut self, offset: usize) {
+        for origin in &mut self.origins {
+            *origin += offset;
+        }
     }
     pub fn apply_witness(&self, witness: &Vec<usize>) -> Constraint<usize> {
         let a = apply_vectored_correspondence(&self.a, witness);
         let b = apply_vectored_correspondence(&self.b, witness);
         let c = apply_vectored_correspondence(&self.c, witness);
-        Constraint::new(a, b, c)
+        let mut new = Constraint::new(a, b, c);
+        new.origins = self.origins.clone();
+        new
     }
 }
 
@@ -1302,6 +1339,12 @@ where
     coefficients_as_correspondence
 }
 
+pub(crate) fn merge_raw_origins(origins: &mut Vec<usize>, other: &[usize]) {
+    origins.extend_from_slice(other);
+    origins.sort_unstable();
+    origins.dedup();
+}
+
 fn apply_raw_offset(h: &HashMap<usize, BigInt>, offset: usize) -> HashMap<usize, BigInt> {
     let mut new = HashMap::new();
     let constant: usize = Constraint::constant_coefficient();
@@ -1529,4 +1572,39 @@ mod test {
         assert_eq!(*y_c, expected_y_c);
         assert_eq!(*constant_c, expected_constant_c);
     }
+
+    #[test]
+    fn algebra_substitution_merges_origins() {
+        let field = BigInt::parse_bytes(FIELD.as_bytes(), 10)
+            .expect("generating the big int was not possible");
+        let x = 1;
+        let y = 2;
+        let z = 3;
+
+        // constraint: x * y = 0, written at the origin 1
+        let mut a = HashMap::new();
+        a.insert(x, BigInt::from(1));
+        let mut b = HashMap::new();
+        b.insert(y, BigInt::from(1));
+        let mut constraint = C::new(a, b, HashMap::new());
+        constraint.set_origins(vec![1]);
+
+        // substitution: x = y, written at the origin 0
+        let mut x_to_y = S::new(x, A::Signal { symbol: y }).unwrap();
+        x_to_y.origins = vec![0];
+        C::apply_substitution(&mut constraint, &x_to_y, &field);
+        assert_eq!(*constraint.origins(), vec![0, 1]);
+
+        // a substitution of a signal that does not appear keeps the origins
+        let mut z_to_y = S::new(z, A::Signal { symbol: y }).unwrap();
+        z_to_y.origins = vec![2];
+        C::apply_substitution(&mut constraint, &z_to_y, &field);
+        assert_eq!(*constraint.origins(), vec![0, 1]);
+
+        // substitution: y = z, merged into x = y
+        let mut y_to_z = S::new(y, A::Signal { symbol: z }).unwrap();
+        y_to_z.origins = vec![3];
+        S::apply_substitution(&mut x_to_y, &y_to_z, &field);
+        assert_eq!(*x_to_y.origins(), vec![0, 3]);
+    }
 }
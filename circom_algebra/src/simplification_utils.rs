@@ -210,11 +210,13 @@ fn treat_constraint_1(
             break;
         }
         let in_conflict = in_conflict.unwrap();
+        let origins = merge_origins(&substitution, &in_conflict);
         let right = S::decompose(in_conflict).1;
         let left = S::decompose(substitution).1;
         let merge = A::sub(&left, &right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
         C::remove_zero_value_coefficients(&mut work);
+        C::set_origins(&mut work, origins);
     }
 }
 
@@ -244,6 +246,7 @@ fn treat_constraint_2(
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merge_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -253,6 +256,7 @@ fn treat_constraint_2(
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
         C::remove_zero_value_coefficients(&mut work);
+        C::set_origins(&mut work, origins);
     }
 }
 
@@ -281,6 +285,7 @@ fn treat_constraint_3(
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merge_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -290,6 +295,7 @@ fn treat_constraint_3(
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
         C::remove_zero_value_coefficients(&mut work);
+        C::set_origins(&mut work, origins);
     }
 }
 
@@ -336,6 +342,7 @@ fn treat_constraint_4(
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merge_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -345,9 +352,16 @@ fn treat_constraint_4(
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
         C::remove_zero_value_coefficients(&mut work);
+        C::set_origins(&mut work, origins);
     }
 }
 
+fn merge_origins(left: &S, right: &S) -> Vec<usize> {
+    let mut origins = left.origins.clone();
+    crate::algebra::merge_raw_origins(&mut origins, &right.origins);
+    origins
+}
+
 #[allow(dead_code)]
 fn take_signal_1(signals: &SignalDefinition, constraint: &C) -> Option<usize> {
     let mut ret = Option::None;
@@ -429,7 +443,8 @@ fn normalize_substitutions(substitutions: SHNotNormalized, field: &BigInt) -> SH
             &A::Number {value : inv.clone()}, 
             field
         );
-        let new_sub = S::new(signal.clone(), mult_by_inverse).unwrap(); 
+        let mut new_sub = S::new(signal.clone(), mult_by_inverse).unwrap();
+        new_sub.origins = sub.origins;
         tree.insert(signal, new_sub);
         i = i + 1;
     }
@@ -493,32 +508,29 @@ pub fn debug_substitution_check(substitutions: &HashMap<usize, S>) -> bool {
     result
 }
 
-pub fn fast_encoded_constraint_substitution(c: &mut C, enc: &HashMap<usize, A>, field: &BigInt) {
+pub fn fast_encoded_constraint_substitution(c: &mut C, enc: &HashMap<usize, S>, field: &BigInt) {
     let signals = C::take_cloned_signals(c);
     for signal in signals {
-        if let Some(expr) = HashMap::get(enc, &signal) {
-            let sub = S::new(signal, expr.clone()).unwrap();
-            C::apply_substitution(c, &sub, field);
+        if let Some(sub) = HashMap::get(enc, &signal) {
+            C::apply_substitution(c, sub, field);
         }
     }
 }
 
-pub fn fast_encoded_substitution_substitution(s: &mut S, enc: &HashMap<usize, A>, field: &BigInt) {
+pub fn fast_encoded_substitution_substitution(s: &mut S, enc: &HashMap<usize, S>, field: &BigInt) {
     let signals = S::take_cloned_signals(s);
     for signal in signals {
-        if let Some(expr) = HashMap::get(enc, &signal) {
-            let sub = S::new(signal, expr.clone()).unwrap();
-            S::apply_substitution(s, &sub, field);
+        if let Some(sub) = HashMap::get(enc, &signal) {
+            S::apply_substitution(s, sub, field);
         }
     }
     S::rmv_zero_coefficients(s)
 }
 
-pub fn build_encoded_fast_substitutions(fast_sub: LinkedList<S>) -> HashMap<usize, A> {
+pub fn build_encoded_fast_substitutions(fast_sub: LinkedList<S>) -> HashMap<usize, S> {
     let mut encoded = HashMap::with_capacity(LinkedList::len(&fast_sub));
     for sub in fast_sub {
-        let (from, to) = S::decompose(sub);
-        HashMap::insert(&mut encoded, from, to);
+        HashMap::insert(&mut encoded, *sub.from(), sub);
     }
     encoded
 }
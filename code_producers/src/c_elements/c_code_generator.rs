@@ -360,9 +360,77 @@ pub fn generate_my_trace() -> String {
     format!("{}->getTrace({})", CIRCOM_CALC_WIT, MY_ID)
 }
 
+pub fn build_trace_run(cmp_id: String) -> String {
+    format!("{}->traceRun({});", CIRCOM_CALC_WIT, cmp_id)
+}
+
+pub fn build_trace_signal(cmp_id: String, name: &str, value: String, size: usize) -> String {
+    format!("{}->traceSignal({}, \"{}\", {}, {});", CIRCOM_CALC_WIT, cmp_id, name, value, size)
+}
+
+pub fn build_trace_call(name: &str, args: String, size: usize) -> String {
+    format!("{}->traceCall({}, \"{}\", {}, {});", CIRCOM_CALC_WIT, MY_ID, name, args, size)
+}
+
+// Measures the run of a template or the call of a function until the end of
+// the block (only with --c-profile)
+pub fn build_profile_scope(profile_id: usize) -> String {
+    format!("Circom_ProfileScope profileScope({}, {});", CIRCOM_CALC_WIT, profile_id)
+}
+
+pub fn build_profile_field_op() -> String {
+    "circomFieldOps++;".to_string()
+}
+
+// Locates the following code in a line of the circom sources. The directives
+// without file keep the file of the previous one (only with --source-map)
+pub fn build_line_directive(line: usize, file: Option<&str>) -> String {
+    match file {
+        Some(file) => format!("#line {} \"{}\"", line, file.replace('\\', "\\\\").replace('"', "\\\"")),
+        None => format!("#line {}", line),
+    }
+}
+
+// Placeholder of the directive that locates the code that follows a template
+// or a function back in the generated file, whose lines are only known once
+// it is written
+pub const GENERATED_LINE_DIRECTIVE: &str = "#line generated";
+
+// Replaces the placeholders, and repeats the line of the circom sources before
+// every line of code of a statement, as a directive only locates the next line
+pub fn locate_generated_code(code: String, file: &str) -> String {
+    let mut located = String::with_capacity(2 * code.len());
+    let mut written_lines = 0;
+    let mut source_line: Option<usize> = None;
+    let mut after_directive = false;
+    for line in code.lines() {
+        if line == GENERATED_LINE_DIRECTIVE {
+            // the directive gives the number of the next line
+            located.push_str(&build_line_directive(written_lines + 2, Some(file)));
+            source_line = None;
+            after_directive = true;
+        } else if let Some(directive) = line.strip_prefix("#line ") {
+            located.push_str(line);
+            source_line = directive.split_whitespace().next().and_then(|n| n.parse().ok());
+            after_directive = true;
+        } else {
+            if let (Some(source_line), false) = (source_line, after_directive || line.trim().is_empty()) {
+                located.push_str(&build_line_directive(source_line, None));
+                located.push('\n');
+                written_lines += 1;
+            }
+            located.push_str(line);
+            after_directive = false;
+        }
+        located.push('\n');
+        written_lines += 1;
+    }
+    located
+}
+
 pub fn build_failed_assert_message(line: usize) -> String{
     
-    format!("std::cout << \"Failed assert in template/function \" << {} << \" line {}. \" <<  \"Followed trace of components: \" << {} << std::endl" ,
+    format!("circom_fail(CIRCOM_ERROR_ASSERT, \"Failed assert in template/function \" + {} + \" line {}. \" + \"Followed trace of components: \" + {})" ,
         MY_TEMPLATE_NAME,
         line,
         generate_my_trace()
@@ -472,6 +540,16 @@ pub fn generate_dat_witness_to_signal_list(signal_list: &Vec<usize>) -> Vec<u8>
 
 pub fn generate_dat_constant_list(producer: &CProducer, constant_list: &Vec<String>) -> Vec<u8> {
     let mut constant_list_data = vec![];
+    if producer.get_field_layout() == FieldLayout::Goldilocks64 {
+        let p = producer.get_prime().parse::<BigInt>().unwrap();
+        for s in constant_list {
+            let n = s.parse::<BigInt>().unwrap();
+            let (_, mut bytes) = (((n % &p) + &p) % &p).to_bytes_le();
+            bytes.resize(8, 0);
+            constant_list_data.append(&mut bytes);
+        }
+        return constant_list_data;
+    }
     for s in constant_list {
         //      For sort/long or short/montgomery
         let mut n = s.parse::<BigInt>().unwrap();
@@ -604,7 +682,9 @@ pub fn generate_dat_io_signals_info(
 
  */
 
-pub fn generate_dat_file(dat_file: &mut dyn Write, producer: &CProducer) -> std::io::Result<()> {
+// Tables of the circuit read by loadCircuitData, without the fingerprint
+pub fn generate_dat_tables(producer: &CProducer) -> Vec<u8> {
+    let mut dat_file = vec![];
     //let p = producer.get_prime().as_bytes();
     //let pl = p.len() as u32;
     //dfile.write_all(&pl.to_be_bytes())?;
@@ -616,21 +696,17 @@ pub fn generate_dat_file(dat_file: &mut dyn Write, producer: &CProducer) -> std:
     let hashmap = generate_dat_from_hash_map(&map); //bytes u64 --> u64
                                                     //let hml = 256 as u32;
                                                     //dfile.write_all(&hml.to_be_bytes())?;
-    dat_file.write_all(&hashmap)?;
-    dat_file.flush()?;
+    dat_file.extend(hashmap);
     let s = generate_dat_witness_to_signal_list(producer.get_witness_to_signal_list()); // list of bytes u64
                                                                                         //let sl = s.len() as u64; //8 bytes
                                                                                         //dfile.write_all(&sl.to_be_bytes())?;
-    dat_file.write_all(&s)?;
-    dat_file.flush()?;
+    dat_file.extend(s);
     let s = generate_dat_constant_list(producer, producer.get_field_constant_list()); // list of bytes Fr
-    dat_file.write_all(&s)?;
-    dat_file.flush()?;
+    dat_file.extend(s);
     //let ioml = producer.get_io_map().len() as u64;
     //dfile.write_all(&ioml.to_be_bytes())?;
     let iomap = generate_dat_io_signals_info(&producer, producer.get_io_map());
-    dat_file.write_all(&iomap)?;
-    dat_file.flush()?;
+    dat_file.extend(iomap);
     /*
         let ml = producer.get_message_list();
         let mll = ml.len() as u64;
@@ -643,9 +719,89 @@ pub fn generate_dat_file(dat_file: &mut dyn Write, producer: &CProducer) -> std:
             dfile.flush()?;
         }
     */
+    dat_file
+}
+
+// The .dat file starts with the fingerprint of its tables, which the
+// generated code checks to reject the data of other circuits
+pub fn dat_fingerprint(tables: &[u8]) -> u64 {
+    let mut hash: u64 = 0xCBF29CE484222325;
+    for byte in tables {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001B3);
+    }
+    hash
+}
+
+pub fn generate_dat_file(dat_file: &mut dyn Write, producer: &CProducer) -> std::io::Result<()> {
+    let tables = generate_dat_tables(producer);
+    dat_file.write_all(&dat_fingerprint(&tables).to_le_bytes())?;
+    dat_file.write_all(&tables)?;
+    dat_file.flush()?;
     Ok(())
 }
 
+// Fingerprint expected in the circuit data and, with --embed-dat, the
+// contents of the .dat file
+pub fn generate_circuit_data_def(producer: &CProducer) -> Vec<String> {
+    let tables = generate_dat_tables(producer);
+    let fingerprint = dat_fingerprint(&tables);
+    let mut instructions = vec![];
+    instructions.push(format!("u64 get_circuit_data_fingerprint() {{return 0x{:016x}ULL;}}\n", fingerprint));
+    if !producer.embeds_dat() {
+        instructions.push("u8 const *get_circuit_data() {return NULL;}\n".to_string());
+        instructions.push("u64 get_size_of_circuit_data() {return 0;}\n".to_string());
+        return instructions;
+    }
+    let mut data = fingerprint.to_le_bytes().to_vec();
+    data.extend(tables);
+    instructions.push(format!("static constexpr u8 circuitData[{}] = {{", data.len()));
+    for (i, row) in data.chunks(32).enumerate() {
+        let bytes: Vec<_> = row.iter().map(|b| format!("0x{:02x}", b)).collect();
+        let separator = if i == 0 { "" } else { "," };
+        instructions.push(format!("{}{}", separator, bytes.join(",")));
+    }
+    instructions.push("};".to_string());
+    instructions.push("u8 const *get_circuit_data() {return circuitData;}\n".to_string());
+    instructions.push(format!("u64 get_size_of_circuit_data() {{return {};}}\n", data.len()));
+    instructions
+}
+
+// Names of the entries of the profile: the template instances, as in
+// template_instance_list, followed by the functions
+pub fn generate_profile_def(producer: &CProducer, function_headers: &[String]) -> Vec<String> {
+    let mut instructions = vec![];
+    if !producer.needs_profile() {
+        instructions.push("uint get_size_of_profile() {return 0;}\n".to_string());
+        instructions.push("uint get_number_of_profiled_templates() {return 0;}\n".to_string());
+        instructions.push("const char *get_profile_name(uint i) {return NULL;}\n".to_string());
+        return instructions;
+    }
+    let names: Vec<_> = producer.get_template_instance_list().iter()
+        .chain(function_headers)
+        .map(|name| format!("\"{}\"", name))
+        .collect();
+    instructions.push(format!("static const char *profileNames[{}] = {{{}}};", names.len().max(1), names.join(",")));
+    instructions.push(format!("uint get_size_of_profile() {{return {};}}\n", names.len()));
+    instructions.push(format!("uint get_number_of_profiled_templates() {{return {};}}\n", producer.get_number_of_template_instances()));
+    instructions.push("const char *get_profile_name(uint i) {return profileNames[i];}\n".to_string());
+    instructions
+}
+
+// Schema of the inputs, used by the executable to validate the input files
+pub fn generate_input_schema_def(producer: &CProducer) -> Vec<String> {
+    // a JSON string is also a valid C string literal
+    let schema = serde_json::to_string(&producer.get_input_schema().to_string()).unwrap();
+    vec![format!("const char *get_input_schema() {{return {};}}\n", schema)]
+}
+
+pub fn generate_input_schema_file(c_folder: &PathBuf, run_name: &str, producer: &CProducer) -> std::io::Result<()> {
+    let mut file_path = c_folder.clone();
+    file_path.push(format!("{}.schema.json", run_name));
+    let schema = serde_json::to_string_pretty(&producer.get_input_schema()).unwrap();
+    std::fs::write(file_path, schema + "\n")
+}
+
 pub fn generate_function_list(_producer: &CProducer, list: &TemplateList) -> String {
     let mut func_list = "".to_string();
     if list.len() > 0 {
@@ -720,7 +876,6 @@ pub fn generate_fr_hpp_file(c_folder: &PathBuf, prime: &String) -> std::io::Resu
     let file = match prime.as_ref(){
         "bn128" => include_str!("bn128/fr.hpp"),
         "bls12381" => include_str!("bls12381/fr.hpp"),
-        "goldilocks" => include_str!("goldilocks/fr.hpp"),
         _ => unreachable!(),
     };
     for line in file.lines() {
@@ -759,7 +914,6 @@ pub fn generate_fr_cpp_file(c_folder: &PathBuf, prime: &String) -> std::io::Resu
     let file = match prime.as_ref(){
         "bn128" => include_str!("bn128/fr.cpp"),
         "bls12381" => include_str!("bls12381/fr.cpp"),
-        "goldilocks" => include_str!("goldilocks/fr.cpp"),
         _ => unreachable!(),
     };
     for line in file.lines() {
@@ -787,6 +941,40 @@ pub fn generate_calcwit_cpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
     Ok(())
 }
 
+pub fn generate_circom_witness_h_file(c_folder: &PathBuf) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let mut file_path = c_folder.clone();
+    file_path.push("circom_witness");
+    file_path.set_extension("h");
+    let file_name = file_path.to_str().unwrap();
+    let mut c_file = BufWriter::new(File::create(file_name).unwrap());
+    let mut code = "".to_string();
+    let file = include_str!("common/circom_witness.h");
+    for line in file.lines() {
+        code = format!("{}{}\n", code, line);
+    }
+    c_file.write_all(code.as_bytes())?;
+    c_file.flush()?;
+    Ok(())
+}
+
+pub fn generate_circom_witness_cpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let mut file_path = c_folder.clone();
+    file_path.push("circom_witness");
+    file_path.set_extension("cpp");
+    let file_name = file_path.to_str().unwrap();
+    let mut c_file = BufWriter::new(File::create(file_name).unwrap());
+    let mut code = "".to_string();
+    let file = include_str!("common/circom_witness.cpp");
+    for line in file.lines() {
+        code = format!("{}{}\n", code, line);
+    }
+    c_file.write_all(code.as_bytes())?;
+    c_file.flush()?;
+    Ok(())
+}
+
 pub fn generate_fr_asm_file(c_folder: &PathBuf, prime: &String) -> std::io::Result<()> {
     use std::io::BufWriter;
     let mut file_path = c_folder.clone();
@@ -798,7 +986,6 @@ pub fn generate_fr_asm_file(c_folder: &PathBuf, prime: &String) -> std::io::Resu
     let file = match prime.as_ref(){
         "bn128" => include_str!("bn128/fr.asm"),
         "bls12381" => include_str!("bls12381/fr.asm"),
-        "goldilocks" => include_str!("goldilocks/fr.asm"),
         _ => unreachable!(),
     };    
     for line in file.lines() {
@@ -809,6 +996,94 @@ pub fn generate_fr_asm_file(c_folder: &PathBuf, prime: &String) -> std::io::Resu
     Ok(())
 }
 
+// Limbs of 64 bits, least significant first, of a constant of the portable field
+fn portable_fr_limbs(num: &BigInt, n64: usize) -> String {
+    let (_, mut bytes) = num.to_bytes_le();
+    bytes.resize(n64 * 8, 0);
+    let limbs: Vec<String> = bytes
+        .chunks(8)
+        .map(|limb| {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(limb);
+            format!("0x{:016x}", u64::from_le_bytes(value))
+        })
+        .collect();
+    limbs.join(", ")
+}
+
+fn portable_fr_data(prime: &str) -> serde_json::Value {
+    let q = prime.parse::<BigInt>().unwrap();
+    let n_bits = q.bits();
+    let n64 = n_bits.div_ceil(64);
+    let r = BigInt::from(1) << (64 * n64);
+    let r2 = (&r * &r) % &q;
+    let r3 = (&r2 * &r) % &q;
+    let half = (&q - BigInt::from(1)) / BigInt::from(2);
+    // np = -q^-1 mod 2^64, each Newton step doubles the correct bits
+    let (_, bytes) = q.to_bytes_le();
+    let mut q0 = [0u8; 8];
+    q0[..bytes.len().min(8)].copy_from_slice(&bytes[..bytes.len().min(8)]);
+    let q0 = u64::from_le_bytes(q0);
+    let mut inv: u64 = 1;
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(q0.wrapping_mul(inv)));
+    }
+    let lbo_mask = if n_bits % 64 == 0 { u64::MAX } else { (1u64 << (n_bits % 64)) - 1 };
+    json!({
+        "prime": prime,
+        "n64": n64,
+        "max_bits": n_bits,
+        "n_bits": n_bits,
+        "q": portable_fr_limbs(&q, n64),
+        "half": portable_fr_limbs(&half, n64),
+        "r2": portable_fr_limbs(&r2, n64),
+        "r3": portable_fr_limbs(&r3, n64),
+        "np": format!("0x{:016x}", inv.wrapping_neg()),
+        "lbo_mask": format!("0x{:016x}", lbo_mask),
+    })
+}
+
+fn generate_portable_fr_file(c_folder: &PathBuf, file_name: &str, template: &str, prime: &str) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let code = handlebars::Handlebars::new()
+        .render_template(template, &portable_fr_data(prime))
+        .expect("must render");
+    let mut file_path = c_folder.clone();
+    file_path.push(file_name);
+    let mut c_file = BufWriter::new(File::create(file_path)?);
+    c_file.write_all(code.as_bytes())?;
+    c_file.flush()?;
+    Ok(())
+}
+
+// Field implementation in plain C++ for any prime, used instead of fr.asm
+pub fn generate_portable_fr_hpp_file(c_folder: &PathBuf, prime: &str) -> std::io::Result<()> {
+    generate_portable_fr_file(c_folder, "fr.hpp", include_str!("portable/fr.hpp"), prime)
+}
+
+pub fn generate_portable_fr_cpp_file(c_folder: &PathBuf, prime: &str) -> std::io::Result<()> {
+    generate_portable_fr_file(c_folder, "fr.cpp", include_str!("portable/fr.cpp"), prime)
+}
+
+fn generate_fr_file(c_folder: &PathBuf, file_name: &str, code: &str) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let mut file_path = c_folder.clone();
+    file_path.push(file_name);
+    let mut c_file = BufWriter::new(File::create(file_path)?);
+    c_file.write_all(code.as_bytes())?;
+    c_file.flush()?;
+    Ok(())
+}
+
+// Field implementation of Goldilocks with the canonical value in a single u64
+pub fn generate_goldilocks64_fr_hpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
+    generate_fr_file(c_folder, "fr.hpp", include_str!("goldilocks64/fr.hpp"))
+}
+
+pub fn generate_goldilocks64_fr_cpp_file(c_folder: &PathBuf) -> std::io::Result<()> {
+    generate_fr_file(c_folder, "fr.cpp", include_str!("goldilocks64/fr.cpp"))
+}
+
 pub fn generate_make_file(
     c_folder: &PathBuf,
     run_name: &str,
@@ -825,6 +1100,8 @@ pub fn generate_make_file(
             &json!({
                 "run_name": run_name,
                 "has_parallelism": producer.has_parallelism,
+                "portable": !producer.needs_fr_asm(),
+                "library": producer.has_library(),
             }),
         )
         .expect("must render");
@@ -904,4 +1181,34 @@ mod tests {
         let _rc = generate_c_file(pathc, &producer);
         assert!(true);
     }
+
+    #[test]
+    fn every_line_of_a_statement_is_located() {
+        let code = vec![
+            "void f(){".to_string(),
+            build_line_directive(4, Some("a.circom")),
+            "{\nx = 1;\n}".to_string(),
+            "}".to_string(),
+            GENERATED_LINE_DIRECTIVE.to_string(),
+            "void g(){}".to_string(),
+        ];
+        let located = locate_generated_code(merge_code(code), "c.cpp");
+        assert_eq!(
+            located,
+            "void f(){\n#line 4 \"a.circom\"\n{\n#line 4\nx = 1;\n#line 4\n}\n#line 4\n}\n#line 11 \"c.cpp\"\nvoid g(){}\n"
+        );
+    }
+
+    #[test]
+    fn portable_fr_constants_match_the_asm() {
+        let producer = create_producer();
+        let data = portable_fr_data(producer.get_prime());
+        assert_eq!(data["n64"], 4);
+        assert_eq!(data["n_bits"], 254);
+        assert_eq!(data["half"], "0xa1f0fac9f8000000, 0x9419f4243cdcb848, 0xdc2822db40c0ac2e, 0x183227397098d014");
+        assert_eq!(data["r2"], "0x1bb8e645ae216da7, 0x53fe3ab1e35c59e3, 0x8c49833d53bb8085, 0x0216d0b17f4e44a5");
+        assert_eq!(data["r3"], "0x5e94d8e1b4bf0040, 0x2a489cbe1cfbb6b8, 0x893cc664a19fcfed, 0x0cf8594b7fcc657c");
+        assert_eq!(data["lbo_mask"], "0x3fffffffffffffff");
+        assert_eq!(data["np"], "0xc2e1f593efffffff");
+    }
 }
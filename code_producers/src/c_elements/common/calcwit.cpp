@@ -1,6 +1,9 @@
 #include <iomanip>
+#include <iostream>
 #include <sstream>
+#include <string.h>
 #include <assert.h>
+#include <algorithm>
 #include "calcwit.hpp"
 
 extern void run(Circom_CalcWit* ctx);
@@ -23,6 +26,168 @@ u64 fnv1a(std::string s) {
   return hash;
 }
 
+#ifdef CIRCOM_LIBRARY
+bool circomThrowFailures = true;
+#else
+bool circomThrowFailures = false;
+#endif
+
+void circom_fail(int code, std::string const &message) {
+  if (circomThrowFailures) throw Circom_Failure(code, message);
+  // failed asserts have always been reported in the standard output
+  if (code == CIRCOM_ERROR_ASSERT) std::cout << message << std::endl;
+  else std::cerr << message << std::endl;
+  // keeps the end of the trace, which leads to the failure
+  fflush(NULL);
+  assert(false);
+  abort();
+}
+
+thread_local u64 circomFieldOps = 0;
+
+// the innermost scope of the current thread
+static thread_local Circom_ProfileScope *currentProfileScope = NULL;
+
+Circom_Profile::Circom_Profile() {
+  entries = new Circom_ProfileEntry[get_size_of_profile()]();
+}
+
+Circom_Profile::~Circom_Profile() {
+  delete [] entries;
+}
+
+void Circom_Profile::add(Circom_Profile const &other) {
+  for (uint i = 0; i < get_size_of_profile(); i++) {
+    entries[i].calls += other.entries[i].calls;
+    entries[i].totalTime += other.entries[i].totalTime;
+    entries[i].selfTime += other.entries[i].selfTime;
+    entries[i].fieldOps += other.entries[i].fieldOps;
+  }
+}
+
+void Circom_Profile::write(std::ostream &out) const {
+  std::vector<uint> called;
+  for (uint i = 0; i < get_size_of_profile(); i++) {
+    if (entries[i].calls > 0) called.push_back(i);
+  }
+  std::stable_sort(called.begin(), called.end(), [this](uint a, uint b) {
+    return entries[a].selfTime > entries[b].selfTime;
+  });
+  out << std::left << std::setw(10) << "kind" << std::right
+      << std::setw(12) << "calls" << std::setw(14) << "total ms" << std::setw(14) << "self ms"
+      << std::setw(16) << "field ops" << "  name" << std::endl;
+  out << std::fixed << std::setprecision(3);
+  for (uint i : called) {
+    out << std::left << std::setw(10) << (i < get_number_of_profiled_templates() ? "template" : "function") << std::right
+        << std::setw(12) << entries[i].calls
+        << std::setw(14) << entries[i].totalTime / 1e6
+        << std::setw(14) << entries[i].selfTime / 1e6
+        << std::setw(16) << entries[i].fieldOps
+        << "  " << get_profile_name(i) << std::endl;
+  }
+}
+
+Circom_ProfileScope::Circom_ProfileScope(Circom_CalcWit *ctx, uint id) {
+  entry = &ctx->profile->entries[id];
+  parent = currentProfileScope;
+  outermost = true;
+  for (Circom_ProfileScope *scope = parent; scope != NULL && outermost; scope = scope->parent) {
+    outermost = scope->entry != entry;
+  }
+  currentProfileScope = this;
+  nestedTime = 0;
+  nestedFieldOps = 0;
+  startFieldOps = circomFieldOps;
+  start = std::chrono::steady_clock::now();
+}
+
+Circom_ProfileScope::~Circom_ProfileScope() {
+  u64 time = std::chrono::duration_cast<std::chrono::nanoseconds>(std::chrono::steady_clock::now() - start).count();
+  u64 fieldOps = circomFieldOps - startFieldOps;
+  entry->calls++;
+  if (outermost) entry->totalTime += time;
+  entry->selfTime += time - nestedTime;
+  entry->fieldOps += fieldOps - nestedFieldOps;
+  if (parent != NULL) {
+    parent->nestedTime += time;
+    parent->nestedFieldOps += fieldOps;
+  }
+  currentProfileScope = parent;
+}
+
+static void readCircuitData(void *dest, u8 const *bdata, u64 size, u64 pos, u64 len) {
+  if (pos + len > size) circom_fail(CIRCOM_ERROR_DATA, "The circuit data is too short");
+  memcpy(dest, bdata + pos, len);
+}
+
+Circom_Circuit* loadCircuitData(u8 const *bdata, u64 size) {
+    Circom_Circuit *circuit = new Circom_Circuit;
+    try {
+      u64 fingerprint;
+      readCircuitData((void *)&fingerprint, bdata, size, 0, sizeof(u64));
+      if (fingerprint != get_circuit_data_fingerprint()) {
+        circom_fail(CIRCOM_ERROR_DATA, "The circuit data belongs to another compilation of the circuit");
+      }
+
+      circuit->InputHashMap = new HashSignalInfo[get_size_of_input_hashmap()];
+      u64 inisize = sizeof(u64);
+      u64 dsize = get_size_of_input_hashmap()*sizeof(HashSignalInfo);
+      readCircuitData((void *)(circuit->InputHashMap), bdata, size, inisize, dsize);
+
+      circuit->witness2SignalList = new u64[get_size_of_witness()];
+      inisize += dsize;
+      dsize = get_size_of_witness()*sizeof(u64);
+      readCircuitData((void *)(circuit->witness2SignalList), bdata, size, inisize, dsize);
+
+      circuit->circuitConstants = new FrElement[get_size_of_constants()];
+      inisize += dsize;
+      dsize = get_size_of_constants()*sizeof(FrElement);
+      readCircuitData((void *)(circuit->circuitConstants), bdata, size, inisize, dsize);
+
+      if (get_size_of_io_map()>0) {
+        std::vector<u32> index(get_size_of_io_map());
+        inisize += dsize;
+        dsize = get_size_of_io_map()*sizeof(u32);
+        readCircuitData((void *)index.data(), bdata, size, inisize, dsize);
+        inisize += dsize;
+        for (uint i = 0; i < get_size_of_io_map(); i++) {
+          IODefPair p;
+          readCircuitData((void *)&p.len, bdata, size, inisize, sizeof(u32));
+          inisize += sizeof(u32);
+          p.defs = new IODef[p.len]();
+          // registered before reading the definitions to free them on failures
+          circuit->templateInsId2IOSignalInfo[index[i]] = p;
+          for (u32 j = 0; j < p.len; j++) {
+            readCircuitData((void *)&p.defs[j].offset, bdata, size, inisize, sizeof(u32));
+            readCircuitData((void *)&p.defs[j].len, bdata, size, inisize + sizeof(u32), sizeof(u32));
+            inisize += 2*sizeof(u32);
+            p.defs[j].lengths = new u32[p.defs[j].len];
+            readCircuitData((void *)p.defs[j].lengths, bdata, size, inisize, p.defs[j].len*sizeof(u32));
+            inisize += p.defs[j].len*sizeof(u32);
+          }
+        }
+      }
+    } catch (...) {
+      // only reached in the library, where the failures throw
+      freeCircuit(circuit);
+      throw;
+    }
+    return circuit;
+}
+
+void freeCircuit(Circom_Circuit *circuit) {
+  for (auto &it : circuit->templateInsId2IOSignalInfo) {
+    for (u32 j = 0; j < it.second.len; j++) {
+      delete [] it.second.defs[j].lengths;
+    }
+    delete [] it.second.defs;
+  }
+  delete [] circuit->InputHashMap;
+  delete [] circuit->witness2SignalList;
+  delete [] circuit->circuitConstants;
+  delete circuit;
+}
+
 Circom_CalcWit::Circom_CalcWit (Circom_Circuit *aCircuit, uint maxTh) {
   circuit = aCircuit;
   inputSignalAssignedCounter = get_main_input_signal_no();
@@ -41,10 +206,42 @@ Circom_CalcWit::Circom_CalcWit (Circom_Circuit *aCircuit, uint maxTh) {
   // parallelism
   numThread = 0;
 
+  traceFile = NULL;
+
+  profile = get_size_of_profile() > 0 ? new Circom_Profile() : NULL;
 }
 
 Circom_CalcWit::~Circom_CalcWit() {
-  // ...
+  freeComponents();
+  delete [] componentMemory;
+  delete [] signalValues;
+  delete [] inputSignalAssigned;
+  delete profile;
+}
+
+// the arrays allocated when the components are created
+void Circom_CalcWit::freeComponents() {
+  for (uint i = 0; i < get_number_of_components(); i++) {
+    delete [] componentMemory[i].subcomponents;
+    delete [] componentMemory[i].outputIsSet;
+    delete [] componentMemory[i].mutexes;
+    delete [] componentMemory[i].cvs;
+    delete [] componentMemory[i].sbct;
+    componentMemory[i].subcomponents = NULL;
+    componentMemory[i].outputIsSet = NULL;
+    componentMemory[i].mutexes = NULL;
+    componentMemory[i].cvs = NULL;
+    componentMemory[i].sbct = NULL;
+  }
+}
+
+void Circom_CalcWit::reset() {
+  freeComponents();
+  inputSignalAssignedCounter = get_main_input_signal_no();
+  for (uint i = 0; i < inputSignalAssignedCounter; i++) {
+    inputSignalAssigned[i] = false;
+  }
+  numThread = 0;
 }
 
 uint Circom_CalcWit::getInputSignalHashPosition(u64 h) {
@@ -56,39 +253,48 @@ uint Circom_CalcWit::getInputSignalHashPosition(u64 h) {
     while (pos != inipos) {
       if (circuit->InputHashMap[pos].hash==h) return pos;
       if (circuit->InputHashMap[pos].hash==0) {
-	fprintf(stderr, "Signal not found\n");
-	assert(false);
+	circom_fail(CIRCOM_ERROR_INPUT, "Signal not found");
       }
       pos = (pos+1)%n; 
     }
-    fprintf(stderr, "Signals not found\n");
-    assert(false);
+    circom_fail(CIRCOM_ERROR_INPUT, "Signals not found");
   }
   return pos;
 }
 
 void Circom_CalcWit::setInputSignal(u64 h, uint i,  FrElement & val){
   if (inputSignalAssignedCounter == 0) {
-    fprintf(stderr, "No more signals to be assigned\n");
-    assert(false);
+    circom_fail(CIRCOM_ERROR_INPUT, "No more signals to be assigned");
   }
   uint pos = getInputSignalHashPosition(h);
   if (i >= circuit->InputHashMap[pos].signalsize) {
-    fprintf(stderr, "Input signal array access exceeds the size\n");
-    assert(false);
+    circom_fail(CIRCOM_ERROR_INPUT, "Input signal array access exceeds the size");
   }
-  
-  uint si = circuit->InputHashMap[pos].signalid+i;
+  assignInputSignal(circuit->InputHashMap[pos].signalid+i, val);
+}
+
+void Circom_CalcWit::setInputSignalAt(uint i, FrElement & val){
+  if (i >= get_main_input_signal_no()) {
+    circom_fail(CIRCOM_ERROR_INPUT, "Input signal index exceeds the number of inputs");
+  }
+  assignInputSignal(get_main_input_signal_start()+i, val);
+}
+
+void Circom_CalcWit::assignInputSignal(uint si, FrElement & val){
   if (inputSignalAssigned[si-get_main_input_signal_start()]) {
-    fprintf(stderr, "Signal assigned twice: %d\n", si);
-    assert(false);
+    circom_fail(CIRCOM_ERROR_INPUT, "Signal assigned twice: " + std::to_string(si));
   }
   signalValues[si] = val;
   inputSignalAssigned[si-get_main_input_signal_start()] = true;
   inputSignalAssignedCounter--;
-  if (inputSignalAssignedCounter == 0) {
-    run(this);
+}
+
+void Circom_CalcWit::calculate() {
+  if (inputSignalAssignedCounter != 0) {
+    circom_fail(CIRCOM_ERROR_MISSING_INPUTS, "Not all inputs have been set. Only " + std::to_string(get_main_input_signal_no()-inputSignalAssignedCounter) + " out of " + std::to_string(get_main_input_signal_no()));
   }
+  run(this);
+  if (traceFile != NULL) fflush(traceFile);
 }
 
 u64 Circom_CalcWit::getInputSignalSize(u64 h) {
@@ -120,3 +326,58 @@ std::string Circom_CalcWit::generate_position_array(uint* dimensions, uint size_
   return positions;
 }
 
+
+void Circom_CalcWit::setTrace(FILE *file, std::vector<std::string> filters) {
+  traceFile = file;
+  traceFilters = filters;
+}
+
+bool Circom_CalcWit::isTraced(std::string const &path) {
+  if (traceFile == NULL) return false;
+  if (traceFilters.empty()) return true;
+  // a filter keeps a component and the components inside it: main.m[1]
+  // keeps main.m[1].c but not main.m[10]
+  for (auto &prefix : traceFilters) {
+    if (path.compare(0, prefix.size(), prefix) != 0) continue;
+    if (path.size() == prefix.size() || path[prefix.size()] == '.' || path[prefix.size()] == '[') return true;
+  }
+  return false;
+}
+
+void Circom_CalcWit::writeTrace(std::string const &line) {
+  // components may run in parallel, keep each line in one piece
+  std::lock_guard<std::mutex> guard(traceMutex);
+  fprintf(traceFile, "%s\n", line.c_str());
+}
+
+void Circom_CalcWit::traceRun(u64 id_cmp) {
+  if (traceFile == NULL) return;
+  std::string path = getTrace(id_cmp);
+  if (!isTraced(path)) return;
+  writeTrace("R " + path);
+}
+
+void Circom_CalcWit::traceSignal(u64 id_cmp, const char *name, PFrElement values, uint size) {
+  if (traceFile == NULL) return;
+  std::string path = getTrace(id_cmp);
+  if (!isTraced(path)) return;
+  for (uint i = 0; i < size; i++) {
+    char *value = Fr_element2str(&values[i]);
+    writeTrace("S " + path + " " + name + " " + std::to_string(&values[i] - signalValues) + " " + value);
+    delete [] value;
+  }
+}
+
+void Circom_CalcWit::traceCall(u64 id_cmp, const char *name, PFrElement args, uint size) {
+  if (traceFile == NULL) return;
+  std::string path = getTrace(id_cmp);
+  if (!isTraced(path)) return;
+  std::string line = "F " + path + " " + name + "(";
+  for (uint i = 0; i < size; i++) {
+    char *value = Fr_element2str(&args[i]);
+    if (i > 0) line += ",";
+    line += value;
+    delete [] value;
+  }
+  writeTrace(line + ")");
+}
@@ -6,14 +6,65 @@
 #include <functional>
 #include <atomic>
 #include <memory>
+#include <vector>
+#include <string>
+#include <stdexcept>
+#include <ostream>
+#include <chrono>
 
 #include "circom.hpp"
 #include "fr.hpp"
 
 #define NMUTEXES 12 //512
 
+// Codes of the failures, the same of circom_witness.h
+#define CIRCOM_ERROR_DATA 1
+#define CIRCOM_ERROR_INPUT 2
+#define CIRCOM_ERROR_MISSING_INPUTS 3
+#define CIRCOM_ERROR_ASSERT 4
+#define CIRCOM_ERROR_STATE 5
+#define CIRCOM_ERROR_FILE 6
+
+class Circom_Failure : public std::runtime_error {
+public:
+  int code;
+  Circom_Failure(int aCode, std::string const &message) : std::runtime_error(message), code(aCode) {}
+};
+
+// Throws a Circom_Failure if circomThrowFailures is set, which is always the
+// case in the library (compiled with CIRCOM_LIBRARY) and in the batches of the
+// executable, and otherwise prints the message and aborts
+[[noreturn]] void circom_fail(int code, std::string const &message);
+extern bool circomThrowFailures;
+
 u64 fnv1a(std::string s);
 
+// Calls, times in nanoseconds and field operations of a template or function
+struct Circom_ProfileEntry {
+  std::atomic<u64> calls;
+  std::atomic<u64> totalTime;
+  std::atomic<u64> selfTime;
+  std::atomic<u64> fieldOps;
+};
+
+// Profile of the witness calculations of circuits compiled with --c-profile
+class Circom_Profile {
+public:
+  Circom_ProfileEntry *entries;
+
+  Circom_Profile();
+  ~Circom_Profile();
+  void add(Circom_Profile const &other);
+  // report of the entries that were called, by decreasing self time
+  void write(std::ostream &out) const;
+};
+
+// field operations done by the current thread
+extern thread_local u64 circomFieldOps;
+
+Circom_Circuit* loadCircuitData(u8 const *bdata, u64 size);
+void freeCircuit(Circom_Circuit *circuit);
+
 class Circom_CalcWit {
 
   bool *inputSignalAssigned;
@@ -29,6 +80,9 @@ public:
   std::map<u32,IODefPair> templateInsId2IOSignalInfo; 
   std::string* listOfTemplateMessages; 
 
+  // NULL unless the circuit was compiled with --c-profile
+  Circom_Profile *profile;
+
   // parallelism
   std::mutex numThreadMutex;
   std::condition_variable ntcvs;
@@ -42,6 +96,15 @@ public:
 
   // Public functions
   void setInputSignal(u64 h, uint i, FrElement &val);
+
+  // i-th signal of the main inputs, with the arrays flattened in order of declaration
+  void setInputSignalAt(uint i, FrElement &val);
+
+  // runs the circuit once all the inputs are set
+  void calculate();
+
+  // prepares the memory of the last calculation to compute another witness
+  void reset();
   
   u64 getInputSignalSize(u64 h);
 
@@ -57,12 +120,45 @@ public:
 
   std::string generate_position_array(uint* dimensions, uint size_dimensions, uint index);
 
+  // execution trace (only called by circuits compiled with --trace)
+  void setTrace(FILE *file, std::vector<std::string> filters);
+  void traceRun(u64 id_cmp);
+  void traceSignal(u64 id_cmp, const char *name, PFrElement values, uint size);
+  void traceCall(u64 id_cmp, const char *name, PFrElement args, uint size);
+
 private:
   
   uint getInputSignalHashPosition(u64 h);
+  void freeComponents();
+  void assignInputSignal(uint si, FrElement &val);
+
+  FILE *traceFile;
+  std::vector<std::string> traceFilters;
+  std::mutex traceMutex;
+
+  bool isTraced(std::string const &path);
+  void writeTrace(std::string const &line);
 
 };
 
 typedef void (*Circom_TemplateFunction)(uint __cIdx, Circom_CalcWit* __ctx); 
 
+// Measures a run of a template or a call of a function until it is destroyed.
+// The runs and calls nested in the same thread are excluded from its self
+// time and field operations, and a recursive call only adds to the total time
+// of the outermost one.
+class Circom_ProfileScope {
+  Circom_ProfileEntry *entry;
+  Circom_ProfileScope *parent;
+  std::chrono::steady_clock::time_point start;
+  u64 startFieldOps;
+  u64 nestedTime;
+  u64 nestedFieldOps;
+  bool outermost;
+
+public:
+  Circom_ProfileScope(Circom_CalcWit *ctx, uint id);
+  ~Circom_ProfileScope();
+};
+
 #endif // CIRCOM_CALCWIT_H
@@ -2,7 +2,6 @@
 #define __CIRCOM_H
 
 #include <map>
-#include <gmp.h>
 #include <mutex>
 #include <condition_variable>
 #include <thread>
@@ -33,9 +32,9 @@ struct IODefPair {
 
 struct Circom_Circuit {
   //  const char *P;
-  HashSignalInfo* InputHashMap;
-  u64* witness2SignalList;
-  FrElement* circuitConstants;  
+  HashSignalInfo* InputHashMap = NULL;
+  u64* witness2SignalList = NULL;
+  FrElement* circuitConstants = NULL;  
   std::map<u32,IODefPair> templateInsId2IOSignalInfo;
 };
 
@@ -47,11 +46,11 @@ struct Circom_Component {
   std::string templateName;
   std::string componentName;
   u64 idFather; 
-  u32* subcomponents;
-  bool *outputIsSet;  //one for each output
-  std::mutex *mutexes;  //one for each output
-  std::condition_variable *cvs;
-  std::thread *sbct; //subcomponent threads
+  u32* subcomponents = NULL;
+  bool *outputIsSet = NULL;  //one for each output
+  std::mutex *mutexes = NULL;  //one for each output
+  std::condition_variable *cvs = NULL;
+  std::thread *sbct = NULL; //subcomponent threads
 };
 
 /*
@@ -80,5 +79,16 @@ uint get_size_of_input_hashmap();
 uint get_size_of_witness();
 uint get_size_of_constants();
 uint get_size_of_io_map();
+u64 get_circuit_data_fingerprint();
+// the contents of the .dat file when they are embedded, NULL otherwise
+u8 const *get_circuit_data();
+u64 get_size_of_circuit_data();
+// JSON Schema of the main inputs, with their dimensions in x-circom-dimensions
+const char *get_input_schema();
+// entries of the profile of circuits compiled with --c-profile, 0 otherwise:
+// the template instances followed by the functions
+uint get_size_of_profile();
+uint get_number_of_profiled_templates();
+const char *get_profile_name(uint i);
 
 #endif  // __CIRCOM_H
@@ -0,0 +1,219 @@
+#include <stdio.h>
+#include <string.h>
+#include <fstream>
+#include <iterator>
+#include <string>
+#include <vector>
+
+#include "calcwit.hpp"
+#include "circom_witness.h"
+
+struct circom_circuit {
+  Circom_Circuit *circuit;
+};
+
+struct circom_witness {
+  Circom_CalcWit *ctx;
+  // the calculation has started, and has finished without failures
+  bool calculated;
+  bool completed;
+  std::string error;
+};
+
+// Runs f converting the failures into error codes, recording their message
+// in the calculation when there is one
+template <typename F>
+static int guard(circom_witness *witness, F f) {
+  int code = CIRCOM_OK;
+  std::string message;
+  try {
+    f();
+  } catch (Circom_Failure &e) {
+    code = e.code;
+    message = e.what();
+  } catch (std::exception &e) {
+    code = CIRCOM_ERROR_STATE;
+    message = e.what();
+  }
+  if (witness != NULL) witness->error = message;
+  return code;
+}
+
+static void fromBytes(PFrElement e, const uint8_t *bytes) {
+#ifndef Fr_U64
+  e->type = Fr_LONG;
+  e->shortVal = 0;
+#endif
+  memcpy(e->longVal, bytes, Fr_N64*8);
+  for (int i = Fr_N64 - 1; i >= 0; i--) {
+    if (e->longVal[i] < Fr_q.longVal[i]) return;
+    if (e->longVal[i] > Fr_q.longVal[i]) break;
+  }
+  circom_fail(CIRCOM_ERROR_INPUT, "The value is not an element of the field");
+}
+
+static void toBytes(uint8_t *bytes, PFrElement e) {
+  FrElement v;
+  Fr_toLongNormal(&v, e);
+  memcpy(bytes, v.longVal, Fr_N64*8);
+}
+
+static void checkNotCalculated(circom_witness *witness) {
+  if (witness->calculated) {
+    circom_fail(CIRCOM_ERROR_STATE, "The witness has already been calculated");
+  }
+}
+
+extern "C" {
+
+const char *circom_error_string(int code) {
+  switch (code) {
+  case CIRCOM_OK: return "Success";
+  case CIRCOM_ERROR_DATA: return "Invalid circuit data";
+  case CIRCOM_ERROR_INPUT: return "Invalid input";
+  case CIRCOM_ERROR_MISSING_INPUTS: return "Missing inputs";
+  case CIRCOM_ERROR_ASSERT: return "Failed assert";
+  case CIRCOM_ERROR_STATE: return "Invalid state";
+  case CIRCOM_ERROR_FILE: return "Cannot read the circuit data file";
+  default: return "Unknown error";
+  }
+}
+
+uint32_t circom_field_size(void) {
+  return Fr_N64*8;
+}
+
+void circom_field_prime(uint8_t *prime) {
+  memcpy(prime, Fr_q.longVal, Fr_N64*8);
+}
+
+uint32_t circom_witness_size(void) {
+  return get_size_of_witness();
+}
+
+uint32_t circom_input_count(void) {
+  return get_main_input_signal_no();
+}
+
+uint64_t circom_input_hash(const char *name) {
+  return fnv1a(name);
+}
+
+const char *circom_input_schema(void) {
+  return get_input_schema();
+}
+
+int circom_circuit_load(const uint8_t *data, size_t size, circom_circuit **circuit) {
+  *circuit = NULL;
+  return guard(NULL, [&]() {
+    Circom_Circuit *loaded = loadCircuitData(data, size);
+    *circuit = new circom_circuit{loaded};
+  });
+}
+
+int circom_circuit_load_file(const char *path, circom_circuit **circuit) {
+  *circuit = NULL;
+  std::ifstream file(path, std::ios::binary);
+  if (!file) return CIRCOM_ERROR_FILE;
+  std::vector<uint8_t> data((std::istreambuf_iterator<char>(file)), std::istreambuf_iterator<char>());
+  if (file.bad()) return CIRCOM_ERROR_FILE;
+  return circom_circuit_load(data.data(), data.size(), circuit);
+}
+
+int circom_circuit_load_embedded(circom_circuit **circuit) {
+  *circuit = NULL;
+  if (get_circuit_data() == NULL) return CIRCOM_ERROR_STATE;
+  return circom_circuit_load(get_circuit_data(), get_size_of_circuit_data(), circuit);
+}
+
+void circom_circuit_free(circom_circuit *circuit) {
+  if (circuit == NULL) return;
+  freeCircuit(circuit->circuit);
+  delete circuit;
+}
+
+int circom_witness_new(const circom_circuit *circuit, circom_witness **witness) {
+  *witness = NULL;
+  return guard(NULL, [&]() {
+    Circom_CalcWit *ctx = new Circom_CalcWit(circuit->circuit);
+    *witness = new circom_witness{ctx, false, false, ""};
+  });
+}
+
+void circom_witness_free(circom_witness *witness) {
+  if (witness == NULL) return;
+  delete witness->ctx;
+  delete witness;
+}
+
+int circom_witness_input_length(circom_witness *witness, uint64_t hash, uint32_t *length) {
+  return guard(witness, [&]() {
+    *length = witness->ctx->getInputSignalSize(hash);
+  });
+}
+
+int circom_witness_set_input(circom_witness *witness, uint64_t hash, uint32_t index, const uint8_t *value) {
+  return guard(witness, [&]() {
+    checkNotCalculated(witness);
+    FrElement v;
+    fromBytes(&v, value);
+    witness->ctx->setInputSignal(hash, index, v);
+  });
+}
+
+int circom_witness_set_input_string(circom_witness *witness, uint64_t hash, uint32_t index, const char *value) {
+  return guard(witness, [&]() {
+    checkNotCalculated(witness);
+    const char *digits = value[0] == '-' ? value + 1 : value;
+    if (digits[0] == 0 || strspn(digits, "0123456789") != strlen(digits)) {
+      circom_fail(CIRCOM_ERROR_INPUT, std::string("The value is not a decimal number: ") + value);
+    }
+    FrElement v;
+    Fr_str2element(&v, value);
+    witness->ctx->setInputSignal(hash, index, v);
+  });
+}
+
+int circom_witness_set_input_at(circom_witness *witness, uint32_t i, const uint8_t *value) {
+  return guard(witness, [&]() {
+    checkNotCalculated(witness);
+    FrElement v;
+    fromBytes(&v, value);
+    witness->ctx->setInputSignalAt(i, v);
+  });
+}
+
+int circom_witness_calculate(circom_witness *witness) {
+  return guard(witness, [&]() {
+    checkNotCalculated(witness);
+    // missing inputs can still be set afterwards
+    witness->calculated = witness->ctx->getRemaingInputsToBeSet() == 0;
+    witness->ctx->calculate();
+    witness->completed = true;
+  });
+}
+
+int circom_witness_get(const circom_witness *witness, uint32_t index, uint8_t *value) {
+  if (!witness->completed) return CIRCOM_ERROR_STATE;
+  if (index >= get_size_of_witness()) return CIRCOM_ERROR_INPUT;
+  FrElement v;
+  witness->ctx->getWitness(index, &v);
+  toBytes(value, &v);
+  return CIRCOM_OK;
+}
+
+int circom_witness_get_all(const circom_witness *witness, uint8_t *values) {
+  if (!witness->completed) return CIRCOM_ERROR_STATE;
+  for (uint i = 0; i < get_size_of_witness(); i++) {
+    FrElement v;
+    witness->ctx->getWitness(i, &v);
+    toBytes(values + i*Fr_N64*8, &v);
+  }
+  return CIRCOM_OK;
+}
+
+const char *circom_witness_error(const circom_witness *witness) {
+  return witness->error.c_str();
+}
+
+}
@@ -0,0 +1,107 @@
+#ifndef CIRCOM_WITNESS_H
+#define CIRCOM_WITNESS_H
+
+/*
+C API of the witness generator, built as a static (libNAME.a) and a shared
+(libNAME.so) library by `make lib`.
+
+A circuit is loaded once from the contents of its .dat file, or from the copy
+embedded with --embed-dat, and shared by any number of witness calculations, each one with its own inputs:
+
+    circom_circuit *circuit;
+    circom_witness *witness;
+    circom_circuit_load_file("circuit.dat", &circuit);
+    circom_witness_new(circuit, &witness);
+    circom_witness_set_input_string(witness, circom_input_hash("a"), 0, "3");
+    ...
+    if (circom_witness_calculate(witness) != CIRCOM_OK) {
+        fprintf(stderr, "%s\n", circom_witness_error(witness));
+    }
+    circom_witness_get_all(witness, values);
+    circom_witness_free(witness);
+    circom_circuit_free(circuit);
+
+Field elements are given and returned as circom_field_size() bytes in little
+endian, the format of the .wtns files. Every function returning an int returns
+CIRCOM_OK or one of the error codes below, and the message of the last error
+of a calculation is available with circom_witness_error.
+
+The calculations are independent and can run in different threads, but a
+single calculation must not be used by several threads at the same time. An
+assert that fails in a component run in parallel still aborts the process.
+*/
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CIRCOM_OK 0
+// the circuit data is malformed or belongs to another compilation of the circuit
+#define CIRCOM_ERROR_DATA 1
+// unknown input signal, index out of bounds, value assigned twice or not in the field
+#define CIRCOM_ERROR_INPUT 2
+// the calculation started before all the inputs were set
+#define CIRCOM_ERROR_MISSING_INPUTS 3
+// an assert of the circuit failed during the calculation
+#define CIRCOM_ERROR_ASSERT 4
+// the call is not allowed in the current state of the calculation
+#define CIRCOM_ERROR_STATE 5
+// the circuit data file cannot be read
+#define CIRCOM_ERROR_FILE 6
+
+typedef struct circom_circuit circom_circuit;
+typedef struct circom_witness circom_witness;
+
+// Short description of an error code
+const char *circom_error_string(int code);
+
+// Size in bytes of a field element and the prime of the field
+uint32_t circom_field_size(void);
+void circom_field_prime(uint8_t *prime);
+
+// Number of elements of the witness and of signals of the main inputs
+uint32_t circom_witness_size(void);
+uint32_t circom_input_count(void);
+
+// Hash of the name of a main input signal
+uint64_t circom_input_hash(const char *name);
+
+// JSON Schema of the main inputs, with their names and dimensions
+const char *circom_input_schema(void);
+
+int circom_circuit_load(const uint8_t *data, size_t size, circom_circuit **circuit);
+int circom_circuit_load_file(const char *path, circom_circuit **circuit);
+// Only for circuits compiled with --embed-dat, CIRCOM_ERROR_STATE otherwise
+int circom_circuit_load_embedded(circom_circuit **circuit);
+void circom_circuit_free(circom_circuit *circuit);
+
+int circom_witness_new(const circom_circuit *circuit, circom_witness **witness);
+void circom_witness_free(circom_witness *witness);
+
+// Number of elements of the input signal, 1 if it is not an array
+int circom_witness_input_length(circom_witness *witness, uint64_t hash, uint32_t *length);
+
+// Sets the element index of an input signal given by the hash of its name
+int circom_witness_set_input(circom_witness *witness, uint64_t hash, uint32_t index, const uint8_t *value);
+// The same with a decimal number, which may be negative
+int circom_witness_set_input_string(circom_witness *witness, uint64_t hash, uint32_t index, const char *value);
+// Sets the i-th signal of the main inputs, with the arrays flattened in order of declaration
+int circom_witness_set_input_at(circom_witness *witness, uint32_t i, const uint8_t *value);
+
+int circom_witness_calculate(circom_witness *witness);
+
+// Element index of the witness, or the circom_witness_size() elements
+int circom_witness_get(const circom_witness *witness, uint32_t index, uint8_t *value);
+int circom_witness_get_all(const circom_witness *witness, uint8_t *values);
+
+// Message of the last error of the calculation, empty if there was none
+const char *circom_witness_error(const circom_witness *witness);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // CIRCOM_WITNESS_H
@@ -6,9 +6,14 @@
 #include <sys/mman.h>
 #include <fcntl.h>
 #include <unistd.h>
+#include <dirent.h>
 #include <nlohmann/json.hpp>
 #include <vector>
 #include <chrono>
+#include <thread>
+#include <mutex>
+#include <algorithm>
+#include <cmath>
 
 using json = nlohmann::json;
 
@@ -20,7 +25,9 @@ using json = nlohmann::json;
            do { perror(msg); exit(EXIT_FAILURE); } while (0)
 
 Circom_Circuit* loadCircuit(std::string const &datFileName) {
-    Circom_Circuit *circuit = new Circom_Circuit;
+    if (get_circuit_data() != NULL) {
+      return loadCircuitData(get_circuit_data(), get_size_of_circuit_data());
+    }
 
     int fd;
     struct stat sb;
@@ -38,58 +45,7 @@ Circom_Circuit* loadCircuit(std::string const &datFileName) {
     u8* bdata = (u8*)mmap(NULL, sb.st_size, PROT_READ , MAP_PRIVATE, fd, 0);
     close(fd);
 
-    circuit->InputHashMap = new HashSignalInfo[get_size_of_input_hashmap()];
-    uint dsize = get_size_of_input_hashmap()*sizeof(HashSignalInfo);
-    memcpy((void *)(circuit->InputHashMap), (void *)bdata, dsize);
-
-    circuit->witness2SignalList = new u64[get_size_of_witness()];
-    uint inisize = dsize;    
-    dsize = get_size_of_witness()*sizeof(u64);
-    memcpy((void *)(circuit->witness2SignalList), (void *)(bdata+inisize), dsize);
-
-    circuit->circuitConstants = new FrElement[get_size_of_constants()];
-    if (get_size_of_constants()>0) {
-      inisize += dsize;
-      dsize = get_size_of_constants()*sizeof(FrElement);
-      memcpy((void *)(circuit->circuitConstants), (void *)(bdata+inisize), dsize);
-    }
-
-    std::map<u32,IODefPair> templateInsId2IOSignalInfo1;
-    if (get_size_of_io_map()>0) {
-      u32 index[get_size_of_io_map()];
-      inisize += dsize;
-      dsize = get_size_of_io_map()*sizeof(u32);
-      memcpy((void *)index, (void *)(bdata+inisize), dsize);
-      inisize += dsize;
-      assert(inisize % sizeof(u32) == 0);    
-      assert(sb.st_size % sizeof(u32) == 0);
-      u32 dataiomap[(sb.st_size-inisize)/sizeof(u32)];
-      memcpy((void *)dataiomap, (void *)(bdata+inisize), sb.st_size-inisize);
-      u32* pu32 = dataiomap;
-
-      for (int i = 0; i < get_size_of_io_map(); i++) {
-	u32 n = *pu32;
-	IODefPair p;
-	p.len = n;
-	IODef defs[n];
-	pu32 += 1;
-	for (u32 j = 0; j <n; j++){
-	  defs[j].offset=*pu32;
-	  u32 len = *(pu32+1);
-	  defs[j].len = len;
-	  defs[j].lengths = new u32[len];
-	  memcpy((void *)defs[j].lengths,(void *)(pu32+2),len*sizeof(u32));
-	  pu32 += len + 2;
-	}
-	p.defs = (IODef*)calloc(10, sizeof(IODef));
-	for (u32 j = 0; j < p.len; j++){
-	  p.defs[j] = defs[j];
-	}
-	templateInsId2IOSignalInfo1[index[i]] = p;
-      }
-    }
-    circuit->templateInsId2IOSignalInfo = move(templateInsId2IOSignalInfo1);
-    
+    Circom_Circuit *circuit = loadCircuitData(bdata, sb.st_size);
     munmap(bdata, sb.st_size);
     
     return circuit;
@@ -119,14 +75,107 @@ void json2FrElements (json val, std::vector<FrElement> & vval){
 }
 
 
-void loadJson(Circom_CalcWit *ctx, std::string filename) {
-  std::ifstream inStream(filename);
-  json j;
-  inStream >> j;
-  
+// Position of the i-th element, in row-major order, of an array of the given dimensions
+std::string elementPosition(std::vector<uint> const &dims, uint i) {
+  std::string position;
+  for (int d = dims.size() - 1; d >= 0; d--) {
+    position = "[" + std::to_string(i % dims[d]) + "]" + position;
+    i /= dims[d];
+  }
+  return position;
+}
+
+void validateElement(json const &v, std::string const &name, std::string const &prime, std::vector<std::string> &errors) {
+  std::string s;
+  if (v.is_array()) {
+    errors.push_back("Input signal " + name + " must be a field element, not an array");
+    return;
+  } else if (v.is_string()) {
+    s = v.get<std::string>();
+  } else if (v.is_number_integer()) {
+    s = v.dump();
+  } else if (v.is_number_float() && v.get<double>() == std::floor(v.get<double>())) {
+    std::stringstream stream;
+    stream << std::fixed << std::setprecision(0) << v.get<double>();
+    s = stream.str();
+  }
+  uint start = (!s.empty() && s[0] == '-') ? 1 : 0;
+  if (s.size() == start || s.find_first_not_of("0123456789", start) != std::string::npos) {
+    errors.push_back("Input signal " + name + " is not an integer: " + v.dump());
+    return;
+  }
+  std::string digits = s.substr(std::min(s.find_first_not_of('0', start), s.size() - 1));
+  if (digits.size() > prime.size() || (digits.size() == prime.size() && digits >= prime)) {
+    errors.push_back("Input signal " + name + " is out of the range of the field: " + s.substr(0, start) + digits);
+  }
+}
+
+void validateArray(json const &v, std::vector<uint> const &dims, uint d, std::string const &name, std::string const &prime, std::vector<std::string> &errors) {
+  if (d == dims.size()) {
+    validateElement(v, name, prime, errors);
+    return;
+  }
+  if (!v.is_array()) {
+    errors.push_back("Input signal " + name + " must be an array of " + std::to_string(dims[d]) + " elements");
+    return;
+  }
+  if (d == 0 && dims.size() > 1 && std::none_of(v.begin(), v.end(), [](json const &e) { return e.is_array(); })) {
+    // a flattened array, in row-major order
+    uint total = 1;
+    for (uint length : dims) total *= length;
+    if (v.size() != total) {
+      errors.push_back("Input signal " + name + " has " + std::to_string(v.size()) + " elements instead of " + std::to_string(dims[0]) + ", or " + std::to_string(total) + " when flattened");
+      return;
+    }
+    for (uint i = 0; i < v.size(); i++) {
+      validateElement(v[i], name + elementPosition(dims, i), prime, errors);
+    }
+    return;
+  }
+  if (v.size() != dims[d]) {
+    errors.push_back("Input signal " + name + " has " + std::to_string(v.size()) + " elements instead of " + std::to_string(dims[d]));
+    return;
+  }
+  for (uint i = 0; i < v.size(); i++) {
+    validateArray(v[i], dims, d + 1, name + "[" + std::to_string(i) + "]", prime, errors);
+  }
+}
+
+// Checks the inputs against the schema of the main inputs before setting any
+// of them, and fails with every unknown, missing or malformed signal
+void validateInputs(json const &j) {
+  static const json schema = json::parse(get_input_schema());
+  json const &dimensions = schema["x-circom-dimensions"];
+  std::string prime = schema["x-circom-prime"].get<std::string>();
+  if (!j.is_object()) {
+    throw std::runtime_error("The inputs must be a JSON object\n");
+  }
+  std::vector<std::string> errors;
+  for (json::const_iterator it = j.begin(); it != j.end(); ++it) {
+    if (dimensions.find(it.key()) == dimensions.end()) {
+      errors.push_back("Unknown input signal " + it.key());
+    }
+  }
+  for (json const &required : schema["required"]) {
+    std::string name = required.get<std::string>();
+    if (j.find(name) == j.end()) {
+      errors.push_back("Missing input signal " + name);
+    } else {
+      validateArray(j[name], dimensions[name].get<std::vector<uint>>(), 0, name, prime, errors);
+    }
+  }
+  if (!errors.empty()) {
+    std::string message;
+    for (std::string const &error : errors) message += error + "\n";
+    throw std::runtime_error(message);
+  }
+}
+
+void loadInputs(Circom_CalcWit *ctx, json const &j) {
+  validateInputs(j);
   u64 nItems = j.size();
   // printf("Items : %llu\n",nItems);
-  for (json::iterator it = j.begin(); it != j.end(); ++it) {
+  for (json::const_iterator it = j.begin(); it != j.end(); ++it) {
     // std::cout << it.key() << " => " << it.value() << '\n';
     u64 h = fnv1a(it.key());
     std::vector<FrElement> v;
@@ -155,10 +204,18 @@ void loadJson(Circom_CalcWit *ctx, std::string filename) {
   }
 }
 
+void loadJson(Circom_CalcWit *ctx, std::string filename) {
+  std::ifstream inStream(filename);
+  json j;
+  inStream >> j;
+  loadInputs(ctx, j);
+}
+
 void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName) {
     FILE *write_ptr;
 
     write_ptr = fopen(wtnsFileName.c_str(),"wb");
+    if (write_ptr == NULL) handle_error("wtns file");
 
     fwrite("wtns", 4, 1, write_ptr);
 
@@ -203,26 +260,190 @@ void writeBinWitness(Circom_CalcWit *ctx, std::string wtnsFileName) {
     fclose(write_ptr);
 }
 
+// Inputs of a batch, taken one at a time by the workers: the lines of a JSON
+// lines file, or of the standard input with "-", or the .json files of a
+// directory. Each witness is named after the line number or the file.
+class BatchInputs {
+  std::mutex mutex;
+  std::ifstream file;
+  std::istream *lines;
+  uint lineNumber;
+  std::string directory;
+  std::vector<std::string> files;
+  uint nextFile;
+
+public:
+  BatchInputs(std::string const &source) {
+    struct stat sb;
+    lines = NULL;
+    lineNumber = 0;
+    nextFile = 0;
+    if (source == "-") {
+      lines = &std::cin;
+    } else if (stat(source.c_str(), &sb) == 0 && S_ISDIR(sb.st_mode)) {
+      directory = source;
+      DIR *dir = opendir(source.c_str());
+      if (dir == NULL) handle_error("input directory");
+      struct dirent *entry;
+      while ((entry = readdir(dir)) != NULL) {
+        std::string name(entry->d_name);
+        if (name.size() > 5 && name.compare(name.size() - 5, 5, ".json") == 0) {
+          files.push_back(name);
+        }
+      }
+      closedir(dir);
+      std::sort(files.begin(), files.end());
+    } else {
+      file.open(source);
+      if (!file) handle_error("input file");
+      lines = &file;
+    }
+  }
+
+  bool next(std::string &name, std::string &text) {
+    std::lock_guard<std::mutex> guard(mutex);
+    if (lines != NULL) {
+      while (std::getline(*lines, text)) {
+        lineNumber++;
+        if (text.find_first_not_of(" \t\r") != std::string::npos) {
+          name = std::to_string(lineNumber);
+          return true;
+        }
+      }
+      return false;
+    }
+    if (nextFile == files.size()) return false;
+    name = files[nextFile].substr(0, files[nextFile].size() - 5);
+    std::ifstream in(directory + "/" + files[nextFile++]);
+    std::stringstream content;
+    content << in.rdbuf();
+    text = content.str();
+    return true;
+  }
+};
+
+// Writes the profile of circuits compiled with --c-profile in the given file,
+// or in the standard error
+void writeProfile(Circom_Profile const *profile, std::string const &profileFile) {
+  if (profile == NULL) {
+    if (!profileFile.empty()) std::cerr << "The circuit was not compiled with --c-profile" << std::endl;
+    return;
+  }
+  if (profileFile.empty()) {
+    profile->write(std::cerr);
+    return;
+  }
+  std::ofstream out(profileFile);
+  if (!out) handle_error("profile file");
+  profile->write(out);
+}
+
+// Computes witnesses of the batch until there are no more inputs, reusing the
+// memory of the calculations. The failures are reported and counted, and the
+// profile, if any, added to the one of the batch.
+void runBatch(Circom_Circuit *circuit, BatchInputs *inputs, std::string const &outputDir, std::mutex *reportMutex, uint *failures, Circom_Profile *profile) {
+  Circom_CalcWit *ctx = new Circom_CalcWit(circuit);
+  std::string name, text;
+  while (inputs->next(name, text)) {
+    std::string error;
+    ctx->reset();
+    try {
+      loadInputs(ctx, json::parse(text));
+      ctx->calculate();
+      writeBinWitness(ctx, outputDir + "/" + name + ".wtns");
+    } catch (std::exception &e) {
+      error = e.what();
+    } catch (std::runtime_error *e) {
+      error = e->what();
+      delete e;
+    }
+    if (!error.empty()) {
+      error.erase(error.find_last_not_of("\n") + 1);
+      std::lock_guard<std::mutex> guard(*reportMutex);
+      std::cerr << "Failed to compute the witness of " << name << ": " << error << std::endl;
+      (*failures)++;
+    }
+  }
+  if (profile != NULL) {
+    std::lock_guard<std::mutex> guard(*reportMutex);
+    profile->add(*ctx->profile);
+  }
+  delete ctx;
+}
+
 int main (int argc, char *argv[]) {
   std::string cl(argv[0]);
-  if (argc!=3) {
-        std::cout << "Usage: " << cl << " <input.json> <output.wtns>\n";
+  std::vector<std::string> args;
+  std::string tracefile;
+  std::string profilefile;
+  std::vector<std::string> tracefilters;
+  bool batch = false;
+  uint threads = 1;
+  for (int i = 1; i < argc; i++) {
+    std::string arg(argv[i]);
+    if (arg == "--batch") {
+      batch = true;
+    } else if (arg == "--threads" && i+1 < argc) {
+      threads = std::max(1, atoi(argv[++i]));
+    } else if (arg == "--trace" && i+1 < argc) {
+      tracefile = argv[++i];
+    } else if (arg == "--profile" && i+1 < argc) {
+      profilefile = argv[++i];
+    } else if (arg == "--trace-filter" && i+1 < argc) {
+      tracefilters.push_back(argv[++i]);
+    } else {
+      args.push_back(arg);
+    }
+  }
+  if (args.size()!=2 || (batch && !tracefile.empty())) {
+        std::cout << "Usage: " << cl << " <input.json> <output.wtns> [--trace <trace file>] [--trace-filter <component path prefix>]... [--profile <profile file>]\n";
+        std::cout << "       " << cl << " --batch <inputs.jsonl | input directory | -> <output directory> [--threads <n>] [--profile <profile file>]\n";
+  } else if (batch) {
+    Circom_Circuit *circuit = loadCircuit(cl + ".dat");
+    BatchInputs inputs(args[0]);
+    if (mkdir(args[1].c_str(), 0777) == -1 && errno != EEXIST) handle_error("output directory");
+    // the failures of an input do not stop the others
+    circomThrowFailures = true;
+    std::mutex reportMutex;
+    uint failures = 0;
+    Circom_Profile *profile = get_size_of_profile() > 0 ? new Circom_Profile() : NULL;
+    std::vector<std::thread> workers;
+    for (uint i = 1; i < threads; i++) {
+      workers.push_back(std::thread(runBatch, circuit, &inputs, args[1], &reportMutex, &failures, profile));
+    }
+    runBatch(circuit, &inputs, args[1], &reportMutex, &failures, profile);
+    for (auto &worker : workers) worker.join();
+    writeProfile(profile, profilefile);
+    delete profile;
+    freeCircuit(circuit);
+    return failures == 0 ? EXIT_SUCCESS : EXIT_FAILURE;
   } else {
     std::string datfile = cl + ".dat";
-    std::string jsonfile(argv[1]);
-    std::string wtnsfile(argv[2]);
+    std::string jsonfile(args[0]);
+    std::string wtnsfile(args[1]);
   
     // auto t_start = std::chrono::high_resolution_clock::now();
 
    Circom_Circuit *circuit = loadCircuit(datfile);
 
    Circom_CalcWit *ctx = new Circom_CalcWit(circuit);
+
+   FILE *trace_ptr = NULL;
+   if (!tracefile.empty()) {
+     trace_ptr = fopen(tracefile.c_str(), "w");
+     if (trace_ptr == NULL) handle_error("trace file");
+     ctx->setTrace(trace_ptr, tracefilters);
+   }
   
-   loadJson(ctx, jsonfile);
-   if (ctx->getRemaingInputsToBeSet()!=0) {
-     std::cerr << "Not all inputs have been set. Only " << get_main_input_signal_no()-ctx->getRemaingInputsToBeSet() << " out of " << get_main_input_signal_no() << std::endl;
-     assert(false);
+   try {
+     loadJson(ctx, jsonfile);
+   } catch (std::exception &e) {
+     std::string error(e.what());
+     error.erase(error.find_last_not_of("\n") + 1);
+     std::cerr << error << std::endl;
+     return EXIT_FAILURE;
    }
+   ctx->calculate();
    /*
      for (uint i = 0; i<get_size_of_witness(); i++){
      FrElement x;
@@ -235,6 +456,8 @@ int main (int argc, char *argv[]) {
    //std::cout << std::chrono::duration<double, std::milli>(t_mid-t_start).count()<<std::endl;
 
    writeBinWitness(ctx,wtnsfile);
+   if (trace_ptr != NULL) fclose(trace_ptr);
+   writeProfile(ctx->profile, profilefile);
   
    //auto t_end = std::chrono::high_resolution_clock::now();
    //std::cout << std::chrono::duration<double, std::milli>(t_end-t_mid).count()<<std::endl;
@@ -0,0 +1,95 @@
+#include "fr.hpp"
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <assert.h>
+#include <inttypes.h>
+
+FrElement Fr_q = { { Fr_p } };
+
+static uint64_t rawPow(uint64_t a, uint64_t e) {
+    uint64_t r = 1;
+    while (e) {
+        if (e & 1) r = Fr_rawMul(r, a);
+        a = Fr_rawMul(a, a);
+        e >>= 1;
+    }
+    return r;
+}
+
+// a^(p-2), which is 0 for 0
+static inline uint64_t rawInv(uint64_t a) {
+    return rawPow(a, Fr_p - 2);
+}
+
+// A negative amount, p - n, shifts n bits in the opposite direction, and the
+// shifts of 64 bits or more give 0
+void Fr_shl(PFrElement r, PFrElement a, PFrElement b) {
+    uint64_t n = b->longVal[0];
+    if (n < 64) {
+        r->longVal[0] = Fr_rawReduce(a->longVal[0] << n);
+    } else if (Fr_p - n < 64) {
+        r->longVal[0] = a->longVal[0] >> (Fr_p - n);
+    } else {
+        r->longVal[0] = 0;
+    }
+}
+
+void Fr_shr(PFrElement r, PFrElement a, PFrElement b) {
+    uint64_t n = b->longVal[0];
+    if (n < 64) {
+        r->longVal[0] = a->longVal[0] >> n;
+    } else if (Fr_p - n < 64) {
+        r->longVal[0] = Fr_rawReduce(a->longVal[0] << (Fr_p - n));
+    } else {
+        r->longVal[0] = 0;
+    }
+}
+
+void Fr_idiv(PFrElement r, PFrElement a, PFrElement b) {
+    if (b->longVal[0] == 0) Fr_fail();
+    r->longVal[0] = a->longVal[0] / b->longVal[0];
+}
+
+void Fr_mod(PFrElement r, PFrElement a, PFrElement b) {
+    if (b->longVal[0] == 0) Fr_fail();
+    r->longVal[0] = a->longVal[0] % b->longVal[0];
+}
+
+void Fr_pow(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = rawPow(a->longVal[0], b->longVal[0]);
+}
+
+void Fr_inv(PFrElement r, PFrElement a) {
+    r->longVal[0] = rawInv(a->longVal[0]);
+}
+
+void Fr_div(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawMul(a->longVal[0], rawInv(b->longVal[0]));
+}
+
+// Canonical value of the decimal number s modulo p
+void Fr_str2element(PFrElement pE, char const *s) {
+    uint64_t acc = 0;
+    bool negative = false;
+    while (*s == ' ' || *s == '\t' || *s == '\n') s++;
+    if (*s == '-') {
+        negative = true;
+        s++;
+    }
+    for (; *s; s++) {
+        if (*s < '0' || *s > '9') continue;
+        acc = (uint64_t)(((unsigned __int128)acc * 10 + (*s - '0')) % Fr_p);
+    }
+    pE->longVal[0] = negative ? Fr_rawSub(0, acc) : acc;
+}
+
+char *Fr_element2str(PFrElement pE) {
+    char *r = new char[32];
+    sprintf(r, "%" PRIu64, pE->longVal[0]);
+    return r;
+}
+
+void Fr_fail() {
+    assert(false);
+}
@@ -0,0 +1,171 @@
+#ifndef __FR_H
+#define __FR_H
+
+#include <stdint.h>
+#include <string>
+
+// Elements of the Goldilocks field, p = 2^64 - 2^32 + 1, hold their canonical
+// value in a single u64, without the type and the short value of the
+// multi-limb layout
+#define Fr_N64 1
+#define Fr_U64
+typedef uint64_t FrRawElement[Fr_N64];
+typedef struct {
+    FrRawElement longVal;
+} FrElement;
+typedef FrElement *PFrElement;
+extern FrElement Fr_q;
+
+static const uint64_t Fr_p = 0xffffffff00000001ull;
+static const uint64_t Fr_half = 0x7fffffff80000000ull;
+// 2^64 mod p
+static const uint64_t Fr_epsilon = 0x00000000ffffffffull;
+
+void Fr_fail();
+
+static inline uint64_t Fr_rawReduce(uint64_t x) {
+    return x >= Fr_p ? x - Fr_p : x;
+}
+
+static inline uint64_t Fr_rawAdd(uint64_t a, uint64_t b) {
+    uint64_t s = a + b;
+    if (s < a) return s + Fr_epsilon;
+    return Fr_rawReduce(s);
+}
+
+static inline uint64_t Fr_rawSub(uint64_t a, uint64_t b) {
+    return a < b ? a - b + Fr_p : a - b;
+}
+
+// Reduction of hi*2^64 + lo with 2^64 = 2^32 - 1 and 2^96 = -1 (mod p)
+static inline uint64_t Fr_rawReduce128(uint64_t hi, uint64_t lo) {
+    uint64_t hiHi = hi >> 32;
+    uint64_t t0 = lo - hiHi;
+    if (lo < hiHi) t0 -= Fr_epsilon;
+    uint64_t t1 = (hi & Fr_epsilon) * Fr_epsilon;
+    uint64_t t2 = t0 + t1;
+    if (t2 < t1) return t2 + Fr_epsilon;
+    return Fr_rawReduce(t2);
+}
+
+static inline uint64_t Fr_rawMul(uint64_t a, uint64_t b) {
+    unsigned __int128 m = (unsigned __int128)a * b;
+    return Fr_rawReduce128((uint64_t)(m >> 64), (uint64_t)m);
+}
+
+// Values greater than (p-1)/2 are compared as negative numbers
+static inline int64_t Fr_rawSigned(uint64_t x) {
+    return x > Fr_half ? (int64_t)(x - Fr_p) : (int64_t)x;
+}
+
+static inline void Fr_copy(PFrElement r, PFrElement a) {
+    r->longVal[0] = a->longVal[0];
+}
+
+static inline void Fr_copyn(PFrElement r, PFrElement a, int n) {
+    for (int i=0; i<n; i++) r[i].longVal[0] = a[i].longVal[0];
+}
+
+static inline void Fr_add(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawAdd(a->longVal[0], b->longVal[0]);
+}
+
+static inline void Fr_sub(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSub(a->longVal[0], b->longVal[0]);
+}
+
+static inline void Fr_neg(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_rawSub(0, a->longVal[0]);
+}
+
+static inline void Fr_mul(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawMul(a->longVal[0], b->longVal[0]);
+}
+
+static inline void Fr_square(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_rawMul(a->longVal[0], a->longVal[0]);
+}
+
+static inline void Fr_eq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = a->longVal[0] == b->longVal[0];
+}
+
+static inline void Fr_neq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = a->longVal[0] != b->longVal[0];
+}
+
+static inline void Fr_lt(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSigned(a->longVal[0]) < Fr_rawSigned(b->longVal[0]);
+}
+
+static inline void Fr_gt(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSigned(a->longVal[0]) > Fr_rawSigned(b->longVal[0]);
+}
+
+static inline void Fr_leq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSigned(a->longVal[0]) <= Fr_rawSigned(b->longVal[0]);
+}
+
+static inline void Fr_geq(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawSigned(a->longVal[0]) >= Fr_rawSigned(b->longVal[0]);
+}
+
+static inline int Fr_isTrue(PFrElement pE) {
+    return pE->longVal[0] != 0;
+}
+
+static inline void Fr_land(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_isTrue(a) && Fr_isTrue(b);
+}
+
+static inline void Fr_lor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_isTrue(a) || Fr_isTrue(b);
+}
+
+static inline void Fr_lnot(PFrElement r, PFrElement a) {
+    r->longVal[0] = !Fr_isTrue(a);
+}
+
+// The bit operations work on the 64 bits of the values and reduce the result once
+static inline void Fr_band(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawReduce(a->longVal[0] & b->longVal[0]);
+}
+
+static inline void Fr_bor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawReduce(a->longVal[0] | b->longVal[0]);
+}
+
+static inline void Fr_bxor(PFrElement r, PFrElement a, PFrElement b) {
+    r->longVal[0] = Fr_rawReduce(a->longVal[0] ^ b->longVal[0]);
+}
+
+static inline void Fr_bnot(PFrElement r, PFrElement a) {
+    r->longVal[0] = Fr_rawReduce(~a->longVal[0]);
+}
+
+static inline void Fr_toNormal(PFrElement r, PFrElement a) {
+    Fr_copy(r, a);
+}
+
+static inline void Fr_toLongNormal(PFrElement r, PFrElement a) {
+    Fr_copy(r, a);
+}
+
+static inline int Fr_toInt(PFrElement pE) {
+    int64_t v = Fr_rawSigned(pE->longVal[0]);
+    if (v < INT32_MIN || v > INT32_MAX) Fr_fail();
+    return (int)v;
+}
+
+void Fr_shl(PFrElement r, PFrElement a, PFrElement b);
+void Fr_shr(PFrElement r, PFrElement a, PFrElement b);
+void Fr_idiv(PFrElement r, PFrElement a, PFrElement b);
+void Fr_mod(PFrElement r, PFrElement a, PFrElement b);
+void Fr_pow(PFrElement r, PFrElement a, PFrElement b);
+void Fr_inv(PFrElement r, PFrElement a);
+void Fr_div(PFrElement r, PFrElement a, PFrElement b);
+
+void Fr_str2element(PFrElement pE, char const*s);
+char *Fr_element2str(PFrElement pE);
+
+#endif // __FR_H
@@ -18,12 +18,20 @@ pub struct CProducer {
     pub size_32_shift: usize,
     pub prime: String,
     pub prime_str: String,
+    pub field_layout: FieldLayout,
     pub main_input_list: InputList,
+    pub main_input_dimensions: Vec<Vec<usize>>,
     pub witness_to_signal_list: SignalList,
     pub io_map: TemplateInstanceIOMap,
     pub template_instance_list: TemplateList,
     pub message_list: MessageList,
     pub field_tracking: Vec<String>,
+    pub trace_flag: bool,
+    pub portable_flag: bool,
+    pub lib_flag: bool,
+    pub embed_dat_flag: bool,
+    pub profile_flag: bool,
+    pub source_map_flag: bool,
     version: usize,
     name_tag: String,
     string_table: Vec<String>,
@@ -62,9 +70,11 @@ impl Default for CProducer {
             prime: "21888242871839275222246405745257275088548364400416034343698204186575808495617"
                 .to_string(),
             prime_str: "bn128".to_string(),
+            field_layout: FieldLayout::MultiLimb,
             number_of_main_outputs: 1,
             number_of_main_inputs: 2,
             main_input_list: [("in1".to_string(), 2, 1), ("in2".to_string(), 3, 1)].to_vec(), //[].to_vec(),
+            main_input_dimensions: [vec![], vec![]].to_vec(),
             signals_in_witness: 20,
             witness_to_signal_list: [
                 0, 1, 2, 3, 4, 5, 6, 12, 16, 19, 24, 27, 33, 42, 46, 50, 51, 65, 78, 79,
@@ -87,6 +97,12 @@ impl Default for CProducer {
             size_32_shift: 5,
             io_map: my_map, //TemplateInstanceIOMap::new(),
             template_instance_list: [].to_vec(),
+            trace_flag: false,
+            portable_flag: false,
+            lib_flag: false,
+            embed_dat_flag: false,
+            profile_flag: false,
+            source_map_flag: false,
             // fix values
             version: 2,
             name_tag: "name".to_string(),
@@ -105,12 +121,37 @@ impl CProducer {
     pub fn get_has_parallelism(&self) -> bool {
         self.has_parallelism
     }
+    pub fn needs_trace(&self) -> bool {
+        self.trace_flag
+    }
+    pub fn is_portable(&self) -> bool {
+        self.portable_flag
+    }
+    // The 64 bit layout is always plain C++
+    pub fn needs_fr_asm(&self) -> bool {
+        !self.portable_flag && self.field_layout == FieldLayout::MultiLimb
+    }
+    pub fn has_library(&self) -> bool {
+        self.lib_flag
+    }
+    pub fn embeds_dat(&self) -> bool {
+        self.embed_dat_flag
+    }
+    pub fn needs_profile(&self) -> bool {
+        self.profile_flag
+    }
+    pub fn needs_source_map(&self) -> bool {
+        self.source_map_flag
+    }
     pub fn get_main_signal_offset(&self) -> usize {
         self.main_signal_offset
     }
     pub fn get_prime(&self) -> &str {
         &self.prime
     }
+    pub fn get_field_layout(&self) -> FieldLayout {
+        self.field_layout
+    }
     pub fn get_number_of_main_outputs(&self) -> usize {
         self.number_of_main_outputs+1
     }
@@ -120,6 +161,16 @@ impl CProducer {
     pub fn get_main_input_list(&self) -> &InputList {
         &self.main_input_list
     }
+    pub fn get_main_input_dimensions(&self) -> &Vec<Vec<usize>> {
+        &self.main_input_dimensions
+    }
+    pub fn get_input_schema(&self) -> serde_json::Value {
+        let inputs: Vec<(String, Vec<usize>)> = self.main_input_list.iter()
+            .zip(&self.main_input_dimensions)
+            .map(|((name, _, _), dimensions)| (name.clone(), dimensions.clone()))
+            .collect();
+        input_schema(&self.prime, &inputs)
+    }
     pub fn get_number_of_witness(&self) -> usize {
         self.signals_in_witness
     }
@@ -0,0 +1,764 @@
+#include "fr.hpp"
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <assert.h>
+#include <string>
+
+// Montgomery arithmetic over 64 bit limbs with R = 2^(64*Fr_N64) for
+// q = {{prime}}
+
+typedef unsigned __int128 Fr_uint128;
+
+FrElement Fr_q = {0, Fr_LONG, { {{q}} } };
+FrElement Fr_R3 = {0, Fr_LONGMONTGOMERY, { {{r3}} } };
+FrRawElement Fr_rawq = { {{q}} };
+FrRawElement Fr_rawR3 = { {{r3}} };
+static FrRawElement Fr_rawR2 = { {{r2}} };
+static FrRawElement Fr_rawHalf = { {{half}} };
+static const uint64_t Fr_np = {{np}};
+static const uint64_t Fr_lboMask = {{lbo_mask}};
+static const int Fr_nBits = {{n_bits}};
+
+
+static inline int rawCmp(const FrRawElement a, const FrRawElement b) {
+    for (int i=Fr_N64-1; i>=0; i--) {
+        if (a[i] != b[i]) return a[i] > b[i] ? 1 : -1;
+    }
+    return 0;
+}
+
+static inline uint64_t rawAddCarry(FrRawElement r, const FrRawElement a, const FrRawElement b) {
+    Fr_uint128 carry = 0;
+    for (int i=0; i<Fr_N64; i++) {
+        carry += (Fr_uint128)a[i] + b[i];
+        r[i] = (uint64_t)carry;
+        carry >>= 64;
+    }
+    return (uint64_t)carry;
+}
+
+static inline uint64_t rawSubBorrow(FrRawElement r, const FrRawElement a, const FrRawElement b) {
+    uint64_t borrow = 0;
+    for (int i=0; i<Fr_N64; i++) {
+        uint64_t d = a[i] - b[i];
+        uint64_t nextBorrow = (a[i] < b[i]) | (d < borrow);
+        r[i] = d - borrow;
+        borrow = nextBorrow;
+    }
+    return borrow;
+}
+
+static inline void rawSetUI(FrRawElement r, uint64_t v) {
+    r[0] = v;
+    for (int i=1; i<Fr_N64; i++) r[i] = 0;
+}
+
+// Normal representation of a small signed value
+static inline void rawSetInt(FrRawElement r, int64_t v) {
+    if (v >= 0) {
+        rawSetUI(r, (uint64_t)v);
+    } else {
+        rawSetUI(r, (uint64_t)0 - (uint64_t)v);
+        rawSubBorrow(r, Fr_rawq, r);
+    }
+}
+
+// Masks the value to the bits of q and reduces it once, as the bit operations do
+static inline void rawMaskAndReduce(FrRawElement r) {
+    r[Fr_N64-1] &= Fr_lboMask;
+    if (rawCmp(r, Fr_rawq) >= 0) rawSubBorrow(r, r, Fr_rawq);
+}
+
+void Fr_rawCopy(FrRawElement pRawResult, const FrRawElement pRawA) {
+    for (int i=0; i<Fr_N64; i++) pRawResult[i] = pRawA[i];
+}
+
+void Fr_rawSwap(FrRawElement pRawResult, FrRawElement pRawA) {
+    for (int i=0; i<Fr_N64; i++) {
+        uint64_t tmp = pRawResult[i];
+        pRawResult[i] = pRawA[i];
+        pRawA[i] = tmp;
+    }
+}
+
+void Fr_rawAdd(FrRawElement pRawResult, const FrRawElement pRawA, const FrRawElement pRawB) {
+    uint64_t carry = rawAddCarry(pRawResult, pRawA, pRawB);
+    if (carry || rawCmp(pRawResult, Fr_rawq) >= 0) rawSubBorrow(pRawResult, pRawResult, Fr_rawq);
+}
+
+void Fr_rawSub(FrRawElement pRawResult, const FrRawElement pRawA, const FrRawElement pRawB) {
+    if (rawSubBorrow(pRawResult, pRawA, pRawB)) rawAddCarry(pRawResult, pRawResult, Fr_rawq);
+}
+
+void Fr_rawNeg(FrRawElement pRawResult, const FrRawElement pRawA) {
+    if (Fr_rawIsZero(pRawA)) {
+        rawSetUI(pRawResult, 0);
+    } else {
+        rawSubBorrow(pRawResult, Fr_rawq, pRawA);
+    }
+}
+
+// Coarsely integrated operand scanning, the result is fully reduced
+void Fr_rawMMul(FrRawElement pRawResult, const FrRawElement pRawA, const FrRawElement pRawB) {
+    uint64_t t[Fr_N64 + 2];
+    for (int i=0; i<Fr_N64+2; i++) t[i] = 0;
+    for (int i=0; i<Fr_N64; i++) {
+        Fr_uint128 c = 0;
+        for (int j=0; j<Fr_N64; j++) {
+            c += (Fr_uint128)pRawA[j] * pRawB[i] + t[j];
+            t[j] = (uint64_t)c;
+            c >>= 64;
+        }
+        c += t[Fr_N64];
+        t[Fr_N64] = (uint64_t)c;
+        t[Fr_N64+1] = (uint64_t)(c >> 64);
+
+        uint64_t m = t[0] * Fr_np;
+        c = ((Fr_uint128)m * Fr_rawq[0] + t[0]) >> 64;
+        for (int j=1; j<Fr_N64; j++) {
+            c += (Fr_uint128)m * Fr_rawq[j] + t[j];
+            t[j-1] = (uint64_t)c;
+            c >>= 64;
+        }
+        c += t[Fr_N64];
+        t[Fr_N64-1] = (uint64_t)c;
+        t[Fr_N64] = t[Fr_N64+1] + (uint64_t)(c >> 64);
+    }
+    if (t[Fr_N64] || rawCmp(t, Fr_rawq) >= 0) rawSubBorrow(t, t, Fr_rawq);
+    Fr_rawCopy(pRawResult, t);
+}
+
+void Fr_rawMSquare(FrRawElement pRawResult, const FrRawElement pRawA) {
+    Fr_rawMMul(pRawResult, pRawA, pRawA);
+}
+
+void Fr_rawMMul1(FrRawElement pRawResult, const FrRawElement pRawA, uint64_t pRawB) {
+    FrRawElement b;
+    rawSetUI(b, pRawB);
+    Fr_rawMMul(pRawResult, pRawA, b);
+}
+
+void Fr_rawToMontgomery(FrRawElement pRawResult, const FrRawElement pRawA) {
+    Fr_rawMMul(pRawResult, pRawA, Fr_rawR2);
+}
+
+void Fr_rawFromMontgomery(FrRawElement pRawResult, const FrRawElement pRawA) {
+    FrRawElement one;
+    rawSetUI(one, 1);
+    Fr_rawMMul(pRawResult, pRawA, one);
+}
+
+int Fr_rawIsEq(const FrRawElement pRawA, const FrRawElement pRawB) {
+    return rawCmp(pRawA, pRawB) == 0;
+}
+
+int Fr_rawIsZero(const FrRawElement pRawB) {
+    for (int i=0; i<Fr_N64; i++) {
+        if (pRawB[i]) return 0;
+    }
+    return 1;
+}
+
+// r = base^e with base and r in Montgomery
+static void rawExp(FrRawElement r, const FrRawElement base, const FrRawElement e) {
+    FrRawElement res;
+    FrRawElement b;
+    rawSetUI(res, 1);
+    Fr_rawToMontgomery(res, res);
+    Fr_rawCopy(b, base);
+    for (int i=Fr_N64*64-1; i>=0; i--) {
+        Fr_rawMSquare(res, res);
+        if ((e[i/64] >> (i%64)) & 1) Fr_rawMMul(res, res, b);
+    }
+    Fr_rawCopy(r, res);
+}
+
+static void rawInv(FrRawElement r, const FrRawElement a) {
+    FrRawElement e;
+    FrRawElement two;
+    rawSetUI(two, 2);
+    rawSubBorrow(e, Fr_rawq, two);
+    rawExp(r, a, e);
+}
+
+// Quotient and remainder of non negative values, b must not be zero
+static void rawDivMod(FrRawElement quotient, FrRawElement remainder, const FrRawElement a, const FrRawElement b) {
+    FrRawElement qt;
+    FrRawElement rm;
+    rawSetUI(qt, 0);
+    rawSetUI(rm, 0);
+    for (int i=Fr_N64*64-1; i>=0; i--) {
+        uint64_t carry = rm[Fr_N64-1] >> 63;
+        for (int j=Fr_N64-1; j>0; j--) rm[j] = (rm[j] << 1) | (rm[j-1] >> 63);
+        rm[0] = (rm[0] << 1) | ((a[i/64] >> (i%64)) & 1);
+        if (carry || rawCmp(rm, b) >= 0) {
+            rawSubBorrow(rm, rm, b);
+            qt[i/64] |= (uint64_t)1 << (i%64);
+        }
+    }
+    Fr_rawCopy(quotient, qt);
+    Fr_rawCopy(remainder, rm);
+}
+
+// Divides a by d in place and returns the remainder
+static uint64_t rawDivUI(FrRawElement a, uint64_t d) {
+    Fr_uint128 rem = 0;
+    for (int i=Fr_N64-1; i>=0; i--) {
+        Fr_uint128 cur = (rem << 64) | a[i];
+        a[i] = (uint64_t)(cur / d);
+        rem = cur % d;
+    }
+    return (uint64_t)rem;
+}
+
+static std::string rawToString(const FrRawElement a, uint32_t radix) {
+    static const char digits[] = "0123456789abcdefghijklmnopqrstuvwxyz";
+    FrRawElement tmp;
+    std::string res;
+    Fr_rawCopy(tmp, a);
+    do {
+        res.insert(res.begin(), digits[rawDivUI(tmp, radix)]);
+    } while (!Fr_rawIsZero(tmp));
+    return res;
+}
+
+// Normal representation of the decimal number s modulo q
+static void rawFromString(FrRawElement r, char const *s) {
+    FrRawElement acc;
+    FrRawElement ten;
+    FrRawElement digit;
+    bool negative = false;
+    rawSetUI(acc, 0);
+    rawSetUI(ten, 10);
+    Fr_rawToMontgomery(ten, ten);
+    while (*s == ' ' || *s == '\t' || *s == '\n') s++;
+    if (*s == '-') {
+        negative = true;
+        s++;
+    }
+    for (; *s; s++) {
+        if (*s < '0' || *s > '9') continue;
+        rawSetUI(digit, *s - '0');
+        Fr_rawToMontgomery(digit, digit);
+        Fr_rawMMul(acc, acc, ten);
+        Fr_rawAdd(acc, acc, digit);
+    }
+    Fr_rawFromMontgomery(r, acc);
+    if (negative) Fr_rawNeg(r, r);
+}
+
+static inline int isShort(PFrElement a) {
+    return !(a->type & Fr_LONG);
+}
+
+static inline void setShort(PFrElement r, int32_t v) {
+    r->type = Fr_SHORT;
+    r->shortVal = v;
+}
+
+static inline void setLong(PFrElement r, uint32_t type, const FrRawElement v) {
+    r->type = type;
+    r->shortVal = 0;
+    Fr_rawCopy(r->longVal, v);
+}
+
+static inline void setInt64(PFrElement r, int64_t v) {
+    if (v >= INT32_MIN && v <= INT32_MAX) {
+        setShort(r, (int32_t)v);
+    } else {
+        FrRawElement tmp;
+        rawSetInt(tmp, v);
+        setLong(r, Fr_LONG, tmp);
+    }
+}
+
+// Results of the integer operations are short when they fit in an int
+static inline void setNormal(PFrElement r, const FrRawElement v) {
+    int fits = v[0] <= INT32_MAX;
+    for (int i=1; i<Fr_N64; i++) fits = fits && !v[i];
+    if (fits) {
+        setShort(r, (int32_t)v[0]);
+    } else {
+        setLong(r, Fr_LONG, v);
+    }
+}
+
+static inline void toLongNormalRaw(FrRawElement r, PFrElement a) {
+    if (isShort(a)) {
+        rawSetInt(r, a->shortVal);
+    } else if (a->type == Fr_LONGMONTGOMERY) {
+        Fr_rawFromMontgomery(r, a->longVal);
+    } else {
+        Fr_rawCopy(r, a->longVal);
+    }
+}
+
+static inline void toMontgomeryRaw(FrRawElement r, PFrElement a) {
+    if (isShort(a)) {
+        rawSetInt(r, a->shortVal);
+        Fr_rawToMontgomery(r, r);
+    } else if (a->type == Fr_LONGMONTGOMERY) {
+        Fr_rawCopy(r, a->longVal);
+    } else {
+        Fr_rawToMontgomery(r, a->longVal);
+    }
+}
+
+void Fr_copy(PFrElement r, PFrElement a) {
+    *r = *a;
+}
+
+void Fr_copyn(PFrElement r, PFrElement a, int n) {
+    for (int i=0; i<n; i++) r[i] = a[i];
+}
+
+void Fr_toNormal(PFrElement r, PFrElement a) {
+    if (a->type == Fr_LONGMONTGOMERY) {
+        FrRawElement tmp;
+        Fr_rawFromMontgomery(tmp, a->longVal);
+        setLong(r, Fr_LONG, tmp);
+    } else {
+        *r = *a;
+    }
+}
+
+void Fr_toLongNormal(PFrElement r, PFrElement a) {
+    FrRawElement tmp;
+    toLongNormalRaw(tmp, a);
+    setLong(r, Fr_LONG, tmp);
+}
+
+void Fr_toMontgomery(PFrElement r, PFrElement a) {
+    FrRawElement tmp;
+    toMontgomeryRaw(tmp, a);
+    if (isShort(a)) {
+        r->type = Fr_SHORTMONTGOMERY;
+        r->shortVal = a->shortVal;
+        Fr_rawCopy(r->longVal, tmp);
+    } else {
+        setLong(r, Fr_LONGMONTGOMERY, tmp);
+    }
+}
+
+void Fr_add(PFrElement r, PFrElement a, PFrElement b) {
+    FrRawElement x;
+    FrRawElement y;
+    if (isShort(a) && isShort(b)) {
+        setInt64(r, (int64_t)a->shortVal + b->shortVal);
+    } else if (a->type == Fr_LONGMONTGOMERY || b->type == Fr_LONGMONTGOMERY) {
+        toMontgomeryRaw(x, a);
+        toMontgomeryRaw(y, b);
+        Fr_rawAdd(x, x, y);
+        setLong(r, Fr_LONGMONTGOMERY, x);
+    } else {
+        toLongNormalRaw(x, a);
+        toLongNormalRaw(y, b);
+        Fr_rawAdd(x, x, y);
+        setLong(r, Fr_LONG, x);
+    }
+}
+
+void Fr_sub(PFrElement r, PFrElement a, PFrElement b) {
+    FrRawElement x;
+    FrRawElement y;
+    if (isShort(a) && isShort(b)) {
+        setInt64(r, (int64_t)a->shortVal - b->shortVal);
+    } else if (a->type == Fr_LONGMONTGOMERY || b->type == Fr_LONGMONTGOMERY) {
+        toMontgomeryRaw(x, a);
+        toMontgomeryRaw(y, b);
+        Fr_rawSub(x, x, y);
+        setLong(r, Fr_LONGMONTGOMERY, x);
+    } else {
+        toLongNormalRaw(x, a);
+        toLongNormalRaw(y, b);
+        Fr_rawSub(x, x, y);
+        setLong(r, Fr_LONG, x);
+    }
+}
+
+void Fr_neg(PFrElement r, PFrElement a) {
+    if (isShort(a)) {
+        setInt64(r, -(int64_t)a->shortVal);
+    } else {
+        FrRawElement x;
+        Fr_rawNeg(x, a->longVal);
+        setLong(r, a->type, x);
+    }
+}
+
+// A product with a single Montgomery factor is normal, and the product of two
+// normal factors is taken back to Montgomery with R3
+void Fr_mul(PFrElement r, PFrElement a, PFrElement b) {
+    if (isShort(a) && isShort(b)) {
+        setInt64(r, (int64_t)a->shortVal * b->shortVal);
+        return;
+    }
+    FrRawElement x;
+    FrRawElement y;
+    int am = isShort(a) || a->type == Fr_LONGMONTGOMERY;
+    int bm = isShort(b) || b->type == Fr_LONGMONTGOMERY;
+    if (am) toMontgomeryRaw(x, a); else Fr_rawCopy(x, a->longVal);
+    if (bm) toMontgomeryRaw(y, b); else Fr_rawCopy(y, b->longVal);
+    Fr_rawMMul(x, x, y);
+    if (am && bm) {
+        setLong(r, Fr_LONGMONTGOMERY, x);
+    } else if (am || bm) {
+        setLong(r, Fr_LONG, x);
+    } else {
+        Fr_rawMMul(x, x, Fr_rawR3);
+        setLong(r, Fr_LONGMONTGOMERY, x);
+    }
+}
+
+void Fr_square(PFrElement r, PFrElement a) {
+    Fr_mul(r, a, a);
+}
+
+// Values greater than (q-1)/2 are compared as negative numbers
+static int signedCmp(PFrElement a, PFrElement b) {
+    if (isShort(a) && isShort(b)) {
+        return a->shortVal < b->shortVal ? -1 : (a->shortVal > b->shortVal ? 1 : 0);
+    }
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    int xNeg = rawCmp(x, Fr_rawHalf) > 0;
+    int yNeg = rawCmp(y, Fr_rawHalf) > 0;
+    if (xNeg != yNeg) return xNeg ? -1 : 1;
+    return rawCmp(x, y);
+}
+
+static int isEq(PFrElement a, PFrElement b) {
+    if (isShort(a) && isShort(b)) return a->shortVal == b->shortVal;
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    return Fr_rawIsEq(x, y);
+}
+
+void Fr_eq(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, isEq(a, b));
+}
+
+void Fr_neq(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, !isEq(a, b));
+}
+
+void Fr_lt(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, signedCmp(a, b) < 0);
+}
+
+void Fr_gt(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, signedCmp(a, b) > 0);
+}
+
+void Fr_leq(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, signedCmp(a, b) <= 0);
+}
+
+void Fr_geq(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, signedCmp(a, b) >= 0);
+}
+
+int Fr_isTrue(PFrElement pE) {
+    if (isShort(pE)) return pE->shortVal != 0;
+    return !Fr_rawIsZero(pE->longVal);
+}
+
+int Fr_toInt(PFrElement pE) {
+    if (isShort(pE)) return pE->shortVal;
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, pE);
+    int upperZero = 1;
+    for (int i=1; i<Fr_N64; i++) upperZero = upperZero && !x[i];
+    if (upperZero && x[0] <= INT32_MAX) return (int)x[0];
+    rawSubBorrow(y, Fr_rawq, x);
+    upperZero = 1;
+    for (int i=1; i<Fr_N64; i++) upperZero = upperZero && !y[i];
+    if (upperZero && y[0] <= (uint64_t)INT32_MAX + 1) return (int)(-(int64_t)y[0]);
+    Fr_fail();
+    return 0;
+}
+
+void Fr_land(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, Fr_isTrue(a) && Fr_isTrue(b));
+}
+
+void Fr_lor(PFrElement r, PFrElement a, PFrElement b) {
+    setShort(r, Fr_isTrue(a) || Fr_isTrue(b));
+}
+
+void Fr_lnot(PFrElement r, PFrElement a) {
+    setShort(r, !Fr_isTrue(a));
+}
+
+static inline int arePositiveShorts(PFrElement a, PFrElement b) {
+    return isShort(a) && isShort(b) && a->shortVal >= 0 && b->shortVal >= 0;
+}
+
+void Fr_band(PFrElement r, PFrElement a, PFrElement b) {
+    if (arePositiveShorts(a, b)) {
+        setShort(r, a->shortVal & b->shortVal);
+        return;
+    }
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    for (int i=0; i<Fr_N64; i++) x[i] &= y[i];
+    rawMaskAndReduce(x);
+    setLong(r, Fr_LONG, x);
+}
+
+void Fr_bor(PFrElement r, PFrElement a, PFrElement b) {
+    if (arePositiveShorts(a, b)) {
+        setShort(r, a->shortVal | b->shortVal);
+        return;
+    }
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    for (int i=0; i<Fr_N64; i++) x[i] |= y[i];
+    rawMaskAndReduce(x);
+    setLong(r, Fr_LONG, x);
+}
+
+void Fr_bxor(PFrElement r, PFrElement a, PFrElement b) {
+    if (arePositiveShorts(a, b)) {
+        setShort(r, a->shortVal ^ b->shortVal);
+        return;
+    }
+    FrRawElement x;
+    FrRawElement y;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    for (int i=0; i<Fr_N64; i++) x[i] ^= y[i];
+    rawMaskAndReduce(x);
+    setLong(r, Fr_LONG, x);
+}
+
+void Fr_bnot(PFrElement r, PFrElement a) {
+    FrRawElement x;
+    toLongNormalRaw(x, a);
+    for (int i=0; i<Fr_N64; i++) x[i] = ~x[i];
+    rawMaskAndReduce(x);
+    setLong(r, Fr_LONG, x);
+}
+
+// Amount of a shift by b: a negative b, that is q - n, shifts n bits in the
+// opposite direction, and shifts of at least the bits of q give 0
+static int shiftAmount(PFrElement b, int *opposite) {
+    *opposite = 0;
+    if (isShort(b)) {
+        if (b->shortVal >= 0) return b->shortVal < Fr_nBits ? b->shortVal : -1;
+        *opposite = 1;
+        uint32_t n = (uint32_t)0 - (uint32_t)b->shortVal;
+        return n < (uint32_t)Fr_nBits ? (int)n : -1;
+    }
+    FrRawElement x;
+    FrRawElement bits;
+    toLongNormalRaw(x, b);
+    rawSetUI(bits, Fr_nBits);
+    if (rawCmp(x, bits) < 0) return (int)x[0];
+    *opposite = 1;
+    rawSubBorrow(x, Fr_rawq, x);
+    if (rawCmp(x, bits) < 0) return (int)x[0];
+    return -1;
+}
+
+static void doShr(PFrElement r, PFrElement a, int n) {
+    if (isShort(a) && a->shortVal >= 0) {
+        setShort(r, n < 32 ? a->shortVal >> n : 0);
+        return;
+    }
+    FrRawElement x;
+    toLongNormalRaw(x, a);
+    int limbs = n / 64;
+    int bits = n % 64;
+    for (int i=0; i<Fr_N64; i++) {
+        uint64_t lo = i + limbs < Fr_N64 ? x[i + limbs] : 0;
+        uint64_t hi = i + limbs + 1 < Fr_N64 ? x[i + limbs + 1] : 0;
+        x[i] = bits ? (lo >> bits) | (hi << (64 - bits)) : lo;
+    }
+    setLong(r, Fr_LONG, x);
+}
+
+static void doShl(PFrElement r, PFrElement a, int n) {
+    if (isShort(a) && a->shortVal >= 0 && n < 31) {
+        uint64_t v = (uint64_t)a->shortVal << n;
+        if (v <= INT32_MAX) {
+            setShort(r, (int32_t)v);
+            return;
+        }
+    }
+    FrRawElement x;
+    toLongNormalRaw(x, a);
+    int limbs = n / 64;
+    int bits = n % 64;
+    for (int i=Fr_N64-1; i>=0; i--) {
+        uint64_t hi = i - limbs >= 0 ? x[i - limbs] : 0;
+        uint64_t lo = i - limbs - 1 >= 0 ? x[i - limbs - 1] : 0;
+        x[i] = bits ? (hi << bits) | (lo >> (64 - bits)) : hi;
+    }
+    rawMaskAndReduce(x);
+    setLong(r, Fr_LONG, x);
+}
+
+void Fr_shr(PFrElement r, PFrElement a, PFrElement b) {
+    int opposite;
+    int n = shiftAmount(b, &opposite);
+    if (n < 0) {
+        setShort(r, 0);
+    } else if (opposite) {
+        doShl(r, a, n);
+    } else {
+        doShr(r, a, n);
+    }
+}
+
+void Fr_shl(PFrElement r, PFrElement a, PFrElement b) {
+    int opposite;
+    int n = shiftAmount(b, &opposite);
+    if (n < 0) {
+        setShort(r, 0);
+    } else if (opposite) {
+        doShr(r, a, n);
+    } else {
+        doShl(r, a, n);
+    }
+}
+
+void Fr_str2element(PFrElement pE, char const *s) {
+    FrRawElement x;
+    rawFromString(x, s);
+    setNormal(pE, x);
+}
+
+char *Fr_element2str(PFrElement pE) {
+    if (isShort(pE) && pE->shortVal >= 0) {
+        char *r = new char[32];
+        sprintf(r, "%d", pE->shortVal);
+        return r;
+    }
+    FrRawElement x;
+    toLongNormalRaw(x, pE);
+    std::string s = rawToString(x, 10);
+    char *r = new char[s.size() + 1];
+    strcpy(r, s.c_str());
+    return r;
+}
+
+void Fr_idiv(PFrElement r, PFrElement a, PFrElement b) {
+    FrRawElement x;
+    FrRawElement y;
+    FrRawElement rm;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    if (Fr_rawIsZero(y)) Fr_fail();
+    rawDivMod(x, rm, x, y);
+    setNormal(r, x);
+}
+
+void Fr_mod(PFrElement r, PFrElement a, PFrElement b) {
+    FrRawElement x;
+    FrRawElement y;
+    FrRawElement qt;
+    toLongNormalRaw(x, a);
+    toLongNormalRaw(y, b);
+    if (Fr_rawIsZero(y)) Fr_fail();
+    rawDivMod(qt, x, x, y);
+    setNormal(r, x);
+}
+
+void Fr_pow(PFrElement r, PFrElement a, PFrElement b) {
+    FrRawElement x;
+    FrRawElement e;
+    toMontgomeryRaw(x, a);
+    toLongNormalRaw(e, b);
+    rawExp(x, x, e);
+    Fr_rawFromMontgomery(x, x);
+    setNormal(r, x);
+}
+
+void Fr_inv(PFrElement r, PFrElement a) {
+    FrRawElement x;
+    toMontgomeryRaw(x, a);
+    rawInv(x, x);
+    Fr_rawFromMontgomery(x, x);
+    setNormal(r, x);
+}
+
+void Fr_div(PFrElement r, PFrElement a, PFrElement b) {
+    FrElement tmp;
+    Fr_inv(&tmp, b);
+    Fr_mul(r, a, &tmp);
+}
+
+void Fr_fail() {
+    assert(false);
+}
+
+
+RawFr::RawFr() {
+    fromString(fZero, "0");
+    fromString(fOne, "1");
+    neg(fNegOne, fOne);
+}
+
+RawFr::~RawFr() {
+}
+
+void RawFr::fromString(Element &r, std::string s) {
+    rawFromString(r.v, s.c_str());
+    Fr_rawToMontgomery(r.v, r.v);
+}
+
+void RawFr::fromUI(Element &r, unsigned long int v) {
+    rawSetUI(r.v, v);
+    Fr_rawToMontgomery(r.v, r.v);
+}
+
+std::string RawFr::toString(Element &a, uint32_t radix) {
+    Element tmp;
+    Fr_rawFromMontgomery(tmp.v, a.v);
+    return rawToString(tmp.v, radix);
+}
+
+void RawFr::inv(Element &r, Element &a) {
+    rawInv(r.v, a.v);
+}
+
+void RawFr::div(Element &r, Element &a, Element &b) {
+    Element tmp;
+    inv(tmp, b);
+    mul(r, a, tmp);
+}
+
+#define BIT_IS_SET(s, p) (s[p>>3] & (1 << (p & 0x7)))
+void RawFr::exp(Element &r, Element &base, uint8_t* scalar, unsigned int scalarSize) {
+    bool oneFound = false;
+    Element copyBase;
+    copy(copyBase, base);
+    for (int i=scalarSize*8-1; i>=0; i--) {
+        if (!oneFound) {
+            if ( !BIT_IS_SET(scalar, i) ) continue;
+            copy(r, copyBase);
+            oneFound = true;
+            continue;
+        }
+        square(r, r);
+        if ( BIT_IS_SET(scalar, i) ) {
+            mul(r, r, copyBase);
+        }
+    }
+    if (!oneFound) {
+        copy(r, fOne);
+    }
+}
+
+RawFr RawFr::field;
@@ -3,10 +3,10 @@
 
 #include <stdint.h>
 #include <string>
-#include <gmp.h>
 
-#define Fr_N64 1
+#define Fr_N64 {{n64}}
 #define Fr_SHORT 0x00000000
+#define Fr_SHORTMONTGOMERY 0x40000000
 #define Fr_LONG 0x80000000
 #define Fr_LONGMONTGOMERY 0xC0000000
 typedef uint64_t FrRawElement[Fr_N64];
@@ -58,16 +58,13 @@ extern "C" void Fr_rawNeg(FrRawElement pRawResult, const FrRawElement pRawA);
 extern "C" void Fr_rawMMul(FrRawElement pRawResult, const FrRawElement pRawA, const FrRawElement pRawB);
 extern "C" void Fr_rawMSquare(FrRawElement pRawResult, const FrRawElement pRawA);
 extern "C" void Fr_rawMMul1(FrRawElement pRawResult, const FrRawElement pRawA, uint64_t pRawB);
-extern "C" void Fr_rawToMontgomery(FrRawElement pRawResult, const FrRawElement &pRawA);
-extern "C" void Fr_rawFromMontgomery(FrRawElement pRawResult, const FrRawElement &pRawA);
+extern "C" void Fr_rawToMontgomery(FrRawElement pRawResult, const FrRawElement pRawA);
+extern "C" void Fr_rawFromMontgomery(FrRawElement pRawResult, const FrRawElement pRawA);
 extern "C" int Fr_rawIsEq(const FrRawElement pRawA, const FrRawElement pRawB);
 extern "C" int Fr_rawIsZero(const FrRawElement pRawB);
 
 extern "C" void Fr_fail();
 
-
-// Pending functions to convert
-
 void Fr_str2element(PFrElement pE, char const*s);
 char *Fr_element2str(PFrElement pE);
 void Fr_idiv(PFrElement r, PFrElement a, PFrElement b);
@@ -80,7 +77,7 @@ class RawFr {
 
 public:
     const static int N64 = Fr_N64;
-    const static int MaxBits = 64;
+    const static int MaxBits = {{max_bits}};
 
 
     struct Element {
@@ -97,56 +94,30 @@ public:
     RawFr();
     ~RawFr();
 
-    const Element &zero() { return fZero; };
-    const Element &one() { return fOne; };
-    const Element &negOne() { return fNegOne; };
-    Element set(int value);
-    void set(Element &r, int value);
+    Element &zero() { return fZero; };
+    Element &one() { return fOne; };
+    Element &negOne() { return fNegOne; };
 
-    void fromString(Element &r, const std::string &n, uint32_t radix = 10);
-    std::string toString(const Element &a, uint32_t radix = 10);
+    void fromString(Element &r, std::string n);
+    std::string toString(Element &a, uint32_t radix = 10);
 
-    void inline copy(Element &r, const Element &a) { Fr_rawCopy(r.v, a.v); };
+    void inline copy(Element &r, Element &a) { Fr_rawCopy(r.v, a.v); };
     void inline swap(Element &a, Element &b) { Fr_rawSwap(a.v, b.v); };
-    void inline add(Element &r, const Element &a, const Element &b) { Fr_rawAdd(r.v, a.v, b.v); };
-    void inline sub(Element &r, const Element &a, const Element &b) { Fr_rawSub(r.v, a.v, b.v); };
-    void inline mul(Element &r, const Element &a, const Element &b) { Fr_rawMMul(r.v, a.v, b.v); };
-
-    Element inline add(const Element &a, const Element &b) { Element r; Fr_rawAdd(r.v, a.v, b.v); return r;};
-    Element inline sub(const Element &a, const Element &b) { Element r; Fr_rawSub(r.v, a.v, b.v); return r;};
-    Element inline mul(const Element &a, const Element &b) { Element r; Fr_rawMMul(r.v, a.v, b.v); return r;};
-
-    Element inline neg(const Element &a) { Element r; Fr_rawNeg(r.v, a.v); return r; };
-    Element inline square(const Element &a) { Element r; Fr_rawMSquare(r.v, a.v); return r; };
-
-    Element inline add(int a, const Element &b) { return add(set(a), b);};
-    Element inline sub(int a, const Element &b) { return sub(set(a), b);};
-    Element inline mul(int a, const Element &b) { return mul(set(a), b);};
-
-    Element inline add(const Element &a, int b) { return add(a, set(b));};
-    Element inline sub(const Element &a, int b) { return sub(a, set(b));};
-    Element inline mul(const Element &a, int b) { return mul(a, set(b));};
-    
-    void inline mul1(Element &r, const Element &a, uint64_t b) { Fr_rawMMul1(r.v, a.v, b); };
-    void inline neg(Element &r, const Element &a) { Fr_rawNeg(r.v, a.v); };
-    void inline square(Element &r, const Element &a) { Fr_rawMSquare(r.v, a.v); };
-    void inv(Element &r, const Element &a);
-    void div(Element &r, const Element &a, const Element &b);
-    void exp(Element &r, const Element &base, uint8_t* scalar, unsigned int scalarSize);
-
-    void inline toMontgomery(Element &r, const Element &a) { Fr_rawToMontgomery(r.v, a.v); };
-    void inline fromMontgomery(Element &r, const Element &a) { Fr_rawFromMontgomery(r.v, a.v); };
-    int inline eq(const Element &a, const Element &b) { return Fr_rawIsEq(a.v, b.v); };
-    int inline isZero(const Element &a) { return Fr_rawIsZero(a.v); };
-
-    void toMpz(mpz_t r, const Element &a);
-    void fromMpz(Element &a, const mpz_t r);
-
-    int toRprBE(const Element &element, uint8_t *data, int bytes);
-    int fromRprBE(Element &element, const uint8_t *data, int bytes);
-    
-    int bytes ( void ) { return Fr_N64 * 8; };
-    
+    void inline add(Element &r, Element &a, Element &b) { Fr_rawAdd(r.v, a.v, b.v); };
+    void inline sub(Element &r, Element &a, Element &b) { Fr_rawSub(r.v, a.v, b.v); };
+    void inline mul(Element &r, Element &a, Element &b) { Fr_rawMMul(r.v, a.v, b.v); };
+    void inline mul1(Element &r, Element &a, uint64_t b) { Fr_rawMMul1(r.v, a.v, b); };
+    void inline neg(Element &r, Element &a) { Fr_rawNeg(r.v, a.v); };
+    void inline square(Element &r, Element &a) { Fr_rawMSquare(r.v, a.v); };
+    void inv(Element &r, Element &a);
+    void div(Element &r, Element &a, Element &b);
+    void exp(Element &r, Element &base, uint8_t* scalar, unsigned int scalarSize);
+
+    void inline toMontgomery(Element &r, Element &a) { Fr_rawToMontgomery(r.v, a.v); };
+    void inline fromMontgomery(Element &r, Element &a) { Fr_rawFromMontgomery(r.v, a.v); };
+    int inline eq(Element &a, Element &b) { return Fr_rawIsEq(a.v, b.v); };
+    int inline isZero(Element &a) { return Fr_rawIsZero(a.v); };
+
     void fromUI(Element &r, unsigned long int v);
 
     static RawFr field;
@@ -155,6 +126,3 @@ public:
 
 
 #endif // __FR_H
-
-
-
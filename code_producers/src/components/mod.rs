@@ -15,9 +15,96 @@ pub type InputOutputList = Vec<IODef>;
 pub type TemplateInstanceIOMap = BTreeMap<usize, InputOutputList>;
 pub type MessageList = Vec<String>;
 
+// Representation of the field elements in the witness generators
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FieldLayout {
+    // Short values and Montgomery limbs with a type tag, for any prime
+    MultiLimb,
+    // The canonical value in a single u64, for the Goldilocks prime
+    Goldilocks64,
+}
+
+impl FieldLayout {
+    pub fn for_prime(prime_str: &str) -> FieldLayout {
+        match prime_str {
+            "goldilocks" => FieldLayout::Goldilocks64,
+            _ => FieldLayout::MultiLimb,
+        }
+    }
+}
+
 pub fn hasher(value: &str) -> u64 {
     use lz_fnv::FnvHasher;
     let mut fnv_hasher: Fnv1a<u64> = Fnv1a::with_key(14695981039346656037);
     fnv_hasher.write(value.as_bytes());
     fnv_hasher.finish()
 }
+
+// JSON Schema of the inputs of the main component, given with their dimensions.
+// The witness generators validate the inputs with the prime and the dimensions
+// of the x-circom annotations, which describe the same inputs as the schema.
+pub fn input_schema(prime: &str, inputs: &[(String, Vec<usize>)]) -> serde_json::Value {
+    use serde_json::{json, Map, Value};
+    fn array_schema(lengths: &[usize]) -> Value {
+        match lengths.split_first() {
+            None => json!({ "$ref": "#/$defs/field" }),
+            Some((length, rest)) => json!({
+                "type": "array",
+                "minItems": length,
+                "maxItems": length,
+                "items": array_schema(rest)
+            }),
+        }
+    }
+    let mut properties = Map::new();
+    let mut dimensions = Map::new();
+    for (name, lengths) in inputs {
+        let schema = if lengths.len() > 1 {
+            // arrays can also be given flattened, in row-major order
+            json!({ "anyOf": [array_schema(lengths), array_schema(&[lengths.iter().product()])] })
+        } else {
+            array_schema(lengths)
+        };
+        properties.insert(name.clone(), schema);
+        dimensions.insert(name.clone(), json!(lengths));
+    }
+    let required: Vec<&String> = inputs.iter().map(|(name, _)| name).collect();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Inputs of the main component",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+        "$defs": {
+            "field": {
+                "description": format!("Element of the field, an integer greater than -p and lower than p = {}", prime),
+                "anyOf": [
+                    { "type": "integer" },
+                    { "type": "string", "pattern": "^-?[0-9]+$" }
+                ]
+            }
+        },
+        "x-circom-prime": prime,
+        "x-circom-dimensions": dimensions
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_schema_describes_the_dimensions() {
+        let inputs = vec![("a".to_string(), vec![]), ("m".to_string(), vec![2, 3])];
+        let schema = input_schema("17", &inputs);
+        assert_eq!(schema["required"], serde_json::json!(["a", "m"]));
+        assert_eq!(schema["properties"]["a"]["$ref"], "#/$defs/field");
+        let nested = &schema["properties"]["m"]["anyOf"][0];
+        assert_eq!(nested["maxItems"], 2);
+        assert_eq!(nested["items"]["minItems"], 3);
+        assert_eq!(schema["properties"]["m"]["anyOf"][1]["maxItems"], 6);
+        assert_eq!(schema["x-circom-dimensions"]["m"], serde_json::json!([2, 3]));
+        assert_eq!(schema["x-circom-prime"], "17");
+    }
+}
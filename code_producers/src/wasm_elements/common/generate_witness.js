@@ -1,20 +1,92 @@
 const wc  = require("./witness_calculator.js");
-const { readFileSync, writeFile } = require("fs");
+const { readFileSync, writeFile, writeFileSync, mkdirSync, readdirSync, statSync, existsSync, createReadStream } = require("fs");
+const path = require("path");
+const readline = require("readline");
 
-if (process.argv.length != 5) {
-    console.log("Usage: node generate_witness.js <file.wasm> <input.json> <output.wtns>");
+const args = [];
+const traceFilter = [];
+let traceFile;
+let batch = false;
+for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i] == "--batch") {
+	batch = true;
+    } else if (process.argv[i] == "--trace" && i+1 < process.argv.length) {
+	traceFile = process.argv[++i];
+    } else if (process.argv[i] == "--trace-filter" && i+1 < process.argv.length) {
+	traceFilter.push(process.argv[++i]);
+    } else {
+	args.push(process.argv[i]);
+    }
+}
+
+if (args.length != 3 || (batch && traceFile)) {
+    console.log("Usage: node generate_witness.js <file.wasm> <input.json> <output.wtns> [--trace <trace file>] [--trace-filter <component path prefix>]...");
+    console.log("       node generate_witness.js --batch <file.wasm> <inputs.jsonl | input directory | -> <output directory>");
+} else if (batch) {
+    const buffer = readFileSync(args[0]);
+    wc(buffer, { inputSchema: inputSchema(args[0]) }).then(async witnessCalculator => {
+	mkdirSync(args[2], { recursive: true });
+	// the failures of an input do not stop the others
+	let failures = 0;
+	for await (const [name, text] of batchInputs(args[1])) {
+	    try {
+		const buff = await witnessCalculator.calculateWTNSBin(JSON.parse(text), 0);
+		writeFileSync(path.join(args[2], name + ".wtns"), buff);
+	    } catch (err) {
+		failures++;
+		console.error(`Failed to compute the witness of ${name}: ${err.message.trim()}`);
+	    }
+	}
+	process.exitCode = failures == 0 ? 0 : 1;
+    });
 } else {
-    const input = JSON.parse(readFileSync(process.argv[3], "utf8"));
+    const input = JSON.parse(readFileSync(args[1], "utf8"));
+    const traceLines = [];
+    const options = traceFile ? { trace: (line) => traceLines.push(line), traceFilter: traceFilter } : {};
+    options.inputSchema = inputSchema(args[0]);
     
-    const buffer = readFileSync(process.argv[2]);
-    wc(buffer).then(async witnessCalculator => {
+    const buffer = readFileSync(args[0]);
+    wc(buffer, options).then(async witnessCalculator => {
 	//    const w= await witnessCalculator.calculateWitness(input,0);
 	//    for (let i=0; i< w.length; i++){
 	//	console.log(w[i]);
 	//    }
-	const buff= await witnessCalculator.calculateWTNSBin(input,0);
-	writeFile(process.argv[4], buff, function(err) {
-	    if (err) throw err;
-	});
+	try {
+	    const buff= await witnessCalculator.calculateWTNSBin(input,0);
+	    writeFile(args[2], buff, function(err) {
+		if (err) throw err;
+	    });
+	} catch (err) {
+	    console.error(err.message.trim());
+	    process.exitCode = 1;
+	} finally {
+	    if (traceFile) {
+		writeFileSync(traceFile, traceLines.map((line) => line + "\n").join(""));
+	    }
+	}
     });
 }
+
+// Schema of the inputs written by the compiler next to the wasm file, if any
+function inputSchema(wasmFile) {
+    const schemaFile = wasmFile.replace(/\.wasm$/, "") + ".schema.json";
+    return existsSync(schemaFile) ? JSON.parse(readFileSync(schemaFile, "utf8")) : undefined;
+}
+
+// Inputs of a batch as [name, JSON text]: the lines of a JSON lines file, or of
+// the standard input with "-", named after their line number, or the .json
+// files of a directory, named after the file
+async function* batchInputs(source) {
+    if (source != "-" && statSync(source).isDirectory()) {
+	for (const file of readdirSync(source).filter((f) => f.endsWith(".json")).sort()) {
+	    yield [file.slice(0, -5), readFileSync(path.join(source, file), "utf8")];
+	}
+	return;
+    }
+    const input = (source == "-") ? process.stdin : createReadStream(source);
+    let lineNumber = 0;
+    for await (const line of readline.createInterface({ input: input, crlfDelay: Infinity })) {
+	lineNumber++;
+	if (line.trim() != "") yield [String(lineNumber), line];
+    }
+}
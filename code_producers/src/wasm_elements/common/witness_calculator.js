@@ -14,6 +14,13 @@ module.exports = async function builder(code, options) {
     let wc;
 
     let errStr = "";
+
+    // execution trace, only produced by circuits compiled with --trace
+    const trace = options.trace;
+    const traceFilter = options.traceFilter || [];
+    const tracePaths = new Map();
+    const traceStack = [];
+    let traceValues = [];
     
     const instance = await WebAssembly.instantiate(wasmModule, {
         runtime: {
@@ -34,7 +41,10 @@ module.exports = async function builder(code, options) {
 		} else {
 		    err = "Unknown error.\n";
                 }
-                throw new Error(err + errStr);
+		// the calculator can be used again after the error
+		const message = err + errStr;
+		errStr = "";
+                throw new Error(message);
             },
 	    printErrorMessage : function() {
 		errStr += getMessage() + "\n";
@@ -45,7 +55,30 @@ module.exports = async function builder(code, options) {
 	    },
 	    showSharedRWMemory : function() {
 		printSharedRWMemory ();
-            }
+            },
+	    traceComponent : function(father, cmp, index) {
+		tracePaths.set(cmp, tracePath(father) + "." + componentName(getMessage(), index));
+	    },
+	    traceRun : function(cmp) {
+		traceStack.push(cmp);
+		const path = tracePath(cmp);
+		writeTrace(path, "R " + path);
+	    },
+	    traceEnd : function() {
+		traceStack.pop();
+	    },
+	    traceValue : function() {
+		traceValues.push(readSharedRWMemory());
+	    },
+	    traceSignal : function(cmp, signal) {
+		const path = tracePath(cmp);
+		writeTrace(path, `S ${path} ${getMessage()} ${signal} ${traceValues.pop()}`);
+	    },
+	    traceCall : function(n) {
+		const path = tracePath(traceStack[traceStack.length-1]);
+		const args = traceValues.splice(0, n);
+		writeTrace(path, `F ${path} ${getMessage()}(${args.join(",")})`);
+	    }
 
         }
     });
@@ -62,7 +95,7 @@ module.exports = async function builder(code, options) {
 //        );
 
     
-    wc = new WitnessCalculator(instance, sanityCheck);
+    wc = new WitnessCalculator(instance, sanityCheck, options.inputSchema);
     return wc;
 
     function getMessage() {
@@ -75,6 +108,48 @@ module.exports = async function builder(code, options) {
         return message;
     }
 	
+    function readSharedRWMemory () {
+	const shared_rw_memory_size = instance.exports.getFieldNumLen32();
+	const arr = new Uint32Array(shared_rw_memory_size);
+	for (let j=0; j<shared_rw_memory_size; j++) {
+	    arr[shared_rw_memory_size-1-j] = instance.exports.readSharedRWMemory(j);
+	}
+	return fromArray32(arr);
+    }
+
+    // the main component is the only one created without being traced
+    function tracePath(cmp) {
+	const path = tracePaths.get(cmp);
+	return (typeof path === "undefined") ? "main" : path;
+    }
+
+    // arrays of components are traced with their dimensions, e.g. c[2][3]
+    function componentName(name, index) {
+	if (index < 0) return name;
+	const base = name.slice(0, name.indexOf("["));
+	const dims = name.slice(base.length + 1, -1).split("][").map((d) => parseInt(d));
+	let positions = "";
+	for (let i = dims.length-1; i >= 0; i--) {
+	    positions = "[" + (index % dims[i]) + "]" + positions;
+	    index = Math.floor(index / dims[i]);
+	}
+	return base + positions;
+    }
+
+    // a filter keeps a component and the components inside it: main.m[1]
+    // keeps main.m[1].c but not main.m[10]
+    function isTraced(path) {
+	return traceFilter.length == 0 || traceFilter.some((prefix) =>
+	    path.startsWith(prefix) &&
+		(path.length == prefix.length || path[prefix.length] == "." || path[prefix.length] == "["));
+    }
+
+    function writeTrace(path, line) {
+	if (!trace) return;
+	if (!isTraced(path)) return;
+	trace(line);
+    }
+
     function printSharedRWMemory () {
 	const shared_rw_memory_size = instance.exports.getFieldNumLen32();
 	const arr = new Uint32Array(shared_rw_memory_size);
@@ -89,7 +164,7 @@ module.exports = async function builder(code, options) {
 };
 
 class WitnessCalculator {
-    constructor(instance, sanityCheck) {
+    constructor(instance, sanityCheck, inputSchema) {
         this.instance = instance;
 
 	this.version = this.instance.exports.getVersion();
@@ -105,6 +180,8 @@ class WitnessCalculator {
         this.witnessSize = this.instance.exports.getWitnessSize();
 
         this.sanityCheck = sanityCheck;
+
+        this.inputSchema = inputSchema;
     }
     
     circom_version() {
@@ -113,6 +190,9 @@ class WitnessCalculator {
 
     async _doCalculateWitness(input, sanityCheck) {
 	//input is assumed to be a map from signals to arrays of bigints
+	if (this.inputSchema) {
+	    validateInputs(input, this.inputSchema);
+	}
         this.instance.exports.init((this.sanityCheck || sanityCheck) ? 1 : 0);
         const keys = Object.keys(input);
 	var input_counter = 0;
@@ -132,7 +212,8 @@ class WitnessCalculator {
 		throw new Error(`Too many values for input signal ${k}\n`);
 	    }
             for (let i=0; i<fArr.length; i++) {
-                const arrFr = toArray32(BigInt(fArr[i])%this.prime,this.n32)
+                // negative values are taken modulo the prime as well
+                const arrFr = toArray32(((BigInt(fArr[i]) % this.prime) + this.prime) % this.prime,this.n32)
                 for (let j=0; j<this.n32; j++) {
 		    this.instance.exports.writeSharedRWMemory(j,arrFr[this.n32-1-j]);
 		}
@@ -257,6 +338,91 @@ class WitnessCalculator {
 }
 
 
+// Checks the inputs against the schema of the main inputs before setting any
+// of them, and fails with every unknown, missing or malformed signal
+function validateInputs(input, schema) {
+    const dimensions = schema["x-circom-dimensions"];
+    const prime = BigInt(schema["x-circom-prime"]);
+    if (typeof input !== "object" || input === null || Array.isArray(input)) {
+	throw new Error("The inputs must be a JSON object\n");
+    }
+    const errors = [];
+    for (const name of Object.keys(input)) {
+	if (!Object.prototype.hasOwnProperty.call(dimensions, name)) {
+	    errors.push(`Unknown input signal ${name}`);
+	}
+    }
+    for (const name of schema.required) {
+	if (!Object.prototype.hasOwnProperty.call(input, name)) {
+	    errors.push(`Missing input signal ${name}`);
+	} else {
+	    validateArray(input[name], dimensions[name], 0, name);
+	}
+    }
+    if (errors.length > 0) {
+	throw new Error(errors.map((error) => error + "\n").join(""));
+    }
+
+    function validateArray(v, dims, d, name) {
+	if (d == dims.length) {
+	    validateElement(v, name);
+	    return;
+	}
+	if (!Array.isArray(v)) {
+	    errors.push(`Input signal ${name} must be an array of ${dims[d]} elements`);
+	    return;
+	}
+	if (d == 0 && dims.length > 1 && !v.some((e) => Array.isArray(e))) {
+	    // a flattened array, in row-major order
+	    const total = dims.reduce((a, b) => a * b, 1);
+	    if (v.length != total) {
+		errors.push(`Input signal ${name} has ${v.length} elements instead of ${dims[0]}, or ${total} when flattened`);
+		return;
+	    }
+	    for (let i = 0; i < v.length; i++) {
+		validateElement(v[i], name + elementPosition(dims, i));
+	    }
+	    return;
+	}
+	if (v.length != dims[d]) {
+	    errors.push(`Input signal ${name} has ${v.length} elements instead of ${dims[d]}`);
+	    return;
+	}
+	for (let i = 0; i < v.length; i++) {
+	    validateArray(v[i], dims, d + 1, `${name}[${i}]`);
+	}
+    }
+
+    function validateElement(v, name) {
+	if (Array.isArray(v)) {
+	    errors.push(`Input signal ${name} must be a field element, not an array`);
+	    return;
+	}
+	let value;
+	if (typeof v === "bigint" || (typeof v === "number" && Number.isInteger(v))) {
+	    value = BigInt(v);
+	} else if (typeof v === "string" && /^-?[0-9]+$/.test(v)) {
+	    value = BigInt(v);
+	} else {
+	    errors.push(`Input signal ${name} is not an integer: ${JSON.stringify(v)}`);
+	    return;
+	}
+	if (value >= prime || -value >= prime) {
+	    errors.push(`Input signal ${name} is out of the range of the field: ${value}`);
+	}
+    }
+}
+
+// Position of the i-th element, in row-major order, of an array of the given dimensions
+function elementPosition(dims, i) {
+    let position = "";
+    for (let d = dims.length-1; d >= 0; d--) {
+	position = "[" + (i % dims[d]) + "]" + position;
+	i = Math.floor(i / dims[d]);
+    }
+    return position;
+}
+
 function toArray32(rem,size) {
     const res = []; //new Uint32Array(size); //has no unshift
     const radix = BigInt(0x100000000);
@@ -0,0 +1,650 @@
+;; Arithmetic of the Goldilocks field, p = 2^64 - 2^32 + 1, with every element
+;; stored as its canonical value in a single i64
+(func $Fr_raw_reduce (type $_sig_i64ri64)
+     (param $x i64)
+    (result i64)
+    get_local $x
+    get_local $x
+    i64.const -4294967295
+    i64.sub
+    get_local $x
+    i64.const -4294967295
+    i64.lt_u
+    select
+)
+(func $Fr_raw_add (type $_sig_i64i64ri64)
+     (param $a i64)
+     (param $b i64)
+    (result i64)
+     (local $s i64)
+    get_local $a
+    get_local $b
+    i64.add
+    set_local $s
+    get_local $s
+    get_local $a
+    i64.lt_u
+    if
+        ;; 2^64 = 2^32 - 1 (mod p)
+        get_local $s
+        i64.const 4294967295
+        i64.add
+        return
+    end
+    get_local $s
+    call $Fr_raw_reduce
+)
+(func $Fr_raw_sub (type $_sig_i64i64ri64)
+     (param $a i64)
+     (param $b i64)
+    (result i64)
+    get_local $a
+    get_local $b
+    i64.sub
+    i64.const -4294967295
+    i64.const 0
+    get_local $a
+    get_local $b
+    i64.lt_u
+    select
+    i64.add
+)
+;; Product of 128 bits from halves of 32 bits, reduced with 2^64 = 2^32 - 1
+;; and 2^96 = -1 (mod p)
+(func $Fr_raw_mul (type $_sig_i64i64ri64)
+     (param $a i64)
+     (param $b i64)
+    (result i64)
+     (local $a0 i64)
+     (local $a1 i64)
+     (local $b0 i64)
+     (local $b1 i64)
+     (local $p00 i64)
+     (local $p10 i64)
+     (local $mid i64)
+     (local $lo i64)
+     (local $hi i64)
+     (local $t0 i64)
+     (local $t1 i64)
+    get_local $a
+    i64.const 4294967295
+    i64.and
+    set_local $a0
+    get_local $a
+    i64.const 32
+    i64.shr_u
+    set_local $a1
+    get_local $b
+    i64.const 4294967295
+    i64.and
+    set_local $b0
+    get_local $b
+    i64.const 32
+    i64.shr_u
+    set_local $b1
+    get_local $a0
+    get_local $b0
+    i64.mul
+    set_local $p00
+    get_local $a1
+    get_local $b0
+    i64.mul
+    set_local $p10
+    get_local $a0
+    get_local $b1
+    i64.mul
+    get_local $p00
+    i64.const 32
+    i64.shr_u
+    i64.add
+    get_local $p10
+    i64.const 4294967295
+    i64.and
+    i64.add
+    set_local $mid
+    get_local $mid
+    i64.const 32
+    i64.shl
+    get_local $p00
+    i64.const 4294967295
+    i64.and
+    i64.or
+    set_local $lo
+    get_local $a1
+    get_local $b1
+    i64.mul
+    get_local $mid
+    i64.const 32
+    i64.shr_u
+    i64.add
+    get_local $p10
+    i64.const 32
+    i64.shr_u
+    i64.add
+    set_local $hi
+    get_local $lo
+    get_local $hi
+    i64.const 32
+    i64.shr_u
+    i64.sub
+    set_local $t0
+    get_local $lo
+    get_local $hi
+    i64.const 32
+    i64.shr_u
+    i64.lt_u
+    if
+        get_local $t0
+        i64.const 4294967295
+        i64.sub
+        set_local $t0
+    end
+    get_local $hi
+    i64.const 4294967295
+    i64.and
+    i64.const 4294967295
+    i64.mul
+    set_local $t1
+    get_local $t0
+    get_local $t1
+    i64.add
+    set_local $t0
+    get_local $t0
+    get_local $t1
+    i64.lt_u
+    if
+        get_local $t0
+        i64.const 4294967295
+        i64.add
+        return
+    end
+    get_local $t0
+    call $Fr_raw_reduce
+)
+(func $Fr_raw_pow (type $_sig_i64i64ri64)
+     (param $a i64)
+     (param $e i64)
+    (result i64)
+     (local $r i64)
+    i64.const 1
+    set_local $r
+    block
+        loop
+            get_local $e
+            i64.eqz
+            br_if 1
+            get_local $e
+            i64.const 1
+            i64.and
+            i32.wrap_i64
+            if
+                get_local $r
+                get_local $a
+                call $Fr_raw_mul
+                set_local $r
+            end
+            get_local $a
+            get_local $a
+            call $Fr_raw_mul
+            set_local $a
+            get_local $e
+            i64.const 1
+            i64.shr_u
+            set_local $e
+            br 0
+        end
+    end
+    get_local $r
+)
+;; Values greater than (p-1)/2 are negative in the comparisons
+(func $Fr_raw_signed (type $_sig_i64ri64)
+     (param $x i64)
+    (result i64)
+    get_local $x
+    i64.const -4294967295
+    i64.sub
+    get_local $x
+    get_local $x
+    i64.const 9223372034707292160
+    i64.gt_u
+    select
+)
+(func $Fr_int_copy (type $_sig_i32i32)
+     (param $px i32)
+     (param $pr i32)
+    get_local $pr
+    get_local $px
+    i64.load
+    i64.store
+)
+(func $Fr_copy (type $_sig_i32i32)
+     (param $pr i32)
+     (param $px i32)
+    get_local $pr
+    get_local $px
+    i64.load
+    i64.store
+)
+(func $Fr_copyn (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $px i32)
+     (param $n i32)
+    block
+        loop
+            get_local $n
+            i32.eqz
+            br_if 1
+            get_local $pr
+            get_local $px
+            i64.load
+            i64.store
+            get_local $pr
+            i32.const 8
+            i32.add
+            set_local $pr
+            get_local $px
+            i32.const 8
+            i32.add
+            set_local $px
+            get_local $n
+            i32.const 1
+            i32.sub
+            set_local $n
+            br 0
+        end
+    end
+)
+(func $Fr_isTrue (type $_sig_i32ri32)
+     (param $px i32)
+    (result i32)
+    get_local $px
+    i64.load
+    i64.eqz
+    i32.eqz
+)
+(func $Fr_toInt (type $_sig_i32ri32)
+     (param $px i32)
+    (result i32)
+     (local $x i64)
+    get_local $px
+    i64.load
+    set_local $x
+    get_local $x
+    i64.const 9223372034707292160
+    i64.gt_u
+    if
+        i32.const 0
+        i64.const -4294967295
+        get_local $x
+        i64.sub
+        i32.wrap_i64
+        i32.sub
+        return
+    end
+    get_local $x
+    i32.wrap_i64
+)
+(func $Fr_add (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    call $Fr_raw_add
+    i64.store
+)
+(func $Fr_sub (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    call $Fr_raw_sub
+    i64.store
+)
+(func $Fr_neg (type $_sig_i32i32)
+     (param $pr i32)
+     (param $pa i32)
+    get_local $pr
+    i64.const 0
+    get_local $pa
+    i64.load
+    call $Fr_raw_sub
+    i64.store
+)
+(func $Fr_mul (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    call $Fr_raw_mul
+    i64.store
+)
+(func $Fr_pow (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    call $Fr_raw_pow
+    i64.store
+)
+;; a^(p-2), which is 0 for 0
+(func $Fr_inv (type $_sig_i32i32)
+     (param $pr i32)
+     (param $pa i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    i64.const -4294967297
+    call $Fr_raw_pow
+    i64.store
+)
+(func $Fr_div (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.const -4294967297
+    call $Fr_raw_pow
+    call $Fr_raw_mul
+    i64.store
+)
+(func $Fr_idiv (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.div_u
+    i64.store
+)
+(func $Fr_mod (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.rem_u
+    i64.store
+)
+(func $Fr_eq (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.eq
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_neq (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.ne
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_lt (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    call $Fr_raw_signed
+    get_local $pb
+    i64.load
+    call $Fr_raw_signed
+    i64.lt_s
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_gt (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    call $Fr_raw_signed
+    get_local $pb
+    i64.load
+    call $Fr_raw_signed
+    i64.gt_s
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_leq (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    call $Fr_raw_signed
+    get_local $pb
+    i64.load
+    call $Fr_raw_signed
+    i64.le_s
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_geq (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    call $Fr_raw_signed
+    get_local $pb
+    i64.load
+    call $Fr_raw_signed
+    i64.ge_s
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_land (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    call $Fr_isTrue
+    get_local $pb
+    call $Fr_isTrue
+    i32.and
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_lor (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    call $Fr_isTrue
+    get_local $pb
+    call $Fr_isTrue
+    i32.or
+    i64.extend_i32_u
+    i64.store
+)
+(func $Fr_lnot (type $_sig_i32i32)
+     (param $pr i32)
+     (param $pa i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    i64.eqz
+    i64.extend_i32_u
+    i64.store
+)
+;; The bit operations work on the 64 bits of the values and reduce the result once
+(func $Fr_band (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.and
+    call $Fr_raw_reduce
+    i64.store
+)
+(func $Fr_bor (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.or
+    call $Fr_raw_reduce
+    i64.store
+)
+(func $Fr_bxor (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    get_local $pb
+    i64.load
+    i64.xor
+    call $Fr_raw_reduce
+    i64.store
+)
+(func $Fr_bnot (type $_sig_i32i32)
+     (param $pr i32)
+     (param $pa i32)
+    get_local $pr
+    get_local $pa
+    i64.load
+    i64.const -1
+    i64.xor
+    call $Fr_raw_reduce
+    i64.store
+)
+;; A negative amount, p - n, shifts n bits in the opposite direction, and the
+;; shifts of 64 bits or more give 0
+(func $Fr_shl (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+     (local $n i64)
+    get_local $pb
+    i64.load
+    set_local $n
+    get_local $n
+    i64.const 64
+    i64.lt_u
+    if
+        get_local $pr
+        get_local $pa
+        i64.load
+        get_local $n
+        i64.shl
+        call $Fr_raw_reduce
+        i64.store
+        return
+    end
+    i64.const -4294967295
+    get_local $n
+    i64.sub
+    set_local $n
+    get_local $n
+    i64.const 64
+    i64.lt_u
+    if
+        get_local $pr
+        get_local $pa
+        i64.load
+        get_local $n
+        i64.shr_u
+        i64.store
+        return
+    end
+    get_local $pr
+    i64.const 0
+    i64.store
+)
+(func $Fr_shr (type $_sig_i32i32i32)
+     (param $pr i32)
+     (param $pa i32)
+     (param $pb i32)
+     (local $n i64)
+    get_local $pb
+    i64.load
+    set_local $n
+    get_local $n
+    i64.const 64
+    i64.lt_u
+    if
+        get_local $pr
+        get_local $pa
+        i64.load
+        get_local $n
+        i64.shr_u
+        i64.store
+        return
+    end
+    i64.const -4294967295
+    get_local $n
+    i64.sub
+    set_local $n
+    get_local $n
+    i64.const 64
+    i64.lt_u
+    if
+        get_local $pr
+        get_local $pa
+        i64.load
+        get_local $n
+        i64.shl
+        call $Fr_raw_reduce
+        i64.store
+        return
+    end
+    get_local $pr
+    i64.const 0
+    i64.store
+)
@@ -0,0 +1 @@
+;; The elements are canonical u64 values and the constants of the field are immediates
@@ -1,8 +1,11 @@
+pub mod wasm_binary;
 pub mod wasm_code_generator;
+pub mod wasm_disassembler;
+pub mod wasm_library;
 
 use crate::components::*;
+pub use wasm_binary::WasmInstruction;
 
-type WasmInstruction = String;
 
 pub struct WASMProducer {
     pub main_signal_offset: usize,
@@ -18,13 +21,18 @@ pub struct WASMProducer {
     pub main_header: String,
     pub prime: String,
     pub prime_str: String,
+    pub field_layout: FieldLayout,
     pub main_input_list: InputList,
+    // dimensions of the main inputs, in the order of main_input_list
+    pub main_input_dimensions: Vec<Vec<usize>>,
     pub witness_to_signal_list: SignalList,
     pub io_map: TemplateInstanceIOMap,
     pub template_instance_list: TemplateList,
     pub message_list: MessageList,
     pub field_tracking: Vec<String>,
     pub wat_flag: bool,
+    pub trace_flag: bool,
+    pub source_map_flag: bool,
     version: usize,
     stack_free_pos: usize,
     local_info_size_u32: usize,
@@ -50,6 +58,7 @@ pub struct WASMProducer {
     create_loop_offset_tag: String,
     create_loop_counter_tag: String,
     merror_tag: String,
+    trace_dest_tag: String,
     string_table:  Vec<String>,
 }
 
@@ -65,12 +74,14 @@ impl Default for WASMProducer {
             prime: "21888242871839275222246405745257275088548364400416034343698204186575808495617"
                 .to_string(),
             prime_str: "bn128".to_string(),
+            field_layout: FieldLayout::MultiLimb,
             fr_memory_size: 1948,
             size_32_bit: 8,
             size_32_shift: 5,
             number_of_main_outputs: 0, //2,
             number_of_main_inputs: 0,  // 4,
             main_input_list: [("in1".to_string(), 1, 1), ("in2".to_string(), 2, 1)].to_vec(), //("inpair".to_string(),2),
+            main_input_dimensions: [vec![], vec![]].to_vec(),
             signals_in_witness: 0,                                                      //20,
             witness_to_signal_list: [].to_vec(), //[0,1,2,3,4,5,6,12,16,19,24,27,33,42,46,50,51,65,78,79].to_vec(),
             message_list: [].to_vec(), //["Main".to_string(),"Hola Herme".to_string(),"Hola Albert".to_string()].to_vec(),
@@ -81,6 +92,8 @@ impl Default for WASMProducer {
             template_instance_list: [].to_vec(),
             field_tracking: [].to_vec(),
             wat_flag: true,
+            trace_flag: false,
+            source_map_flag: false,
             // fix values
             version: 2,
             stack_free_pos: 0,
@@ -107,6 +120,7 @@ impl Default for WASMProducer {
             create_loop_offset_tag: "$createloopoffset".to_string(),
             create_loop_counter_tag: "$createloopcounter".to_string(),
 	        merror_tag: "$merror".to_string(),
+            trace_dest_tag: "$tracedest".to_string(),
             string_table: Vec::new(),
         }
     }
@@ -153,6 +167,9 @@ impl WASMProducer {
     pub fn get_prime(&self) -> &str {
         &self.prime
     }
+    pub fn get_field_layout(&self) -> FieldLayout {
+        self.field_layout
+    }
     pub fn get_number_of_main_outputs(&self) -> usize {
         self.number_of_main_outputs
     }
@@ -162,6 +179,16 @@ impl WASMProducer {
     pub fn get_main_input_list(&self) -> &InputList {
         &self.main_input_list
     }
+    pub fn get_main_input_dimensions(&self) -> &Vec<Vec<usize>> {
+        &self.main_input_dimensions
+    }
+    pub fn get_input_schema(&self) -> serde_json::Value {
+        let inputs: Vec<(String, Vec<usize>)> = self.main_input_list.iter()
+            .zip(&self.main_input_dimensions)
+            .map(|((name, _, _), dimensions)| (name.clone(), dimensions.clone()))
+            .collect();
+        input_schema(&self.prime, &inputs)
+    }
     pub fn get_number_of_witness(&self) -> usize {
         self.signals_in_witness
     }
@@ -238,10 +265,10 @@ impl WASMProducer {
         4 + self.fr_memory_size
     }
     pub fn get_shared_rw_memory_start(&self) -> usize {
-        (4 * self.size_32_bit) + 8 + self.get_raw_prime_start()
+        4 * self.get_size_32_bits_in_memory() + self.get_raw_prime_start()
     }
     pub fn get_input_signals_hashmap_start(&self) -> usize {
-        (4 * self.size_32_bit) + 8 + self.get_shared_rw_memory_start()
+        4 * self.get_size_32_bits_in_memory() + self.get_shared_rw_memory_start()
     }
     pub fn get_remaining_input_signal_counter(&self) -> usize {
         self.get_input_signals_hashmap_start() + 4096 // 256*(8(h)+4(pos)+4(size))
@@ -259,7 +286,7 @@ impl WASMProducer {
         self.get_signal_free_pos() + 4
     }
     pub fn get_component_free_pos(&self) -> usize {
-        let a = 2 + self.get_size_32_bit();
+        let a = self.get_size_32_bits_in_memory();
         let b = 4 * self.get_total_number_of_signals();
         let c = self.get_signal_memory_start();
         a * b + c
@@ -309,10 +336,15 @@ impl WASMProducer {
     }
     
     pub fn get_var_stack_memory_start(&self) -> usize {
-        self.get_constant_numbers_start() + (self.size_32_bit + 2) * 4 * self.field_tracking.len()
+        self.get_constant_numbers_start() + self.get_size_32_bits_in_memory() * 4 * self.field_tracking.len()
     }
+    // Words of 32 bits of a field element in memory, with the type and the
+    // short value before the limbs in the multi-limb layout
     pub fn get_size_32_bits_in_memory(&self) -> usize {
-        self.size_32_bit + 2
+        match self.field_layout {
+            FieldLayout::MultiLimb => self.size_32_bit + 2,
+            FieldLayout::Goldilocks64 => self.size_32_bit,
+        }
     }
     pub fn get_offset_tag(&self) -> &str {
         &self.offset_tag
@@ -374,8 +406,15 @@ impl WASMProducer {
     pub fn get_merror_tag(&self) -> &str {
 	&self.merror_tag
     }
-    pub fn needs_comments(&self) -> bool{
-        self.wat_flag
+    pub fn get_trace_dest_tag(&self) -> &str {
+        &self.trace_dest_tag
+    }
+    pub fn needs_trace(&self) -> bool {
+        self.trace_flag
+    }
+
+    pub fn needs_source_map(&self) -> bool {
+        self.source_map_flag
     }
 
     pub fn get_string_table(&self) -> &Vec<String> {
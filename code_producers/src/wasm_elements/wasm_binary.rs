@@ -0,0 +1,1164 @@
+use std::collections::HashMap;
+
+// Typed WebAssembly instructions and the encoder of the binary module. The code
+// generators produce the fields and the instructions of the module, which are
+// encoded as soon as they are added to it, so its text is never built
+
+pub const MAGIC: &[u8] = b"\0asm";
+pub const VERSION: &[u8] = &[1, 0, 0, 0];
+
+pub const SECTION_CUSTOM: u8 = 0;
+pub const SECTION_TYPE: u8 = 1;
+pub const SECTION_IMPORT: u8 = 2;
+pub const SECTION_FUNCTION: u8 = 3;
+pub const SECTION_TABLE: u8 = 4;
+pub const SECTION_MEMORY: u8 = 5;
+pub const SECTION_EXPORT: u8 = 7;
+pub const SECTION_ELEMENT: u8 = 9;
+pub const SECTION_CODE: u8 = 10;
+pub const SECTION_DATA: u8 = 11;
+
+pub const NAME_FUNCTIONS: u8 = 1;
+pub const NAME_LOCALS: u8 = 2;
+pub const NAME_TYPES: u8 = 4;
+pub const NAME_TABLES: u8 = 5;
+
+pub const EXTERNAL_FUNCTION: u8 = 0;
+pub const EXTERNAL_TABLE: u8 = 1;
+pub const EXTERNAL_MEMORY: u8 = 2;
+
+pub const FUNCREF: u8 = 0x70;
+pub const FUNCTION_TYPE: u8 = 0x60;
+pub const EMPTY_BLOCK: u8 = 0x40;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValType {
+    I32,
+    I64,
+}
+impl ValType {
+    pub fn code(&self) -> u8 {
+        match self {
+            ValType::I32 => 0x7f,
+            ValType::I64 => 0x7e,
+        }
+    }
+    pub fn from_code(code: u8) -> Option<ValType> {
+        match code {
+            0x7f => Some(ValType::I32),
+            0x7e => Some(ValType::I64),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValType::I32 => "i32",
+            ValType::I64 => "i64",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<ValType> {
+        match name {
+            "i32" => Some(ValType::I32),
+            "i64" => Some(ValType::I64),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FuncType {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+macro_rules! operators {
+    ($name:ident { $($variant:ident => $text:literal, $opcode:literal;)* }) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum $name {
+            $($variant,)*
+        }
+        impl $name {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $text,)*
+                }
+            }
+            pub fn opcode(&self) -> u8 {
+                match self {
+                    $($name::$variant => $opcode,)*
+                }
+            }
+            pub fn from_name(name: &str) -> Option<$name> {
+                match name {
+                    $($text => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+            pub fn from_opcode(opcode: u8) -> Option<$name> {
+                match opcode {
+                    $($opcode => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+// Instructions without immediates
+operators!(Operator {
+    Unreachable => "unreachable", 0x00;
+    Nop => "nop", 0x01;
+    Return => "return", 0x0f;
+    Drop => "drop", 0x1a;
+    Select => "select", 0x1b;
+    I32Eqz => "i32.eqz", 0x45;
+    I32Eq => "i32.eq", 0x46;
+    I32Ne => "i32.ne", 0x47;
+    I32LtS => "i32.lt_s", 0x48;
+    I32LtU => "i32.lt_u", 0x49;
+    I32GtS => "i32.gt_s", 0x4a;
+    I32GtU => "i32.gt_u", 0x4b;
+    I32LeS => "i32.le_s", 0x4c;
+    I32LeU => "i32.le_u", 0x4d;
+    I32GeS => "i32.ge_s", 0x4e;
+    I32GeU => "i32.ge_u", 0x4f;
+    I64Eqz => "i64.eqz", 0x50;
+    I64Eq => "i64.eq", 0x51;
+    I64Ne => "i64.ne", 0x52;
+    I64LtS => "i64.lt_s", 0x53;
+    I64LtU => "i64.lt_u", 0x54;
+    I64GtS => "i64.gt_s", 0x55;
+    I64GtU => "i64.gt_u", 0x56;
+    I64LeS => "i64.le_s", 0x57;
+    I64LeU => "i64.le_u", 0x58;
+    I64GeS => "i64.ge_s", 0x59;
+    I64GeU => "i64.ge_u", 0x5a;
+    I32Clz => "i32.clz", 0x67;
+    I32Ctz => "i32.ctz", 0x68;
+    I32Popcnt => "i32.popcnt", 0x69;
+    I32Add => "i32.add", 0x6a;
+    I32Sub => "i32.sub", 0x6b;
+    I32Mul => "i32.mul", 0x6c;
+    I32DivS => "i32.div_s", 0x6d;
+    I32DivU => "i32.div_u", 0x6e;
+    I32RemS => "i32.rem_s", 0x6f;
+    I32RemU => "i32.rem_u", 0x70;
+    I32And => "i32.and", 0x71;
+    I32Or => "i32.or", 0x72;
+    I32Xor => "i32.xor", 0x73;
+    I32Shl => "i32.shl", 0x74;
+    I32ShrS => "i32.shr_s", 0x75;
+    I32ShrU => "i32.shr_u", 0x76;
+    I32Rotl => "i32.rotl", 0x77;
+    I32Rotr => "i32.rotr", 0x78;
+    I64Clz => "i64.clz", 0x79;
+    I64Ctz => "i64.ctz", 0x7a;
+    I64Popcnt => "i64.popcnt", 0x7b;
+    I64Add => "i64.add", 0x7c;
+    I64Sub => "i64.sub", 0x7d;
+    I64Mul => "i64.mul", 0x7e;
+    I64DivS => "i64.div_s", 0x7f;
+    I64DivU => "i64.div_u", 0x80;
+    I64RemS => "i64.rem_s", 0x81;
+    I64RemU => "i64.rem_u", 0x82;
+    I64And => "i64.and", 0x83;
+    I64Or => "i64.or", 0x84;
+    I64Xor => "i64.xor", 0x85;
+    I64Shl => "i64.shl", 0x86;
+    I64ShrS => "i64.shr_s", 0x87;
+    I64ShrU => "i64.shr_u", 0x88;
+    I64Rotl => "i64.rotl", 0x89;
+    I64Rotr => "i64.rotr", 0x8a;
+    I32WrapI64 => "i32.wrap_i64", 0xa7;
+    I64ExtendI32S => "i64.extend_i32_s", 0xac;
+    I64ExtendI32U => "i64.extend_i32_u", 0xad;
+});
+
+// Loads and stores, whose immediate is a memarg
+operators!(MemoryOperator {
+    I32Load => "i32.load", 0x28;
+    I64Load => "i64.load", 0x29;
+    I32Load8S => "i32.load8_s", 0x2c;
+    I32Load8U => "i32.load8_u", 0x2d;
+    I32Load16S => "i32.load16_s", 0x2e;
+    I32Load16U => "i32.load16_u", 0x2f;
+    I64Load8S => "i64.load8_s", 0x30;
+    I64Load8U => "i64.load8_u", 0x31;
+    I64Load16S => "i64.load16_s", 0x32;
+    I64Load16U => "i64.load16_u", 0x33;
+    I64Load32S => "i64.load32_s", 0x34;
+    I64Load32U => "i64.load32_u", 0x35;
+    I32Store => "i32.store", 0x36;
+    I64Store => "i64.store", 0x37;
+    I32Store8 => "i32.store8", 0x3a;
+    I32Store16 => "i32.store16", 0x3b;
+    I64Store8 => "i64.store8", 0x3c;
+    I64Store16 => "i64.store16", 0x3d;
+    I64Store32 => "i64.store32", 0x3e;
+});
+
+impl MemoryOperator {
+    // Log2 of the number of bytes accessed, the alignment used when none is given
+    pub fn natural_alignment(&self) -> u32 {
+        use MemoryOperator::*;
+        match self {
+            I32Load8S | I32Load8U | I64Load8S | I64Load8U | I32Store8 | I64Store8 => 0,
+            I32Load16S | I32Load16U | I64Load16S | I64Load16U | I32Store16 | I64Store16 => 1,
+            I32Load | I64Load32S | I64Load32U | I32Store | I64Store32 => 2,
+            I64Load | I64Store => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MemArg {
+    pub align: u32,
+    pub offset: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    Block,
+    Loop,
+    If,
+    Else,
+    End,
+    Br(u32),
+    BrIf(u32),
+    Call(u32),
+    CallIndirect { type_index: u32, table_index: u32 },
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    I32Const(i32),
+    I64Const(i64),
+    Memory(MemoryOperator, MemArg),
+    MemorySize,
+    MemoryGrow,
+    Operator(Operator),
+}
+
+impl Instruction {
+    pub fn encode(&self, sink: &mut Vec<u8>) {
+        use Instruction::*;
+        match self {
+            Block => sink.extend_from_slice(&[0x02, EMPTY_BLOCK]),
+            Loop => sink.extend_from_slice(&[0x03, EMPTY_BLOCK]),
+            If => sink.extend_from_slice(&[0x04, EMPTY_BLOCK]),
+            Else => sink.push(0x05),
+            End => sink.push(0x0b),
+            Br(depth) => {
+                sink.push(0x0c);
+                write_u32(sink, *depth);
+            }
+            BrIf(depth) => {
+                sink.push(0x0d);
+                write_u32(sink, *depth);
+            }
+            Call(function) => {
+                sink.push(0x10);
+                write_u32(sink, *function);
+            }
+            CallIndirect { type_index, table_index } => {
+                sink.push(0x11);
+                write_u32(sink, *type_index);
+                write_u32(sink, *table_index);
+            }
+            LocalGet(local) => {
+                sink.push(0x20);
+                write_u32(sink, *local);
+            }
+            LocalSet(local) => {
+                sink.push(0x21);
+                write_u32(sink, *local);
+            }
+            LocalTee(local) => {
+                sink.push(0x22);
+                write_u32(sink, *local);
+            }
+            I32Const(value) => {
+                sink.push(0x41);
+                write_i64(sink, *value as i64);
+            }
+            I64Const(value) => {
+                sink.push(0x42);
+                write_i64(sink, *value);
+            }
+            Memory(operator, memarg) => {
+                sink.push(operator.opcode());
+                write_u32(sink, memarg.align);
+                write_u32(sink, memarg.offset);
+            }
+            MemorySize => sink.extend_from_slice(&[0x3f, 0x00]),
+            MemoryGrow => sink.extend_from_slice(&[0x40, 0x00]),
+            Operator(operator) => sink.push(operator.opcode()),
+        }
+    }
+
+    pub fn decode(reader: &mut Reader) -> Result<Instruction, String> {
+        use Instruction::*;
+        let opcode = reader.read_u8()?;
+        let instruction = match opcode {
+            0x02..=0x04 => {
+                let block_type = reader.read_u8()?;
+                if block_type != EMPTY_BLOCK {
+                    return Result::Err(format!("unsupported block type 0x{:02x}", block_type));
+                }
+                match opcode {
+                    0x02 => Block,
+                    0x03 => Loop,
+                    _ => If,
+                }
+            }
+            0x05 => Else,
+            0x0b => End,
+            0x0c => Br(reader.read_u32()?),
+            0x0d => BrIf(reader.read_u32()?),
+            0x10 => Call(reader.read_u32()?),
+            0x11 => {
+                let type_index = reader.read_u32()?;
+                let table_index = reader.read_u32()?;
+                CallIndirect { type_index, table_index }
+            }
+            0x20 => LocalGet(reader.read_u32()?),
+            0x21 => LocalSet(reader.read_u32()?),
+            0x22 => LocalTee(reader.read_u32()?),
+            0x41 => I32Const(reader.read_i64()? as i32),
+            0x42 => I64Const(reader.read_i64()?),
+            0x3f => {
+                reader.read_u8()?;
+                MemorySize
+            }
+            0x40 => {
+                reader.read_u8()?;
+                MemoryGrow
+            }
+            _ => {
+                if let Some(operator) = MemoryOperator::from_opcode(opcode) {
+                    let align = reader.read_u32()?;
+                    let offset = reader.read_u32()?;
+                    Memory(operator, MemArg { align, offset })
+                } else if let Some(operator) = self::Operator::from_opcode(opcode) {
+                    Operator(operator)
+                } else {
+                    return Result::Err(format!("unsupported opcode 0x{:02x}", opcode));
+                }
+            }
+        };
+        Result::Ok(instruction)
+    }
+}
+
+pub fn write_u32(sink: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            sink.push(byte);
+            return;
+        }
+        sink.push(byte | 0x80);
+    }
+}
+
+pub fn write_i64(sink: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            sink.push(byte);
+            return;
+        }
+        sink.push(byte | 0x80);
+    }
+}
+
+pub fn write_name(sink: &mut Vec<u8>, name: &str) {
+    write_bytes(sink, name.as_bytes());
+}
+
+pub fn write_bytes(sink: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(sink, bytes.len() as u32);
+    sink.extend_from_slice(bytes);
+}
+
+fn write_section(sink: &mut Vec<u8>, id: u8, content: &[u8]) {
+    sink.push(id);
+    write_bytes(sink, content);
+}
+
+fn write_limits(sink: &mut Vec<u8>, min: u32, max: Option<u32>) {
+    match max {
+        Some(max) => {
+            sink.push(0x01);
+            write_u32(sink, min);
+            write_u32(sink, max);
+        }
+        None => {
+            sink.push(0x00);
+            write_u32(sink, min);
+        }
+    }
+}
+
+fn write_name_map(sink: &mut Vec<u8>, names: &[(u32, String)]) {
+    write_u32(sink, names.len() as u32);
+    for (index, name) in names {
+        write_u32(sink, *index);
+        write_name(sink, name);
+    }
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.position).ok_or_else(|| "unexpected end of the module".to_string())?;
+        self.position += 1;
+        Result::Ok(byte)
+    }
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Result::Ok(value as u32);
+            }
+            shift += 7;
+            if shift > 28 {
+                return Result::Err("integer representation too long".to_string());
+            }
+        }
+    }
+    pub fn read_i64(&mut self) -> Result<i64, String> {
+        let mut value: i64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Result::Ok(value);
+            }
+            if shift > 63 {
+                return Result::Err("integer representation too long".to_string());
+            }
+        }
+    }
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], String> {
+        let length = self.read_u32()? as usize;
+        if self.position + length > self.bytes.len() {
+            return Result::Err("unexpected end of the module".to_string());
+        }
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Result::Ok(bytes)
+    }
+    pub fn read_name(&mut self) -> Result<String, String> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_err| "invalid name".to_string())
+    }
+}
+
+// Identifiers of an index space. An identifier gets its index the first time
+// it is used, so functions and types can be referenced before their definition
+#[derive(Default)]
+struct IndexSpace {
+    indices: HashMap<String, u32>,
+    names: Vec<Option<String>>,
+}
+
+impl IndexSpace {
+    fn len(&self) -> u32 {
+        self.names.len() as u32
+    }
+    fn index(&mut self, id: &str) -> Result<u32, String> {
+        let name = id.strip_prefix('$').ok_or_else(|| format!("invalid identifier {}", id))?;
+        if let Some(index) = self.indices.get(name) {
+            return Result::Ok(*index);
+        }
+        let index = self.len();
+        self.indices.insert(name.to_string(), index);
+        self.names.push(Some(name.to_string()));
+        Result::Ok(index)
+    }
+    fn is_known(&self, id: &str) -> bool {
+        id.strip_prefix('$').is_some_and(|name| self.indices.contains_key(name))
+    }
+    fn name_map(&self) -> Vec<(u32, String)> {
+        let mut names = vec![];
+        for (index, name) in self.names.iter().enumerate() {
+            if let Some(name) = name {
+                names.push((index as u32, name.clone()));
+            }
+        }
+        names
+    }
+    fn describe(&self, index: usize) -> String {
+        match &self.names[index] {
+            Some(name) => format!("${}", name),
+            None => index.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ExportKind {
+    Function(String),
+    Table(String),
+    Memory(u32),
+}
+
+// Module fields and instructions produced by the code generators. Functions,
+// types, tables and locals are referenced by their identifiers, written with
+// their $ as in the text format, and the module gives them their indices. The
+// code of a function follows its Func, parameters and locals until its
+// EndFunction
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum WasmInstruction {
+    Type(String, FuncType),
+    Import { module: String, field: String, function: String, type_name: String },
+    Memory(u32),
+    Table { name: String, min: u32, max: Option<u32> },
+    Export(String, ExportKind),
+    Elem { table: String, offset: i32, functions: Vec<String> },
+    Data(i32, Vec<u8>),
+    Func { name: String, type_name: String },
+    Param(String, ValType),
+    Local(String, ValType),
+    EndFunction,
+    // the following code comes from a line of the circom sources, and from
+    // a file when it changes
+    Location(u32, Option<String>),
+    Call(String),
+    CallIndirect { table: String, type_name: String },
+    LocalGet(String),
+    LocalSet(String),
+    LocalTee(String),
+    Code(Instruction),
+}
+
+// Offset of the code and its source and line, None where the code has no
+// location
+type SourceLocation = (u32, Option<(u32, u32)>);
+
+struct Function {
+    type_index: u32,
+    body: Vec<u8>,
+    local_names: Vec<(u32, String)>,
+    locations: Vec<SourceLocation>,
+}
+
+// Function whose declarations or code are being added
+struct OpenFunction {
+    index: u32,
+    type_index: u32,
+    params: u32,
+    locals: Vec<ValType>,
+    local_indices: HashMap<String, u32>,
+    local_names: Vec<(u32, String)>,
+    code: Vec<u8>,
+    locations: Vec<SourceLocation>,
+}
+
+impl OpenFunction {
+    fn declare(&mut self, id: &str) -> Result<u32, String> {
+        let name = id.strip_prefix('$').ok_or_else(|| format!("invalid identifier {}", id))?;
+        let index = self.params + self.locals.len() as u32;
+        self.local_indices.insert(name.to_string(), index);
+        self.local_names.push((index, name.to_string()));
+        Result::Ok(index)
+    }
+    fn local(&self, id: &str) -> Result<u32, String> {
+        id.strip_prefix('$')
+            .and_then(|name| self.local_indices.get(name))
+            .cloned()
+            .ok_or_else(|| format!("unknown local {}", id))
+    }
+    fn finish(self) -> Function {
+        let mut body = vec![];
+        // consecutive locals of the same type are declared together
+        let mut groups: Vec<(u32, ValType)> = vec![];
+        for local in &self.locals {
+            match groups.last_mut() {
+                Some((count, value_type)) if value_type == local => *count += 1,
+                _ => groups.push((1, *local)),
+            }
+        }
+        write_u32(&mut body, groups.len() as u32);
+        for (count, value_type) in groups {
+            write_u32(&mut body, count);
+            body.push(value_type.code());
+        }
+        let start = body.len() as u32;
+        body.extend_from_slice(&self.code);
+        Instruction::End.encode(&mut body);
+        let mut locations: Vec<_> =
+            self.locations.into_iter().map(|(offset, location)| (start + offset, location)).collect();
+        // the following functions are not located by the last line of this one
+        if !locations.is_empty() {
+            locations.push((body.len() as u32, None));
+        }
+        Function { type_index: self.type_index, body, local_names: self.local_names, locations }
+    }
+}
+
+struct Import {
+    module: String,
+    field: String,
+    type_index: u32,
+}
+
+struct Export {
+    name: String,
+    kind: u8,
+    index: u32,
+}
+
+struct Element {
+    table: u32,
+    offset: i32,
+    functions: Vec<u32>,
+}
+
+struct Data {
+    offset: i32,
+    bytes: Vec<u8>,
+}
+
+// WebAssembly module built from the code of the generators
+#[derive(Default)]
+pub struct WasmModule {
+    types: IndexSpace,
+    type_definitions: Vec<Option<FuncType>>,
+    functions: IndexSpace,
+    imports: Vec<Import>,
+    bodies: Vec<Option<Function>>,
+    tables: IndexSpace,
+    table_limits: Vec<(u32, Option<u32>)>,
+    memory: Option<(u32, Option<u32>)>,
+    exports: Vec<Export>,
+    elements: Vec<Element>,
+    data: Vec<Data>,
+    open_function: Option<OpenFunction>,
+    sources: Vec<String>,
+    source: Option<u32>,
+}
+
+impl WasmModule {
+    pub fn new() -> WasmModule {
+        WasmModule::default()
+    }
+
+    // Adds the fields and the code of the generators. A function can span
+    // several calls, until its EndFunction
+    pub fn add_code(&mut self, code: &[WasmInstruction]) -> Result<(), String> {
+        for instruction in code {
+            match instruction {
+                WasmInstruction::Location(line, file) => self.add_location(*line, file.as_deref()),
+                _ if self.open_function.is_some() => self.add_function_code(instruction)?,
+                _ => self.add_definition(instruction)?,
+            }
+        }
+        Result::Ok(())
+    }
+
+    // Locates the following code of the open function
+    fn add_location(&mut self, line: u32, file: Option<&str>) {
+        if let Some(file) = file {
+            let index = match self.sources.iter().position(|source| source == file) {
+                Some(index) => index,
+                None => {
+                    self.sources.push(file.to_string());
+                    self.sources.len() - 1
+                }
+            };
+            self.source = Some(index as u32);
+        }
+        if let (Some(function), Some(source)) = (self.open_function.as_mut(), self.source) {
+            function.locations.push((function.code.len() as u32, Some((source, line))));
+        }
+    }
+
+    fn add_definition(&mut self, definition: &WasmInstruction) -> Result<(), String> {
+        match definition {
+            WasmInstruction::Type(name, function_type) => {
+                let index = self.types.index(name)?;
+                while self.type_definitions.len() <= index as usize {
+                    self.type_definitions.push(None);
+                }
+                if self.type_definitions[index as usize].is_some() {
+                    return Result::Err(format!("type {} defined twice", name));
+                }
+                self.type_definitions[index as usize] = Some(function_type.clone());
+                Result::Ok(())
+            }
+            WasmInstruction::Import { module, field, function, type_name } => {
+                // imported functions take the first indices
+                if self.functions.len() != self.imports.len() as u32 {
+                    return Result::Err(format!("the import {}.{} comes after the use of functions", module, field));
+                }
+                if self.functions.is_known(function) {
+                    return Result::Err(format!("function {} defined twice", function));
+                }
+                self.functions.index(function)?;
+                let type_index = self.types.index(type_name)?;
+                self.imports.push(Import { module: module.clone(), field: field.clone(), type_index });
+                Result::Ok(())
+            }
+            WasmInstruction::Memory(min) => {
+                if self.memory.is_some() {
+                    return Result::Err("only one memory can be defined".to_string());
+                }
+                self.memory = Some((*min, None));
+                Result::Ok(())
+            }
+            WasmInstruction::Table { name, min, max } => {
+                let index = self.tables.index(name)?;
+                while self.table_limits.len() <= index as usize {
+                    self.table_limits.push((0, None));
+                }
+                self.table_limits[index as usize] = (*min, *max);
+                Result::Ok(())
+            }
+            WasmInstruction::Export(name, kind) => {
+                let (kind, index) = match kind {
+                    ExportKind::Function(function) => (EXTERNAL_FUNCTION, self.functions.index(function)?),
+                    ExportKind::Table(table) => (EXTERNAL_TABLE, self.tables.index(table)?),
+                    ExportKind::Memory(memory) => (EXTERNAL_MEMORY, *memory),
+                };
+                self.exports.push(Export { name: name.clone(), kind, index });
+                Result::Ok(())
+            }
+            WasmInstruction::Elem { table, offset, functions } => {
+                let table = self.tables.index(table)?;
+                let mut indices = vec![];
+                for function in functions {
+                    indices.push(self.functions.index(function)?);
+                }
+                self.elements.push(Element { table, offset: *offset, functions: indices });
+                Result::Ok(())
+            }
+            WasmInstruction::Data(offset, bytes) => {
+                self.data.push(Data { offset: *offset, bytes: bytes.clone() });
+                Result::Ok(())
+            }
+            WasmInstruction::Func { name, type_name } => {
+                let index = self.functions.index(name)?;
+                let defined = index as usize;
+                if defined < self.imports.len() {
+                    return Result::Err(format!("function {} defined twice", name));
+                }
+                if let Some(Some(_)) = self.bodies.get(defined - self.imports.len()) {
+                    return Result::Err(format!("function {} defined twice", name));
+                }
+                let type_index = self.types.index(type_name)?;
+                self.open_function = Some(OpenFunction {
+                    index,
+                    type_index,
+                    params: 0,
+                    locals: vec![],
+                    local_indices: HashMap::new(),
+                    local_names: vec![],
+                    code: vec![],
+                    locations: vec![],
+                });
+                Result::Ok(())
+            }
+            _ => Result::Err(format!("{:?} outside of a function", definition)),
+        }
+    }
+
+    fn add_function_code(&mut self, instruction: &WasmInstruction) -> Result<(), String> {
+        let function = self.open_function.as_mut().unwrap();
+        let in_header = function.code.is_empty();
+        let instruction = match instruction {
+            WasmInstruction::EndFunction => {
+                let function = self.open_function.take().unwrap();
+                let defined = function.index as usize - self.imports.len();
+                while self.bodies.len() <= defined {
+                    self.bodies.push(None);
+                }
+                self.bodies[defined] = Some(function.finish());
+                return Result::Ok(());
+            }
+            WasmInstruction::Param(name, _) if in_header && function.locals.is_empty() => {
+                function.declare(name)?;
+                function.params += 1;
+                return Result::Ok(());
+            }
+            WasmInstruction::Local(name, value_type) if in_header => {
+                // the locals follow the parameters of the type
+                let type_params = match self.type_definitions.get(function.type_index as usize) {
+                    Some(Some(function_type)) => function_type.params.len() as u32,
+                    _ => function.params,
+                };
+                function.params = function.params.max(type_params);
+                function.declare(name)?;
+                function.locals.push(*value_type);
+                return Result::Ok(());
+            }
+            WasmInstruction::Call(name) => Instruction::Call(self.functions.index(name)?),
+            WasmInstruction::CallIndirect { table, type_name } => {
+                let table_index = self.tables.index(table)?;
+                let type_index = self.types.index(type_name)?;
+                Instruction::CallIndirect { type_index, table_index }
+            }
+            WasmInstruction::LocalGet(name) => Instruction::LocalGet(function.local(name)?),
+            WasmInstruction::LocalSet(name) => Instruction::LocalSet(function.local(name)?),
+            WasmInstruction::LocalTee(name) => Instruction::LocalTee(function.local(name)?),
+            WasmInstruction::Code(instruction) => instruction.clone(),
+            _ => {
+                return Result::Err(format!(
+                    "unexpected {:?} in function {}",
+                    instruction,
+                    self.functions.describe(function.index as usize)
+                ))
+            }
+        };
+        instruction.encode(&mut self.open_function.as_mut().unwrap().code);
+        Result::Ok(())
+    }
+
+    // Binary encoding of the module, with the names of the functions, their
+    // locals, the types and the tables in the name section
+    pub fn encode(self) -> Result<Vec<u8>, String> {
+        Result::Ok(self.encode_module(None)?.0)
+    }
+
+    // Binary encoding of the module and its source map, which is found by
+    // the debuggers at the url of the sourceMappingURL section
+    pub fn encode_with_source_map(mut self, url: &str) -> Result<(Vec<u8>, String), String> {
+        let sources = std::mem::take(&mut self.sources);
+        let (module, locations) = self.encode_module(Some(url))?;
+        Result::Ok((module, source_map(&sources, &locations)))
+    }
+
+    fn encode_module(self, source_map_url: Option<&str>) -> Result<(Vec<u8>, Vec<SourceLocation>), String> {
+        if let Some(function) = &self.open_function {
+            return Result::Err(format!(
+                "function {} is not closed",
+                self.functions.describe(function.index as usize)
+            ));
+        }
+        let mut types = vec![];
+        for index in 0..self.types.len() as usize {
+            match self.type_definitions.get(index) {
+                Some(Some(function_type)) => types.push(function_type),
+                _ => return Result::Err(format!("type {} is not defined", self.types.describe(index))),
+            }
+        }
+        let mut functions = vec![];
+        for index in self.imports.len()..self.functions.len() as usize {
+            match self.bodies.get(index - self.imports.len()) {
+                Some(Some(function)) => functions.push(function),
+                _ => return Result::Err(format!("function {} is not defined", self.functions.describe(index))),
+            }
+        }
+        if self.table_limits.len() < self.tables.len() as usize {
+            return Result::Err(format!("table {} is not defined", self.tables.describe(self.table_limits.len())));
+        }
+
+        let mut module = vec![];
+        module.extend_from_slice(MAGIC);
+        module.extend_from_slice(VERSION);
+
+        let mut section = vec![];
+        write_u32(&mut section, types.len() as u32);
+        for function_type in &types {
+            section.push(FUNCTION_TYPE);
+            write_u32(&mut section, function_type.params.len() as u32);
+            section.extend(function_type.params.iter().map(|value_type| value_type.code()));
+            write_u32(&mut section, function_type.results.len() as u32);
+            section.extend(function_type.results.iter().map(|value_type| value_type.code()));
+        }
+        write_section(&mut module, SECTION_TYPE, &section);
+
+        section.clear();
+        write_u32(&mut section, self.imports.len() as u32);
+        for import in &self.imports {
+            write_name(&mut section, &import.module);
+            write_name(&mut section, &import.field);
+            section.push(EXTERNAL_FUNCTION);
+            write_u32(&mut section, import.type_index);
+        }
+        write_section(&mut module, SECTION_IMPORT, &section);
+
+        section.clear();
+        write_u32(&mut section, functions.len() as u32);
+        for function in &functions {
+            write_u32(&mut section, function.type_index);
+        }
+        write_section(&mut module, SECTION_FUNCTION, &section);
+
+        if !self.table_limits.is_empty() {
+            section.clear();
+            write_u32(&mut section, self.table_limits.len() as u32);
+            for (min, max) in &self.table_limits {
+                section.push(FUNCREF);
+                write_limits(&mut section, *min, *max);
+            }
+            write_section(&mut module, SECTION_TABLE, &section);
+        }
+
+        if let Some((min, max)) = self.memory {
+            section.clear();
+            write_u32(&mut section, 1);
+            write_limits(&mut section, min, max);
+            write_section(&mut module, SECTION_MEMORY, &section);
+        }
+
+        section.clear();
+        write_u32(&mut section, self.exports.len() as u32);
+        for export in &self.exports {
+            write_name(&mut section, &export.name);
+            section.push(export.kind);
+            write_u32(&mut section, export.index);
+        }
+        write_section(&mut module, SECTION_EXPORT, &section);
+
+        if !self.elements.is_empty() {
+            section.clear();
+            write_u32(&mut section, self.elements.len() as u32);
+            for element in &self.elements {
+                if element.table == 0 {
+                    write_u32(&mut section, 0);
+                } else {
+                    // active segment of an explicit table, with funcref elements
+                    write_u32(&mut section, 2);
+                    write_u32(&mut section, element.table);
+                }
+                Instruction::I32Const(element.offset).encode(&mut section);
+                Instruction::End.encode(&mut section);
+                if element.table != 0 {
+                    section.push(0x00);
+                }
+                write_u32(&mut section, element.functions.len() as u32);
+                for function in &element.functions {
+                    write_u32(&mut section, *function);
+                }
+            }
+            write_section(&mut module, SECTION_ELEMENT, &section);
+        }
+
+        section.clear();
+        let mut locations = vec![];
+        write_u32(&mut section, functions.len() as u32);
+        for function in &functions {
+            write_u32(&mut section, function.body.len() as u32);
+            let start = section.len() as u32;
+            locations.extend(function.locations.iter().map(|(offset, location)| (start + offset, *location)));
+            section.extend_from_slice(&function.body);
+        }
+        // the offsets of the source map start at the beginning of the module
+        let mut size = vec![];
+        write_u32(&mut size, section.len() as u32);
+        let start = (module.len() + 1 + size.len()) as u32;
+        for (offset, _) in &mut locations {
+            *offset += start;
+        }
+        write_section(&mut module, SECTION_CODE, &section);
+
+        section.clear();
+        write_u32(&mut section, self.data.len() as u32);
+        for data in &self.data {
+            write_u32(&mut section, 0);
+            Instruction::I32Const(data.offset).encode(&mut section);
+            Instruction::End.encode(&mut section);
+            write_bytes(&mut section, &data.bytes);
+        }
+        write_section(&mut module, SECTION_DATA, &section);
+
+        section.clear();
+        write_name(&mut section, "name");
+        let mut subsection = vec![];
+        write_name_map(&mut subsection, &self.functions.name_map());
+        section.push(NAME_FUNCTIONS);
+        write_bytes(&mut section, &subsection);
+        subsection.clear();
+        let offset = self.imports.len() as u32;
+        let with_locals: Vec<_> = functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| !function.local_names.is_empty())
+            .collect();
+        write_u32(&mut subsection, with_locals.len() as u32);
+        for (index, function) in with_locals {
+            write_u32(&mut subsection, offset + index as u32);
+            write_name_map(&mut subsection, &function.local_names);
+        }
+        section.push(NAME_LOCALS);
+        write_bytes(&mut section, &subsection);
+        subsection.clear();
+        write_name_map(&mut subsection, &self.types.name_map());
+        section.push(NAME_TYPES);
+        write_bytes(&mut section, &subsection);
+        subsection.clear();
+        write_name_map(&mut subsection, &self.tables.name_map());
+        section.push(NAME_TABLES);
+        write_bytes(&mut section, &subsection);
+        write_section(&mut module, SECTION_CUSTOM, &section);
+
+        if let Some(url) = source_map_url {
+            section.clear();
+            write_name(&mut section, "sourceMappingURL");
+            write_name(&mut section, url);
+            write_section(&mut module, SECTION_CUSTOM, &section);
+        }
+
+        Result::Ok((module, locations))
+    }
+}
+
+fn write_vlq(sink: &mut String, value: i64) {
+    const DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut vlq = if value < 0 { (-value << 1) | 1 } else { value << 1 };
+    loop {
+        let mut digit = vlq & 31;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 32;
+        }
+        sink.push(DIGITS[digit as usize] as char);
+        if vlq == 0 {
+            return;
+        }
+    }
+}
+
+// Version 3 source map of the code. The code of a module is a single line
+// whose columns are the offsets of the instructions in the module
+fn source_map(sources: &[String], locations: &[SourceLocation]) -> String {
+    let mut mappings = String::new();
+    let (mut last_offset, mut last_source, mut last_line) = (0, 0, 0);
+    for (offset, location) in locations {
+        if !mappings.is_empty() {
+            mappings.push(',');
+        }
+        write_vlq(&mut mappings, *offset as i64 - last_offset);
+        last_offset = *offset as i64;
+        if let Some((source, line)) = location {
+            // the lines of the source map start at 0, and the columns are
+            // always 0
+            let line = *line as i64 - 1;
+            write_vlq(&mut mappings, *source as i64 - last_source);
+            write_vlq(&mut mappings, line - last_line);
+            write_vlq(&mut mappings, 0);
+            last_source = *source as i64;
+            last_line = line;
+        }
+    }
+    serde_json::json!({
+        "version": 3,
+        "sources": sources,
+        "names": [],
+        "mappings": mappings
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wasm_elements::wasm_disassembler::disassemble;
+
+    #[test]
+    fn leb128() {
+        let mut sink = vec![];
+        write_u32(&mut sink, 624485);
+        assert_eq!(sink, vec![0xe5, 0x8e, 0x26]);
+        sink.clear();
+        write_i64(&mut sink, -123456);
+        assert_eq!(sink, vec![0xc0, 0xbb, 0x78]);
+        let mut reader = Reader::new(&sink);
+        assert_eq!(reader.read_i64(), Result::Ok(-123456));
+    }
+
+    fn function_type(params: Vec<ValType>) -> FuncType {
+        FuncType { params, results: vec![] }
+    }
+
+    #[test]
+    fn functions_are_referenced_before_their_definition() {
+        let mut module = WasmModule::new();
+        let code = vec![
+            WasmInstruction::Import {
+                module: "runtime".to_string(),
+                field: "log".to_string(),
+                function: "$log".to_string(),
+                type_name: "$_t_i32".to_string(),
+            },
+            WasmInstruction::Type("$_t_i32".to_string(), function_type(vec![ValType::I32])),
+            WasmInstruction::Export("run".to_string(), ExportKind::Function("$run".to_string())),
+            WasmInstruction::Func { name: "$run".to_string(), type_name: "$_t_i32".to_string() },
+            WasmInstruction::Param("$x".to_string(), ValType::I32),
+            WasmInstruction::Local("$y".to_string(), ValType::I64),
+            WasmInstruction::LocalGet("$x".to_string()),
+            WasmInstruction::Code(Instruction::Memory(MemoryOperator::I32Load, MemArg { align: 2, offset: 8 })),
+            WasmInstruction::Call("$log".to_string()),
+            WasmInstruction::Code(Instruction::I64Const(0xFFFFFFFF00000000u64 as i64)),
+            WasmInstruction::LocalSet("$y".to_string()),
+            WasmInstruction::EndFunction,
+            WasmInstruction::Data(16, vec![0x01, 0xff]),
+        ];
+        module.add_code(&code).unwrap();
+        let text = disassemble(&module.encode().unwrap()).unwrap();
+        assert!(text.contains("(import \"runtime\" \"log\" (func $log (type $_t_i32)))"));
+        assert!(text.contains(" (param $x i32)\n (local $y i64)\n local.get $x\n i32.load offset=8\n call $log"));
+        assert!(text.contains("i64.const -4294967296"));
+        assert!(text.contains("(data (i32.const 16) \"\\01\\ff\")"));
+    }
+
+    #[test]
+    fn source_map_locates_the_instructions() {
+        let mut module = WasmModule::new();
+        let code = vec![
+            WasmInstruction::Type("$_t_void".to_string(), function_type(vec![])),
+            WasmInstruction::Func { name: "$f".to_string(), type_name: "$_t_void".to_string() },
+            WasmInstruction::Location(3, Some("a.circom".to_string())),
+            WasmInstruction::Code(Instruction::Operator(Operator::Nop)),
+            WasmInstruction::Location(5, None),
+            WasmInstruction::Code(Instruction::Operator(Operator::Unreachable)),
+            WasmInstruction::EndFunction,
+        ];
+        module.add_code(&code).unwrap();
+        let (bytes, map) = module.encode_with_source_map("a.wasm.map").unwrap();
+        assert!(bytes.ends_with(b"a.wasm.map"));
+        let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+        assert_eq!(map["sources"], serde_json::json!(["a.circom"]));
+        // nop and unreachable are in the lines 2 and 4 counting from 0, and
+        // the code after the end of the function has no location
+        let nop = bytes.windows(3).position(|window| window == [0x01, 0x00, 0x0b]).unwrap();
+        let mut mappings = String::new();
+        write_vlq(&mut mappings, nop as i64);
+        mappings.push_str("AEA,CAEA,E");
+        assert_eq!(map["mappings"], mappings.as_str());
+    }
+
+    #[test]
+    fn undefined_functions_are_reported() {
+        let mut module = WasmModule::new();
+        module.add_code(&[WasmInstruction::Export("run".to_string(), ExportKind::Function("$run".to_string()))]).unwrap();
+        assert!(module.encode().is_err());
+        let mut module = WasmModule::new();
+        assert!(module.add_code(&[WasmInstruction::Call("$run".to_string())]).is_err());
+    }
+}
@@ -1,25 +1,19 @@
 use super::*;
+use super::wasm_binary::{ExportKind, FuncType, Instruction, MemArg, MemoryOperator, Operator, ValType, WasmInstruction};
 use num_bigint_dig::BigInt;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::PathBuf;
 
-pub fn wasm_hexa(nbytes: usize, num: &BigInt) -> String {
-    let inbytes = num.to_str_radix(16).to_string();
+// Little endian bytes of a number, as stored in the memory of the module
+pub fn wasm_bytes(nbytes: usize, num: &BigInt) -> Vec<u8> {
+    let (_, mut bytes) = num.to_bytes_le();
     assert!(
-        2 * nbytes >= inbytes.len(),
+        nbytes >= bytes.len(),
         "the size of memory needs addresses beyond 32 bits long. This circuit cannot be run on WebAssembly\n Try to run circom --c in order to generate c++ code instead"
     );
-    let mut temp = "0".repeat(2 * nbytes - inbytes.len());
-    temp.push_str(&inbytes);
-    let mut res: String = "".to_string();
-    for i in 0..nbytes {
-        let mut aux = "\\".to_string();
-        aux.push_str(&temp[2 * i..2 * i + 2]);
-        aux.push_str(&res);
-        res = aux;
-    }
-    res
+    bytes.resize(nbytes, 0);
+    bytes
 }
 
 pub fn merge_code(instructions: Vec<String>) -> String {
@@ -27,166 +21,189 @@ pub fn merge_code(instructions: Vec<String>) -> String {
     code
 }
 
-pub fn set_constant(value: &str) -> WasmInstruction {
-    format!("i32.const {}", value)
+// Locates the following instructions in a line of the circom sources. The
+// locations without file keep the file of the previous one (only with
+// --source-map)
+pub fn source_location(line: usize, file: Option<&str>) -> WasmInstruction {
+    WasmInstruction::Location(line as u32, file.map(|file| file.to_string()))
+}
+
+// ----- module fields -----------------
+
+pub fn type_definition(name: &str, params: &[ValType], results: &[ValType]) -> WasmInstruction {
+    WasmInstruction::Type(name.to_string(), FuncType { params: params.to_vec(), results: results.to_vec() })
+}
+pub fn import_function(field: &str, name: &str, type_name: &str) -> WasmInstruction {
+    WasmInstruction::Import {
+        module: "runtime".to_string(),
+        field: field.to_string(),
+        function: name.to_string(),
+        type_name: type_name.to_string(),
+    }
+}
+pub fn export_function(field: &str, name: &str) -> WasmInstruction {
+    WasmInstruction::Export(field.to_string(), ExportKind::Function(name.to_string()))
+}
+pub fn data(offset: usize, bytes: Vec<u8>) -> WasmInstruction {
+    WasmInstruction::Data(offset as i32, bytes)
+}
+pub fn function_header(name: &str, type_name: &str) -> WasmInstruction {
+    WasmInstruction::Func { name: name.to_string(), type_name: type_name.to_string() }
+}
+pub fn param(name: &str) -> WasmInstruction {
+    WasmInstruction::Param(name.to_string(), ValType::I32)
+}
+pub fn param64(name: &str) -> WasmInstruction {
+    WasmInstruction::Param(name.to_string(), ValType::I64)
+}
+pub fn local(name: &str) -> WasmInstruction {
+    WasmInstruction::Local(name.to_string(), ValType::I32)
+}
+pub fn end_function() -> WasmInstruction {
+    WasmInstruction::EndFunction
+}
+
+// ----- instructions -----------------
+
+fn operator(operator: Operator) -> WasmInstruction {
+    WasmInstruction::Code(Instruction::Operator(operator))
 }
-pub fn set_constant_64(value: &str) -> WasmInstruction {
-    format!("i64.const {}", value)
+fn memory_access(operator: MemoryOperator, offset: Option<usize>) -> WasmInstruction {
+    let memarg = MemArg { align: operator.natural_alignment(), offset: offset.unwrap_or(0) as u32 };
+    WasmInstruction::Code(Instruction::Memory(operator, memarg))
+}
+
+pub fn set_constant(value: i32) -> WasmInstruction {
+    WasmInstruction::Code(Instruction::I32Const(value))
+}
+pub fn set_constant_64(value: i64) -> WasmInstruction {
+    WasmInstruction::Code(Instruction::I64Const(value))
 }
 pub fn get_local(value: &str) -> WasmInstruction {
-    format!("local.get {}", value)
+    WasmInstruction::LocalGet(value.to_string())
 }
 pub fn set_local(value: &str) -> WasmInstruction {
-    format!("local.set {}", value)
+    WasmInstruction::LocalSet(value.to_string())
 }
 pub fn tee_local(value: &str) -> WasmInstruction {
-    format!("local.tee {}", value)
+    WasmInstruction::LocalTee(value.to_string())
 }
 pub fn add32() -> WasmInstruction {
-    "i32.add".to_string()
+    operator(Operator::I32Add)
 }
 pub fn sub32() -> WasmInstruction {
-    "i32.sub".to_string()
+    operator(Operator::I32Sub)
 }
 pub fn mul32() -> WasmInstruction {
-    "i32.mul".to_string()
+    operator(Operator::I32Mul)
 }
 pub fn div32_u() -> WasmInstruction {
-    "i32.div_u".to_string()
+    operator(Operator::I32DivU)
 }
 pub fn rem32_u() -> WasmInstruction {
-    "i32.rem_u".to_string()
+    operator(Operator::I32RemU)
 }
 pub fn extend_i32_u64() -> WasmInstruction {
-    "i64.extend_i32_u".to_string()
+    operator(Operator::I64ExtendI32U)
 }
 pub fn wrap_i6432() -> WasmInstruction {
-    "i32.wrap_i64".to_string()
+    operator(Operator::I32WrapI64)
 }
-pub fn load32_8u(offset: Option<&str>) -> WasmInstruction {
-    let code = "i32.load8_u".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn load32_8u(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I32Load8U, offset)
 }
-pub fn load32(offset: Option<&str>) -> WasmInstruction {
-    let code = "i32.load".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn load32(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I32Load, offset)
 }
-pub fn load64(offset: Option<&str>) -> WasmInstruction {
-    let code = "i64.load".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn load64(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I64Load, offset)
 }
-pub fn store32_8(offset: Option<&str>) -> WasmInstruction {
-    let code = "i32.store8".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn store32_8(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I32Store8, offset)
 }
-pub fn store32(offset: Option<&str>) -> WasmInstruction {
-    let code = "i32.store".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn store32(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I32Store, offset)
 }
-pub fn store64(offset: Option<&str>) -> WasmInstruction {
-    let code = "i64.store".to_string();
-    if let Option::Some(o) = offset {
-        format!("{} offset={}", code, o)
-    } else {
-        code
-    }
+pub fn store64(offset: Option<usize>) -> WasmInstruction {
+    memory_access(MemoryOperator::I64Store, offset)
 }
 //The 𝗆𝖾𝗆𝗈𝗋𝗒.𝗌𝗂𝗓𝖾 instruction returns the current size of a memory.
 pub fn memory_size() -> WasmInstruction {
-    "memory.size".to_string()
+    WasmInstruction::Code(Instruction::MemorySize)
 }
 //The 𝗆𝖾𝗆𝗈𝗋𝗒.𝗀𝗋𝗈𝗐 instruction grows memory by a given delta and returns the previous size, or −1 if enough memory cannot be allocated.
 pub fn memory_grow() -> WasmInstruction {
-    "memory.grow".to_string()
+    WasmInstruction::Code(Instruction::MemoryGrow)
 }
 
 pub fn shr32_u() -> WasmInstruction {
-    "i32.shr_u".to_string()
+    operator(Operator::I32ShrU)
 }
 pub fn shl32() -> WasmInstruction {
-    "i32.shl".to_string()
+    operator(Operator::I32Shl)
 }
 pub fn shl64() -> WasmInstruction {
-    "i64.shl".to_string()
+    operator(Operator::I64Shl)
 }
 pub fn call(to: &str) -> WasmInstruction {
-    format!("call {}", to)
+    WasmInstruction::Call(to.to_string())
 }
 pub fn call_indirect(table_name: &str, type_name: &str) -> WasmInstruction {
-    format!("call_indirect {} {}", table_name, type_name)
+    WasmInstruction::CallIndirect { table: table_name.to_string(), type_name: type_name.to_string() }
 }
 pub fn and32() -> WasmInstruction {
-    "i32.and".to_string()
+    operator(Operator::I32And)
 }
 pub fn or64() -> WasmInstruction {
-    "i64.or".to_string()
+    operator(Operator::I64Or)
 }
 pub fn gt32_u() -> WasmInstruction {
-    "i32.gt_u".to_string()
+    operator(Operator::I32GtU)
 }
 pub fn ge32_u() -> WasmInstruction {
-    "i32.ge_u".to_string()
+    operator(Operator::I32GeU)
 }
 pub fn eq32() -> WasmInstruction {
-    "i32.eq".to_string()
+    operator(Operator::I32Eq)
 }
 pub fn eq64() -> WasmInstruction {
-    "i64.eq".to_string()
+    operator(Operator::I64Eq)
 }
 pub fn eqz32() -> WasmInstruction {
-    "i32.eqz".to_string()
+    operator(Operator::I32Eqz)
 }
 pub fn eqz64() -> WasmInstruction {
-    "i64.eqz".to_string()
+    operator(Operator::I64Eqz)
 }
 pub fn drop() -> WasmInstruction {
-    "drop".to_string()
+    operator(Operator::Drop)
 }
 pub fn add_block() -> WasmInstruction {
-    "block".to_string()
+    WasmInstruction::Code(Instruction::Block)
 }
 pub fn add_loop() -> WasmInstruction {
-    "loop".to_string()
+    WasmInstruction::Code(Instruction::Loop)
 }
-pub fn br_if(value: &str) -> WasmInstruction {
-    format!("br_if {}", value)
+pub fn br_if(depth: u32) -> WasmInstruction {
+    WasmInstruction::Code(Instruction::BrIf(depth))
 }
-pub fn br(value: &str) -> WasmInstruction {
-    format!("br {}", value)
+pub fn br(depth: u32) -> WasmInstruction {
+    WasmInstruction::Code(Instruction::Br(depth))
 }
 pub fn add_if() -> WasmInstruction {
-    "if".to_string()
+    WasmInstruction::Code(Instruction::If)
 }
 pub fn add_else() -> WasmInstruction {
-    "else".to_string()
+    WasmInstruction::Code(Instruction::Else)
 }
 pub fn add_end() -> WasmInstruction {
-    "end".to_string()
+    WasmInstruction::Code(Instruction::End)
 }
 pub fn add_return() -> WasmInstruction {
-    "return".to_string()
+    operator(Operator::Return)
 }
 
+
 // ----- exception codes and other constants -----------------
 
 pub fn default_memory_for_stack_kib() -> usize {
@@ -241,20 +258,20 @@ pub fn generate_hash_map(signal_name_list: &Vec<(String, usize, usize)>) -> Vec<
     hash_map
 }
 
-pub fn generate_data_from_hash_map(map: &Vec<(u64, usize, usize)>) -> String {
-    let mut hash_map_data = "".to_string();
+pub fn generate_data_from_hash_map(map: &Vec<(u64, usize, usize)>) -> Vec<u8> {
+    let mut hash_map_data = vec![];
     for (h, p, s) in map {
-        hash_map_data.push_str(&wasm_hexa(8, &BigInt::from(*h))); //64bits 8 stots of 8bits
-        hash_map_data.push_str(&wasm_hexa(4, &BigInt::from(*p))); //32bits 4 stots of 8bits
-        hash_map_data.push_str(&wasm_hexa(4, &BigInt::from(*s))); //32bits 4 stots of 8bits
+        hash_map_data.extend(wasm_bytes(8, &BigInt::from(*h))); //64bits 8 stots of 8bits
+        hash_map_data.extend(wasm_bytes(4, &BigInt::from(*p))); //32bits 4 stots of 8bits
+        hash_map_data.extend(wasm_bytes(4, &BigInt::from(*s))); //32bits 4 stots of 8bits
     }
     hash_map_data
 }
 
-pub fn generate_data_witness_to_signal_list(signal_list: &Vec<usize>) -> String {
-    let mut signallist_data = "".to_string();
+pub fn generate_data_witness_to_signal_list(signal_list: &Vec<usize>) -> Vec<u8> {
+    let mut signallist_data = vec![];
     for s in signal_list {
-        signallist_data.push_str(&wasm_hexa(4, &BigInt::from(*s))); //32bits 4 stots of 8bits
+        signallist_data.extend(wasm_bytes(4, &BigInt::from(*s))); //32bits 4 stots of 8bits
     }
     signallist_data
 }
@@ -262,16 +279,16 @@ pub fn generate_data_witness_to_signal_list(signal_list: &Vec<usize>) -> String
 pub fn generate_data_template_instance_to_io(
     producer: &WASMProducer,
     io_map: &TemplateInstanceIOMap,
-) -> String {
-    let mut io_map_data = "".to_string();
+) -> Vec<u8> {
+    let mut io_map_data = vec![];
     let mut s = producer.get_io_signals_to_info_start();
     for c in 0..producer.get_number_of_template_instances() {
         match io_map.get(&c) {
             Some(value) => {
-                io_map_data.push_str(&&wasm_hexa(4, &BigInt::from(s)));
+                io_map_data.extend(wasm_bytes(4, &BigInt::from(s)));
                 s += value.len() * 4;
             }
-            None => io_map_data.push_str(&&wasm_hexa(4, &BigInt::from(0))),
+            None => io_map_data.extend(wasm_bytes(4, &BigInt::from(0))),
         }
     }
     io_map_data
@@ -280,8 +297,8 @@ pub fn generate_data_template_instance_to_io(
 pub fn generate_data_io_signals_to_info(
     producer: &WASMProducer,
     io_map: &TemplateInstanceIOMap,
-) -> String {
-    let mut io_signals = "".to_string();
+) -> Vec<u8> {
+    let mut io_signals = vec![];
     let mut pos = producer.get_io_signals_info_start();
     for c in 0..producer.get_number_of_template_instances() {
         match io_map.get(&c) {
@@ -289,7 +306,7 @@ pub fn generate_data_io_signals_to_info(
                 let mut n = 0;
                 for s in value {
                     assert_eq!(s.code, n);
-                    io_signals.push_str(&&wasm_hexa(4, &BigInt::from(pos)));
+                    io_signals.extend(wasm_bytes(4, &BigInt::from(pos)));
                     //do not store code and the first one of lengths
                     if s.lengths.len() == 0 {
                         pos += 4;
@@ -308,19 +325,19 @@ pub fn generate_data_io_signals_to_info(
 pub fn generate_data_io_signals_info(
     producer: &WASMProducer,
     io_map: &TemplateInstanceIOMap,
-) -> String {
-    let mut io_signals_info = "".to_string();
+) -> Vec<u8> {
+    let mut io_signals_info = vec![];
     for c in 0..producer.get_number_of_components() {
         match io_map.get(&c) {
             Some(value) => {
                 for s in value {
                     // add the actual offset in memory, taking into account the size of field nums
-                    io_signals_info.push_str(&&wasm_hexa(
+                    io_signals_info.extend(wasm_bytes(
                         4,
                         &BigInt::from(s.offset * producer.get_size_32_bits_in_memory() * 4),
                     ));
                     for i in 1..s.lengths.len() {
-                        io_signals_info.push_str(&&wasm_hexa(4, &BigInt::from(s.lengths[i])));
+                        io_signals_info.extend(wasm_bytes(4, &BigInt::from(s.lengths[i])));
                     }
                 }
             }
@@ -330,8 +347,17 @@ pub fn generate_data_io_signals_info(
     io_signals_info
 }
 
-pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<String>) -> String {
-    let mut constant_list_data = "".to_string();
+pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<String>) -> Vec<u8> {
+    let mut constant_list_data = vec![];
+    if producer.get_field_layout() == FieldLayout::Goldilocks64 {
+        let p = producer.get_prime().parse::<BigInt>().unwrap();
+        for s in constant_list {
+            let n = s.parse::<BigInt>().unwrap();
+            let n = ((n % &p) + &p) % &p;
+            constant_list_data.extend(wasm_bytes(8, &n));
+        }
+        return constant_list_data;
+    }
     //    For short/long form
     //    let szero = wasm_hexa(producer.get_size_32_bit()*4,&BigInt::from(0));
     for s in constant_list {
@@ -381,16 +407,16 @@ pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<Stri
             if nn < BigInt::from(0) {
                 nn = BigInt::parse_bytes(b"100000000", 16).unwrap() + nn;
             }
-            constant_list_data.push_str(&wasm_hexa(4, &nn));
-            constant_list_data.push_str("\\00\\00\\00\\40"); // 0100
+            constant_list_data.extend(wasm_bytes(4, &nn));
+            constant_list_data.extend([0, 0, 0, 0x40]); // 0100
         } else {
             //It is long. Only Montgomery
-            constant_list_data.push_str("\\00\\00\\00\\00\\00\\00\\00\\C0"); // 1100
+            constant_list_data.extend([0, 0, 0, 0, 0, 0, 0, 0xC0]); // 1100
         }
         // Montgomery
         // n*R mod P
         n = (n * BigInt::clone(&r)) % BigInt::clone(&p);
-        constant_list_data.push_str(&wasm_hexa(producer.get_size_32_bit() * 4, &n));
+        constant_list_data.extend(wasm_bytes(producer.get_size_32_bit() * 4, &n));
     }
     constant_list_data
 }
@@ -400,44 +426,44 @@ pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<Stri
                 if min_int <= nn && nn <= max_int {
                     instructions.push(get_local(producer.get_expaux_tag()));
                     let size = self.op_aux_no * producer.get_size_32_bits_in_memory() * 4;
-                    instructions.push(set_constant(&size.to_string()));
+                    instructions.push(set_constant(size as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_temp_tag()));
                     instructions.push(get_local(producer.get_temp_tag()));
-                    instructions.push(set_constant(&nn.to_string()));
+                    instructions.push(set_constant(nn as i32));
                     instructions.push(store32(None));
                     instructions.push(get_local(producer.get_temp_tag()));
-                    instructions.push(set_constant("0"));
-                    instructions.push(store32(Some("4")));
+                    instructions.push(set_constant(0));
+                    instructions.push(store32(Some(4)));
                     let mut offset = 8;
                     if (producer.get_size_32_bit() & 1) == 0 {
                         // is even
                         for _i in 0..producer.get_size_32_bit() / 2 {
                             instructions.push(get_local(producer.get_temp_tag()));
-                            instructions.push(set_constant_64("0"));
-                            instructions.push(store64(Some(&offset.to_string())));
+                            instructions.push(set_constant_64(0));
+                            instructions.push(store64(Some(offset)));
                             offset += 8;
                         }
                     } else {
                         for _i in 0..producer.get_size_32_bit() {
                             instructions.push(get_local(producer.get_temp_tag()));
-                            instructions.push(set_constant("0"));
-                            instructions.push(store32(Some(&offset.to_string())));
+                            instructions.push(set_constant(0));
+                            instructions.push(store32(Some(offset)));
                             offset += 4;
                         }
                     }
                 } else {
                     instructions.push(get_local(producer.get_expaux_tag()));
                     let size = self.op_aux_no * producer.get_size_32_bits_in_memory() * 4;
-                    instructions.push(set_constant(&size.to_string()));
+                    instructions.push(set_constant(size as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_temp_tag()));
                     instructions.push(get_local(producer.get_temp_tag()));
-                    instructions.push(set_constant("0"));
+                    instructions.push(set_constant(0));
                     instructions.push(store32(None));
                     instructions.push(get_local(producer.get_temp_tag()));
-                    instructions.push(set_constant("-2147483648"));
-                    instructions.push(store32(Some("4")));
+                    instructions.push(set_constant(-2147483648));
+                    instructions.push(store32(Some(4)));
                     let mut offset = 8;
                     if (producer.get_size_32_bit() & 1) == 0 {
                         // is even
@@ -445,7 +471,7 @@ pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<Stri
                         for b in in64_hex {
                             instructions.push(get_local(producer.get_temp_tag()));
                             instructions.push(set_constant_64(&b));
-                            instructions.push(store64(Some(&offset.to_string())));
+                            instructions.push(store64(Some(offset)));
                             offset += 8;
                         }
                     } else {
@@ -453,7 +479,7 @@ pub fn generate_data_constants(producer: &WASMProducer, constant_list: &Vec<Stri
                         for b in in32_hex {
                             instructions.push(get_local(producer.get_temp_tag()));
                             instructions.push(set_constant(&b));
-                            instructions.push(store32(Some(&offset.to_string())));
+                            instructions.push(store32(Some(offset)));
                             offset += 4;
                         }
                     }
@@ -490,147 +516,127 @@ pub fn to_array_hex(num: &BigInt, size: usize, group_size: usize) -> Vec<String>
 
 pub fn generate_imports_list() -> Vec<WasmInstruction> {
     let mut imports = vec![];
-    imports.push(
-        "(import \"runtime\" \"exceptionHandler\" (func $exceptionHandler (type $_t_i32)))"
-            .to_string(),
-    );
-    imports.push(
-        "(import \"runtime\" \"printErrorMessage\" (func $printErrorMessage (type $_t_void)))"
-            .to_string(),
-    );
-    imports.push(
-        "(import \"runtime\" \"writeBufferMessage\" (func $writeBufferMessage (type $_t_void)))"
-            .to_string(),
-    );
-    imports.push(
-        "(import \"runtime\" \"showSharedRWMemory\" (func $showSharedRWMemory (type $_t_void)))"
-            .to_string(),
-    );
+    imports.push(import_function("exceptionHandler", "$exceptionHandler", "$_t_i32"));
+    imports.push(import_function("printErrorMessage", "$printErrorMessage", "$_t_void"));
+    imports.push(import_function("writeBufferMessage", "$writeBufferMessage", "$_t_void"));
+    imports.push(import_function("showSharedRWMemory", "$showSharedRWMemory", "$_t_void"));
+    imports
+}
+
+pub fn generate_trace_imports_list() -> Vec<WasmInstruction> {
+    let mut imports = vec![];
+    imports.push(import_function("traceComponent", "$traceComponent", "$_t_i32i32i32"));
+    imports.push(import_function("traceRun", "$traceRun", "$_t_i32"));
+    imports.push(import_function("traceEnd", "$traceEnd", "$_t_void"));
+    imports.push(import_function("traceValue", "$traceValue", "$_t_void"));
+    imports.push(import_function("traceSignal", "$traceSignal", "$_t_i32i32"));
+    imports.push(import_function("traceCall", "$traceCall", "$_t_i32"));
     imports
 }
 
 pub fn generate_memory_def_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut wmemory = vec![];
-    wmemory.push(format!("(memory {})", get_initial_size_of_memory(&producer)));
+    wmemory.push(WasmInstruction::Memory(get_initial_size_of_memory(&producer) as u32));
     wmemory
 }
 
 pub fn generate_types_list() -> Vec<WasmInstruction> {
+    use ValType::I32;
     let mut types = vec![];
-    types.push("(type $_t_void (func ))".to_string());
-    types.push("(type $_t_ri32 (func  (result i32)))".to_string());
-    types.push("(type $_t_i32 (func  (param i32)))".to_string());
-    types.push("(type $_t_i32ri32 (func  (param i32) (result i32)))".to_string());
-    types.push("(type $_t_i64ri32 (func  (param i64) (result i32)))".to_string());
-    types.push("(type $_t_i32i32 (func  (param i32 i32)))".to_string());
-    types.push("(type $_t_i32i32ri32 (func  (param i32 i32) (result i32)))".to_string());
-    types.push("(type $_t_i32i32i32  (func  (param i32 i32 i32)))".to_string());
+    types.push(type_definition("$_t_void", &[], &[]));
+    types.push(type_definition("$_t_ri32", &[], &[I32]));
+    types.push(type_definition("$_t_i32", &[I32], &[]));
+    types.push(type_definition("$_t_i32ri32", &[I32], &[I32]));
+    types.push(type_definition("$_t_i64ri32", &[ValType::I64], &[I32]));
+    types.push(type_definition("$_t_i32i32", &[I32, I32], &[]));
+    types.push(type_definition("$_t_i32i32ri32", &[I32, I32], &[I32]));
+    types.push(type_definition("$_t_i32i32i32", &[I32, I32, I32], &[]));
+    types.push(type_definition("$_t_i32i32i32i32", &[I32, I32, I32, I32], &[]));
     types
 }
 
 pub fn generate_exports_list() -> Vec<WasmInstruction> {
     let mut exports = vec![];
-    exports.push("(export \"memory\" (memory 0))".to_string());
-    exports.push("(export \"getVersion\" (func $getVersion))".to_string());
-    exports.push("(export \"getSharedRWMemoryStart\" (func $getSharedRWMemoryStart))".to_string());
-    exports.push("(export \"readSharedRWMemory\" (func $readSharedRWMemory))".to_string());
-    exports.push("(export \"writeSharedRWMemory\" (func $writeSharedRWMemory))".to_string());
-    exports.push("(export \"init\" (func $init))".to_string());
-    exports.push("(export \"setInputSignal\" (func $setInputSignal))".to_string());
-    exports.push("(export \"getInputSignalSize\" (func $getInputSignalSize))".to_string());
-    exports.push("(export \"getRawPrime\" (func $getRawPrime))".to_string());
-    exports.push("(export \"getFieldNumLen32\" (func $getFieldNumLen32))".to_string());
-    exports.push("(export \"getWitnessSize\" (func $getWitnessSize))".to_string());
-    exports.push("(export \"getInputSize\" (func $getInputSize))".to_string());
-    exports.push("(export \"getWitness\" (func $getWitness))".to_string());
-    exports.push("(export \"getMessageChar\" (func $getMessageChar))".to_string());
+    exports.push(WasmInstruction::Export("memory".to_string(), ExportKind::Memory(0)));
+    exports.push(export_function("getVersion", "$getVersion"));
+    exports.push(export_function("getSharedRWMemoryStart", "$getSharedRWMemoryStart"));
+    exports.push(export_function("readSharedRWMemory", "$readSharedRWMemory"));
+    exports.push(export_function("writeSharedRWMemory", "$writeSharedRWMemory"));
+    exports.push(export_function("init", "$init"));
+    exports.push(export_function("setInputSignal", "$setInputSignal"));
+    exports.push(export_function("getInputSignalSize", "$getInputSignalSize"));
+    exports.push(export_function("getRawPrime", "$getRawPrime"));
+    exports.push(export_function("getFieldNumLen32", "$getFieldNumLen32"));
+    exports.push(export_function("getWitnessSize", "$getWitnessSize"));
+    exports.push(export_function("getInputSize", "$getInputSize"));
+    exports.push(export_function("getWitness", "$getWitness"));
+    exports.push(export_function("getMessageChar", "$getMessageChar"));
     exports
 }
 
+// Message of at most the size of a message, ended by 0
+fn message_data(message: &str, size: usize) -> Vec<u8> {
+    let mut bytes = if message.len() < size {
+        wasm_library::unescape(message)
+    } else {
+        wasm_library::unescape(&message[..size - 1])
+    };
+    bytes.push(0);
+    bytes
+}
+
 pub fn generate_data_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut wdata = vec![];
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
-        0,
-        wasm_hexa(4, &BigInt::from(producer.get_var_stack_memory_start()))
-    ));
+    wdata.push(data(0, wasm_bytes(4, &BigInt::from(producer.get_var_stack_memory_start()))));
     let p = producer.get_prime().parse::<BigInt>().unwrap();
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
-        producer.get_raw_prime_start(),
-        wasm_hexa(producer.get_size_32_bit()*4, &p)
-    ));
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
-        producer.get_shared_rw_memory_start() - 8,
-        "\\00\\00\\00\\00\\00\\00\\00\\80"
-    ));
+    wdata.push(data(producer.get_raw_prime_start(), wasm_bytes(producer.get_size_32_bit() * 4, &p)));
+    if producer.get_field_layout() == FieldLayout::MultiLimb {
+        wdata.push(data(producer.get_shared_rw_memory_start() - 8, vec![0, 0, 0, 0, 0, 0, 0, 0x80]));
+    }
     let map = generate_hash_map(&producer.get_main_input_list());
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
-        producer.get_input_signals_hashmap_start(),
-        generate_data_from_hash_map(&map)
-    ));
+    wdata.push(data(producer.get_input_signals_hashmap_start(), generate_data_from_hash_map(&map)));
     let s = generate_data_witness_to_signal_list(producer.get_witness_to_signal_list());
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
-        producer.get_witness_signal_id_list_start(),
-        s
-    ));
-    wdata.push(format!("(data (i32.const {}) \"{}{}\")",producer.get_signal_memory_start(),"\\00\\00\\00\\00\\00\\00\\00\\80",wasm_hexa(producer.get_size_32_bit()*4, &BigInt::from(1)))); //setting 'one' as long normal 1
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
+    wdata.push(data(producer.get_witness_signal_id_list_start(), s));
+    match producer.get_field_layout() {
+        FieldLayout::MultiLimb => {
+            //setting 'one' as long normal 1
+            let mut one = vec![0, 0, 0, 0, 0, 0, 0, 0x80];
+            one.extend(wasm_bytes(producer.get_size_32_bit() * 4, &BigInt::from(1)));
+            wdata.push(data(producer.get_signal_memory_start(), one));
+        }
+        FieldLayout::Goldilocks64 => wdata.push(data(producer.get_signal_memory_start(), wasm_bytes(8, &BigInt::from(1)))),
+    }
+    wdata.push(data(
         producer.get_template_instance_to_io_signal_start(),
-        generate_data_template_instance_to_io(&producer, producer.get_io_map())
+        generate_data_template_instance_to_io(&producer, producer.get_io_map()),
     ));
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
+    wdata.push(data(
         producer.get_io_signals_to_info_start(),
-        generate_data_io_signals_to_info(&producer, producer.get_io_map())
+        generate_data_io_signals_to_info(&producer, producer.get_io_map()),
     ));
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
+    wdata.push(data(
         producer.get_io_signals_info_start(),
-        generate_data_io_signals_info(&producer, producer.get_io_map())
+        generate_data_io_signals_info(&producer, producer.get_io_map()),
     ));
     let ml = producer.get_message_list();
     let m = producer.get_message_list_start();
     for i in 0..ml.len() {
-        if ml[i].len() < producer.get_size_of_message_in_bytes() {
-            wdata.push(format!(
-                "(data (i32.const {}) \"{}\\00\")",
-                m + i * producer.get_size_of_message_in_bytes(),
-                ml[i]
-            ));
-        } else {
-            wdata.push(format!(
-                "(data (i32.const {}) \"{}\\00\")",
-                m + i * producer.get_size_of_message_in_bytes(),
-                &ml[i][..producer.get_size_of_message_in_bytes()-1]
-            ));
-        }
+        wdata.push(data(
+            m + i * producer.get_size_of_message_in_bytes(),
+            message_data(&ml[i], producer.get_size_of_message_in_bytes()),
+        ));
     }
     let st = producer.get_string_table();
     let s = producer.get_string_list_start();
     for i in 0..st.len() {
-        if st[i].len() < producer.get_size_of_message_in_bytes() {
-            wdata.push(format!(
-                "(data (i32.const {}) \"{}\\00\")",
-                s + i * producer.get_size_of_message_in_bytes(),
-                st[i]
-            ));
-        } else {
-            wdata.push(format!(
-                "(data (i32.const {}) \"{}\\00\")",
-                s + i * producer.get_size_of_message_in_bytes(),
-                &st[i][..producer.get_size_of_message_in_bytes()-1]
-            ));
-        }
+        wdata.push(data(
+            s + i * producer.get_size_of_message_in_bytes(),
+            message_data(&st[i], producer.get_size_of_message_in_bytes()),
+        ));
     }
-    wdata.push(format!(
-        "(data (i32.const {}) \"{}\")",
+    wdata.push(data(
         producer.get_constant_numbers_start(),
-        generate_data_constants(&producer, producer.get_field_constant_list())
+        generate_data_constants(&producer, producer.get_field_constant_list()),
     ));
     wdata
 }
@@ -639,7 +645,7 @@ pub fn generate_data_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
 
 pub fn reserve_stack_fr(producer: &WASMProducer, nbytes: usize) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    instructions.push(set_constant(&nbytes.to_string()));
+    instructions.push(set_constant(nbytes as i32));
     instructions.push(call("$reserveStackFr"));
     instructions.push(set_local(producer.get_cstack_tag()));
     instructions
@@ -647,27 +653,26 @@ pub fn reserve_stack_fr(producer: &WASMProducer, nbytes: usize) -> Vec<WasmInstr
 
 pub fn reserve_stack_fr_function_generator() -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $reserveStackFr (type $_t_i32ri32)".to_string();
+    let header = function_header("$reserveStackFr", "$_t_i32ri32");
     instructions.push(header);
-    instructions.push(" (param $nbytes i32)".to_string());
-    instructions.push("(result i32)".to_string());
-    instructions.push(" (local $inistack i32)".to_string());
-    instructions.push(" (local $newbsize i32)".to_string());
-    instructions.push(" (local $memorybsize i32)".to_string());
-    instructions.push(set_constant("0"));
+    instructions.push(param("$nbytes"));
+    instructions.push(local("$inistack"));
+    instructions.push(local("$newbsize"));
+    instructions.push(local("$memorybsize"));
+    instructions.push(set_constant(0));
     instructions.push(load32(None));
     instructions.push(set_local("$inistack"));
     instructions.push(get_local("$inistack"));
     instructions.push(get_local("$nbytes"));
     instructions.push(add32());
     instructions.push(set_local("$newbsize"));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(get_local("$newbsize"));
     instructions.push(store32(None));
     // check if enough memory; otherwise grow
     // bytes per page 64 * 1024 = 2^16
     instructions.push(memory_size());
-    instructions.push(set_constant("16"));
+    instructions.push(set_constant(16));
     instructions.push(shl32());
     instructions.push(set_local("$memorybsize"));
     instructions.push(get_local("$newbsize"));
@@ -677,26 +682,26 @@ pub fn reserve_stack_fr_function_generator() -> Vec<WasmInstruction> {
     instructions.push(get_local("$newbsize"));
     instructions.push(get_local("$memorybsize"));
     instructions.push(sub32());
-    instructions.push(set_constant("65535")); //64KiB-1
+    instructions.push(set_constant(65535)); //64KiB-1
     instructions.push(add32());
-    instructions.push(set_constant("16"));
+    instructions.push(set_constant(16));
     instructions.push(shr32_u()); //needed pages
     instructions.push(memory_grow());
-    instructions.push(set_constant("-1"));
+    instructions.push(set_constant(-1));
     instructions.push(eq32());
     instructions.push(add_if());
-    instructions.push(set_constant(&exception_code_not_enough_memory().to_string()));
+    instructions.push(set_constant(exception_code_not_enough_memory() as i32));
     instructions.push(call("$exceptionHandler"));
     instructions.push(add_end());
     instructions.push(add_end());
     instructions.push(get_local("$inistack"));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn free_stack(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(get_local(producer.get_cstack_tag()));
     instructions.push(store32(Option::None));
     instructions
@@ -706,149 +711,144 @@ pub fn free_stack(producer: &WASMProducer) -> Vec<WasmInstruction> {
 
 pub fn desp_io_subcomponent_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getOffsetIOSubComponet (type $_t_i32i32ri32)".to_string();
+    let header = function_header("$getOffsetIOSubComponet", "$_t_i32i32ri32");
     instructions.push(header);
-    instructions.push(" (param $comp i32)".to_string());
-    instructions.push(" (param $ios i32)".to_string());
-    instructions.push("(result i32)".to_string());
+    instructions.push(param("$comp"));
+    instructions.push(param("$ios"));
     instructions
-        .push(set_constant(&producer.get_template_instance_to_io_signal_start().to_string()));
+        .push(set_constant(producer.get_template_instance_to_io_signal_start() as i32));
     instructions.push(get_local("$comp"));
     instructions.push(add32());
     instructions.push(load32(None));
     instructions.push(get_local("$ios"));
-    instructions.push(set_constant("4"));
+    instructions.push(set_constant(4));
     instructions.push(mul32());
     instructions.push(add32());
     instructions.push(load32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_shared_rw_memory_start_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getSharedRWMemoryStart (type $_t_ri32)".to_string();
+    let header = function_header("$getSharedRWMemoryStart", "$_t_ri32");
     instructions.push(header);
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn read_shared_rw_memory_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $readSharedRWMemory (type $_t_i32ri32)".to_string();
+    let header = function_header("$readSharedRWMemory", "$_t_i32ri32");
     instructions.push(header);
-    instructions.push(" (param $p i32)".to_string());
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
+    instructions.push(param("$p"));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
     instructions.push(get_local("$p"));
-    instructions.push(set_constant("4"));
+    instructions.push(set_constant(4));
     instructions.push(mul32());
     instructions.push(add32());
     instructions.push(load32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn write_shared_rw_memory_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $writeSharedRWMemory (type $_t_i32i32)".to_string();
+    let header = function_header("$writeSharedRWMemory", "$_t_i32i32");
     instructions.push(header);
-    instructions.push(" (param $p i32)".to_string());
-    instructions.push(" (param $v i32)".to_string());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
+    instructions.push(param("$p"));
+    instructions.push(param("$v"));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
     instructions.push(get_local("$p"));
-    instructions.push(set_constant("4"));
+    instructions.push(set_constant(4));
     instructions.push(mul32());
     instructions.push(add32());
     instructions.push(get_local("$v"));
     instructions.push(store32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_version_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getVersion (type $_t_ri32)".to_string();
+    let header = function_header("$getVersion", "$_t_ri32");
     instructions.push(header);
-    instructions.push(set_constant(&producer.get_version().to_string()));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(producer.get_version() as i32));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn init_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $init (type $_t_i32)".to_string();
+    let header = function_header("$init", "$_t_i32");
     instructions.push(header);
-    instructions.push(" (param $t i32)".to_string());
-    instructions.push(" (local $i i32)".to_string());
+    instructions.push(param("$t"));
+    instructions.push(local("$i"));
     // initialize set counter
-    instructions.push(set_constant(&producer.get_remaining_input_signal_counter().to_string()));
-    instructions.push(";; Number of Main inputs".to_string());
-    instructions.push(set_constant(&producer.get_number_of_main_inputs().to_string()));
+    instructions.push(set_constant(producer.get_remaining_input_signal_counter() as i32));
+    instructions.push(set_constant(producer.get_number_of_main_inputs() as i32));
     instructions.push(store32(None));
     // initialize set positions
-    instructions.push(set_constant(&producer.get_input_signal_set_map_start().to_string()));
+    instructions.push(set_constant(producer.get_input_signal_set_map_start() as i32));
     instructions.push(set_local("$i"));
     instructions.push(add_block()); //block 1
     instructions.push(add_loop()); //loop 2
     instructions.push(get_local("$i"));
     let end_pos =
         producer.get_input_signal_set_map_start() + 4 * producer.get_number_of_main_inputs();
-    instructions.push(set_constant(&end_pos.to_string()));
+    instructions.push(set_constant(end_pos as i32));
     instructions.push(eq32());
-    instructions.push(br_if("1"));
+    instructions.push(br_if(1));
     instructions.push(get_local("$i"));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(store32(None));
     instructions.push(get_local("$i"));
-    instructions.push(set_constant("4"));
+    instructions.push(set_constant(4));
     instructions.push(add32());
     instructions.push(set_local("$i"));
-    instructions.push(br("0"));
+    instructions.push(br(0));
     instructions.push(add_end()); //end loop 2
     instructions.push(add_end()); //end block 1
                                   // initialize component_free_pos
-    instructions.push(set_constant(&producer.get_component_free_pos().to_string()));
-    instructions.push(set_constant(&producer.get_component_tree_start().to_string()));
+    instructions.push(set_constant(producer.get_component_free_pos() as i32));
+    instructions.push(set_constant(producer.get_component_tree_start() as i32));
     instructions.push(store32(None));
     //signal offset of the main component
     let next_to_one = producer.get_signal_memory_start()
         + producer.get_main_signal_offset() * producer.get_size_32_bits_in_memory() * 4;
     //    // initialize signal_free_pos
-    //    instructions.push(set_constant(&producer.get_signal_free_pos().to_string()));
-    //    instructions.push(set_constant(&next_to_one.to_string()));
+    //    instructions.push(set_constant(producer.get_signal_free_pos() as i32));
+    //    instructions.push(set_constant(next_to_one as i32));
     //    instructions.push(store32(None));
-    instructions.push(set_constant(&next_to_one.to_string()));
+    instructions.push(set_constant(next_to_one as i32));
     let funcname = format!("${}_create", producer.get_main_header());
     instructions.push(call(&funcname));
     instructions.push(drop());
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_input_signal_map_position_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getInputSignalMapPosition (type $_t_i64ri32)".to_string();
+    let header = function_header("$getInputSignalMapPosition", "$_t_i64ri32");
     instructions.push(header);
-    instructions.push(" (param $hn i64)".to_string());
-    instructions.push("(result i32)".to_string());
-    instructions.push(" (local $ini i32)".to_string());
-    instructions.push(" (local $i i32)".to_string());
-    instructions.push(" (local $aux i32)".to_string());
+    instructions.push(param64("$hn"));
+    instructions.push(local("$ini"));
+    instructions.push(local("$i"));
+    instructions.push(local("$aux"));
     instructions.push(get_local("$hn"));
     instructions.push(wrap_i6432());
-    instructions.push(set_constant("255"));
+    instructions.push(set_constant(255));
     instructions.push(and32());
     instructions.push(set_local("$ini"));
     instructions.push(get_local("$ini"));
     instructions.push(set_local("$i"));
     instructions.push(add_block()); // block 1
     instructions.push(add_loop()); // loop 2
-    instructions.push(set_constant(&producer.get_input_signals_hashmap_start().to_string()));
+    instructions.push(set_constant(producer.get_input_signals_hashmap_start() as i32));
     instructions.push(get_local("$i"));
-    instructions.push(set_constant("16")); // 8(h)+4(p)+4(s)
+    instructions.push(set_constant(16)); // 8(h)+4(p)+4(s)
     instructions.push(mul32());
     instructions.push(add32());
     instructions.push(set_local("$aux"));
@@ -864,41 +864,40 @@ pub fn get_input_signal_map_position_generator(producer: &WASMProducer) -> Vec<W
     instructions.push(load64(None));
     instructions.push(eqz64());
     instructions.push(add_if()); // if 4
-    instructions.push(set_constant("0")); // error
+    instructions.push(set_constant(0)); // error
     instructions.push(add_return());
     instructions.push(add_end()); // end if 4
     instructions.push(get_local("$i"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
-    instructions.push(set_constant("255"));
+    instructions.push(set_constant(255));
     instructions.push(and32());
     instructions.push(set_local("$i"));
     instructions.push(get_local("$i"));
     instructions.push(get_local("$ini"));
     instructions.push(eq32());
     instructions.push(add_if()); //if 5
-    instructions.push(set_constant("0")); // error
+    instructions.push(set_constant(0)); // error
     instructions.push(add_return());
     instructions.push(add_end()); // end if 5
-    instructions.push(br("0"));
+    instructions.push(br(0));
     instructions.push(add_end()); // end loop 2
     instructions.push(add_end()); // end block 1
-    instructions.push(set_constant("0"));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(0));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn check_if_input_signal_set_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $checkIfInputSignalSet (type $_t_i32ri32)".to_string();
+    let header = function_header("$checkIfInputSignalSet", "$_t_i32ri32");
     instructions.push(header);
-    instructions.push(" (param $sip i32)".to_string());
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_input_signal_set_map_start().to_string()));
+    instructions.push(param("$sip"));
+    instructions.push(set_constant(producer.get_input_signal_set_map_start() as i32));
     instructions.push(get_local("$sip"));
     instructions.push(add32());
     instructions.push(load32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
@@ -908,29 +907,29 @@ pub fn set_input_signal_generator(producer: &WASMProducer) -> Vec<WasmInstructio
     instructions.append(&mut code_aux);
     code_aux = check_if_input_signal_set_generator(&producer);
     instructions.append(&mut code_aux);
-    let header = "(func $setInputSignal (type $_t_i32i32i32)".to_string();
+    let header = function_header("$setInputSignal", "$_t_i32i32i32");
     instructions.push(header);
-    instructions.push(" (param $hmsb i32)".to_string());
-    instructions.push(" (param $hlsb i32)".to_string());
-    instructions.push(" (param $pos i32)".to_string());
-    instructions.push(" (local $ns i32) ;; number of signals to set".to_string());
-    instructions.push(" (local $mp i32) ;; map position".to_string());
-    instructions.push(" (local $sip i32) ;; signal+position number".to_string());
-    instructions.push(" (local $sipm i32) ;; position in the signal memory".to_string());
-    instructions.push(" (local $vint i32)".to_string());
-    instructions.push(format!(" (local {} i32)", producer.get_merror_tag()));
-    instructions.push(set_constant(&producer.get_remaining_input_signal_counter().to_string()));
+    instructions.push(param("$hmsb"));
+    instructions.push(param("$hlsb"));
+    instructions.push(param("$pos"));
+    instructions.push(local("$ns")); // number of signals to set
+    instructions.push(local("$mp")); // map position
+    instructions.push(local("$sip")); // signal+position number
+    instructions.push(local("$sipm")); // position in the signal memory
+    instructions.push(local("$vint"));
+    instructions.push(local(producer.get_merror_tag()));
+    instructions.push(set_constant(producer.get_remaining_input_signal_counter() as i32));
     instructions.push(load32(None));
     instructions.push(set_local("$ns"));
     instructions.push(get_local("$ns"));
     instructions.push(eqz32());
     instructions.push(add_if()); // if 1
-    instructions.push(set_constant(&exception_code_no_remaing_singals_to_set().to_string()));
+    instructions.push(set_constant(exception_code_no_remaing_singals_to_set() as i32));
     instructions.push(call("$exceptionHandler"));
     instructions.push(add_else()); // else if 1
     instructions.push(get_local("$hmsb"));
     instructions.push(extend_i32_u64());
-    instructions.push(set_constant_64("32"));
+    instructions.push(set_constant_64(32));
     instructions.push(shl64());
     instructions.push(get_local("$hlsb"));
     instructions.push(extend_i32_u64());
@@ -939,81 +938,88 @@ pub fn set_input_signal_generator(producer: &WASMProducer) -> Vec<WasmInstructio
     instructions.push(tee_local("$mp"));
     instructions.push(eqz32());
     instructions.push(add_if()); // if 2
-    instructions.push(set_constant(&exception_code_singal_not_found().to_string()));
+    instructions.push(set_constant(exception_code_singal_not_found() as i32));
     instructions.push(call("$exceptionHandler"));
     instructions.push(add_else()); // else if 2
     instructions.push(get_local("$pos"));
     instructions.push(get_local("$mp"));
-    instructions.push(load32(Some("12"))); // load the second component (signal size)
+    instructions.push(load32(Some(12))); // load the second component (signal size)
     instructions.push(ge32_u());
     instructions.push(add_if()); // if 3
-    instructions.push(set_constant(&exception_code_input_array_access_exeeds_size().to_string()));
+    instructions.push(set_constant(exception_code_input_array_access_exeeds_size() as i32));
     instructions.push(call("$exceptionHandler"));
     instructions.push(add_else()); // else if 3    
     instructions.push(get_local("$mp"));
-    instructions.push(load32(Some("8"))); // load the first component (signal position)
+    instructions.push(load32(Some(8))); // load the first component (signal position)
     instructions.push(get_local("$pos"));
     instructions.push(add32());
     instructions.push(tee_local("$sip"));
     let o = producer.get_number_of_main_outputs() + 1;
-    instructions.push(set_constant(&o.to_string()));
+    instructions.push(set_constant(o as i32));
     instructions.push(sub32());
     instructions.push(call("$checkIfInputSignalSet"));
     instructions.push(add_if()); // if 4
-    instructions.push(set_constant(&exception_code_singals_already_set().to_string()));
+    instructions.push(set_constant(exception_code_singals_already_set() as i32));
     instructions.push(call("$exceptionHandler"));
     instructions.push(add_else()); // else if 4
     instructions.push(get_local("$sip"));
     let s = producer.get_size_32_bits_in_memory() * 4;
-    instructions.push(set_constant(&s.to_string()));
+    instructions.push(set_constant(s as i32));
     instructions.push(mul32());
-    instructions.push(set_constant(&producer.get_signal_memory_start().to_string()));
+    instructions.push(set_constant(producer.get_signal_memory_start() as i32));
     instructions.push(add32()); // address of the signal in memory
     instructions.push(set_local("$sipm"));
-    instructions.push(get_local("$sipm"));
-    let p_fr_rw_memory = producer.get_shared_rw_memory_start() - 8; // address of the shared memory as Fr
-    instructions.push(set_constant(&p_fr_rw_memory.to_string()));
-    instructions.push(call("$Fr_toInt")); // value as Int (if Int)
-    instructions.push(set_local("$vint"));
-    instructions.push(get_local("$vint"));
-    instructions.push(store32(None));
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant("0"));
-    instructions.push(store32(Some("4")));
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant("8"));
-    instructions.push(add32());
-    instructions.push(call("$Fr_int_zero")); // sets zeros in the long positions
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant(&p_fr_rw_memory.to_string())); // address of the shared memory as Fr
-    instructions.push(call("$Fr_eqR"));
-    instructions.push(add_if()); // if 5
-    instructions.push(get_local("$sipm"));
-    instructions.push(get_local("$vint"));
-    instructions.push(store32(None));
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant("0"));
-    instructions.push(store32(Some("4")));
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant("8"));
-    instructions.push(add32());
-    instructions.push(call("$Fr_int_zero")); // sets zeros in the long positions
-    instructions.push(add_else()); // else if 5
-    instructions.push(get_local("$sipm"));
-    instructions.push(set_constant(&p_fr_rw_memory.to_string())); // address of the shared memory as Fr
-    instructions.push(call("$Fr_copy"));
-    instructions.push(add_end()); // end else if 5
+    if producer.get_field_layout() == FieldLayout::Goldilocks64 {
+        // the value in the shared memory is already reduced
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+        instructions.push(call("$Fr_copy"));
+    } else {
+        instructions.push(get_local("$sipm"));
+        let p_fr_rw_memory = producer.get_shared_rw_memory_start() - 8; // address of the shared memory as Fr
+        instructions.push(set_constant(p_fr_rw_memory as i32));
+        instructions.push(call("$Fr_toInt")); // value as Int (if Int)
+        instructions.push(set_local("$vint"));
+        instructions.push(get_local("$vint"));
+        instructions.push(store32(None));
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(0));
+        instructions.push(store32(Some(4)));
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(8));
+        instructions.push(add32());
+        instructions.push(call("$Fr_int_zero")); // sets zeros in the long positions
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(p_fr_rw_memory as i32)); // address of the shared memory as Fr
+        instructions.push(call("$Fr_eqR"));
+        instructions.push(add_if()); // if 5
+        instructions.push(get_local("$sipm"));
+        instructions.push(get_local("$vint"));
+        instructions.push(store32(None));
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(0));
+        instructions.push(store32(Some(4)));
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(8));
+        instructions.push(add32());
+        instructions.push(call("$Fr_int_zero")); // sets zeros in the long positions
+        instructions.push(add_else()); // else if 5
+        instructions.push(get_local("$sipm"));
+        instructions.push(set_constant(p_fr_rw_memory as i32)); // address of the shared memory as Fr
+        instructions.push(call("$Fr_copy"));
+        instructions.push(add_end()); // end else if 5
+    }
     instructions.push(get_local("$ns"));
-    instructions.push(set_constant("-1"));
+    instructions.push(set_constant(-1));
     instructions.push(add32());
     instructions.push(set_local("$ns"));
-    instructions.push(set_constant(&producer.get_remaining_input_signal_counter().to_string()));
+    instructions.push(set_constant(producer.get_remaining_input_signal_counter() as i32));
     instructions.push(get_local("$ns"));
     instructions.push(store32(None));
     instructions.push(get_local("$ns"));
     instructions.push(eqz32());
     instructions.push(add_if()); // if 6
-    instructions.push(set_constant(&producer.get_component_tree_start().to_string()));
+    instructions.push(set_constant(producer.get_component_tree_start() as i32));
     let funcname = format!("${}_run", producer.get_main_header());
     instructions.push(call(&funcname));
     instructions.push(tee_local(producer.get_merror_tag()));
@@ -1026,205 +1032,215 @@ pub fn set_input_signal_generator(producer: &WASMProducer) -> Vec<WasmInstructio
     instructions.push(add_end()); // end else if 3
     instructions.push(add_end()); // end else if 2
     instructions.push(add_end()); // end else if 1
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_input_signal_size_generator(_producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getInputSignalSize (type $_t_i32i32ri32)".to_string();
+    let header = function_header("$getInputSignalSize", "$_t_i32i32ri32");
     instructions.push(header);
-    instructions.push(" (param $hmsb i32)".to_string());
-    instructions.push(" (param $hlsb i32)".to_string());
-    instructions.push("(result i32)".to_string());
+    instructions.push(param("$hmsb"));
+    instructions.push(param("$hlsb"));
     instructions.push(get_local("$hmsb"));
     instructions.push(extend_i32_u64());
-    instructions.push(set_constant_64("32"));
+    instructions.push(set_constant_64(32));
     instructions.push(shl64());
     instructions.push(get_local("$hlsb"));
     instructions.push(extend_i32_u64());
     instructions.push(or64());
     instructions.push(call("$getInputSignalMapPosition"));
-    instructions.push(load32(Some("12")));
-    instructions.push(")".to_string());
+    instructions.push(load32(Some(12)));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_raw_prime_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getRawPrime (type $_t_void)".to_string();
+    let header = function_header("$getRawPrime", "$_t_void");
     instructions.push(header);
-    instructions.push(set_constant(&producer.get_raw_prime_start().to_string())); // address of the raw prime number
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string())); // address of the shared memory
+    instructions.push(set_constant(producer.get_raw_prime_start() as i32)); // address of the raw prime number
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32)); // address of the shared memory
     instructions.push(call("$Fr_int_copy"));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_field_num_len32_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getFieldNumLen32 (type $_t_ri32)".to_string();
+    let header = function_header("$getFieldNumLen32", "$_t_ri32");
     instructions.push(header);
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_size_32_bit().to_string()));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(producer.get_size_32_bit() as i32));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_input_size_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getInputSize (type $_t_ri32)".to_string();
+    let header = function_header("$getInputSize", "$_t_ri32");
     instructions.push(header);
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_number_of_main_inputs().to_string()));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(producer.get_number_of_main_inputs() as i32));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_witness_size_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getWitnessSize (type $_t_ri32)".to_string();
+    let header = function_header("$getWitnessSize", "$_t_ri32");
     instructions.push(header);
-    instructions.push("(result i32)".to_string());
-    instructions.push(set_constant(&producer.get_number_of_witness().to_string()));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(producer.get_number_of_witness() as i32));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn copy_32_in_shared_rw_memory_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $copy32inSharedRWMemory (type $_t_i32)".to_string(); //receives i32 to be put in 0 of SharedRWMemory
+    let header = function_header("$copy32inSharedRWMemory", "$_t_i32"); //receives i32 to be put in 0 of SharedRWMemory
     instructions.push(header);
-    instructions.push(" (param $p i32)".to_string());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
+    instructions.push(param("$p"));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
     instructions.push(get_local("$p"));
     instructions.push(store32(None));
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
-    instructions.push(set_constant("0"));
-    instructions.push(store32(Some("4")));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+    instructions.push(set_constant(0));
+    instructions.push(store32(Some(4)));
     for i in 1..producer.get_size_32_bit()/2 {
 	let pos = 8*i;
-	instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
-	instructions.push(set_constant_64("0"));
-	instructions.push(store64(Some(&pos.to_string())));
+	instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+	instructions.push(set_constant_64(0));
+	instructions.push(store64(Some(pos)));
     }
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn copy_fr_in_shared_rw_memory_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $copyFr2SharedRWMemory (type $_t_i32)".to_string(); //receives address to be copied
+    let header = function_header("$copyFr2SharedRWMemory", "$_t_i32"); //receives address to be copied
     instructions.push(header);
-    instructions.push(" (param $p i32)".to_string());
+    instructions.push(param("$p"));
+    if producer.get_field_layout() == FieldLayout::Goldilocks64 {
+        instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+        instructions.push(get_local("$p"));
+        instructions.push(call("$Fr_copy"));
+        instructions.push(end_function());
+        return instructions;
+    }
     instructions.push(get_local("$p"));
     instructions.push(call("$Fr_toLongNormal"));
     instructions.push(get_local("$p"));
-    instructions.push(set_constant("8"));
+    instructions.push(set_constant(8));
     instructions.push(add32());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
     instructions.push(call("$Fr_int_copy"));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_witness_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getWitness (type $_t_i32)".to_string();
+    let header = function_header("$getWitness", "$_t_i32");
     instructions.push(header);
-    instructions.push(" (param $p i32)".to_string());
-    instructions.push(" (local $c i32)".to_string());
-    instructions.push(set_constant(&producer.get_witness_signal_id_list_start().to_string()));
+    instructions.push(param("$p"));
+    instructions.push(local("$c"));
+    instructions.push(set_constant(producer.get_witness_signal_id_list_start() as i32));
     instructions.push(get_local("$p"));
-    instructions.push(set_constant("2")); // 32 bytes per witness
+    instructions.push(set_constant(2)); // 32 bytes per witness
     instructions.push(shl32());
     instructions.push(add32()); // address of the witness in the witness list
     instructions.push(load32(None)); // number of the signal in the signal Memory
-    instructions.push(set_constant(&format!("{}",producer.get_size_32_bit()*4+8)));//40
+    instructions.push(set_constant((producer.get_size_32_bits_in_memory() * 4) as i32));//40
     instructions.push(mul32());
-    instructions.push(set_constant(&producer.get_signal_memory_start().to_string()));
+    instructions.push(set_constant(producer.get_signal_memory_start() as i32));
     instructions.push(add32()); // address of the signal in the signal Memory
     instructions.push(set_local("$c"));
+    if producer.get_field_layout() == FieldLayout::Goldilocks64 {
+        instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
+        instructions.push(get_local("$c"));
+        instructions.push(call("$Fr_copy"));
+        instructions.push(end_function());
+        return instructions;
+    }
     instructions.push(get_local("$c"));
     instructions.push(call("$Fr_toLongNormal"));
     instructions.push(get_local("$c"));
-    instructions.push(set_constant("8"));
+    instructions.push(set_constant(8));
     instructions.push(add32());
-    instructions.push(set_constant(&producer.get_shared_rw_memory_start().to_string()));
+    instructions.push(set_constant(producer.get_shared_rw_memory_start() as i32));
     instructions.push(call("$Fr_int_copy"));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
 pub fn get_message_char_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $getMessageChar (type $_t_ri32)".to_string();
+    let header = function_header("$getMessageChar", "$_t_ri32");
     instructions.push(header);
-    instructions.push(" (local $c i32)".to_string());
-    instructions.push(set_constant(&producer.get_message_buffer_counter_position().to_string()));
+    instructions.push(local("$c"));
+    instructions.push(set_constant(producer.get_message_buffer_counter_position() as i32));
     instructions.push(load32(None)); // current position in buffer
     instructions.push(set_local("$c"));
     instructions.push(get_local("$c"));
-    instructions.push(set_constant(&producer.get_size_of_message_buffer_in_bytes().to_string()));
+    instructions.push(set_constant(producer.get_size_of_message_buffer_in_bytes() as i32));
     instructions.push(ge32_u());
     instructions.push(add_if());
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(add_return());
     instructions.push(add_else());
-    instructions.push(set_constant(&producer.get_message_buffer_start().to_string()));
+    instructions.push(set_constant(producer.get_message_buffer_start() as i32));
     instructions.push(get_local("$c"));
     instructions.push(add32());
     instructions.push(load32_8u(None));
-    instructions.push(set_constant(&producer.get_message_buffer_counter_position().to_string()));
+    instructions.push(set_constant(producer.get_message_buffer_counter_position() as i32));
     instructions.push(get_local("$c"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(store32(None)); // new current position in buffer
     instructions.push(add_return());
     instructions.push(add_end());
-    instructions.push(set_constant("0"));
-    instructions.push(")".to_string());
+    instructions.push(set_constant(0));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn build_log_message_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $buildLogMessage (type $_t_i32)".to_string();
+    let header = function_header("$buildLogMessage", "$_t_i32");
     instructions.push(header);
-    instructions.push(" (param $m i32)".to_string()); //string position
-    instructions.push(" (local $em i32)".to_string()); //position in error message
-    instructions.push(" (local $bm i32)".to_string()); //position in buffer
-    instructions.push(" (local $mc i32)".to_string()); //message char
+    instructions.push(param("$m")); //string position
+    instructions.push(local("$em")); //position in error message
+    instructions.push(local("$bm")); //position in buffer
+    instructions.push(local("$mc")); //message char
     instructions.push(get_local("$m"));
     instructions.push(set_local("$em"));
-    instructions.push(set_constant(&producer.get_message_buffer_start().to_string()));
+    instructions.push(set_constant(producer.get_message_buffer_start() as i32));
     instructions.push(set_local("$bm"));
     instructions.push(add_block());
     instructions.push(add_loop()); //move bytes until end of message or zero found
                                    // check if end of message
     let final_pos = producer.get_size_of_message_in_bytes() + producer.get_message_buffer_start();
-    instructions.push(set_constant(&final_pos.to_string()));
+    instructions.push(set_constant(final_pos as i32));
     instructions.push(get_local("$em"));
     instructions.push(eq32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$em"));
     instructions.push(load32_8u(None));
     instructions.push(set_local("$mc"));
     instructions.push(get_local("$mc"));
     instructions.push(eqz32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$bm"));
     instructions.push(get_local("$mc"));
     instructions.push(store32_8(None));
     instructions.push(get_local("$em"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$em"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
-    instructions.push(br("0"));
+    instructions.push(br(0));
     instructions.push(add_end());
     instructions.push(add_end());
     //fill rest of buffer with 0's
@@ -1233,141 +1249,237 @@ pub fn build_log_message_generator(producer: &WASMProducer) -> Vec<WasmInstructi
     instructions.push(get_local("$bm"));
     let buff_final_pos =
         producer.get_message_buffer_start() + producer.get_size_of_message_buffer_in_bytes();
-    instructions.push(set_constant(&buff_final_pos.to_string()));
+    instructions.push(set_constant(buff_final_pos as i32));
     instructions.push(eq32());
-    instructions.push(br_if("1")); //jump to the end of block
+    instructions.push(br_if(1)); //jump to the end of block
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(store32_8(None)); // stores the digit in the buffer
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
-    instructions.push(br("0")); // jump to the loop
+    instructions.push(br(0)); // jump to the loop
     instructions.push(add_end());
     instructions.push(add_end());
     // initialize message buffer position to 0
-    instructions.push(set_constant(&producer.get_message_buffer_counter_position().to_string()));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(producer.get_message_buffer_counter_position() as i32));
+    instructions.push(set_constant(0));
     instructions.push(store32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
+    instructions
+}
+
+pub fn trace_create_generator(_producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let mut instructions = vec![];
+    let header = function_header("$traceCreate", "$_t_i32i32i32i32");
+    instructions.push(header);
+    instructions.push(param("$father"));
+    instructions.push(param("$cmp"));
+    instructions.push(param("$name")); //string position
+    instructions.push(param("$index")); //position in the array, -1 if not an array
+    instructions.push(get_local("$name"));
+    instructions.push(call("$buildLogMessage"));
+    instructions.push(get_local("$father"));
+    instructions.push(get_local("$cmp"));
+    instructions.push(get_local("$index"));
+    instructions.push(call("$traceComponent"));
+    instructions.push(end_function());
+    instructions
+}
+
+pub fn trace_signals_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let mut instructions = vec![];
+    let header = function_header("$traceSignals", "$_t_i32i32i32i32");
+    instructions.push(header);
+    instructions.push(param("$cmp"));
+    instructions.push(param("$name")); //string position
+    instructions.push(param("$p")); //address of the first signal
+    instructions.push(param("$n")); //number of signals
+    let fr_size = producer.get_size_32_bits_in_memory() * 4;
+    instructions.push(add_block());
+    instructions.push(add_loop());
+    instructions.push(get_local("$n"));
+    instructions.push(eqz32());
+    instructions.push(br_if(1));
+    instructions.push(get_local("$p"));
+    instructions.push(call("$copyFr2SharedRWMemory"));
+    instructions.push(call("$traceValue"));
+    instructions.push(get_local("$name"));
+    instructions.push(call("$buildLogMessage"));
+    instructions.push(get_local("$cmp"));
+    instructions.push(get_local("$p"));
+    instructions.push(set_constant(producer.get_signal_memory_start() as i32));
+    instructions.push(sub32());
+    instructions.push(set_constant(fr_size as i32));
+    instructions.push(div32_u()); // number of the signal in the signal memory
+    instructions.push(call("$traceSignal"));
+    instructions.push(get_local("$p"));
+    instructions.push(set_constant(fr_size as i32));
+    instructions.push(add32());
+    instructions.push(set_local("$p"));
+    instructions.push(get_local("$n"));
+    instructions.push(set_constant(1));
+    instructions.push(sub32());
+    instructions.push(set_local("$n"));
+    instructions.push(br(0));
+    instructions.push(add_end());
+    instructions.push(add_end());
+    instructions.push(end_function());
+    instructions
+}
+
+pub fn trace_args_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let mut instructions = vec![];
+    let header = function_header("$traceArgs", "$_t_i32i32i32");
+    instructions.push(header);
+    instructions.push(param("$name")); //string position
+    instructions.push(param("$p")); //address of the first argument
+    instructions.push(param("$n")); //number of field elements in the arguments
+    instructions.push(local("$i"));
+    let fr_size = producer.get_size_32_bits_in_memory() * 4;
+    instructions.push(add_block());
+    instructions.push(add_loop());
+    instructions.push(get_local("$i"));
+    instructions.push(get_local("$n"));
+    instructions.push(eq32());
+    instructions.push(br_if(1));
+    instructions.push(get_local("$p"));
+    instructions.push(call("$copyFr2SharedRWMemory"));
+    instructions.push(call("$traceValue"));
+    instructions.push(get_local("$p"));
+    instructions.push(set_constant(fr_size as i32));
+    instructions.push(add32());
+    instructions.push(set_local("$p"));
+    instructions.push(get_local("$i"));
+    instructions.push(set_constant(1));
+    instructions.push(add32());
+    instructions.push(set_local("$i"));
+    instructions.push(br(0));
+    instructions.push(add_end());
+    instructions.push(add_end());
+    instructions.push(get_local("$name"));
+    instructions.push(call("$buildLogMessage"));
+    instructions.push(get_local("$n"));
+    instructions.push(call("$traceCall"));
+    instructions.push(end_function());
     instructions
 }
 
 pub fn build_buffer_message_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $buildBufferMessage (type $_t_i32i32)".to_string();
+    let header = function_header("$buildBufferMessage", "$_t_i32i32");
     instructions.push(header);
-    instructions.push(" (param $m i32)".to_string()); //message id
-    instructions.push(" (param $l i32)".to_string()); //line
-    instructions.push(" (local $em i32)".to_string()); //position in error message
-    instructions.push(" (local $bm i32)".to_string()); //position in buffer
-    instructions.push(" (local $mc i32)".to_string()); //message char
-    instructions.push(" (local $p10 i32)".to_string()); //power of 10
-    instructions.push(set_constant(&producer.get_message_list_start().to_string()));
+    instructions.push(param("$m")); //message id
+    instructions.push(param("$l")); //line
+    instructions.push(local("$em")); //position in error message
+    instructions.push(local("$bm")); //position in buffer
+    instructions.push(local("$mc")); //message char
+    instructions.push(local("$p10")); //power of 10
+    instructions.push(set_constant(producer.get_message_list_start() as i32));
     instructions.push(get_local("$m"));
-    instructions.push(set_constant(&producer.get_size_of_message_in_bytes().to_string()));
+    instructions.push(set_constant(producer.get_size_of_message_in_bytes() as i32));
     instructions.push(mul32());
     instructions.push(add32());
     instructions.push(set_local("$em"));
-    instructions.push(set_constant(&producer.get_message_buffer_start().to_string()));
+    instructions.push(set_constant(producer.get_message_buffer_start() as i32));
     instructions.push(set_local("$bm"));
     instructions.push(add_block());
     instructions.push(add_loop()); //move bytes until end of message or zero found
                                    // check if end of message
     let final_pos = producer.get_size_of_message_in_bytes() + producer.get_message_buffer_start();
-    instructions.push(set_constant(&final_pos.to_string()));
+    instructions.push(set_constant(final_pos as i32));
     instructions.push(get_local("$em"));
     instructions.push(eq32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$em"));
     instructions.push(load32_8u(None));
     instructions.push(set_local("$mc"));
     instructions.push(get_local("$mc"));
     instructions.push(eqz32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$bm"));
     instructions.push(get_local("$mc"));
     instructions.push(store32_8(None));
     instructions.push(get_local("$em"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$em"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
-    instructions.push(br("0"));
+    instructions.push(br(0));
     instructions.push(add_end());
     instructions.push(add_end());
     //adding the line " line: "
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x20")); //space
+    instructions.push(set_constant(0x20)); //space
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x6C")); //l
+    instructions.push(set_constant(0x6C)); //l
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x69")); //i
+    instructions.push(set_constant(0x69)); //i
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x6E")); //n
+    instructions.push(set_constant(0x6E)); //n
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x65")); //e
+    instructions.push(set_constant(0x65)); //e
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x3A")); //:
+    instructions.push(set_constant(0x3A)); //:
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0x20")); //space
+    instructions.push(set_constant(0x20)); //space
     instructions.push(store32_8(None));
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     //adding the line number
     //compute the power of 10 with the number of digits
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(set_local("$p10"));
     instructions.push(add_block());
     instructions.push(add_loop());
     //check if $p10 * 10 > $l
     instructions.push(get_local("$p10"));
-    instructions.push(set_constant("10"));
+    instructions.push(set_constant(10));
     instructions.push(mul32());
     instructions.push(get_local("$l"));
     instructions.push(gt32_u());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$p10"));
-    instructions.push(set_constant("10"));
+    instructions.push(set_constant(10));
     instructions.push(mul32());
     instructions.push(set_local("$p10"));
-    instructions.push(br("0")); // jump to the loop
+    instructions.push(br(0)); // jump to the loop
     instructions.push(add_end());
     instructions.push(add_end());
 
@@ -1377,17 +1489,17 @@ pub fn build_buffer_message_generator(producer: &WASMProducer) -> Vec<WasmInstru
     //check if $p10 != 0
     instructions.push(get_local("$p10"));
     instructions.push(eqz32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
     instructions.push(get_local("$bm")); //next position in the buffer
                                          //get the next digit left-to-right
     instructions.push(get_local("$l"));
     instructions.push(get_local("$p10"));
     instructions.push(div32_u()); // highest digit
-    instructions.push(set_constant("0x30"));
+    instructions.push(set_constant(0x30));
     instructions.push(add32()); // hex of the digit
     instructions.push(store32_8(None)); // stores the digit in the buffer
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
     instructions.push(get_local("$l"));
@@ -1395,10 +1507,10 @@ pub fn build_buffer_message_generator(producer: &WASMProducer) -> Vec<WasmInstru
     instructions.push(rem32_u()); // remove the highest digit
     instructions.push(set_local("$l"));
     instructions.push(get_local("$p10"));
-    instructions.push(set_constant("10"));
+    instructions.push(set_constant(10));
     instructions.push(div32_u()); // decrease power of 10
     instructions.push(set_local("$p10"));
-    instructions.push(br("0")); // jump to the loop
+    instructions.push(br(0)); // jump to the loop
     instructions.push(add_end());
     instructions.push(add_end());
     //fill rest of buffer with 0's
@@ -1407,24 +1519,24 @@ pub fn build_buffer_message_generator(producer: &WASMProducer) -> Vec<WasmInstru
     instructions.push(get_local("$bm"));
     let buff_final_pos =
         producer.get_message_buffer_start() + producer.get_size_of_message_buffer_in_bytes();
-    instructions.push(set_constant(&buff_final_pos.to_string()));
+    instructions.push(set_constant(buff_final_pos as i32));
     instructions.push(eq32());
-    instructions.push(br_if("1")); //jump to the end of block
+    instructions.push(br_if(1)); //jump to the end of block
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(0));
     instructions.push(store32_8(None)); // stores the digit in the buffer
     instructions.push(get_local("$bm"));
-    instructions.push(set_constant("1"));
+    instructions.push(set_constant(1));
     instructions.push(add32());
     instructions.push(set_local("$bm"));
-    instructions.push(br("0")); // jump to the loop
+    instructions.push(br(0)); // jump to the loop
     instructions.push(add_end());
     instructions.push(add_end());
     // initialize message buffer position to 0
-    instructions.push(set_constant(&producer.get_message_buffer_counter_position().to_string()));
-    instructions.push(set_constant("0"));
+    instructions.push(set_constant(producer.get_message_buffer_counter_position() as i32));
+    instructions.push(set_constant(0));
     instructions.push(store32(None));
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
 
@@ -1432,12 +1544,12 @@ pub fn generate_table_of_template_runs(producer: &WASMProducer) -> Vec<WasmInstr
     let mut instructions = vec![];
     //    if !producer.get_io_map().is_empty() {
     let tlen = producer.get_template_instance_list().len();
-    instructions.push(format!("(table $runsmap {} {} funcref)", tlen, tlen));
-    instructions.push("(elem $runsmap (i32.const 0)".to_string());
+    instructions.push(WasmInstruction::Table { name: "$runsmap".to_string(), min: tlen as u32, max: Some(tlen as u32) });
+    let mut functions = vec![];
     for i in 0..tlen {
-        instructions.push(format!(" ${}_run", producer.get_template_instance_list()[i]));
+        functions.push(format!("${}_run", producer.get_template_instance_list()[i]));
     }
-    instructions.push(")".to_string());
+    instructions.push(WasmInstruction::Elem { table: "$runsmap".to_string(), offset: 0, functions });
     //    }
     instructions
 }
@@ -1450,12 +1562,12 @@ pub fn generate_table_of_template_runs(producer: &WASMProducer) -> Vec<WasmInstr
 /*
 pub fn main_sample_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut instructions = vec![];
-    let header = "(func $main (type $_t_i32)".to_string();
+    let header = function_header("$main", "$_t_i32");
     instructions.push(header);
-    instructions.push(format!(" (param {} i32)",producer.get_offset_tag()));
-    instructions.push(" (local $i i32)".to_string());
-    instructions.push(" (local $im1 i32)".to_string());
-    instructions.push(format!(" (local {} i32)",producer.get_cstack_tag()));
+    instructions.push(param(producer.get_offset_tag()));
+    instructions.push(local("$i"));
+    instructions.push(local("$im1"));
+    instructions.push(local(producer.get_cstack_tag()));
     let mut reserve_stack_fr_code = reserve_stack_fr(producer,1);
     instructions.append(&mut reserve_stack_fr_code);
     let o1 = producer.get_signal_memory_start()+1*40;
@@ -1464,37 +1576,37 @@ pub fn main_sample_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let i2 = i1+40;
     let i3 = i2+40;
     let i4 = i3+40;
-    instructions.push(set_constant(&o1.to_string())); // first output
-    instructions.push(set_constant(&i1.to_string())); // first input
-    instructions.push(set_constant(&i2.to_string())); // second input
+    instructions.push(set_constant(o1 as i32)); // first output
+    instructions.push(set_constant(i1 as i32)); // first input
+    instructions.push(set_constant(i2 as i32)); // second input
     instructions.push(call("$Fr_add"));
-    instructions.push(set_constant(&o2.to_string())); // second output
-    instructions.push(set_constant(&i3.to_string())); // third input
-    instructions.push(set_constant(&i4.to_string())); // forth input
+    instructions.push(set_constant(o2 as i32)); // second output
+    instructions.push(set_constant(i3 as i32)); // third input
+    instructions.push(set_constant(i4 as i32)); // forth input
     instructions.push(call("$Fr_add"));
     // put a one in the stack
     instructions.push(get_local(producer.get_cstack_tag()));
-    instructions.push(set_constant(&producer.get_signal_memory_start().to_string())); // one
+    instructions.push(set_constant(producer.get_signal_memory_start() as i32)); // one
     instructions.push(call("$Fr_copy"));
-    instructions.push(set_constant(&i4.to_string())); // forth input
+    instructions.push(set_constant(i4 as i32)); // forth input
     instructions.push(set_local("$im1"));
     let first_no_input = i4 + 40;
-    instructions.push(set_constant(&first_no_input.to_string()));
+    instructions.push(set_constant(first_no_input as i32));
     instructions.push(set_local("$i"));
     instructions.push(add_block()); // block 1
     instructions.push(add_loop());  // loop 1
     instructions.push(get_local("$i"));
     let end_of_signals = producer.get_signal_memory_start()+producer.get_total_number_of_signals()*40;
-    instructions.push(set_constant(&end_of_signals.to_string()));
+    instructions.push(set_constant(end_of_signals as i32));
     instructions.push(eq32());
-    instructions.push(br_if("1")); // jump to end of block 1
+    instructions.push(br_if(1)); // jump to end of block 1
 /*
     //works!
     //testing error messages
-    instructions.push(set_constant("0"));
-    instructions.push(set_constant("104"));
+    instructions.push(set_constant(0));
+    instructions.push(set_constant(104));
     instructions.push(call("$buildBufferMessage"));
-    instructions.push(set_constant("4"));
+    instructions.push(set_constant(4));
     instructions.push(call("$exceptionHandler"));
 */
     instructions.push(get_local("$i"));
@@ -1502,81 +1614,50 @@ pub fn main_sample_generator(producer: &WASMProducer) -> Vec<WasmInstruction> {
     instructions.push(get_local(producer.get_cstack_tag()));
     instructions.push(call("$Fr_add"));
     instructions.push(get_local("$im1"));
-    instructions.push(set_constant("40"));
+    instructions.push(set_constant(40));
     instructions.push(add32());
     instructions.push(set_local("$im1"));
     instructions.push(get_local("$i"));
-    instructions.push(set_constant("40"));
+    instructions.push(set_constant(40));
     instructions.push(add32());
     instructions.push(set_local("$i"));
-    instructions.push(br("0")); // jump to begin of loop 2
+    instructions.push(br(0)); // jump to begin of loop 2
     instructions.push(add_end()); // end of loop 2
     instructions.push(add_end()); // end of block 1
     let mut free_stack_code = free_stack(producer);
     instructions.append(&mut free_stack_code);
-    instructions.push(")".to_string());
+    instructions.push(end_function());
     instructions
 }
  */
 
-fn get_file_instructions(name: &str) -> Vec<WasmInstruction> {
-    use std::io::BufReader;
-    use std::path::Path;
-    let mut instructions = vec![];
-    let path = format!("./{}.wat", name);
-    if Path::new(&path).exists() {
-        let file = File::open(path).unwrap();
-        let reader = BufReader::new(file);
-        for rline in reader.lines() {
-            if let Result::Ok(line) = rline {
-                instructions.push(line);
-            }
-        }
-    } else {
-        panic!("FILE NOT FOUND {}", name);
-    }
-    instructions
-}
-
-pub fn fr_types(prime: &String) -> Vec<WasmInstruction> {
-    let mut instructions = vec![];
-    let file = match prime.as_ref(){
-        "bn128" => include_str!("bn128/fr-types.wat"),
-        "bls12381" => include_str!("bls12381/fr-types.wat"),
-        "goldilocks" => include_str!("goldilocks/fr-types.wat"),
+pub fn fr_types(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let file = match (producer.get_field_layout(), producer.prime_str.as_str()) {
+        (FieldLayout::Goldilocks64, _) => include_str!("goldilocks64/fr-types.wat"),
+        (FieldLayout::MultiLimb, "bn128") => include_str!("bn128/fr-types.wat"),
+        (FieldLayout::MultiLimb, "bls12381") => include_str!("bls12381/fr-types.wat"),
         _ => unreachable!(),
-    };    
-    for line in file.lines() {
-        instructions.push(line.to_string());
-    }
-    instructions
+    };
+    wasm_library::read_library(file).expect("the field library is well formed")
 }
 
-pub fn fr_data(prime: &String) -> Vec<WasmInstruction> {
-    let mut instructions = vec![];
-    let file = match prime.as_ref(){
-        "bn128" => include_str!("bn128/fr-data.wat"),
-        "bls12381" => include_str!("bls12381/fr-data.wat"),
-        "goldilocks" => include_str!("goldilocks/fr-data.wat"),
+pub fn fr_data(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let file = match (producer.get_field_layout(), producer.prime_str.as_str()) {
+        (FieldLayout::Goldilocks64, _) => include_str!("goldilocks64/fr-data.wat"),
+        (FieldLayout::MultiLimb, "bn128") => include_str!("bn128/fr-data.wat"),
+        (FieldLayout::MultiLimb, "bls12381") => include_str!("bls12381/fr-data.wat"),
         _ => unreachable!(),
-    };    
-    for line in file.lines() {
-        instructions.push(line.to_string());
-    }
-    instructions
-}
-pub fn fr_code(prime: &String) -> Vec<WasmInstruction> {
-    let mut instructions = vec![];
-    let file = match prime.as_ref(){
-        "bn128" => include_str!("bn128/fr-code.wat"),
-        "bls12381" => include_str!("bls12381/fr-code.wat"),
-        "goldilocks" => include_str!("goldilocks/fr-code.wat"),
+    };
+    wasm_library::read_library(file).expect("the field library is well formed")
+}
+pub fn fr_code(producer: &WASMProducer) -> Vec<WasmInstruction> {
+    let file = match (producer.get_field_layout(), producer.prime_str.as_str()) {
+        (FieldLayout::Goldilocks64, _) => include_str!("goldilocks64/fr-code.wat"),
+        (FieldLayout::MultiLimb, "bn128") => include_str!("bn128/fr-code.wat"),
+        (FieldLayout::MultiLimb, "bls12381") => include_str!("bls12381/fr-code.wat"),
         _ => unreachable!(),
-    };    
-    for line in file.lines() {
-        instructions.push(line.to_string());
-    }
-    instructions
+    };
+    wasm_library::read_library(file).expect("the field library is well formed")
 }
 
 /*
@@ -1632,10 +1713,117 @@ pub fn generate_witness_calculator_js_file(js_folder: &PathBuf) -> std::io::Resu
     Ok(())
 }
 
+pub fn generate_witness_calculator_mjs_file(js_folder: &PathBuf) -> std::io::Result<()> {
+    use std::io::BufWriter;
+    let mut file_path  = js_folder.clone();
+    file_path.push("witness_calculator");
+    file_path.set_extension("mjs");
+    let file_name = file_path.to_str().unwrap();
+    let mut js_file = BufWriter::new(File::create(file_name).unwrap());
+    // the same calculator as an ES module
+    let code = include_str!("common/witness_calculator.js").replacen(
+        "module.exports = async function builder",
+        "export default async function builder",
+        1,
+    );
+    js_file.write_all(code.as_bytes())?;
+    js_file.flush()?;
+    Ok(())
+}
+
+// Longest array written as a tuple, beyond it the recursion of Tuple exceeds
+// the limits of the type checker
+const MAX_TUPLE_LENGTH: usize = 999;
+
+fn input_type(dimensions: &[usize]) -> String {
+    match dimensions.split_first() {
+        None => "FieldValue".to_string(),
+        Some((length, rest)) if *length <= MAX_TUPLE_LENGTH => format!("Tuple<{}, {}>", input_type(rest), length),
+        Some((_, rest)) => format!("{}[]", input_type(rest)),
+    }
+}
+
+// Schema of the inputs, read by generate_witness.js next to the wasm file
+pub fn generate_input_schema_file(js_folder: &PathBuf, wasm_name: &str, producer: &WASMProducer) -> std::io::Result<()> {
+    let mut file_path = js_folder.clone();
+    file_path.push(format!("{}.schema.json", wasm_name));
+    let schema = serde_json::to_string_pretty(&producer.get_input_schema()).unwrap();
+    std::fs::write(file_path, schema + "\n")
+}
+
+// Types of the witness calculator shared by both modules, with the inputs of
+// the main component and the size of its witness
+pub fn generate_witness_calculator_types(producer: &WASMProducer) -> Vec<String> {
+    let mut types = vec![];
+    types.push("/** Element of the field, as a number, a bigint or a decimal string */".to_string());
+    types.push("export type FieldValue = bigint | number | string;".to_string());
+    types.push("".to_string());
+    types.push("/** Array of N elements of type T */".to_string());
+    types.push("export type Tuple<T, N extends number, R extends T[] = []> = R[\"length\"] extends N ? R : Tuple<T, N, [...R, T]>;".to_string());
+    types.push("".to_string());
+    types.push("/** Input signals of the main component */".to_string());
+    types.push("export interface CircuitInput {".to_string());
+    for ((name, _, _), dimensions) in producer.get_main_input_list().iter().zip(producer.get_main_input_dimensions()) {
+        types.push(format!("    {}: {};", name, input_type(dimensions)));
+    }
+    types.push("}".to_string());
+    types.push("".to_string());
+    types.push("/** Number of elements of the witness */".to_string());
+    types.push(format!("export type WitnessSize = {};", producer.get_number_of_witness()));
+    types.push("".to_string());
+    types.push("export interface WitnessCalculatorOptions {".to_string());
+    types.push("    /** Receives the lines of the execution trace of circuits compiled with --trace */".to_string());
+    types.push("    trace?: (line: string) => void;".to_string());
+    types.push("    /** Prefixes of the component paths that are traced */".to_string());
+    types.push("    traceFilter?: string[];".to_string());
+    types.push("    /** Schema of the inputs, as in the .schema.json file, to validate them before the calculation */".to_string());
+    types.push("    inputSchema?: object;".to_string());
+    types.push("}".to_string());
+    types.push("".to_string());
+    types.push("export interface WitnessCalculator {".to_string());
+    types.push("    readonly version: number;".to_string());
+    types.push("    readonly n32: number;".to_string());
+    types.push("    readonly prime: bigint;".to_string());
+    types.push("    readonly witnessSize: WitnessSize;".to_string());
+    types.push("    circom_version(): number;".to_string());
+    types.push("    calculateWitness(input: CircuitInput, sanityCheck?: boolean | number): Promise<bigint[]>;".to_string());
+    types.push("    calculateBinWitness(input: CircuitInput, sanityCheck?: boolean | number): Promise<Uint8Array>;".to_string());
+    types.push("    calculateWTNSBin(input: CircuitInput, sanityCheck?: boolean | number): Promise<Uint8Array>;".to_string());
+    types.push("}".to_string());
+    types
+}
+
+// Writes witness_calculator.d.ts for the CommonJS module and
+// witness_calculator.d.mts for the ES module
+pub fn generate_witness_calculator_dts_files(js_folder: &PathBuf, producer: &WASMProducer) -> std::io::Result<()> {
+    let types = generate_witness_calculator_types(producer);
+    let builder = "function builder(code: ArrayBuffer | ArrayBufferView, options?: WitnessCalculatorOptions): Promise<WitnessCalculator>;";
+
+    let mut code = vec!["declare ".to_string() + &builder.replace("WitnessCalculator", "builder.WitnessCalculator")];
+    code.push("declare namespace builder {".to_string());
+    for line in &types {
+        code.push(if line.is_empty() { line.clone() } else { format!("    {}", line) });
+    }
+    code.push("}".to_string());
+    code.push("export = builder;".to_string());
+    let mut file_path = js_folder.clone();
+    file_path.push("witness_calculator.d.ts");
+    std::fs::write(file_path, code.join("\n") + "\n")?;
+
+    let mut code = types;
+    code.push("".to_string());
+    code.push("export default ".to_string() + builder);
+    let mut file_path = js_folder.clone();
+    file_path.push("witness_calculator.d.mts");
+    std::fs::write(file_path, code.join("\n") + "\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::{BufRead, BufReader, BufWriter, Write};
+    use crate::wasm_elements::wasm_binary::WasmModule;
+    use crate::wasm_elements::wasm_disassembler::disassemble;
+    use std::io::{BufWriter, Write};
     use std::path::Path;
     const LOCATION: &'static str = "../target/code_generator_test";
 
@@ -1652,33 +1840,8 @@ mod tests {
         BufWriter::new(file)
     }
 
-    fn get_instructions_from_file(name: &str) -> Vec<WasmInstruction> {
-        //return content of LOCATION/name.wat
-        let mut instructions = vec![];
-        let path = format!("{}/{}.wat", LOCATION, name);
-        if Path::new(&path).exists() {
-            let file = File::open(path).unwrap();
-            let reader = BufReader::new(file);
-            for rline in reader.lines() {
-                if let Result::Ok(line) = rline {
-                    instructions.push(line);
-                    //                    println!("line added");
-                }
-            }
-        } else {
-            eprintln!("NO FILE FOUND");
-        }
-        instructions
-    }
-
-    /*
-        let bytes = empty.read_line(&mut buffer)?;
-        if bytes == 0 {
-            eprintln!("EOF reached");
-        }
-    */
-    fn write_block(writer: &mut BufWriter<File>, code: Vec<WasmInstruction>) {
-        let data = merge_code(code);
+    fn write_module(writer: &mut BufWriter<File>, module: WasmModule) {
+        let data = disassemble(&module.encode().unwrap()).unwrap();
         writer.write_all(data.as_bytes()).unwrap();
         writer.flush().unwrap();
     }
@@ -1687,94 +1850,72 @@ mod tests {
     fn produce_code() {
         let producer = create_producer();
         let mut writer = create_writer();
-        // For every block of code that you want to write in code.wat the following two lines.
-        // In the first line the code you want tow write is produced. Then, to write that code the
-        // test function "write_block" is called.
-        let mut code = vec![];
-        code.push("(module".to_string());
-        let mut code_aux = generate_imports_list();
-        code.append(&mut code_aux);
-        code_aux = generate_memory_def_list(&producer);
-        code.append(&mut code_aux);
+        // Every block of code is added to the module, which is written in
+        // code.wat by the test function "write_module".
+        let mut module = WasmModule::new();
+        module.add_code(&generate_imports_list()).unwrap();
+        module.add_code(&generate_memory_def_list(&producer)).unwrap();
+
+        module.add_code(&fr_types(&producer)).unwrap();
 
-        code_aux = get_instructions_from_file("fr-types");
-        code.append(&mut code_aux);
+        module.add_code(&generate_types_list()).unwrap();
+        module.add_code(&generate_exports_list()).unwrap();
 
-        code_aux = generate_types_list();
-        code.append(&mut code_aux);
-        code_aux = generate_exports_list();
-        code.append(&mut code_aux);
+        module.add_code(&fr_code(&producer)).unwrap();
 
-        code_aux = get_instructions_from_file("fr-code");
-        code.append(&mut code_aux);
+        module.add_code(&desp_io_subcomponent_generator(&producer)).unwrap();
 
-        code_aux = desp_io_subcomponent_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_version_generator(&producer)).unwrap();
 
-        code_aux = get_version_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_shared_rw_memory_start_generator(&producer)).unwrap();
 
-        code_aux = get_shared_rw_memory_start_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&read_shared_rw_memory_generator(&producer)).unwrap();
 
-        code_aux = read_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&write_shared_rw_memory_generator(&producer)).unwrap();
 
-        code_aux = write_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&reserve_stack_fr_function_generator()).unwrap();
 
-        //code_aux = reserve_stack_fr_function_generator(&producer);
-        code_aux = reserve_stack_fr_function_generator();
-        code.append(&mut code_aux);
+        module.add_code(&init_generator(&producer)).unwrap();
 
-        code_aux = init_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&set_input_signal_generator(&producer)).unwrap();
 
-        code_aux = set_input_signal_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_input_signal_size_generator(&producer)).unwrap();
 
-        code_aux = get_input_signal_size_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_raw_prime_generator(&producer)).unwrap();
 
-        code_aux = get_raw_prime_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_field_num_len32_generator(&producer)).unwrap();
 
-        code_aux = get_field_num_len32_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_input_size_generator(&producer)).unwrap();
 
-        code_aux = get_witness_size_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_witness_size_generator(&producer)).unwrap();
 
-        code_aux = get_witness_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_witness_generator(&producer)).unwrap();
 
-        code_aux = copy_32_in_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&copy_32_in_shared_rw_memory_generator(&producer)).unwrap();
 
-        code_aux = copy_fr_in_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&copy_fr_in_shared_rw_memory_generator(&producer)).unwrap();
 
-        code_aux = get_message_char_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&get_message_char_generator(&producer)).unwrap();
 
-        code_aux = build_buffer_message_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&build_buffer_message_generator(&producer)).unwrap();
 
-        code_aux = build_log_message_generator(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&build_log_message_generator(&producer)).unwrap();
 	
-        //code_aux = main_sample_generator(&producer);
-        //code.append(&mut code_aux);
+        //module.add_code(&main_sample_generator(&producer)).unwrap();
 
-        code_aux = get_instructions_from_file("fr-data");
-        code.append(&mut code_aux);
+        // the main template is not generated here, so it just returns 0
+        for function in ["create", "run"] {
+            let header = format!("${}_{}", producer.get_main_header(), function);
+            let code = vec![function_header(&header, "$_t_i32ri32"), param("$x"), set_constant(0), end_function()];
+            module.add_code(&code).unwrap();
+        }
 
-        code_aux = generate_data_list(&producer);
-        code.append(&mut code_aux);
+        module.add_code(&fr_data(&producer)).unwrap();
 
-        code.push(")".to_string());
+        module.add_code(&generate_data_list(&producer)).unwrap();
 
-        write_block(&mut writer, code);
+        module.add_code(&generate_table_of_template_runs(&producer)).unwrap();
+        write_module(&mut writer, module);
 
         //let num = BigInt::parse_bytes(b"2240", 10).unwrap();
         // println!("Hexa: {}",wasm_hexa(4,&num));
@@ -1785,4 +1926,30 @@ mod tests {
         // circom_compiler/target/code_generator_test/code.wat
         assert!(true);
     }
+
+    #[test]
+    fn goldilocks64_elements_are_single_words() {
+        let mut producer = create_producer();
+        producer.prime = "18446744069414584321".to_string();
+        producer.prime_str = "goldilocks".to_string();
+        producer.field_layout = FieldLayout::Goldilocks64;
+        producer.size_32_bit = 2;
+        assert_eq!(producer.get_size_32_bits_in_memory(), 2);
+        let constants = generate_data_constants(&producer, &vec!["-1".to_string(), "5".to_string()]);
+        assert_eq!(constants, vec![0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 5, 0, 0, 0, 0, 0, 0, 0]);
+        let mut module = WasmModule::new();
+        module.add_code(&fr_types(&producer)).unwrap();
+        module.add_code(&fr_code(&producer)).unwrap();
+        assert!(module.encode().is_ok());
+    }
+
+    #[test]
+    fn input_types_follow_the_dimensions() {
+        assert_eq!(input_type(&[]), "FieldValue");
+        assert_eq!(input_type(&[2, 3]), "Tuple<Tuple<FieldValue, 3>, 2>");
+        assert_eq!(input_type(&[1200, 2]), "Tuple<FieldValue, 2>[]");
+        let types = generate_witness_calculator_types(&create_producer());
+        assert!(types.contains(&"    in1: FieldValue;".to_string()));
+        assert!(types.contains(&"export type WitnessSize = 0;".to_string()));
+    }
 }
@@ -0,0 +1,346 @@
+use super::wasm_binary::*;
+use std::collections::HashMap;
+
+// Text format of the binary modules written by the compiler. The names of the
+// name section are used for the functions, locals, types and tables
+
+#[derive(Default)]
+struct Names {
+    functions: HashMap<u32, String>,
+    locals: HashMap<u32, HashMap<u32, String>>,
+    types: HashMap<u32, String>,
+    tables: HashMap<u32, String>,
+}
+
+impl Names {
+    fn function(&self, index: u32) -> String {
+        reference(self.functions.get(&index), index)
+    }
+    fn local(&self, function: u32, index: u32) -> String {
+        reference(self.locals.get(&function).and_then(|locals| locals.get(&index)), index)
+    }
+    fn type_name(&self, index: u32) -> String {
+        reference(self.types.get(&index), index)
+    }
+    fn table(&self, index: u32) -> String {
+        reference(self.tables.get(&index), index)
+    }
+}
+
+fn reference(name: Option<&String>, index: u32) -> String {
+    match name {
+        Some(name) => format!("${}", name),
+        None => index.to_string(),
+    }
+}
+
+fn read_name_map(reader: &mut Reader) -> Result<HashMap<u32, String>, String> {
+    let mut names = HashMap::new();
+    for _ in 0..reader.read_u32()? {
+        let index = reader.read_u32()?;
+        names.insert(index, reader.read_name()?);
+    }
+    Result::Ok(names)
+}
+
+fn read_names(content: &[u8]) -> Result<Names, String> {
+    let mut names = Names::default();
+    let mut reader = Reader::new(content);
+    while !reader.is_empty() {
+        let id = reader.read_u8()?;
+        let mut subsection = Reader::new(reader.read_bytes()?);
+        match id {
+            NAME_FUNCTIONS => names.functions = read_name_map(&mut subsection)?,
+            NAME_LOCALS => {
+                for _ in 0..subsection.read_u32()? {
+                    let function = subsection.read_u32()?;
+                    names.locals.insert(function, read_name_map(&mut subsection)?);
+                }
+            }
+            NAME_TYPES => names.types = read_name_map(&mut subsection)?,
+            NAME_TABLES => names.tables = read_name_map(&mut subsection)?,
+            _ => {}
+        }
+    }
+    Result::Ok(names)
+}
+
+fn read_limits(reader: &mut Reader) -> Result<String, String> {
+    match reader.read_u8()? {
+        0x00 => Result::Ok(reader.read_u32()?.to_string()),
+        0x01 => {
+            let min = reader.read_u32()?;
+            Result::Ok(format!("{} {}", min, reader.read_u32()?))
+        }
+        flags => Result::Err(format!("unsupported limits 0x{:02x}", flags)),
+    }
+}
+
+fn read_value_types(reader: &mut Reader) -> Result<Vec<ValType>, String> {
+    let mut value_types = vec![];
+    for _ in 0..reader.read_u32()? {
+        let code = reader.read_u8()?;
+        value_types.push(ValType::from_code(code).ok_or_else(|| format!("unsupported type 0x{:02x}", code))?);
+    }
+    Result::Ok(value_types)
+}
+
+// Offset of an element or data segment: i32.const n end
+fn read_offset(reader: &mut Reader) -> Result<i32, String> {
+    let offset = match Instruction::decode(reader)? {
+        Instruction::I32Const(offset) => offset,
+        _ => return Result::Err("unsupported segment offset".to_string()),
+    };
+    match Instruction::decode(reader)? {
+        Instruction::End => Result::Ok(offset),
+        _ => Result::Err("unsupported segment offset".to_string()),
+    }
+}
+
+fn value_types_text(keyword: &str, value_types: &[ValType]) -> String {
+    if value_types.is_empty() {
+        return String::new();
+    }
+    let names: Vec<_> = value_types.iter().map(|value_type| value_type.name()).collect();
+    format!(" ({} {})", keyword, names.join(" "))
+}
+
+fn escape(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'"' | b'\\' => text.push_str(&format!("\\{}", *byte as char)),
+            0x20..=0x7e => text.push(*byte as char),
+            _ => text.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    text
+}
+
+fn instruction_text(instruction: &Instruction, function: u32, names: &Names) -> String {
+    use Instruction::*;
+    match instruction {
+        Block => "block".to_string(),
+        Loop => "loop".to_string(),
+        If => "if".to_string(),
+        Else => "else".to_string(),
+        End => "end".to_string(),
+        Br(depth) => format!("br {}", depth),
+        BrIf(depth) => format!("br_if {}", depth),
+        Call(index) => format!("call {}", names.function(*index)),
+        CallIndirect { type_index, table_index } => {
+            format!("call_indirect {} (type {})", names.table(*table_index), names.type_name(*type_index))
+        }
+        LocalGet(local) => format!("local.get {}", names.local(function, *local)),
+        LocalSet(local) => format!("local.set {}", names.local(function, *local)),
+        LocalTee(local) => format!("local.tee {}", names.local(function, *local)),
+        I32Const(value) => format!("i32.const {}", value),
+        I64Const(value) => format!("i64.const {}", value),
+        Memory(operator, memarg) => {
+            let mut text = operator.name().to_string();
+            if memarg.offset != 0 {
+                text.push_str(&format!(" offset={}", memarg.offset));
+            }
+            if memarg.align != operator.natural_alignment() {
+                text.push_str(&format!(" align={}", 1u32 << memarg.align));
+            }
+            text
+        }
+        MemorySize => "memory.size".to_string(),
+        MemoryGrow => "memory.grow".to_string(),
+        Operator(operator) => operator.name().to_string(),
+    }
+}
+
+fn function_text(
+    text: &mut Vec<String>,
+    index: u32,
+    type_index: u32,
+    function_type: &FuncType,
+    body: &[u8],
+    names: &Names,
+) -> Result<(), String> {
+    let mut reader = Reader::new(body);
+    text.push(format!("(func {} (type {})", names.function(index), names.type_name(type_index)));
+    for (local, value_type) in function_type.params.iter().enumerate() {
+        text.push(format!(" (param {} {})", names.local(index, local as u32), value_type.name()));
+    }
+    if !function_type.results.is_empty() {
+        text.push(value_types_text("result", &function_type.results).trim_start().to_string());
+    }
+    let mut local = function_type.params.len() as u32;
+    for _ in 0..reader.read_u32()? {
+        let count = reader.read_u32()?;
+        let code = reader.read_u8()?;
+        let value_type = ValType::from_code(code).ok_or_else(|| format!("unsupported type 0x{:02x}", code))?;
+        for _ in 0..count {
+            text.push(format!(" (local {} {})", names.local(index, local), value_type.name()));
+            local += 1;
+        }
+    }
+    let mut depth = 1;
+    while !reader.is_empty() {
+        let instruction = Instruction::decode(&mut reader)?;
+        match instruction {
+            Instruction::End | Instruction::Else => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            // the end of the function body
+            break;
+        }
+        text.push(format!("{}{}", " ".repeat(depth), instruction_text(&instruction, index, names)));
+        match instruction {
+            Instruction::Block | Instruction::Loop | Instruction::If | Instruction::Else => depth += 1,
+            _ => {}
+        }
+    }
+    text.push(")".to_string());
+    Result::Ok(())
+}
+
+pub fn disassemble(module: &[u8]) -> Result<String, String> {
+    if module.len() < 8 || &module[0..4] != MAGIC || &module[4..8] != VERSION {
+        return Result::Err("not a WebAssembly module".to_string());
+    }
+    let mut sections = vec![];
+    let mut reader = Reader::new(&module[8..]);
+    let mut names = Names::default();
+    while !reader.is_empty() {
+        let id = reader.read_u8()?;
+        let content = reader.read_bytes()?;
+        if id == SECTION_CUSTOM {
+            let mut custom = Reader::new(content);
+            if custom.read_name()? == "name" {
+                names = read_names(custom.remaining())?;
+            }
+        } else {
+            sections.push((id, content));
+        }
+    }
+
+    let mut text = vec!["(module".to_string()];
+    let mut types = vec![];
+    let mut imported = 0;
+    let mut function_types = vec![];
+    for (id, content) in sections {
+        let mut reader = Reader::new(content);
+        match id {
+            SECTION_TYPE => {
+                for index in 0..reader.read_u32()? {
+                    if reader.read_u8()? != FUNCTION_TYPE {
+                        return Result::Err("unsupported type".to_string());
+                    }
+                    let function_type = FuncType {
+                        params: read_value_types(&mut reader)?,
+                        results: read_value_types(&mut reader)?,
+                    };
+                    text.push(format!(
+                        "(type {} (func{}{}))",
+                        names.type_name(index),
+                        value_types_text("param", &function_type.params),
+                        value_types_text("result", &function_type.results)
+                    ));
+                    types.push(function_type);
+                }
+            }
+            SECTION_IMPORT => {
+                for _ in 0..reader.read_u32()? {
+                    let module = reader.read_name()?;
+                    let field = reader.read_name()?;
+                    if reader.read_u8()? != EXTERNAL_FUNCTION {
+                        return Result::Err("unsupported import".to_string());
+                    }
+                    let type_index = reader.read_u32()?;
+                    text.push(format!(
+                        "(import \"{}\" \"{}\" (func {} (type {})))",
+                        escape(module.as_bytes()),
+                        escape(field.as_bytes()),
+                        names.function(imported),
+                        names.type_name(type_index)
+                    ));
+                    imported += 1;
+                }
+            }
+            SECTION_FUNCTION => {
+                for _ in 0..reader.read_u32()? {
+                    function_types.push(reader.read_u32()?);
+                }
+            }
+            SECTION_TABLE => {
+                for index in 0..reader.read_u32()? {
+                    if reader.read_u8()? != FUNCREF {
+                        return Result::Err("unsupported table".to_string());
+                    }
+                    text.push(format!("(table {} {} funcref)", names.table(index), read_limits(&mut reader)?));
+                }
+            }
+            SECTION_MEMORY => {
+                for _ in 0..reader.read_u32()? {
+                    text.push(format!("(memory {})", read_limits(&mut reader)?));
+                }
+            }
+            SECTION_EXPORT => {
+                for _ in 0..reader.read_u32()? {
+                    let name = escape(reader.read_name()?.as_bytes());
+                    let kind = reader.read_u8()?;
+                    let index = reader.read_u32()?;
+                    let external = match kind {
+                        EXTERNAL_FUNCTION => format!("func {}", names.function(index)),
+                        EXTERNAL_TABLE => format!("table {}", names.table(index)),
+                        EXTERNAL_MEMORY => format!("memory {}", index),
+                        _ => return Result::Err("unsupported export".to_string()),
+                    };
+                    text.push(format!("(export \"{}\" ({}))", name, external));
+                }
+            }
+            SECTION_ELEMENT => {
+                for _ in 0..reader.read_u32()? {
+                    let table = match reader.read_u32()? {
+                        0 => 0,
+                        2 => reader.read_u32()?,
+                        flags => return Result::Err(format!("unsupported element segment {}", flags)),
+                    };
+                    let offset = read_offset(&mut reader)?;
+                    if table != 0 && reader.read_u8()? != 0x00 {
+                        return Result::Err("unsupported element kind".to_string());
+                    }
+                    if table == 0 {
+                        text.push(format!("(elem (i32.const {})", offset));
+                    } else {
+                        text.push(format!("(elem (table {}) (i32.const {}) func", names.table(table), offset));
+                    }
+                    for _ in 0..reader.read_u32()? {
+                        text.push(format!(" {}", names.function(reader.read_u32()?)));
+                    }
+                    text.push(")".to_string());
+                }
+            }
+            SECTION_CODE => {
+                let count = reader.read_u32()?;
+                if count as usize != function_types.len() {
+                    return Result::Err("the function and code sections do not match".to_string());
+                }
+                for (position, type_index) in function_types.iter().enumerate() {
+                    let function_type = types.get(*type_index as usize).ok_or_else(|| "unknown type".to_string())?;
+                    let body = reader.read_bytes()?;
+                    let index = imported + position as u32;
+                    function_text(&mut text, index, *type_index, function_type, body, &names)?;
+                }
+            }
+            SECTION_DATA => {
+                for _ in 0..reader.read_u32()? {
+                    if reader.read_u32()? != 0 {
+                        return Result::Err("unsupported data segment".to_string());
+                    }
+                    let offset = read_offset(&mut reader)?;
+                    text.push(format!("(data (i32.const {}) \"{}\")", offset, escape(reader.read_bytes()?)));
+                }
+            }
+            _ => return Result::Err(format!("unsupported section {}", id)),
+        }
+    }
+    text.push(")".to_string());
+    Result::Ok(format!("{}\n", text.join("\n")))
+}
+
@@ -0,0 +1,214 @@
+use super::wasm_binary::*;
+
+// Reader of the field arithmetic shipped with the compiler in fr-types.wat,
+// fr-code.wat and fr-data.wat. These files are written in the text format
+// with a field, a declaration or an instruction per line, and are read into
+// the same fields and instructions as the code of the generators
+
+pub fn read_library(text: &str) -> Result<Vec<WasmInstruction>, String> {
+    let mut library = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        // the data segments are the only lines with strings
+        if line.starts_with("(data ") {
+            library.push(read_data(line)?);
+            continue;
+        }
+        let line = match line.find(";;") {
+            Some(comment) => line[..comment].trim_end(),
+            None => line,
+        };
+        if line.is_empty() {
+            continue;
+        }
+        let words: Vec<&str> = line.split(|c: char| c.is_whitespace() || c == '(' || c == ')').filter(|word| !word.is_empty()).collect();
+        let invalid = || format!("invalid line of the field library: {}", line);
+        let instruction = match words.as_slice() {
+            [] if line.chars().all(|c| c == ')') => WasmInstruction::EndFunction,
+            ["type", name, "func", signature @ ..] => WasmInstruction::Type(name.to_string(), read_signature(signature).ok_or_else(invalid)?),
+            ["func", name, "type", type_name] => WasmInstruction::Func { name: name.to_string(), type_name: type_name.to_string() },
+            ["param", name, value_type] => WasmInstruction::Param(name.to_string(), ValType::from_name(value_type).ok_or_else(invalid)?),
+            ["local", name, value_type] => WasmInstruction::Local(name.to_string(), ValType::from_name(value_type).ok_or_else(invalid)?),
+            // the results are given by the type of the function
+            ["result", ..] => continue,
+            [name, immediates @ ..] => read_instruction(canonical_name(name), immediates).ok_or_else(invalid)?,
+            [] => return Result::Err(invalid()),
+        };
+        library.push(instruction);
+    }
+    Result::Ok(library)
+}
+
+// Parameters and results of a type: param i32 i32 result i32
+fn read_signature(words: &[&str]) -> Option<FuncType> {
+    let mut function_type = FuncType::default();
+    let mut is_param = true;
+    for word in words {
+        match *word {
+            "param" => is_param = true,
+            "result" => is_param = false,
+            _ if is_param => function_type.params.push(ValType::from_name(word)?),
+            _ => function_type.results.push(ValType::from_name(word)?),
+        }
+    }
+    Some(function_type)
+}
+
+// Data segment with its offset and a string: (data (i32.const 88) "\fe\00")
+fn read_data(line: &str) -> Result<WasmInstruction, String> {
+    let invalid = || format!("invalid data segment of the field library: {}", line);
+    let offset = line.strip_prefix("(data (i32.const ").and_then(|rest| rest.split(')').next()).ok_or_else(invalid)?;
+    let offset = parse_i64(offset).ok_or_else(invalid)? as i32;
+    let start = line.find('"').ok_or_else(invalid)?;
+    let end = line.rfind('"').filter(|end| *end > start).ok_or_else(invalid)?;
+    Result::Ok(WasmInstruction::Data(offset, unescape(&line[start + 1..end])))
+}
+
+// Bytes of a string of the text format. The strings of the circom sources
+// are stored with the same escapes, and a backslash that does not start a
+// valid escape is kept as it is
+pub fn unescape(text: &str) -> Vec<u8> {
+    let text = text.as_bytes();
+    let mut bytes = vec![];
+    let mut position = 0;
+    while position < text.len() {
+        let escaped = if text[position] == b'\\' { text.get(position + 1).copied() } else { None };
+        let hex = text.get(position + 1..position + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, hex) {
+            (Some(b'n'), _) => bytes.push(b'\n'),
+            (Some(b't'), _) => bytes.push(b'\t'),
+            (Some(b'r'), _) => bytes.push(b'\r'),
+            (Some(b'\\' | b'\'' | b'"'), _) => bytes.push(text[position + 1]),
+            (Some(_), Some(byte)) => {
+                bytes.push(byte);
+                position += 1;
+            }
+            _ => {
+                bytes.push(text[position]);
+                position += 1;
+                continue;
+            }
+        }
+        position += 2;
+    }
+    bytes
+}
+
+// Older names of the instructions used by the library
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "get_local" => "local.get",
+        "set_local" => "local.set",
+        "tee_local" => "local.tee",
+        "i32.wrap/i64" => "i32.wrap_i64",
+        "i64.extend_u/i32" => "i64.extend_i32_u",
+        "i64.extend_s/i32" => "i64.extend_i32_s",
+        _ => name,
+    }
+}
+
+fn read_instruction(name: &str, immediates: &[&str]) -> Option<WasmInstruction> {
+    let instruction = match (name, immediates) {
+        ("block", []) => Instruction::Block,
+        ("loop", []) => Instruction::Loop,
+        ("if", []) => Instruction::If,
+        ("else", []) => Instruction::Else,
+        ("end", []) => Instruction::End,
+        ("br", [depth]) => Instruction::Br(parse_u32(depth)?),
+        ("br_if", [depth]) => Instruction::BrIf(parse_u32(depth)?),
+        ("i32.const", [value]) => Instruction::I32Const(parse_i32(value)?),
+        ("i64.const", [value]) => Instruction::I64Const(parse_i64(value)?),
+        ("memory.size", []) => Instruction::MemorySize,
+        ("memory.grow", []) => Instruction::MemoryGrow,
+        ("local.get", [local]) => return Some(WasmInstruction::LocalGet(local.to_string())),
+        ("local.set", [local]) => return Some(WasmInstruction::LocalSet(local.to_string())),
+        ("local.tee", [local]) => return Some(WasmInstruction::LocalTee(local.to_string())),
+        ("call", [function]) => return Some(WasmInstruction::Call(function.to_string())),
+        _ => {
+            if let Some(operator) = MemoryOperator::from_name(name) {
+                let mut memarg = MemArg { align: operator.natural_alignment(), offset: 0 };
+                for immediate in immediates {
+                    if let Some(offset) = immediate.strip_prefix("offset=") {
+                        memarg.offset = parse_u32(offset)?;
+                    } else {
+                        let bytes = parse_u32(immediate.strip_prefix("align=")?)?;
+                        if !bytes.is_power_of_two() {
+                            return None;
+                        }
+                        memarg.align = bytes.trailing_zeros();
+                    }
+                }
+                Instruction::Memory(operator, memarg)
+            } else if immediates.is_empty() {
+                Instruction::Operator(Operator::from_name(name)?)
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(WasmInstruction::Code(instruction))
+}
+
+fn parse_u32(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse::<u32>().ok(),
+    }
+}
+
+fn parse_i64(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<u64>().ok()?,
+    };
+    if negative {
+        Some((magnitude as i64).wrapping_neg())
+    } else {
+        Some(magnitude as i64)
+    }
+}
+
+fn parse_i32(text: &str) -> Option<i32> {
+    let value = parse_i64(text)?;
+    if value < i32::MIN as i64 || value > u32::MAX as i64 {
+        return None;
+    }
+    Some(value as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_lines_are_read() {
+        let library = read_library(
+            "(type $_sig_i32ri32 (func  (param i32) (result i32)))\n\
+             ;; a comment\n\
+             (func $f (type $_sig_i32ri32)\n (param $x i32)\n(result i32)\n (local $y i64)\n\
+             get_local $x\n    i64.load offset=8 align=4 ;; the high word\ni32.wrap/i64\n)\n\
+             (data (i32.const 16) \"\\01\\ff)a\")",
+        )
+        .unwrap();
+        let function_type = FuncType { params: vec![ValType::I32], results: vec![ValType::I32] };
+        assert_eq!(
+            library,
+            vec![
+                WasmInstruction::Type("$_sig_i32ri32".to_string(), function_type),
+                WasmInstruction::Func { name: "$f".to_string(), type_name: "$_sig_i32ri32".to_string() },
+                WasmInstruction::Param("$x".to_string(), ValType::I32),
+                WasmInstruction::Local("$y".to_string(), ValType::I64),
+                WasmInstruction::LocalGet("$x".to_string()),
+                WasmInstruction::Code(Instruction::Memory(MemoryOperator::I64Load, MemArg { align: 2, offset: 8 })),
+                WasmInstruction::Code(Instruction::Operator(Operator::I32WrapI64)),
+                WasmInstruction::EndFunction,
+                WasmInstruction::Data(16, vec![0x01, 0xff, b')', b'a']),
+            ]
+        );
+        assert!(read_library("i32.const x").is_err());
+    }
+}
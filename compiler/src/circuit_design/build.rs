@@ -6,6 +6,7 @@ use crate::intermediate_representation::translate;
 use crate::intermediate_representation::translate::{CodeInfo, FieldTracker, TemplateDB};
 use code_producers::c_elements::*;
 use code_producers::wasm_elements::*;
+use program_structure::ast::Statement;
 use program_structure::file_definition::FileLibrary;
 use std::collections::HashMap;
 
@@ -20,6 +21,15 @@ fn matching_lengths_and_offsets(list: &InputOutputList) {
     }
 }
 
+// Path of the file that defines the code, absolute when the file exists
+fn source_file(files: &FileLibrary, code: &Statement) -> String {
+    let name = files.get_name(code.get_meta().get_file_id()).unwrap().trim_matches('"');
+    match std::fs::canonicalize(name) {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(_) => name.to_string(),
+    }
+}
+
 fn build_template_instances(
     circuit: &mut Circuit,
     c_info: &CircuitInfo,
@@ -67,6 +77,7 @@ fn build_template_instances(
             template_database: &c_info.template_database,
             is_parallel: template.is_parallel,
             string_table : string_table,
+            trace_flag: c_info.trace_flag,
         };
         let mut template_info = TemplateCodeInfo {
             name,
@@ -78,6 +89,7 @@ fn build_template_instances(
             number_of_outputs: template.number_of_outputs,
             number_of_intermediates: template.number_of_intermediates,
             has_parallel_sub_cmp: template.has_parallel_sub_cmp,
+            source_file: source_file(&c_info.file_library, &template.code),
             ..TemplateCodeInfo::default()
         };
         let code = template.code;
@@ -128,13 +140,16 @@ fn build_function_instances(
             component_to_parallel: HashMap::with_capacity(0),
             template_database: &c_info.template_database,
             is_parallel: false,
-            string_table : string_table
+            string_table : string_table,
+            trace_flag: c_info.trace_flag,
         };
         let mut function_info = FunctionCodeInfo {
+            id: circuit.functions.len(),
             name,
             params,
             returns,
             header: header.clone(),
+            source_file: source_file(&c_info.file_library, &instance.body),
             ..FunctionCodeInfo::default()
         };
         let code = instance.body;
@@ -151,7 +166,7 @@ fn build_function_instances(
 }
 
 // WASM producer builder
-fn initialize_wasm_producer(vcp: &VCP, database: &TemplateDB, wat_flag:bool) -> WASMProducer {
+fn initialize_wasm_producer(vcp: &VCP, database: &TemplateDB, wat_flag:bool, trace_flag: bool, source_map_flag: bool) -> WASMProducer {
     use program_structure::utils::constants::UsefulConstants;
     let initial_node = vcp.get_main_id();
     let prime = UsefulConstants::new(&vcp.prime).get_p().clone();
@@ -161,13 +176,15 @@ fn initialize_wasm_producer(vcp: &VCP, database: &TemplateDB, wat_flag:bool) ->
     producer.main_signal_offset = 1;
     producer.prime = prime.to_str_radix(10);
     producer.prime_str = vcp.prime.clone();
-    producer.fr_memory_size = match vcp.prime.as_str(){
-        "goldilocks" => 412,
-        "bn128" => 1948,
-        "bls12381" => 1948,
+    producer.field_layout = FieldLayout::for_prime(&vcp.prime);
+    // the 64 bit layout has no tables, 4 bytes keep the elements aligned to 8
+    producer.fr_memory_size = match (producer.field_layout, vcp.prime.as_str()) {
+        (FieldLayout::Goldilocks64, _) => 4,
+        (FieldLayout::MultiLimb, "bn128") => 1948,
+        (FieldLayout::MultiLimb, "bls12381") => 1948,
         _ => unreachable!()
     };
-    //producer.fr_memory_size = 412 if goldilocks and 1948 for bn128 and bls12381
+    //producer.fr_memory_size = 1948 for bn128 and bls12381
     // for each created component we store three u32, for each son we store a u32 in its father
     producer.size_of_component_tree = stats.all_created_components * 3 + stats.all_needed_subcomponents_indexes;
     producer.total_number_of_signals = stats.all_signals + 1;
@@ -185,14 +202,17 @@ fn initialize_wasm_producer(vcp: &VCP, database: &TemplateDB, wat_flag:bool) ->
     producer.number_of_main_inputs = vcp.templates[initial_node].number_of_inputs;
     producer.number_of_main_outputs = vcp.templates[initial_node].number_of_outputs;
     producer.main_input_list = main_input_list(&vcp.templates[initial_node]);
+    producer.main_input_dimensions = main_input_dimensions(&vcp.templates[initial_node]);
     producer.io_map = build_io_map(vcp, database);
     producer.template_instance_list = build_template_list(vcp);
     producer.field_tracking.clear();
     producer.wat_flag = wat_flag;
+    producer.trace_flag = trace_flag;
+    producer.source_map_flag = source_map_flag;
     producer
 }
 
-fn initialize_c_producer(vcp: &VCP, database: &TemplateDB) -> CProducer {
+fn initialize_c_producer(vcp: &VCP, database: &TemplateDB, trace_flag: bool, portable_flag: bool, lib_flag: bool, embed_dat_flag: bool, profile_flag: bool, source_map_flag: bool) -> CProducer {
     use program_structure::utils::constants::UsefulConstants;
     let initial_node = vcp.get_main_id();
     let prime = UsefulConstants::new(&vcp.prime).get_p().clone();
@@ -202,6 +222,7 @@ fn initialize_c_producer(vcp: &VCP, database: &TemplateDB) -> CProducer {
     producer.main_signal_offset = 1;
     producer.prime = prime.to_str_radix(10);
     producer.prime_str = vcp.prime.clone();
+    producer.field_layout = FieldLayout::for_prime(&vcp.prime);
     producer.size_of_component_tree = stats.all_created_components * 3 + stats.all_needed_subcomponents_indexes;
     producer.total_number_of_signals = stats.all_signals + 1;
     producer.size_32_bit = prime.bits() / 32 + if prime.bits() % 32 != 0 { 1 } else { 0 };
@@ -217,11 +238,17 @@ fn initialize_c_producer(vcp: &VCP, database: &TemplateDB) -> CProducer {
     producer.signals_in_witness = producer.witness_to_signal_list.len();
     producer.number_of_main_inputs = vcp.templates[initial_node].number_of_inputs;
     producer.number_of_main_outputs = vcp.templates[initial_node].number_of_outputs;
-    producer.main_input_list = main_input_list(&vcp.templates[initial_node]);   
+    producer.main_input_list = main_input_list(&vcp.templates[initial_node]);
+    producer.main_input_dimensions = main_input_dimensions(&vcp.templates[initial_node]);
     producer.io_map = build_io_map(vcp, database);
     producer.template_instance_list = build_template_list(vcp);
     producer.field_tracking.clear();
-    
+    producer.trace_flag = trace_flag;
+    producer.portable_flag = portable_flag;
+    producer.lib_flag = lib_flag;
+    producer.embed_dat_flag = embed_dat_flag;
+    producer.profile_flag = profile_flag;
+    producer.source_map_flag = source_map_flag;
     producer
 }
 
@@ -236,6 +263,11 @@ fn main_input_list(main: &TemplateInstance) -> InputList {
     input_list
 }
 
+fn main_input_dimensions(main: &TemplateInstance) -> Vec<Vec<usize>> {
+    use program_structure::ast::SignalType::*;
+    main.signals.iter().filter(|s| s.xtype == Input).map(|s| s.lengths.clone()).collect()
+}
+
 fn build_template_list(vcp: &VCP) -> TemplateList {
     let mut tmp_list = MessageList::new();
     for instance in &vcp.templates {
@@ -297,6 +329,7 @@ struct CircuitInfo {
     file_library: FileLibrary,
     functions: HashMap<String, Vec<usize>>,
     template_database: TemplateDB,
+    trace_flag: bool,
 }
 
 pub fn build_circuit(vcp: VCP, flag: CompilationFlags) -> Circuit {
@@ -306,14 +339,15 @@ pub fn build_circuit(vcp: VCP, flag: CompilationFlags) -> Circuit {
     }
     let template_database = TemplateDB::build(&vcp.templates);
     let mut circuit = Circuit::default();
-    circuit.wasm_producer = initialize_wasm_producer(&vcp, &template_database, flag.wat_flag);
-    circuit.c_producer = initialize_c_producer(&vcp, &template_database);
+    circuit.wasm_producer = initialize_wasm_producer(&vcp, &template_database, flag.wat_flag, flag.trace_flag, flag.source_map_flag);
+    circuit.c_producer = initialize_c_producer(&vcp, &template_database, flag.trace_flag, flag.portable_flag, flag.lib_flag, flag.embed_dat_flag, flag.c_profile_flag, flag.source_map_flag);
 
     let field_tracker = FieldTracker::new();
     let circuit_info = CircuitInfo {
         template_database,
         file_library: vcp.file_library,
         functions: vcp.quick_knowledge,
+        trace_flag: flag.trace_flag,
     };
 
     let (field_tracker, string_table) =
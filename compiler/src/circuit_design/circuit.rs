@@ -5,11 +5,18 @@ use crate::hir::very_concrete_program::VCP;
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
 use code_producers::wasm_elements::*;
+use code_producers::wasm_elements::wasm_binary::WasmModule;
 use std::io::Write;
 
 pub struct CompilationFlags {
     pub main_inputs_log: bool,
     pub wat_flag:bool,
+    pub trace_flag: bool,
+    pub portable_flag: bool,
+    pub lib_flag: bool,
+    pub embed_dat_flag: bool,
+    pub c_profile_flag: bool,
+    pub source_map_flag: bool,
 }
 
 pub struct Circuit {
@@ -30,266 +37,58 @@ impl Default for Circuit {
     }
 }
 
-impl WriteWasm for Circuit {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+impl Circuit {
+    // Adds the code of the whole circuit to the module
+    fn write_wasm(&self, module: &mut WasmModule, producer: &WASMProducer) -> Result<(), String> {
         use code_producers::wasm_elements::wasm_code_generator::*;
-        let mut code = vec![];
-        code.push("(module".to_string());
-        let mut code_aux = generate_imports_list();
-        code.append(&mut code_aux);
-        code_aux = generate_memory_def_list(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = fr_types(&producer.prime_str);
-        code.append(&mut code_aux);
-
-        code_aux = generate_types_list();
-        code.append(&mut code_aux);
-        code_aux = generate_exports_list();
-        code.append(&mut code_aux);
-
-        code_aux = fr_code(&producer.prime_str);
-        code.append(&mut code_aux);
-
-        code_aux = desp_io_subcomponent_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_version_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_shared_rw_memory_start_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = read_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = write_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = reserve_stack_fr_function_generator();
-        code.append(&mut code_aux);
-
-        code_aux = init_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = set_input_signal_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_input_signal_size_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_raw_prime_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_field_num_len32_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_input_size_generator(&producer);
-        code.append(&mut code_aux);	
-
-        code_aux = get_witness_size_generator(&producer);
-        code.append(&mut code_aux);
 
-        code_aux = get_witness_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = copy_32_in_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = copy_fr_in_shared_rw_memory_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = get_message_char_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = build_buffer_message_generator(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = build_log_message_generator(&producer);
-        code.append(&mut code_aux);
-
-        // Actual code from the program
-
-        for f in &self.functions {
-            code.append(&mut f.produce_wasm(producer));
+        module.add_code(&generate_imports_list())?;
+        if producer.needs_trace() {
+            module.add_code(&generate_trace_imports_list())?;
         }
-
-        for t in &self.templates {
-            code.append(&mut t.produce_wasm(producer));
+        module.add_code(&generate_memory_def_list(&producer))?;
+        module.add_code(&fr_types(&producer))?;
+        module.add_code(&generate_types_list())?;
+        module.add_code(&generate_exports_list())?;
+        module.add_code(&fr_code(&producer))?;
+        module.add_code(&desp_io_subcomponent_generator(&producer))?;
+        module.add_code(&get_version_generator(&producer))?;
+        module.add_code(&get_shared_rw_memory_start_generator(&producer))?;
+        module.add_code(&read_shared_rw_memory_generator(&producer))?;
+        module.add_code(&write_shared_rw_memory_generator(&producer))?;
+        module.add_code(&reserve_stack_fr_function_generator())?;
+        module.add_code(&init_generator(&producer))?;
+        module.add_code(&set_input_signal_generator(&producer))?;
+        module.add_code(&get_input_signal_size_generator(&producer))?;
+        module.add_code(&get_raw_prime_generator(&producer))?;
+        module.add_code(&get_field_num_len32_generator(&producer))?;
+        module.add_code(&get_input_size_generator(&producer))?;
+        module.add_code(&get_witness_size_generator(&producer))?;
+        module.add_code(&get_witness_generator(&producer))?;
+        module.add_code(&copy_32_in_shared_rw_memory_generator(&producer))?;
+        module.add_code(&copy_fr_in_shared_rw_memory_generator(&producer))?;
+        module.add_code(&get_message_char_generator(&producer))?;
+        module.add_code(&build_buffer_message_generator(&producer))?;
+        module.add_code(&build_log_message_generator(&producer))?;
+        if producer.needs_trace() {
+            module.add_code(&trace_create_generator(&producer))?;
+            module.add_code(&trace_signals_generator(&producer))?;
+            module.add_code(&trace_args_generator(&producer))?;
         }
 
-        code_aux = generate_table_of_template_runs(&producer);
-        code.append(&mut code_aux);
-
-        code_aux = fr_data(&producer.prime_str);
-        code.append(&mut code_aux);
-
-        code_aux = generate_data_list(&producer);
-        code.append(&mut code_aux);
-
-        code.push(")".to_string());
-        code
-    }
-    fn write_wasm<T: Write>(&self, writer: &mut T, producer: &WASMProducer) -> Result<(), ()> {
-        use code_producers::wasm_elements::wasm_code_generator::*;
-
-        writer.write_all("(module".as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        let mut code_aux = generate_imports_list();
-        let mut code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = generate_memory_def_list(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = fr_types(&producer.prime_str);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = generate_types_list();
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = generate_exports_list();
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = fr_code(&producer.prime_str);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = desp_io_subcomponent_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_version_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_shared_rw_memory_start_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = read_shared_rw_memory_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = write_shared_rw_memory_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = reserve_stack_fr_function_generator();
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = init_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = set_input_signal_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_input_signal_size_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_raw_prime_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_field_num_len32_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_input_size_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-	
-        code_aux = get_witness_size_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_witness_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = copy_32_in_shared_rw_memory_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = copy_fr_in_shared_rw_memory_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = get_message_char_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = build_buffer_message_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = build_log_message_generator(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
         // Actual code from the program
 
         for f in &self.functions {
-            f.write_wasm(writer, producer)?;
-            writer.flush().map_err(|_| {})?;
+            f.write_wasm(module, producer)?;
         }
 
         for t in &self.templates {
-            t.write_wasm(writer, producer)?;
-            writer.flush().map_err(|_| {})?;
+            t.write_wasm(module, producer)?;
         }
 
-        code_aux = generate_table_of_template_runs(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = fr_data(&producer.prime_str);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        code_aux = generate_data_list(&producer);
-        code = merge_code(code_aux);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})?;
-
-        writer.write_all(")".as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})
+        module.add_code(&generate_table_of_template_runs(&producer))?;
+        module.add_code(&fr_data(&producer))?;
+        module.add_code(&generate_data_list(&producer))
     }
 }
 
@@ -350,6 +149,10 @@ impl WriteC for Circuit {
             "uint get_size_of_io_map() {{return {};}}\n",
             producer.get_io_map().len()
         ));
+        code.append(&mut generate_circuit_data_def(producer));
+        code.append(&mut generate_input_schema_def(producer));
+        let function_names: Vec<_> = self.functions.iter().map(|f| f.header.clone()).collect();
+        code.append(&mut generate_profile_def(producer, &function_names));
         //code.append(&mut generate_message_list_def(producer, producer.get_message_list()));
 
         // Actual code of the circuit
@@ -420,25 +223,63 @@ impl Circuit {
     pub fn produce_ir_string_for_function(&self, id: ID) -> String {
         self.functions[id].to_string()
     }
-    pub fn produce_c<W: Write>(&self, c_folder: &str, run_name: &str, c_circuit: &mut W, c_dat: &mut W) -> Result<(), ()> {
+    pub fn produce_c<W: Write>(&self, c_folder: &str, run_name: &str, c_circuit: &mut W, c_dat: Option<&mut W>) -> Result<(), ()> {
 	use std::path::Path;
 	let c_folder_path = Path::new(c_folder.clone()).to_path_buf();
         c_code_generator::generate_main_cpp_file(&c_folder_path).map_err(|_err| {})?;
         c_code_generator::generate_circom_hpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_fr_hpp_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
         c_code_generator::generate_calcwit_hpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_fr_cpp_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
         c_code_generator::generate_calcwit_cpp_file(&c_folder_path).map_err(|_err| {})?;
-        c_code_generator::generate_fr_asm_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
+        if self.c_producer.get_field_layout() == FieldLayout::Goldilocks64 {
+            c_code_generator::generate_goldilocks64_fr_hpp_file(&c_folder_path).map_err(|_err| {})?;
+            c_code_generator::generate_goldilocks64_fr_cpp_file(&c_folder_path).map_err(|_err| {})?;
+        } else if self.c_producer.is_portable() {
+            c_code_generator::generate_portable_fr_hpp_file(&c_folder_path, &self.c_producer.prime).map_err(|_err| {})?;
+            c_code_generator::generate_portable_fr_cpp_file(&c_folder_path, &self.c_producer.prime).map_err(|_err| {})?;
+        } else {
+            c_code_generator::generate_fr_hpp_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
+            c_code_generator::generate_fr_cpp_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
+            c_code_generator::generate_fr_asm_file(&c_folder_path, &self.c_producer.prime_str).map_err(|_err| {})?;
+        }
+        if self.c_producer.has_library() {
+            c_code_generator::generate_circom_witness_h_file(&c_folder_path).map_err(|_err| {})?;
+            c_code_generator::generate_circom_witness_cpp_file(&c_folder_path).map_err(|_err| {})?;
+        }
         c_code_generator::generate_make_file(&c_folder_path,run_name,&self.c_producer).map_err(|_err| {})?;
-        c_code_generator::generate_dat_file(c_dat, &self.c_producer).map_err(|_err| {})?;
-        self.write_c(c_circuit, &self.c_producer)
+        c_code_generator::generate_input_schema_file(&c_folder_path, run_name, &self.c_producer).map_err(|_err| {})?;
+        if let Some(c_dat) = c_dat {
+            c_code_generator::generate_dat_file(c_dat, &self.c_producer).map_err(|_err| {})?;
+        }
+        if !self.c_producer.needs_source_map() {
+            return self.write_c(c_circuit, &self.c_producer);
+        }
+        let (code, _) = WriteC::produce_c(self, &self.c_producer);
+        let code = c_code_generator::merge_code(code);
+        let code = c_code_generator::locate_generated_code(code, &format!("{}.cpp", run_name));
+        c_circuit.write_all(code.as_bytes()).map_err(|_err| {})?;
+        c_circuit.flush().map_err(|_err| {})
     }
-    pub fn produce_wasm<W: Write>(&self, js_folder: &str, _wasm_name: &str, writer: &mut W) -> Result<(), ()> {
+    // Writes the javascript files of the witness calculator and returns the
+    // binary module, with its source map when it is requested
+    pub fn produce_wasm(&self, js_folder: &str, wasm_name: &str) -> Result<(Vec<u8>, Option<String>), String> {
 	use std::path::Path;
 	let js_folder_path = Path::new(js_folder.clone()).to_path_buf();
-        wasm_code_generator::generate_generate_witness_js_file(&js_folder_path).map_err(|_err| {})?;
-        wasm_code_generator::generate_witness_calculator_js_file(&js_folder_path).map_err(|_err| {})?;
-        self.write_wasm(writer, &self.wasm_producer)
+        wasm_code_generator::generate_generate_witness_js_file(&js_folder_path)
+            .map_err(|err| format!("could not write the witness generator: {}", err))?;
+        wasm_code_generator::generate_witness_calculator_js_file(&js_folder_path)
+            .map_err(|err| format!("could not write the witness calculator: {}", err))?;
+        wasm_code_generator::generate_witness_calculator_mjs_file(&js_folder_path)
+            .map_err(|err| format!("could not write the witness calculator: {}", err))?;
+        wasm_code_generator::generate_witness_calculator_dts_files(&js_folder_path, &self.wasm_producer)
+            .map_err(|err| format!("could not write the types of the witness calculator: {}", err))?;
+        wasm_code_generator::generate_input_schema_file(&js_folder_path, wasm_name, &self.wasm_producer)
+            .map_err(|err| format!("could not write the schema of the inputs: {}", err))?;
+        let mut module = WasmModule::new();
+        self.write_wasm(&mut module, &self.wasm_producer)?;
+        if self.wasm_producer.needs_source_map() {
+            let (module, source_map) = module.encode_with_source_map(&format!("{}.wasm.map", wasm_name))?;
+            return Result::Ok((module, Some(source_map)));
+        }
+        Result::Ok((module.encode()?, None))
     }
 }
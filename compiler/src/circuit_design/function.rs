@@ -1,6 +1,6 @@
 use super::types::*;
 use crate::hir::very_concrete_program::Param;
-use crate::intermediate_representation::InstructionList;
+use crate::intermediate_representation::{produce_c_body, produce_wasm_body, InstructionList};
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
 use code_producers::wasm_elements::*;
@@ -9,6 +9,7 @@ use code_producers::wasm_elements::*;
 pub type FunctionCode = Box<FunctionCodeInfo>;
 #[derive(Default)]
 pub struct FunctionCodeInfo {
+    pub id: ID,
     pub header: String,
     pub name: String,
     pub params: Vec<Param>,
@@ -16,6 +17,7 @@ pub struct FunctionCodeInfo {
     pub body: InstructionList,
     pub max_number_of_vars: usize,
     pub max_number_of_ops_in_expression: usize,
+    pub source_file: String,
 }
 
 impl ToString for FunctionCodeInfo {
@@ -29,38 +31,37 @@ impl ToString for FunctionCodeInfo {
 }
 
 impl WriteWasm for FunctionCodeInfo {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         //to be revised
         let mut instructions = vec![];
-        let funcdef = format!("(func ${} (type $_t_i32i32ri32)", self.header);
+        let funcdef = function_header(&format!("${}", self.header), "$_t_i32i32ri32");
         instructions.push(funcdef);
-        instructions.push(format!("(param {} i32)", producer.get_result_address_tag()));
-        instructions.push(format!("(param {} i32)", producer.get_result_size_tag()));
-	instructions.push("(result i32)".to_string()); //state 0 = OK; > 0 error
-        instructions.push(format!("(local {} i32)", producer.get_cstack_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_lvar_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_expaux_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_temp_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_store_aux_1_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_store_aux_2_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_copy_counter_tag()));
-        instructions.push(format!("(local {} i32)", producer.get_call_lvar_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_merror_tag()));
+        instructions.push(param(producer.get_result_address_tag()));
+        instructions.push(param(producer.get_result_size_tag()));
+        instructions.push(local(producer.get_cstack_tag()));
+        instructions.push(local(producer.get_lvar_tag()));
+        instructions.push(local(producer.get_expaux_tag()));
+        instructions.push(local(producer.get_temp_tag()));
+        instructions.push(local(producer.get_store_aux_1_tag()));
+        instructions.push(local(producer.get_store_aux_2_tag()));
+        instructions.push(local(producer.get_copy_counter_tag()));
+        instructions.push(local(producer.get_call_lvar_tag()));
+        instructions.push(local(producer.get_merror_tag()));
         let local_info_size_u32 = producer.get_local_info_size_u32();
         //set lvar (start of auxiliar memory for vars)
-        instructions.push(set_constant("0"));
+        instructions.push(set_constant(0));
         instructions.push(load32(None)); // current stack size
         let var_start = local_info_size_u32 * 4; // starts after local info
         if local_info_size_u32 != 0 {
-            instructions.push(set_constant(&var_start.to_string()));
+            instructions.push(set_constant(var_start as i32));
             instructions.push(add32());
         }
         instructions.push(set_local(producer.get_lvar_tag()));
         //set expaux (start of auxiliar memory for expressions)
         instructions.push(get_local(producer.get_lvar_tag()));
         let var_stack_size = self.max_number_of_vars * 4 * (producer.get_size_32_bits_in_memory()); // starts after vars
-        instructions.push(set_constant(&var_stack_size.to_string()));
+        instructions.push(set_constant(var_stack_size as i32));
         instructions.push(add32());
         instructions.push(set_local(producer.get_expaux_tag()));
         //reserve stack and sets cstack (starts of local var memory)
@@ -69,17 +70,12 @@ impl WriteWasm for FunctionCodeInfo {
             + self.max_number_of_ops_in_expression * 4 * (producer.get_size_32_bits_in_memory());
         let mut reserve_stack_fr_code = reserve_stack_fr(producer, needed_stack_bytes);
         instructions.append(&mut reserve_stack_fr_code); //gives value to $cstack
-        if producer.needs_comments() {
-            instructions.push(";; start of the function code".to_string());
-	}
         //generate code
 
-        for t in &self.body {
-            let mut instructions_body = t.produce_wasm(producer);
-            instructions.append(&mut instructions_body);
-        }
-        instructions.push(set_constant("0"));	
-        instructions.push(")".to_string());
+        let mut instructions_body = produce_wasm_body(&self.body, producer, Some(&self.source_file));
+        instructions.append(&mut instructions_body);
+        instructions.push(set_constant(0));	
+        instructions.push(end_function());
         instructions
     }
 }
@@ -100,12 +96,18 @@ impl WriteC for FunctionCodeInfo {
         body.push(format!("{};", declare_expaux(self.max_number_of_ops_in_expression)));
         body.push(format!("{};", declare_my_template_name_function(&self.name)));
         body.push(format!("u64 {} = {};", my_id(), component_father()));
-        for t in &self.body {
-            let (mut instructions_body, _) = t.produce_c(producer);
-            body.append(&mut instructions_body);
+        if producer.needs_profile() {
+            // the functions follow the template instances in the profile
+            body.push(build_profile_scope(producer.get_number_of_template_instances() + self.id));
         }
+        let mut instructions_body = produce_c_body(&self.body, producer, Some(&self.source_file));
+        body.append(&mut instructions_body);
         let callable = build_callable(header, params, body);
-        (vec![callable], "".to_string())
+        let mut code = vec![callable];
+        if producer.needs_source_map() {
+            code.push(GENERATED_LINE_DIRECTIVE.to_string());
+        }
+        (code, "".to_string())
     }
 }
 
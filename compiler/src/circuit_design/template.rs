@@ -1,4 +1,4 @@
-use crate::intermediate_representation::InstructionList;
+use crate::intermediate_representation::{produce_c_body, produce_wasm_body, InstructionList};
 use crate::translating_traits::*;
 use code_producers::c_elements::*;
 use code_producers::wasm_elements::*;
@@ -21,6 +21,7 @@ pub struct TemplateCodeInfo {
     pub expression_stack_depth: usize,
     pub signal_stack_depth: usize, // Not used now
     pub number_of_components: usize,
+    pub source_file: String,
 }
 impl ToString for TemplateCodeInfo {
     fn to_string(&self) -> String {
@@ -32,111 +33,112 @@ impl ToString for TemplateCodeInfo {
     }
 }
 impl WriteWasm for TemplateCodeInfo {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         // create function code
         let mut instructions = vec![];
-        let funcdef1 = format!("(func ${}_create (type $_t_i32ri32)", self.header); //return offset
+        let funcdef1 = function_header(&format!("${}_create", self.header), "$_t_i32ri32"); //return offset
         instructions.push(funcdef1);
-        instructions.push(format!(" (param {} i32)", producer.get_signal_offset_tag()));
-        instructions.push("(result i32)".to_string());
-        instructions.push(format!(" (local {} i32)", producer.get_offset_tag())); //here is a local var to be returned
-        instructions.push(format!(" (local {} i32)", producer.get_merror_tag()));
-        instructions.push(set_constant(&producer.get_component_free_pos().to_string()));
+        instructions.push(param(producer.get_signal_offset_tag()));
+        instructions.push(local(producer.get_offset_tag())); //here is a local var to be returned
+        instructions.push(local(producer.get_merror_tag()));
+        instructions.push(set_constant(producer.get_component_free_pos() as i32));
         instructions.push(load32(None));
         instructions.push(set_local(producer.get_offset_tag()));
         // set component id
         instructions.push(get_local(producer.get_offset_tag()));
-        instructions.push(set_constant(&self.id.to_string()));
+        instructions.push(set_constant(self.id as i32));
         instructions.push(store32(None));
         //set component signal start
         instructions.push(get_local(producer.get_offset_tag()));
         instructions.push(get_local(producer.get_signal_offset_tag()));
-        instructions
-            .push(store32(Some(&producer.get_signal_start_address_in_component().to_string())));
+        instructions.push(store32(Some(producer.get_signal_start_address_in_component())));
         //set component inputs_to_be_set
         instructions.push(get_local(producer.get_offset_tag()));
-        instructions.push(set_constant(&self.number_of_inputs.to_string()));
-        instructions
-            .push(store32(Some(&producer.get_input_counter_address_in_component().to_string())));
+        instructions.push(set_constant(self.number_of_inputs as i32));
+        instructions.push(store32(Some(producer.get_input_counter_address_in_component())));
         //reserve memory for component
-        instructions.push(set_constant(&producer.get_component_free_pos().to_string()));
+        instructions.push(set_constant(producer.get_component_free_pos() as i32));
         instructions.push(get_local(producer.get_offset_tag()));
         let nbytes_component =
             producer.get_sub_component_start_in_component() + self.number_of_components * 4;
-        instructions.push(set_constant(&nbytes_component.to_string()));
+        instructions.push(set_constant(nbytes_component as i32));
         instructions.push(add32());
         instructions.push(store32(None));
         //add the position of the component in the tree as result
         instructions.push(get_local(producer.get_offset_tag()));
-        instructions.push(")".to_string());
+        instructions.push(end_function());
 
         // run function code
 
-        let funcdef2 = format!("(func ${}_run (type $_t_i32ri32)", self.header);
+        let funcdef2 = function_header(&format!("${}_run", self.header), "$_t_i32ri32");
         instructions.push(funcdef2);
-        instructions.push(format!(" (param {} i32)", producer.get_offset_tag()));
-	instructions.push("(result i32)".to_string()); //state 0 = OK; > 0 error
-        instructions.push(format!(" (local {} i32)", producer.get_cstack_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_signal_start_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_sub_cmp_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_sub_cmp_load_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_io_info_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_lvar_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_expaux_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_temp_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_store_aux_1_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_store_aux_2_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_copy_counter_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_call_lvar_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_create_loop_sub_cmp_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_create_loop_offset_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_create_loop_counter_tag()));
-        instructions.push(format!(" (local {} i32)", producer.get_merror_tag()));
+        instructions.push(param(producer.get_offset_tag()));
+        instructions.push(local(producer.get_cstack_tag()));
+        instructions.push(local(producer.get_signal_start_tag()));
+        instructions.push(local(producer.get_sub_cmp_tag()));
+        instructions.push(local(producer.get_sub_cmp_load_tag()));
+        instructions.push(local(producer.get_io_info_tag()));
+        instructions.push(local(producer.get_lvar_tag()));
+        instructions.push(local(producer.get_expaux_tag()));
+        instructions.push(local(producer.get_temp_tag()));
+        instructions.push(local(producer.get_store_aux_1_tag()));
+        instructions.push(local(producer.get_store_aux_2_tag()));
+        instructions.push(local(producer.get_copy_counter_tag()));
+        instructions.push(local(producer.get_call_lvar_tag()));
+        instructions.push(local(producer.get_create_loop_sub_cmp_tag()));
+        instructions.push(local(producer.get_create_loop_offset_tag()));
+        instructions.push(local(producer.get_create_loop_counter_tag()));
+        instructions.push(local(producer.get_merror_tag()));
+        if producer.needs_trace() {
+            instructions.push(local(producer.get_trace_dest_tag()));
+        }
         let local_info_size_u32 = producer.get_local_info_size_u32(); // in the future we can add some info like pointer to run father or text father
                                                                       //set lvar (start of auxiliar memory for vars)
-        instructions.push(set_constant("0"));
+        instructions.push(set_constant(0));
         instructions.push(load32(None));
         let var_start = local_info_size_u32 * 4; // starts after local info
         if local_info_size_u32 != 0 {
-            instructions.push(set_constant(&var_start.to_string()));
+            instructions.push(set_constant(var_start as i32));
             instructions.push(add32());
         }
         instructions.push(set_local(producer.get_lvar_tag()));
         //set expaux (start of auxiliar memory for expressions)
         instructions.push(get_local(producer.get_lvar_tag()));
-        let var_stack_size = self.var_stack_depth * 4 * (producer.get_size_32_bit() + 2); // starts after vars
-        instructions.push(set_constant(&var_stack_size.to_string()));
+        let var_stack_size = self.var_stack_depth * 4 * (producer.get_size_32_bits_in_memory()); // starts after vars
+        instructions.push(set_constant(var_stack_size as i32));
         instructions.push(add32());
         instructions.push(set_local(producer.get_expaux_tag()));
         //reserve stack and sets cstack (starts of local var memory)
         let needed_stack_bytes = var_start
             + var_stack_size
-            + self.expression_stack_depth * 4 * (producer.get_size_32_bit() + 2);
+            + self.expression_stack_depth * 4 * (producer.get_size_32_bits_in_memory());
         let mut reserve_stack_fr_code = reserve_stack_fr(producer, needed_stack_bytes);
         instructions.append(&mut reserve_stack_fr_code);
-        if producer.needs_comments() {
-            instructions.push(";; start of the template code".to_string());
-	}
         //set signalstart local
         instructions.push(get_local(producer.get_offset_tag()));
         instructions
-            .push(set_constant(&producer.get_signal_start_address_in_component().to_string()));
+            .push(set_constant(producer.get_signal_start_address_in_component() as i32));
         instructions.push(add32());
         instructions.push(load32(None));
         instructions.push(set_local(producer.get_signal_start_tag()));
         //generate code
-
-        for t in &self.body {
-            let mut instructions_body = t.produce_wasm(producer);
-            instructions.append(&mut instructions_body);
+        if producer.needs_trace() {
+            instructions.push(get_local(producer.get_offset_tag()));
+            instructions.push(call("$traceRun"));
         }
 
+        let mut instructions_body = produce_wasm_body(&self.body, producer, Some(&self.source_file));
+        instructions.append(&mut instructions_body);
+
+        if producer.needs_trace() {
+            instructions.push(call("$traceEnd"));
+        }
         //free stack
         let mut free_stack_code = free_stack(producer);
         instructions.append(&mut free_stack_code);
-        instructions.push(set_constant("0"));	
-        instructions.push(")".to_string());
+        instructions.push(set_constant(0));	
+        instructions.push(end_function());
         instructions
     }
 }
@@ -249,10 +251,14 @@ impl WriteC for TemplateCodeInfo {
         run_body.push(format!("{};", declare_expaux(self.expression_stack_depth)));
         run_body.push(format!("{};", declare_lvar(self.var_stack_depth)));
         run_body.push(format!("{};", declare_sub_component_aux()));
-        for t in &self.body {
-            let (mut instructions_body, _) = t.produce_c(producer);
-            run_body.append(&mut instructions_body);
+        if producer.needs_trace() {
+            run_body.push(build_trace_run(CTX_INDEX.to_string()));
         }
+        if producer.needs_profile() {
+            run_body.push(build_profile_scope(self.id));
+        }
+        let mut instructions_body = produce_c_body(&self.body, producer, Some(&self.source_file));
+        run_body.append(&mut instructions_body);
 	// parallelism (join at the end of the function)
 	if self.number_of_components > 0 && self.has_parallel_sub_cmp {
             run_body.push(format!("{{"));
@@ -271,7 +277,11 @@ impl WriteC for TemplateCodeInfo {
 	    run_body.push(format!("ctx->ntcvs.notify_one();"));	     
 	}
         let run_fun = build_callable(run_header, run_params, run_body);
-        (vec![create_fun, run_fun], "".to_string())
+        let mut code = vec![create_fun, run_fun];
+        if producer.needs_source_map() {
+            code.push(GENERATED_LINE_DIRECTIVE.to_string());
+        }
+        (code, "".to_string())
     }
 }
 
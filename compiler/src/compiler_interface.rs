@@ -7,10 +7,25 @@ pub struct Config {
     pub debug_output: bool,
     pub produce_input_log: bool,
     pub wat_flag: bool,
+    pub trace_flag: bool,
+    pub portable_flag: bool,
+    pub lib_flag: bool,
+    pub embed_dat_flag: bool,
+    pub c_profile_flag: bool,
+    pub source_map_flag: bool,
 }
 
 pub fn run_compiler(vcp: VCP, config: Config) -> Result<Circuit, ()> {
-    let flags = CompilationFlags { main_inputs_log: config.produce_input_log, wat_flag: config.wat_flag };
+    let flags = CompilationFlags {
+        main_inputs_log: config.produce_input_log,
+        wat_flag: config.wat_flag,
+        trace_flag: config.trace_flag,
+        portable_flag: config.portable_flag,
+        lib_flag: config.lib_flag,
+        embed_dat_flag: config.embed_dat_flag,
+        c_profile_flag: config.c_profile_flag,
+        source_map_flag: config.source_map_flag,
+    };
     let circuit = Circuit::build(vcp, flags);
     if config.debug_output {
         produce_debug_output(&circuit)?;
@@ -18,15 +33,34 @@ pub fn run_compiler(vcp: VCP, config: Config) -> Result<Circuit, ()> {
     Ok(circuit)
 }
 
-pub fn write_wasm(circuit: &Circuit, js_folder: &str, wasm_name: &str, file: &str) -> Result<(), ()> {
+// Writes the binary module in wasm_file, with its source map next to it, and
+// its text format, obtained by disassembling it, in wat_file
+pub fn write_wasm(
+    circuit: &Circuit,
+    js_folder: &str,
+    wasm_name: &str,
+    wasm_file: Option<&str>,
+    wat_file: Option<&str>,
+) -> Result<(), String> {
+    use code_producers::wasm_elements::wasm_disassembler::disassemble;
     use std::path::Path;
     if Path::new(js_folder).is_dir() {
-        std::fs::remove_dir_all(js_folder).map_err(|_err| {})?;
+        std::fs::remove_dir_all(js_folder).map_err(|err| format!("could not remove {}: {}", js_folder, err))?;
     }
-    std::fs::create_dir(js_folder).map_err(|_err| {})?;
-    let file = File::create(file).map_err(|_err| {})?;
-    let mut writer = BufWriter::new(file);
-    circuit.produce_wasm(js_folder, wasm_name, &mut writer)
+    std::fs::create_dir(js_folder).map_err(|err| format!("could not create {}: {}", js_folder, err))?;
+    let (module, source_map) = circuit.produce_wasm(js_folder, wasm_name)?;
+    if let Some(file) = wat_file {
+        let text = disassemble(&module)?;
+        std::fs::write(file, text).map_err(|err| format!("could not write {}: {}", file, err))?;
+    }
+    if let Some(file) = wasm_file {
+        std::fs::write(file, &module).map_err(|err| format!("could not write {}: {}", file, err))?;
+        if let Some(source_map) = source_map {
+            let map_file = format!("{}.map", file);
+            std::fs::write(&map_file, source_map).map_err(|err| format!("could not write {}: {}", map_file, err))?;
+        }
+    }
+    Result::Ok(())
 }
 
 pub fn write_c(circuit: &Circuit, c_folder: &str, c_run_name: &str, c_file: &str, dat_file: &str) -> Result<(), ()> {
@@ -35,11 +69,14 @@ pub fn write_c(circuit: &Circuit, c_folder: &str, c_run_name: &str, c_file: &str
         std::fs::remove_dir_all(c_folder).map_err(|_err| {})?;
     }
     std::fs::create_dir(c_folder).map_err(|_err| {})?;
-    let dat_file = File::create(dat_file).map_err(|_err| {})?;
     let c_file = File::create(c_file).map_err(|_err| {})?;
     let mut c_file = BufWriter::new(c_file);
+    if circuit.c_producer.embeds_dat() {
+        return circuit.produce_c(c_folder, c_run_name, &mut c_file, None);
+    }
+    let dat_file = File::create(dat_file).map_err(|_err| {})?;
     let mut dat_file = BufWriter::new(dat_file);
-    circuit.produce_c(c_folder, c_run_name, &mut c_file, &mut dat_file)
+    circuit.produce_c(c_folder, c_run_name, &mut c_file, Some(&mut dat_file))
 }
 
 fn produce_debug_output(circuit: &Circuit) -> Result<(), ()> {
@@ -60,7 +60,7 @@ fn add_instance(name: &str, args: Vec<Param>, state: &mut State) {
         let body = state.generic_functions.get(name).unwrap().body.clone();
         let new_vcf = VCF {
             name: name.to_string(),
-            header: format!("{}_{}", name, state.vcf_collector.len()),
+            header: format!("{}_{}", name.replace('.', "_"), state.vcf_collector.len()),
             params_types: args.to_vec(),
             return_type: inferred,
             body,
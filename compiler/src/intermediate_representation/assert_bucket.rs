@@ -41,27 +41,21 @@ impl ToString for AssertBucket {
 }
 
 impl WriteWasm for AssertBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; assert bucket".to_string());
-	}
         let mut instructions_eval = self.evaluate.produce_wasm(producer);
         instructions.append(&mut instructions_eval);
         instructions.push(call("$Fr_isTrue"));
         instructions.push(eqz32());
         instructions.push(add_if());
-        instructions.push(set_constant(&self.message_id.to_string()));
-        instructions.push(set_constant(&self.line.to_string()));
+        instructions.push(set_constant(self.message_id as i32));
+        instructions.push(set_constant(self.line as i32));
         instructions.push(call("$buildBufferMessage"));
         instructions.push(call("$printErrorMessage"));
-        instructions.push(set_constant(&exception_code_assert_fail().to_string()));
+        instructions.push(set_constant(exception_code_assert_fail() as i32));
         instructions.push(add_return());
         instructions.push(add_end());
-        if producer.needs_comments() {
-            instructions.push(";; end of assert bucket".to_string());
-	}
         instructions
     }
 }
@@ -71,11 +65,9 @@ impl WriteC for AssertBucket {
         use c_code_generator::*;
         let (prologue, value) = self.evaluate.produce_c(producer);
         let is_true = build_call("Fr_isTrue".to_string(), vec![value]);
-        let if_condition = format!("if (!{}) {};", is_true, build_failed_assert_message(self.line));    
-        let assertion = format!("{};", build_call("assert".to_string(), vec![is_true]));
+        let if_condition = format!("if (!{}) {};", is_true, build_failed_assert_message(self.line));
         let mut assert_c = prologue;
         assert_c.push(if_condition);
-        assert_c.push(assertion);
         (assert_c, "".to_string())
     }
 }
@@ -54,27 +54,20 @@ impl ToString for BranchBucket {
 }
 
 impl WriteWasm for BranchBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; branch bucket".to_string());
-	}
         if self.if_branch.len() > 0 {
             let mut instructions_cond = self.cond.produce_wasm(producer);
             instructions.append(&mut instructions_cond);
             instructions.push(call("$Fr_isTrue"));
             instructions.push(add_if());
-            for ins in &self.if_branch {
-                let mut instructions_if = ins.produce_wasm(producer);
-                instructions.append(&mut instructions_if);
-            }
+            let mut instructions_if = produce_wasm_body(&self.if_branch, producer, None);
+            instructions.append(&mut instructions_if);
             if self.else_branch.len() > 0 {
                 instructions.push(add_else());
-                for ins in &self.else_branch {
-                    let mut instructions_else = ins.produce_wasm(producer);
-                    instructions.append(&mut instructions_else);
-                }
+                let mut instructions_else = produce_wasm_body(&self.else_branch, producer, None);
+                instructions.append(&mut instructions_else);
             }
 	    instructions.push(add_end());
         } else {
@@ -84,16 +77,11 @@ impl WriteWasm for BranchBucket {
                 instructions.push(call("$Fr_isTrue"));
                 instructions.push(eqz32());
                 instructions.push(add_if());
-                for ins in &self.else_branch {
-                    let mut instructions_else = ins.produce_wasm(producer);
-                    instructions.append(&mut instructions_else);
-                }
+                let mut instructions_else = produce_wasm_body(&self.else_branch, producer, None);
+                instructions.append(&mut instructions_else);
 		instructions.push(add_end());
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; end of branch bucket".to_string());
-	}
         instructions
     }
 }
@@ -103,16 +91,8 @@ impl WriteC for BranchBucket {
         use c_code_generator::merge_code;
         let (condition_code, condition_result) = self.cond.produce_c(producer);
         let condition_result = format!("Fr_isTrue({})", condition_result);
-        let mut if_body = Vec::new();
-        for instr in &self.if_branch {
-            let (mut instr_code, _) = instr.produce_c(producer);
-            if_body.append(&mut instr_code);
-        }
-        let mut else_body = Vec::new();
-        for instr in &self.else_branch {
-            let (mut instr_code, _) = instr.produce_c(producer);
-            else_body.append(&mut instr_code);
-        }
+        let if_body = produce_c_body(&self.if_branch, producer, None);
+        let else_body = produce_c_body(&self.else_branch, producer, None);
         let mut conditional = format!("if({}){{\n{}}}", condition_result, merge_code(if_body));
         if !else_body.is_empty() {
             conditional.push_str(&format!("else{{\n{}}}", merge_code(else_body)));
@@ -10,6 +10,8 @@ pub struct FinalData {
     pub dest_is_output: bool,
     pub dest_address_type: AddressType,
     pub dest: LocationRule,
+    // string table id of the destination signal name, only set when tracing
+    pub trace_name: Option<usize>,
 }
 
 #[derive(Clone)]
@@ -28,6 +30,8 @@ pub struct CallBucket {
     pub arena_size: usize,
     pub return_info: ReturnType,
     pub is_parallel: bool,
+    // string table id of the called function name, only set when tracing
+    pub trace_name: Option<usize>,
 }
 
 impl IntoInstruction for CallBucket {
@@ -64,30 +68,24 @@ impl ToString for CallBucket {
 }
 
 impl WriteWasm for CallBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; call bucket".to_string());
-	}
         if self.arguments.len() > 0 {
             let local_info_size_u32 = producer.get_local_info_size_u32();
-            instructions.push(set_constant("0"));
+            instructions.push(set_constant(0));
             instructions.push(load32(None)); // current stack size
             let var_start = local_info_size_u32 * 4; // starts after local info
             if local_info_size_u32 != 0 {
-                instructions.push(set_constant(&var_start.to_string()));
+                instructions.push(set_constant(var_start as i32));
                 instructions.push(add32());
             }
             instructions.push(set_local(producer.get_call_lvar_tag()));
             let mut count = 0;
             let mut i = 0;
             for p in &self.arguments {
-		if producer.needs_comments() {
-                    instructions.push(format!(";; copying argument {}", i));
-		}
                 instructions.push(get_local(producer.get_call_lvar_tag()));
-                instructions.push(set_constant(&count.to_string()));
+                instructions.push(set_constant(count as i32));
                 instructions.push(add32());
                 if self.argument_types[i].size > 1 {
                     instructions.push(set_local(producer.get_store_aux_1_tag()));
@@ -98,46 +96,51 @@ impl WriteWasm for CallBucket {
                     instructions.push(call("$Fr_copy"));
                 } else {
                     instructions.push(set_local(producer.get_store_aux_2_tag()));
-                    instructions.push(set_constant(&self.argument_types[i].size.to_string()));
+                    instructions.push(set_constant(self.argument_types[i].size as i32));
                     instructions.push(set_local(producer.get_copy_counter_tag()));
                     instructions.push(add_block());
                     instructions.push(add_loop());
                     instructions.push(get_local(producer.get_copy_counter_tag()));
                     instructions.push(eqz32());
-                    instructions.push(br_if("1"));
+                    instructions.push(br_if(1));
                     instructions.push(get_local(producer.get_store_aux_1_tag()));
                     instructions.push(get_local(producer.get_store_aux_2_tag()));
                     instructions.push(call("$Fr_copy"));
                     instructions.push(get_local(producer.get_copy_counter_tag()));
-                    instructions.push(set_constant("1"));
+                    instructions.push(set_constant(1));
                     instructions.push(sub32());
                     instructions.push(set_local(producer.get_copy_counter_tag()));
                     instructions.push(get_local(producer.get_store_aux_1_tag()));
                     let s = producer.get_size_32_bits_in_memory() * 4;
-                    instructions.push(set_constant(&s.to_string()));
+                    instructions.push(set_constant(s as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_store_aux_1_tag()));
                     instructions.push(get_local(producer.get_store_aux_2_tag()));
-                    instructions.push(set_constant(&s.to_string()));
+                    instructions.push(set_constant(s as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_store_aux_2_tag()));
-                    instructions.push(br("0"));
+                    instructions.push(br(0));
                     instructions.push(add_end());
                     instructions.push(add_end());
                 }
-		if producer.needs_comments() {
-                    instructions.push(format!(";; end copying argument {}", i));
-		}
                 count += self.argument_types[i].size * 4 * producer.get_size_32_bits_in_memory();
                 i += 1;
             }
         }
+        if let (true, Some(name)) = (producer.needs_trace(), self.trace_name) {
+            let pos = producer.get_string_list_start() + name * producer.get_size_of_message_in_bytes();
+            let number_of_args: usize = self.argument_types.iter().map(|a| a.size).sum();
+            instructions.push(set_constant(pos as i32));
+            instructions.push(get_local(producer.get_call_lvar_tag()));
+            instructions.push(set_constant(number_of_args as i32));
+            instructions.push(call("$traceArgs"));
+        }
         match &self.return_info {
             ReturnType::Intermediate { op_aux_no } => {
                 instructions.push(get_local(producer.get_expaux_tag()));
-                instructions.push(set_constant(&op_aux_no.to_string()));
+                instructions.push(set_constant(*op_aux_no as i32));
                 instructions.push(add32());
-                instructions.push(set_constant("1"));
+                instructions.push(set_constant(1));
                 instructions.push(call(&format!("${}", self.symbol)));
 		instructions.push(tee_local(producer.get_merror_tag()));
 		instructions.push(add_if());
@@ -150,13 +153,10 @@ impl WriteWasm for CallBucket {
                 let mut my_template_header = Option::<String>::None;
                 match &data.dest {
                     LocationRule::Indexed { location, template_header } => {
-			if producer.needs_comments() {
-                            instructions.push(";; getting result address".to_string());
-			}
                         let mut instructions_dest = location.produce_wasm(producer);
                         instructions.append(&mut instructions_dest);
                         let size = producer.get_size_32_bits_in_memory() * 4;
-                        instructions.push(set_constant(&size.to_string()));
+                        instructions.push(set_constant(size as i32));
                         instructions.push(mul32());
                         match &data.dest_address_type {
                             AddressType::Variable => {
@@ -168,21 +168,17 @@ impl WriteWasm for CallBucket {
                             AddressType::SubcmpSignal { cmp_address, .. } => {
                                 my_template_header = template_header.clone();
                                 instructions.push(get_local(producer.get_offset_tag()));
-                                instructions.push(set_constant(
-                                    &producer.get_sub_component_start_in_component().to_string(),
-                                ));
+                                instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                                 instructions.push(add32());
                                 let mut instructions_sci = cmp_address.produce_wasm(producer);
                                 instructions.append(&mut instructions_sci);
-                                instructions.push(set_constant("4")); //size in byte of i32
+                                instructions.push(set_constant(4)); //size in byte of i32
                                 instructions.push(mul32());
                                 instructions.push(add32());
                                 instructions.push(load32(None)); //subcomponent block
                                 instructions.push(set_local(producer.get_sub_cmp_tag()));
                                 instructions.push(get_local(producer.get_sub_cmp_tag()));
-                                instructions.push(set_constant(
-                                    &producer.get_signal_start_address_in_component().to_string(),
-                                ));
+                                instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                                 instructions.push(add32());
                                 instructions.push(load32(None)); //subcomponent start_of_signals
                             }
@@ -192,32 +188,23 @@ impl WriteWasm for CallBucket {
                     LocationRule::Mapped { signal_code, indexes } => {
                         match &data.dest_address_type {
                             AddressType::SubcmpSignal { cmp_address, .. } => {
-				if producer.needs_comments() {
-                                    instructions.push(";; is subcomponent".to_string());
-				}
                                 instructions.push(get_local(producer.get_offset_tag()));
-                                instructions.push(set_constant(
-                                    &producer.get_sub_component_start_in_component().to_string(),
-                                ));
+                                instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                                 instructions.push(add32());
                                 let mut instructions_sci = cmp_address.produce_wasm(producer);
                                 instructions.append(&mut instructions_sci);
-                                instructions.push(set_constant("4")); //size in byte of i32
+                                instructions.push(set_constant(4)); //size in byte of i32
                                 instructions.push(mul32());
                                 instructions.push(add32());
                                 instructions.push(load32(None)); //subcomponent block
                                 instructions.push(set_local(producer.get_sub_cmp_tag()));
                                 instructions.push(get_local(producer.get_sub_cmp_tag()));
                                 instructions.push(load32(None)); // get template id                     A
-                                instructions.push(set_constant("4")); //size in byte of i32
+                                instructions.push(set_constant(4)); //size in byte of i32
                                 instructions.push(mul32());
-                                instructions.push(load32(Some(
-                                    &producer
-                                        .get_template_instance_to_io_signal_start()
-                                        .to_string(),
-                                ))); // get position in component io signal to info list
+                                instructions.push(load32(Some(producer.get_template_instance_to_io_signal_start()))); // get position in component io signal to info list
                                 let signal_code_in_bytes = signal_code * 4; //position in the list of the signal code
-                                instructions.push(load32(Some(&signal_code_in_bytes.to_string()))); // get where the info of this signal is
+                                instructions.push(load32(Some(signal_code_in_bytes))); // get where the info of this signal is
                                                                                                     //now we have first the offset and then the all size dimensions but the last one
                                 if indexes.len() <= 1 {
                                     instructions.push(load32(None)); // get signal offset (it is already the actual one in memory);
@@ -226,7 +213,7 @@ impl WriteWasm for CallBucket {
                                             indexes[0].produce_wasm(producer);
                                         instructions.append(&mut instructions_idx0);
                                         let size = producer.get_size_32_bits_in_memory() * 4;
-                                        instructions.push(set_constant(&size.to_string()));
+                                        instructions.push(set_constant(size as i32));
                                         instructions.push(mul32());
                                         instructions.push(add32());
                                     }
@@ -240,7 +227,7 @@ impl WriteWasm for CallBucket {
                                     for i in 1..indexes.len() {
                                         instructions.push(get_local(producer.get_io_info_tag()));
                                         let offsetdim = 4 * i;
-                                        instructions.push(load32(Some(&offsetdim.to_string()))); // get size of ith dimension
+                                        instructions.push(load32(Some(offsetdim))); // get size of ith dimension
                                         instructions.push(mul32()); // multiply the current move by size of the ith dimension
                                         let mut instructions_idxi =
                                             indexes[i].produce_wasm(producer);
@@ -249,14 +236,12 @@ impl WriteWasm for CallBucket {
                                     }
                                     //we have the total move; and is multiplied by the size of memory Fr in bytes
                                     let size = producer.get_size_32_bits_in_memory() * 4;
-                                    instructions.push(set_constant(&size.to_string()));
+                                    instructions.push(set_constant(size as i32));
                                     instructions.push(mul32()); // We have the total move in bytes
                                     instructions.push(add32()); // add to the offset of the signal
                                 }
                                 instructions.push(get_local(producer.get_sub_cmp_tag()));
-                                instructions.push(set_constant(
-                                    &producer.get_signal_start_address_in_component().to_string(),
-                                ));
+                                instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                                 instructions.push(add32());
                                 instructions.push(load32(None)); //subcomponent start_of_signals
                                 instructions.push(add32()); // we get the position of the signal (with indexes) in memory
@@ -267,45 +252,46 @@ impl WriteWasm for CallBucket {
                         }
                     }
                 }
-                instructions.push(set_constant(&data.context.size.to_string()));
+                let trace_cmp = match (producer.needs_trace(), data.trace_name, &data.dest_address_type) {
+                    (true, Some(name), AddressType::Signal) => Some((name, producer.get_offset_tag())),
+                    (true, Some(name), AddressType::SubcmpSignal { .. }) => Some((name, producer.get_sub_cmp_tag())),
+                    _ => None,
+                };
+                if trace_cmp.is_some() {
+                    instructions.push(tee_local(producer.get_trace_dest_tag()));
+                }
+                instructions.push(set_constant(data.context.size as i32));
                 instructions.push(call(&format!("${}", self.symbol)));
                 instructions.push(tee_local(producer.get_merror_tag()));
 		instructions.push(add_if());
-                instructions.push(set_constant(&self.message_id.to_string()));
-                instructions.push(set_constant(&self.line.to_string()));
+                instructions.push(set_constant(self.message_id as i32));
+                instructions.push(set_constant(self.line as i32));
                 instructions.push(call("$buildBufferMessage"));
                 instructions.push(call("$printErrorMessage"));
 		instructions.push(get_local(producer.get_merror_tag()));    
                 instructions.push(add_return());
                 instructions.push(add_end());
+                if let Some((name, cmp_tag)) = trace_cmp {
+                    let pos = producer.get_string_list_start() + name * producer.get_size_of_message_in_bytes();
+                    instructions.push(get_local(cmp_tag));
+                    instructions.push(set_constant(pos as i32));
+                    instructions.push(get_local(producer.get_trace_dest_tag()));
+                    instructions.push(set_constant(data.context.size as i32));
+                    instructions.push(call("$traceSignals"));
+                }
                 match &data.dest_address_type {
                     AddressType::SubcmpSignal { .. } => {
                         // if subcomponent input check if run needed
-			if producer.needs_comments() {
-                            instructions.push(";; decrease counter".to_string()); // by self.context.size
-			}
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
-                        instructions.push(load32(Some(
-                            &producer.get_input_counter_address_in_component().to_string(),
-                        ))); //remaining inputs to be set
-                        instructions.push(set_constant(&data.context.size.to_string()));
+                        instructions.push(load32(Some(producer.get_input_counter_address_in_component()))); //remaining inputs to be set
+                        instructions.push(set_constant(data.context.size as i32));
                         instructions.push(sub32());
-                        instructions.push(store32(Some(
-                            &producer.get_input_counter_address_in_component().to_string(),
-                        ))); // update remaining inputs to be set
-			if producer.needs_comments() {
-                            instructions.push(";; check if run is needed".to_string());
-			}
+                        instructions.push(store32(Some(producer.get_input_counter_address_in_component()))); // update remaining inputs to be set
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
-                        instructions.push(load32(Some(
-                            &producer.get_input_counter_address_in_component().to_string(),
-                        )));
+                        instructions.push(load32(Some(producer.get_input_counter_address_in_component())));
                         instructions.push(eqz32());
                         instructions.push(add_if());
-			if producer.needs_comments() {
-                            instructions.push(";; run sub component".to_string());
-			}
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
                         match &data.dest {
                             LocationRule::Indexed { .. } => {
@@ -313,8 +299,8 @@ impl WriteWasm for CallBucket {
                                     instructions.push(call(&format!("${}_run", name)));
                                     instructions.push(tee_local(producer.get_merror_tag()));
                                     instructions.push(add_if());
-                                    instructions.push(set_constant(&self.message_id.to_string()));
-                                    instructions.push(set_constant(&self.line.to_string()));
+                                    instructions.push(set_constant(self.message_id as i32));
+                                    instructions.push(set_constant(self.line as i32));
                                     instructions.push(call("$buildBufferMessage"));
                                     instructions.push(call("$printErrorMessage"));
                                     instructions.push(get_local(producer.get_merror_tag()));    
@@ -327,14 +313,11 @@ impl WriteWasm for CallBucket {
                             LocationRule::Mapped { .. } => {
                                 instructions.push(get_local(producer.get_sub_cmp_tag()));
                                 instructions.push(load32(None)); // get template id
-                                instructions.push(call_indirect(
-                                    &"$runsmap".to_string(),
-                                    &"(type $_t_i32ri32)".to_string(),
-                                ));
+                                instructions.push(call_indirect("$runsmap", "$_t_i32ri32"));
                                 instructions.push(tee_local(producer.get_merror_tag()));
                                 instructions.push(add_if());
-                                instructions.push(set_constant(&self.message_id.to_string()));
-                                instructions.push(set_constant(&self.line.to_string()));
+                                instructions.push(set_constant(self.message_id as i32));
+                                instructions.push(set_constant(self.line as i32));
                                 instructions.push(call("$buildBufferMessage"));
                                 instructions.push(call("$printErrorMessage"));
                                 instructions.push(get_local(producer.get_merror_tag()));    
@@ -342,18 +325,12 @@ impl WriteWasm for CallBucket {
                                 instructions.push(add_end());
                             }
                         }
-			if producer.needs_comments() {
-                            instructions.push(";; end run sub component".to_string());
-			}
                         instructions.push(add_end());
                     }
                     _ => (),
                 }
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; end call bucket".to_string());
-	}
         instructions
     }
 }
@@ -389,6 +366,10 @@ impl WriteC for CallBucket {
             count += self.argument_types[i].size;
             i += 1;
         }
+        if let (true, Some(name)) = (producer.needs_trace(), self.trace_name) {
+            let name = &producer.get_string_table()[name];
+            prologue.push(build_trace_call(name, L_VAR_FUNC_CALL_STORAGE.to_string(), count));
+        }
         let result;
         let mut call_arguments = vec![];
         call_arguments.push(CIRCOM_CALC_WIT.to_string());
@@ -465,9 +446,20 @@ impl WriteC for CallBucket {
                         )
                     }
                 };
-                call_arguments.push(result_ref);
+                call_arguments.push(result_ref.clone());
                 call_arguments.push(data.context.size.to_string());
                 prologue.push(format!("{};", build_call(self.symbol.clone(), call_arguments)));
+                if let (true, Some(name)) = (producer.needs_trace(), data.trace_name) {
+                    let cmp_id = match &data.dest_address_type {
+                        AddressType::SubcmpSignal { .. } => Some(format!("{}[{}]", MY_SUBCOMPONENTS, cmp_index_ref)),
+                        AddressType::Signal => Some(CTX_INDEX.to_string()),
+                        AddressType::Variable => None,
+                    };
+                    if let Some(cmp_id) = cmp_id {
+                        let name = &producer.get_string_table()[name];
+                        prologue.push(build_trace_signal(cmp_id, name, result_ref, data.context.size));
+                    }
+                }
 		if let LocationRule::Mapped { indexes, .. } = &data.dest {
 		    if indexes.len() > 0 {
     			prologue.push(format!("}}"));
@@ -124,12 +124,9 @@ impl ToString for ComputeBucket {
     }
 }
 impl WriteWasm for ComputeBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; compute bucket".to_string());
-	}
         match &self.op {
             OperatorType::AddAddress => {}
             OperatorType::MulAddress => {}
@@ -138,7 +135,7 @@ impl WriteWasm for ComputeBucket {
                 //address of the result for the Fr operations
                 instructions.push(get_local(producer.get_expaux_tag()));
                 let size = self.op_aux_no * producer.get_size_32_bits_in_memory() * 4;
-                instructions.push(set_constant(&size.to_string()));
+                instructions.push(set_constant(size as i32));
                 instructions.push(add32());
             }
         }
@@ -146,9 +143,6 @@ impl WriteWasm for ComputeBucket {
             let mut instructions_exp = e.produce_wasm(producer);
             instructions.append(&mut instructions_exp);
         }
-        if producer.needs_comments() {
-            instructions.push(format!(";; OP({})", self.op.to_string()));
-	}
         match &self.op {
             OperatorType::AddAddress => {
                 instructions.push(add32());
@@ -234,13 +228,10 @@ impl WriteWasm for ComputeBucket {
                 }
                 instructions.push(get_local(producer.get_expaux_tag()));
                 let size = self.op_aux_no * producer.get_size_32_bits_in_memory() * 4;
-                instructions.push(set_constant(&size.to_string()));
+                instructions.push(set_constant(size as i32));
                 instructions.push(add32());
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; end of compute bucket".to_string());
-	}
         instructions
     }
 }
@@ -305,6 +296,9 @@ impl WriteC for ComputeBucket {
                 let mut arguments = vec![result_ref.clone()];
                 arguments.append(&mut operands);
                 compute_c.push(format!("{}; // line circom {}", build_call(operator, arguments),self.line.to_string()));
+                if producer.needs_profile() {
+                    compute_c.push(build_profile_field_op());
+                }
 
                 //value address
                 result = result_ref;
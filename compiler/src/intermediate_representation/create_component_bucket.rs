@@ -25,6 +25,8 @@ pub struct CreateCmpBucket {
     pub number_of_cmp: usize,
     pub has_inputs: bool,
     pub is_parallel: bool,
+    // string table id of the component name (with its dimensions), only set when tracing
+    pub trace_name: Option<usize>,
 }
 
 impl IntoInstruction for CreateCmpBucket {
@@ -61,28 +63,42 @@ impl ToString for CreateCmpBucket {
     }
 }
 
+impl CreateCmpBucket {
+    // reports the component just created (its address is in $temp) to the execution trace
+    fn trace_create(&self, producer: &WASMProducer, mut index: Vec<WasmInstruction>) -> Vec<WasmInstruction> {
+        use code_producers::wasm_elements::wasm_code_generator::*;
+        let mut instructions = vec![];
+        if let (true, Some(name)) = (producer.needs_trace(), self.trace_name) {
+            let pos = producer.get_string_list_start() + name * producer.get_size_of_message_in_bytes();
+            instructions.push(get_local(producer.get_offset_tag()));
+            instructions.push(get_local(producer.get_temp_tag()));
+            instructions.push(set_constant(pos as i32));
+            instructions.append(&mut index);
+            instructions.push(call("$traceCreate"));
+        }
+        instructions
+    }
+}
+
 impl WriteWasm for CreateCmpBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; create component bucket".to_string());
-	    }
         //obtain address of the subcomponent inside the component
         instructions.push(get_local(producer.get_offset_tag()));
         instructions
-            .push(set_constant(&producer.get_sub_component_start_in_component().to_string()));
+            .push(set_constant(producer.get_sub_component_start_in_component() as i32));
         instructions.push(add32());
         let mut instructions_sci = self.sub_cmp_id.produce_wasm(producer);
         instructions.append(&mut instructions_sci);
-        instructions.push(set_constant("4")); //size in byte of i32
+        instructions.push(set_constant(4)); //size in byte of i32
         instructions.push(mul32());
         instructions.push(add32()); // address of the subcomponent in the component
         if self.number_of_cmp > 1 {
             instructions.push(set_local(producer.get_create_loop_sub_cmp_tag()));
         }
         let signal_full_offset = self.signal_offset * producer.get_size_32_bits_in_memory() * 4;
-        instructions.push(set_constant(&signal_full_offset.to_string())); //offset of the signals in the subcomponent
+        instructions.push(set_constant(signal_full_offset as i32)); //offset of the signals in the subcomponent
         instructions.push(get_local(producer.get_signal_start_tag()));
         instructions.push(add32());
         if self.number_of_cmp == 1 {
@@ -90,12 +106,13 @@ impl WriteWasm for CreateCmpBucket {
             if !self.has_inputs {
                 instructions.push(tee_local(producer.get_temp_tag())); //here we use $temp to keep the offset created subcomponet
                 instructions.push(store32(None)); //store the offset given by create in the subcomponent address
+                instructions.append(&mut self.trace_create(producer, vec![set_constant(-1)]));
                 instructions.push(get_local(producer.get_temp_tag()));
                 instructions.push(call(&format!("${}_run", self.symbol)));
                 instructions.push(tee_local(producer.get_merror_tag()));
                 instructions.push(add_if());
-                instructions.push(set_constant(&self.message_id.to_string()));
-                instructions.push(set_constant(&self.line.to_string()));
+                instructions.push(set_constant(self.message_id as i32));
+                instructions.push(set_constant(self.line as i32));
                 instructions.push(call("$buildBufferMessage"));
                 instructions.push(call("$printErrorMessage"));		
                 instructions.push(get_local(producer.get_merror_tag()));    
@@ -103,12 +120,16 @@ impl WriteWasm for CreateCmpBucket {
                 instructions.push(add_end());
             }
             else {
+                if producer.needs_trace() {
+                    instructions.push(tee_local(producer.get_temp_tag()));
+                }
                 instructions.push(store32(None)); //store the offset given by create in the subcomponent address
+                instructions.append(&mut self.trace_create(producer, vec![set_constant(-1)]));
             }
 	} else {
             instructions.push(set_local(producer.get_create_loop_offset_tag()));
             if self.number_of_cmp == self.defined_positions.len() {
-                instructions.push(set_constant(&self.number_of_cmp.to_string()));
+                instructions.push(set_constant(self.number_of_cmp as i32));
                 instructions.push(set_local(producer.get_create_loop_counter_tag()));
                 instructions.push(add_block());
                 instructions.push(add_loop());
@@ -116,15 +137,21 @@ impl WriteWasm for CreateCmpBucket {
                 instructions.push(get_local(producer.get_create_loop_offset_tag()));
                 //sub_component signal address start
                 instructions.push(call(&format!("${}_create", self.symbol)));
+                let loop_index = vec![
+                    set_constant(self.number_of_cmp as i32),
+                    get_local(producer.get_create_loop_counter_tag()),
+                    sub32(),
+                ];
                 if !self.has_inputs {
                     instructions.push(tee_local(producer.get_temp_tag())); //here we use $temp to keep the offset created subcomponet
                     instructions.push(store32(None)); //store the offset given by create in the subcomponent address
+                    instructions.append(&mut self.trace_create(producer, loop_index));
                     instructions.push(get_local(producer.get_temp_tag()));
                     instructions.push(call(&format!("${}_run", self.symbol)));
                     instructions.push(tee_local(producer.get_merror_tag()));
                     instructions.push(add_if());
-                    instructions.push(set_constant(&self.message_id.to_string()));
-                    instructions.push(set_constant(&self.line.to_string()));
+                    instructions.push(set_constant(self.message_id as i32));
+                    instructions.push(set_constant(self.line as i32));
                     instructions.push(call("$buildBufferMessage"));
                     instructions.push(call("$printErrorMessage"));
                     instructions.push(get_local(producer.get_merror_tag()));    
@@ -132,35 +159,39 @@ impl WriteWasm for CreateCmpBucket {
                     instructions.push(add_end());
 		}
                 else {
+                    if producer.needs_trace() {
+                        instructions.push(tee_local(producer.get_temp_tag()));
+                    }
                     instructions.push(store32(None)); //store the offset given by create in the subcomponent address
+                    instructions.append(&mut self.trace_create(producer, loop_index));
 		}
                 instructions.push(get_local(producer.get_create_loop_counter_tag()));
-                instructions.push(set_constant("1"));
+                instructions.push(set_constant(1));
                 instructions.push(sub32());
                 instructions.push(set_local(producer.get_create_loop_counter_tag()));
                 instructions.push(get_local(producer.get_create_loop_counter_tag()));
                 instructions.push(eqz32());
-                instructions.push(br_if("1"));
+                instructions.push(br_if(1));
                 // next sub_cmp  is 4 bytes later
                 instructions.push(get_local(producer.get_create_loop_sub_cmp_tag()));
-                instructions.push(set_constant("4"));
+                instructions.push(set_constant(4));
                 instructions.push(add32());
                 instructions.push(set_local(producer.get_create_loop_sub_cmp_tag()));
                 // next signal offset
                 instructions.push(get_local(producer.get_create_loop_offset_tag()));
                 let jump_in_bytes = self.signal_offset_jump * producer.get_size_32_bits_in_memory() * 4;
-                instructions.push(set_constant(&jump_in_bytes.to_string()));
+                instructions.push(set_constant(jump_in_bytes as i32));
                 instructions.push(add32());
                 instructions.push(set_local(producer.get_create_loop_offset_tag()));
                 //back to loop, to create next component
-                instructions.push(br("0"));
+                instructions.push(br(0));
                 instructions.push(add_end());
                 instructions.push(add_end());
             } else {
                 if self.defined_positions[0] != 0 {
                     instructions.push(get_local(producer.get_create_loop_sub_cmp_tag())); //sub_component address in component
                     let jump = 4*self.defined_positions[0];
-                    instructions.push(set_constant(&jump.to_string()));
+                    instructions.push(set_constant(jump as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_create_loop_sub_cmp_tag())); //sub_component address in component
                 }
@@ -168,32 +199,39 @@ impl WriteWasm for CreateCmpBucket {
                 instructions.push(get_local(producer.get_create_loop_offset_tag()));
                 //sub_component signal address start
                 instructions.push(call(&format!("${}_create", self.symbol)));
+                if producer.needs_trace() {
+                    instructions.push(tee_local(producer.get_temp_tag()));
+                }
                 instructions.push(store32(None)); //store the offset given by create in the subcomponent address
+                let first_index = self.defined_positions[0] as i32;
+                instructions.append(&mut self.trace_create(producer, vec![set_constant(first_index)]));
                 //producer.get_create_loop_sub_cmp_tag() contains the position of last created subcomponent
                 //producer.get_create_loop_offset_tag() contains the offset of last created subcomponent
                 for i in 1..self.defined_positions.len() {
                     instructions.push(get_local(producer.get_create_loop_sub_cmp_tag()));
                     let jump = 4*(self.defined_positions[i]-self.defined_positions[i-1]);
-                    instructions.push(set_constant(&jump.to_string()));
+                    instructions.push(set_constant(jump as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_create_loop_sub_cmp_tag()));
                     // next signal offset
                     instructions.push(get_local(producer.get_create_loop_offset_tag()));
                     let jump_in_bytes = self.signal_offset_jump * producer.get_size_32_bits_in_memory() * 4;
-                    instructions.push(set_constant(&jump_in_bytes.to_string()));
+                    instructions.push(set_constant(jump_in_bytes as i32));
                     instructions.push(add32());
                     instructions.push(set_local(producer.get_create_loop_offset_tag()));
                     instructions.push(get_local(producer.get_create_loop_sub_cmp_tag())); //sub_component address in component
                     instructions.push(get_local(producer.get_create_loop_offset_tag()));
                     //sub_component signal address start
                     instructions.push(call(&format!("${}_create", self.symbol)));
+                    if producer.needs_trace() {
+                        instructions.push(tee_local(producer.get_temp_tag()));
+                    }
                     instructions.push(store32(None)); //store the offset given by create in the subcomponent addre
+                    let index = self.defined_positions[i] as i32;
+                    instructions.append(&mut self.trace_create(producer, vec![set_constant(index)]));
                 }
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; end create component bucket".to_string());
-	    }
         instructions
     }
 }
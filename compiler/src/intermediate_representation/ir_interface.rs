@@ -107,7 +107,7 @@ impl CheckCompute for Instruction {
 }
 
 impl WriteWasm for Instruction {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use Instruction::*;
         match self {
             Value(v) => v.produce_wasm(producer),
@@ -162,3 +162,31 @@ impl ToString for Instruction {
         }
     }
 }
+
+// Code of the instructions of a body, each one preceded by its line in the
+// circom sources with --source-map. The file is only given for the bodies of
+// templates and functions, the nested bodies keep it
+pub fn produce_c_body(body: &InstructionList, producer: &CProducer, file: Option<&str>) -> Vec<String> {
+    use code_producers::c_elements::c_code_generator::build_line_directive;
+    let mut code = vec![];
+    for instruction in body {
+        if producer.needs_source_map() && instruction.get_line() > 0 {
+            code.push(build_line_directive(instruction.get_line(), file));
+        }
+        let (mut instruction_code, _) = instruction.produce_c(producer);
+        code.append(&mut instruction_code);
+    }
+    code
+}
+
+pub fn produce_wasm_body(body: &InstructionList, producer: &WASMProducer, file: Option<&str>) -> Vec<WasmInstruction> {
+    use code_producers::wasm_elements::wasm_code_generator::source_location;
+    let mut code = vec![];
+    for instruction in body {
+        if producer.needs_source_map() && instruction.get_line() > 0 {
+            code.push(source_location(instruction.get_line(), file));
+        }
+        code.append(&mut instruction.produce_wasm(producer));
+    }
+    code
+}
@@ -46,80 +46,60 @@ impl ToString for LoadBucket {
     }
 }
 impl WriteWasm for LoadBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; load bucket".to_string());
-	}
         match &self.src {
             LocationRule::Indexed { location, .. } => {
                 let mut instructions_src = location.produce_wasm(producer);
                 instructions.append(&mut instructions_src);
                 let size = producer.get_size_32_bits_in_memory() * 4;
-                instructions.push(set_constant(&size.to_string()));
+                instructions.push(set_constant(size as i32));
                 instructions.push(mul32());
                 match &self.address_type {
                     AddressType::Variable => {
-                        instructions.push(get_local(producer.get_lvar_tag()).to_string());
+                        instructions.push(get_local(producer.get_lvar_tag()));
                     }
                     AddressType::Signal => {
-                        instructions.push(get_local(producer.get_signal_start_tag()).to_string());
+                        instructions.push(get_local(producer.get_signal_start_tag()));
                     }
                     AddressType::SubcmpSignal { cmp_address, .. } => {
-			if producer.needs_comments() {
-			    instructions.push(";; is subcomponent".to_string());
-			}
                         instructions.push(get_local(producer.get_offset_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_sub_component_start_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                         instructions.push(add32());
                         let mut instructions_sci = cmp_address.produce_wasm(producer);
                         instructions.append(&mut instructions_sci);
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent block
-                        instructions.push(set_constant(
-                            &producer.get_signal_start_address_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent start_of_signals
                     }
                 }
                 instructions.push(add32());
-		if producer.needs_comments() {
-                    instructions.push(";; end of load bucket".to_string());
-		}
             }
             LocationRule::Mapped { signal_code, indexes } => {
                 match &self.address_type {
                     AddressType::SubcmpSignal { cmp_address, .. } => {
-			if producer.needs_comments() {
-                            instructions.push(";; is subcomponent".to_string());
-			}
                         instructions.push(get_local(producer.get_offset_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_sub_component_start_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                         instructions.push(add32());
                         let mut instructions_sci = cmp_address.produce_wasm(producer);
                         instructions.append(&mut instructions_sci);
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent block
                         instructions.push(set_local(producer.get_sub_cmp_load_tag()));
                         instructions.push(get_local(producer.get_sub_cmp_load_tag()));
                         instructions.push(load32(None)); // get template id                     A
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
-                        instructions.push(load32(Some(
-                            &producer.get_template_instance_to_io_signal_start().to_string(),
-                        ))); // get position in component io signal to info list
+                        instructions.push(load32(Some(producer.get_template_instance_to_io_signal_start()))); // get position in component io signal to info list
                         let signal_code_in_bytes = signal_code * 4; //position in the list of the signal code
-                        instructions.push(load32(Some(&signal_code_in_bytes.to_string()))); // get where the info of this signal is
+                        instructions.push(load32(Some(signal_code_in_bytes))); // get where the info of this signal is
                                                                                             //now we have first the offset and then the all size dimensions but the last one
                         if indexes.len() <= 1 {
                             instructions.push(load32(None)); // get signal offset (it is already the actual one in memory);
@@ -127,7 +107,7 @@ impl WriteWasm for LoadBucket {
                                 let mut instructions_idx0 = indexes[0].produce_wasm(producer);
                                 instructions.append(&mut instructions_idx0);
                                 let size = producer.get_size_32_bits_in_memory() * 4;
-                                instructions.push(set_constant(&size.to_string()));
+                                instructions.push(set_constant(size as i32));
                                 instructions.push(mul32());
                                 instructions.push(add32());
                             }
@@ -141,7 +121,7 @@ impl WriteWasm for LoadBucket {
                             for i in 1..indexes.len() {
                                 instructions.push(get_local(producer.get_io_info_tag()));
                                 let offsetdim = 4 * i;
-                                instructions.push(load32(Some(&offsetdim.to_string()))); // get size of ith dimension
+                                instructions.push(load32(Some(offsetdim))); // get size of ith dimension
                                 instructions.push(mul32()); // multiply the current move by size of the ith dimension
                                 let mut instructions_idxi = indexes[i].produce_wasm(producer);
                                 instructions.append(&mut instructions_idxi);
@@ -149,20 +129,15 @@ impl WriteWasm for LoadBucket {
                             }
                             //we have the total move; and is multiplied by the size of memory Fr in bytes
                             let size = producer.get_size_32_bits_in_memory() * 4;
-                            instructions.push(set_constant(&size.to_string()));
+                            instructions.push(set_constant(size as i32));
                             instructions.push(mul32()); // We have the total move in bytes
                             instructions.push(add32()); // add to the offset of the signal
                         }
                         instructions.push(get_local(producer.get_sub_cmp_load_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_signal_start_address_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent start_of_signals
                         instructions.push(add32()); // we get the position of the signal (with indexes) in memory
-			if producer.needs_comments() {
-                            instructions.push(";; end of load bucket".to_string());
-			}
                     }
                     _ => {
                         assert!(false);
@@ -56,12 +56,9 @@ impl ToString for LogBucket {
 }
 
 impl WriteWasm for LogBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; log bucket".to_string());
-	    }
         for logarg in self.argsprint.clone() {
 	    match &logarg {
                 LogBucketArg::LogExp(exp) => {
@@ -73,23 +70,20 @@ impl WriteWasm for LogBucket {
 		LogBucketArg::LogStr(stringid) => {
                     let pos = producer.get_string_list_start() +
                               stringid * producer.get_size_of_message_in_bytes();
-                    instructions.push(set_constant(&pos.to_string()));
+                    instructions.push(set_constant(pos as i32));
                     instructions.push(call("$buildLogMessage"));
                     instructions.push(call("$writeBufferMessage"));                    
 	        }
             }
 	}
 	// add nl
-        instructions.push(set_constant(&producer.get_message_buffer_start().to_string()));
-        instructions.push(set_constant("0x0000000a"));
+        instructions.push(set_constant(producer.get_message_buffer_start() as i32));
+        instructions.push(set_constant(0x0000000a));
         instructions.push(store32(None)); // stores \n000 
-        instructions.push(set_constant(&producer.get_message_buffer_counter_position().to_string()));
-        instructions.push(set_constant("0"));
+        instructions.push(set_constant(producer.get_message_buffer_counter_position() as i32));
+        instructions.push(set_constant(0));
         instructions.push(store32(None));
         instructions.push(call("$writeBufferMessage"));
-        if producer.needs_comments() {
-            instructions.push(";; end of log bucket".to_string());
-	}
         instructions
     }
 }
@@ -47,42 +47,34 @@ impl ToString for LoopBucket {
 }
 
 impl WriteWasm for LoopBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(format!(";; loop bucket. Line {}", self.line)); //.to_string()
-	}
         instructions.push(add_block());
         instructions.push(add_loop());
         let mut instructions_continue = self.continue_condition.produce_wasm(producer);
         instructions.append(&mut instructions_continue);
         instructions.push(call("$Fr_isTrue"));
         instructions.push(eqz32());
-        instructions.push(br_if("1"));
-        for ins in &self.body {
-            let mut instructions_loop = ins.produce_wasm(producer);
-            instructions.append(&mut instructions_loop);
-        }
-        instructions.push(br("0"));
+        instructions.push(br_if(1));
+        let mut instructions_loop = produce_wasm_body(&self.body, producer, None);
+        instructions.append(&mut instructions_loop);
+        instructions.push(br(0));
         instructions.push(add_end());
         instructions.push(add_end());
-        if producer.needs_comments() {
-            instructions.push(";; end of loop bucket".to_string());
-	}
         instructions
     }
 }
 
 impl WriteC for LoopBucket {
     fn produce_c(&self, producer: &CProducer) -> (Vec<String>, String) {
-        use c_code_generator::merge_code;
+        use c_code_generator::{build_line_directive, merge_code};
         let (continue_code, continue_result) = self.continue_condition.produce_c(producer);
         let continue_result = format!("Fr_isTrue({})", continue_result);
-        let mut body = vec![];
-        for instr in &self.body {
-            let (mut instr_code, _) = instr.produce_c(producer);
-            body.append(&mut instr_code);
+        let mut body = produce_c_body(&self.body, producer, None);
+        if producer.needs_source_map() && self.line > 0 {
+            // the condition is computed again at the end of the body
+            body.push(build_line_directive(self.line, None));
         }
         body.append(&mut continue_code.clone());
         let while_loop = format!("while({}){{\n{}}}", continue_result, merge_code(body));
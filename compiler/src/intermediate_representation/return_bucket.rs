@@ -43,12 +43,9 @@ impl ToString for ReturnBucket {
 }
 
 impl WriteWasm for ReturnBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; return bucket".to_string());
-	}
         if self.with_size == 1 {
             instructions.push(get_local(producer.get_result_address_tag())); //result address
             let mut instructions_value = self.value.produce_wasm(producer);
@@ -62,34 +59,31 @@ impl WriteWasm for ReturnBucket {
             instructions.push(add_loop());
             instructions.push(get_local(producer.get_result_size_tag()));
             instructions.push(eqz32());
-            instructions.push(br_if("1"));
+            instructions.push(br_if(1));
             instructions.push(get_local(producer.get_result_address_tag())); //result address
             instructions.push(get_local(producer.get_store_aux_2_tag()));
             instructions.push(call("$Fr_copy"));
             instructions.push(get_local(producer.get_result_size_tag())); // update get_result_size
-            instructions.push(set_constant("1"));
+            instructions.push(set_constant(1));
             instructions.push(sub32());
             instructions.push(set_local(producer.get_result_size_tag())); // update get_result_size
             instructions.push(get_local(producer.get_result_address_tag())); //prepare next result address
-            let s = (producer.get_size_32_bit() + 2) * 4;
-            instructions.push(set_constant(&s.to_string()));
+            let s = producer.get_size_32_bits_in_memory() * 4;
+            instructions.push(set_constant(s as i32));
             instructions.push(add32());
             instructions.push(set_local(producer.get_result_address_tag()));
             instructions.push(get_local(producer.get_store_aux_2_tag()));
-            instructions.push(set_constant(&s.to_string()));
+            instructions.push(set_constant(s as i32));
             instructions.push(add32());
             instructions.push(set_local(producer.get_store_aux_2_tag()));
-            instructions.push(br("0"));
+            instructions.push(br(0));
             instructions.push(add_end());
             instructions.push(add_end());
         }
         let mut free_stack_code = free_stack(producer);
         instructions.append(&mut free_stack_code);
-        instructions.push(set_constant("0"));	
+        instructions.push(set_constant(0));	
         instructions.push(add_return());
-        if producer.needs_comments() {
-            instructions.push(";; end of return bucket".to_string());
-	}
         instructions
     }
 }
@@ -13,6 +13,8 @@ pub struct StoreBucket {
     pub dest: LocationRule,
     pub src: InstructionPointer,
     pub is_parallel: bool,
+    // string table id of the stored signal name, only set when tracing
+    pub trace_name: Option<usize>,
 }
 
 impl IntoInstruction for StoreBucket {
@@ -51,22 +53,16 @@ impl ToString for StoreBucket {
 }
 
 impl WriteWasm for StoreBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-	    instructions.push(format!(";; store bucket. Line {}", self.line)); //.to_string()
-	}
         let mut my_template_header = Option::<String>::None;
-        if producer.needs_comments() {
-            instructions.push(";; getting dest".to_string());
-	}
         match &self.dest {
             LocationRule::Indexed { location, template_header } => {
                 let mut instructions_dest = location.produce_wasm(producer);
                 instructions.append(&mut instructions_dest);
                 let size = producer.get_size_32_bits_in_memory() * 4;
-                instructions.push(set_constant(&size.to_string()));
+                instructions.push(set_constant(size as i32));
                 instructions.push(mul32());
                 match &self.dest_address_type {
                     AddressType::Variable => {
@@ -78,21 +74,17 @@ impl WriteWasm for StoreBucket {
                     AddressType::SubcmpSignal { cmp_address, .. } => {
                         my_template_header = template_header.clone();
                         instructions.push(get_local(producer.get_offset_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_sub_component_start_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                         instructions.push(add32());
                         let mut instructions_sci = cmp_address.produce_wasm(producer);
                         instructions.append(&mut instructions_sci);
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent block
                         instructions.push(set_local(producer.get_sub_cmp_tag()));
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_signal_start_address_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent start_of_signals
                     }
@@ -102,30 +94,23 @@ impl WriteWasm for StoreBucket {
             LocationRule::Mapped { signal_code, indexes } => {
                 match &self.dest_address_type {
                     AddressType::SubcmpSignal { cmp_address, .. } => {
-			if producer.needs_comments() {
-                            instructions.push(";; is subcomponent".to_string());
-			}
                         instructions.push(get_local(producer.get_offset_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_sub_component_start_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_sub_component_start_in_component() as i32));
                         instructions.push(add32());
                         let mut instructions_sci = cmp_address.produce_wasm(producer);
                         instructions.append(&mut instructions_sci);
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent block
                         instructions.push(set_local(producer.get_sub_cmp_tag()));
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
                         instructions.push(load32(None)); // get template id                     A
-                        instructions.push(set_constant("4")); //size in byte of i32
+                        instructions.push(set_constant(4)); //size in byte of i32
                         instructions.push(mul32());
-                        instructions.push(load32(Some(
-                            &producer.get_template_instance_to_io_signal_start().to_string(),
-                        ))); // get position in component io signal to info list
+                        instructions.push(load32(Some(producer.get_template_instance_to_io_signal_start()))); // get position in component io signal to info list
                         let signal_code_in_bytes = signal_code * 4; //position in the list of the signal code
-                        instructions.push(load32(Some(&signal_code_in_bytes.to_string()))); // get where the info of this signal is
+                        instructions.push(load32(Some(signal_code_in_bytes))); // get where the info of this signal is
                         //now we have first the offset, and then the all size dimensions but the last one
                         if indexes.len() <= 1 {
                             instructions.push(load32(None)); // get signal offset (it is already the actual one in memory);
@@ -133,7 +118,7 @@ impl WriteWasm for StoreBucket {
                                 let mut instructions_idx0 = indexes[0].produce_wasm(producer);
                                 instructions.append(&mut instructions_idx0);
                                 let size = producer.get_size_32_bits_in_memory() * 4;
-                                instructions.push(set_constant(&size.to_string()));
+                                instructions.push(set_constant(size as i32));
                                 instructions.push(mul32());
                                 instructions.push(add32());
                             }
@@ -147,7 +132,7 @@ impl WriteWasm for StoreBucket {
                             for i in 1..indexes.len() {
                                 instructions.push(get_local(producer.get_io_info_tag()));
                                 let offsetdim = 4 * i;
-                                instructions.push(load32(Some(&offsetdim.to_string()))); // get size of ith dimension
+                                instructions.push(load32(Some(offsetdim))); // get size of ith dimension
                                 instructions.push(mul32()); // multiply the current move by size of the ith dimension
                                 let mut instructions_idxi = indexes[i].produce_wasm(producer);
                                 instructions.append(&mut instructions_idxi);
@@ -155,14 +140,12 @@ impl WriteWasm for StoreBucket {
                             }
                             //we have the total move; and is multiplied by the size of memory Fr in bytes
                             let size = producer.get_size_32_bits_in_memory() * 4;
-                            instructions.push(set_constant(&size.to_string()));
+                            instructions.push(set_constant(size as i32));
                             instructions.push(mul32()); // We have the total move in bytes
                             instructions.push(add32()); // add to the offset of the signal
                         }
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
-                        instructions.push(set_constant(
-                            &producer.get_signal_start_address_in_component().to_string(),
-                        ));
+                        instructions.push(set_constant(producer.get_signal_start_address_in_component() as i32));
                         instructions.push(add32());
                         instructions.push(load32(None)); //subcomponent start_of_signals
                         instructions.push(add32()); // we get the position of the signal (with indexes) in memory
@@ -173,9 +156,14 @@ impl WriteWasm for StoreBucket {
                 }
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; getting src".to_string());
-	}
+        let trace_cmp = match (producer.needs_trace(), self.trace_name, &self.dest_address_type) {
+            (true, Some(name), AddressType::Signal) => Some((name, producer.get_offset_tag())),
+            (true, Some(name), AddressType::SubcmpSignal { .. }) => Some((name, producer.get_sub_cmp_tag())),
+            _ => None,
+        };
+        if trace_cmp.is_some() {
+            instructions.push(tee_local(producer.get_trace_dest_tag()));
+        }
         if self.context.size > 1 {
             instructions.push(set_local(producer.get_store_aux_1_tag()));
         }
@@ -185,61 +173,54 @@ impl WriteWasm for StoreBucket {
             instructions.push(call("$Fr_copy"));
         } else {
             instructions.push(set_local(producer.get_store_aux_2_tag()));
-            instructions.push(set_constant(&self.context.size.to_string()));
+            instructions.push(set_constant(self.context.size as i32));
             instructions.push(set_local(producer.get_copy_counter_tag()));
             instructions.push(add_block());
             instructions.push(add_loop());
             instructions.push(get_local(producer.get_copy_counter_tag()));
             instructions.push(eqz32());
-            instructions.push(br_if("1"));
+            instructions.push(br_if(1));
             instructions.push(get_local(producer.get_store_aux_1_tag()));
             instructions.push(get_local(producer.get_store_aux_2_tag()));
             instructions.push(call("$Fr_copy"));
             instructions.push(get_local(producer.get_copy_counter_tag()));
-            instructions.push(set_constant("1"));
+            instructions.push(set_constant(1));
             instructions.push(sub32());
             instructions.push(set_local(producer.get_copy_counter_tag()));
             instructions.push(get_local(producer.get_store_aux_1_tag()));
             let s = producer.get_size_32_bits_in_memory() * 4;
-            instructions.push(set_constant(&s.to_string()));
+            instructions.push(set_constant(s as i32));
             instructions.push(add32());
             instructions.push(set_local(producer.get_store_aux_1_tag()));
             instructions.push(get_local(producer.get_store_aux_2_tag()));
-            instructions.push(set_constant(&s.to_string()));
+            instructions.push(set_constant(s as i32));
             instructions.push(add32());
             instructions.push(set_local(producer.get_store_aux_2_tag()));
-            instructions.push(br("0"));
+            instructions.push(br(0));
             instructions.push(add_end());
             instructions.push(add_end());
         }
+        if let Some((name, cmp_tag)) = trace_cmp {
+            let pos = producer.get_string_list_start() + name * producer.get_size_of_message_in_bytes();
+            instructions.push(get_local(cmp_tag));
+            instructions.push(set_constant(pos as i32));
+            instructions.push(get_local(producer.get_trace_dest_tag()));
+            instructions.push(set_constant(self.context.size as i32));
+            instructions.push(call("$traceSignals"));
+        }
         match &self.dest_address_type {
             AddressType::SubcmpSignal { .. } => {
                 // if subcomponent input check if run needed
-		if producer.needs_comments() {
-                    instructions.push(";; decrease counter".to_string()); // by self.context.size
-		}
                 instructions.push(get_local(producer.get_sub_cmp_tag())); // to update input signal counter
                 instructions.push(get_local(producer.get_sub_cmp_tag())); // to read input signal counter
-                instructions.push(load32(Some(
-                    &producer.get_input_counter_address_in_component().to_string(),
-                ))); //remaining inputs to be set
-                instructions.push(set_constant(&self.context.size.to_string()));
+                instructions.push(load32(Some(producer.get_input_counter_address_in_component()))); //remaining inputs to be set
+                instructions.push(set_constant(self.context.size as i32));
                 instructions.push(sub32());
-                instructions.push(store32(Some(
-                    &producer.get_input_counter_address_in_component().to_string(),
-                ))); // update remaining inputs to be set
-		if producer.needs_comments() {
-                    instructions.push(";; check if run is needed".to_string());
-		}
+                instructions.push(store32(Some(producer.get_input_counter_address_in_component()))); // update remaining inputs to be set
                 instructions.push(get_local(producer.get_sub_cmp_tag()));
-                instructions.push(load32(Some(
-                    &producer.get_input_counter_address_in_component().to_string(),
-                )));
+                instructions.push(load32(Some(producer.get_input_counter_address_in_component())));
                 instructions.push(eqz32());
                 instructions.push(add_if());
-		if producer.needs_comments() {
-                    instructions.push(";; run sub component".to_string());
-		}
                 instructions.push(get_local(producer.get_sub_cmp_tag()));
                 match &self.dest {
                     LocationRule::Indexed { .. } => {
@@ -247,8 +228,8 @@ impl WriteWasm for StoreBucket {
                             instructions.push(call(&format!("${}_run", name)));
                             instructions.push(tee_local(producer.get_merror_tag()));
                             instructions.push(add_if());
-                            instructions.push(set_constant(&self.message_id.to_string()));
-                            instructions.push(set_constant(&self.line.to_string()));
+                            instructions.push(set_constant(self.message_id as i32));
+                            instructions.push(set_constant(self.line as i32));
                             instructions.push(call("$buildBufferMessage"));
                             instructions.push(call("$printErrorMessage"));
                             instructions.push(get_local(producer.get_merror_tag()));    
@@ -261,14 +242,11 @@ impl WriteWasm for StoreBucket {
                     LocationRule::Mapped { .. } => {
                         instructions.push(get_local(producer.get_sub_cmp_tag()));
                         instructions.push(load32(None)); // get template id
-                        instructions.push(call_indirect(
-                            &"$runsmap".to_string(),
-                            &"(type $_t_i32ri32)".to_string(),
-                        ));
+                        instructions.push(call_indirect("$runsmap", "$_t_i32ri32"));
                         instructions.push(tee_local(producer.get_merror_tag()));
                         instructions.push(add_if());
-                        instructions.push(set_constant(&self.message_id.to_string()));
-                        instructions.push(set_constant(&self.line.to_string()));
+                        instructions.push(set_constant(self.message_id as i32));
+                        instructions.push(set_constant(self.line as i32));
                         instructions.push(call("$buildBufferMessage"));
                         instructions.push(call("$printErrorMessage"));
                         instructions.push(get_local(producer.get_merror_tag()));    
@@ -276,16 +254,10 @@ impl WriteWasm for StoreBucket {
                         instructions.push(add_end());
                     }
                 }
-		if producer.needs_comments() {
-                    instructions.push(";; end run sub component".to_string());
-		}
                 instructions.push(add_end());
             }
             _ => (),
         }
-        if producer.needs_comments() {
-            instructions.push(";; end of store bucket".to_string());
-	}
         instructions
     }
 }
@@ -370,9 +342,25 @@ impl WriteC for StoreBucket {
         prologue.append(&mut src_prologue);
 	prologue.push(format!("// end load src"));	
         std::mem::drop(src_prologue);
+        let trace = match (producer.needs_trace(), self.trace_name, &self.dest_address_type) {
+            (true, Some(name), AddressType::Signal) => vec![build_trace_signal(
+                CTX_INDEX.to_string(),
+                &producer.get_string_table()[name],
+                aux_dest.clone(),
+                self.context.size,
+            )],
+            (true, Some(name), AddressType::SubcmpSignal { .. }) => vec![build_trace_signal(
+                format!("{}[{}]", MY_SUBCOMPONENTS, cmp_index_ref),
+                &producer.get_string_table()[name],
+                aux_dest.clone(),
+                self.context.size,
+            )],
+            _ => vec![],
+        };
         if self.context.size > 1 {
             let copy_arguments = vec![aux_dest, src, self.context.size.to_string()];
             prologue.push(format!("{};", build_call("Fr_copyn".to_string(), copy_arguments)));
+            prologue.extend(trace);
 	    if let AddressType::Signal = &self.dest_address_type {
 		if self.is_parallel && self.dest_is_output {
 		    prologue.push(format!("{{"));
@@ -389,6 +377,7 @@ impl WriteC for StoreBucket {
         } else {
             let copy_arguments = vec![aux_dest, src];
             prologue.push(format!("{};", build_call("Fr_copy".to_string(), copy_arguments)));
+            prologue.extend(trace);
 	    if let AddressType::Signal = &self.dest_address_type {
 		if self.is_parallel && self.dest_is_output {
 		    prologue.push(format!("{}->componentMemory[{}].mutexes[{}].lock();",CIRCOM_CALC_WIT,CTX_INDEX,aux_dest_index.clone()));
@@ -93,6 +93,7 @@ struct State {
     code: InstructionList,
     // string_table
     string_table: HashMap<String, usize>,
+    trace_flag: bool,
 }
 
 impl State {
@@ -118,6 +119,7 @@ impl State {
             max_stack_depth: 0,
             code: vec![],
             string_table : HashMap::new(),
+            trace_flag: false,
         }
     }
     fn reserve(fresh: &mut usize, size: usize) -> usize {
@@ -141,6 +143,15 @@ impl State {
     fn reserve_component_ids(&mut self, no_ids: usize) -> usize {
         State::reserve(&mut self.fresh_cmp_id, no_ids)
     }
+
+    // names shown in the execution trace are kept in the string table
+    fn trace_name(&mut self, name: &str) -> Option<usize> {
+        if !self.trace_flag {
+            return None;
+        }
+        let next = self.string_table.len();
+        Some(*self.string_table.entry(name.to_string()).or_insert(next))
+    }
 }
 
 struct Context<'a> {
@@ -227,6 +238,7 @@ fn initialize_constants(state: &mut State, constants: Vec<Argument>) {
                 dest: LocationRule::Indexed { location: full_address, template_header: None },
                 context: InstrContext { size: 1 },
                 src: content,
+                trace_name: None,
             }
             .allocate();
             state.code.push(store_instruction);
@@ -308,6 +320,16 @@ fn create_uniform_components(state: &mut State, triggers: &[Trigger], cluster: T
         let first = cluster.slice.start;
         let c_info = &triggers[first];
         let symbol = state.environment.get_variable(&c_info.component_name).unwrap().clone();
+        // arrays of components keep their dimensions so that the position can be rebuilt
+        let trace_name = if compute_number_cmp(&symbol.dimensions) > 1 {
+            state.trace_name(&format!(
+                "{}{}",
+                c_info.component_name,
+                symbol.dimensions.iter().fold(String::new(), |acc, d| format!("{}[{}]", acc, d))
+            ))
+        } else {
+            state.trace_name(&c_info.component_name)
+        };
         let creation_instr = CreateCmpBucket {
             line: 0,
             is_parallel: state.is_parallel,
@@ -325,6 +347,7 @@ fn create_uniform_components(state: &mut State, triggers: &[Trigger], cluster: T
             dimensions: symbol.dimensions,
             signal_offset_jump: offset_jump,
 	    component_offset_jump: component_offset_jump,
+            trace_name,
         }
         .allocate();
         state.code.push(creation_instr);
@@ -369,12 +392,14 @@ fn create_mixed_components(state: &mut State, triggers: &[Trigger], cluster: Tri
             stack: vec![symbol.access_instruction.clone(), jump],
         }
         .allocate();
+        let name_subcomponent = format!("{}{}",c_info.component_name.clone(), c_info.indexed_with.iter().fold(String::new(), |acc, &num| format!("{}[{}]", acc, &num.to_string())));
+        let trace_name = state.trace_name(&name_subcomponent);
         let creation_instr = CreateCmpBucket {
             line: 0,
             is_parallel: state.is_parallel,
             message_id: state.message_id,
             symbol: c_info.runs.clone(),
-            name_subcomponent: format!("{}{}",c_info.component_name.clone(), c_info.indexed_with.iter().fold(String::new(), |acc, &num| format!("{}[{}]", acc, &num.to_string()))),
+            name_subcomponent,
             defined_positions: vec![0],
             dimensions: symbol.dimensions,
             cmp_unique_id: id,
@@ -386,6 +411,7 @@ fn create_mixed_components(state: &mut State, triggers: &[Trigger], cluster: Tri
             number_of_cmp: 1,
             signal_offset_jump: 0,
 	    component_offset_jump: 0,
+            trace_name,
         }
         .allocate();
         state.code.push(creation_instr);
@@ -495,7 +521,7 @@ fn translate_call_case(
     use Expression::Call;
     if let Call { id, args, .. } = info.src {
         let args_instr = translate_call_arguments(args, state, context);
-        info.prc_symbol.into_call_assign(id, args_instr, &state)
+        info.prc_symbol.into_call_assign(id, args_instr, state)
     } else {
         unreachable!()
     }
@@ -672,6 +698,7 @@ fn translate_call(
             is_parallel: state.is_parallel,
             line: context.files.get_line(meta.start, meta.get_file_id()).unwrap(),
             message_id: state.message_id,
+            trace_name: state.trace_name(&id),
             symbol: id,
             argument_types: args_inst.argument_data,
             arguments: args_inst.arguments,
@@ -847,6 +874,7 @@ struct ProcessedSymbol {
     xtype: TypeReduction,
     signal: Option<LocationRule>,
     signal_type: Option<SignalType>,
+    signal_name: Option<String>,
     before_signal: Vec<InstructionPointer>,
 }
 
@@ -861,6 +889,7 @@ impl ProcessedSymbol {
         let mut with_length = symbol_info.dimensions.iter().fold(1, |r, c| r * (*c));
         let mut signal = None;
         let mut signal_type = state.signal_to_type.get(&symbol_name).cloned();
+        let mut signal_name = signal_type.map(|_| symbol_name.clone());
         let mut bf_index = vec![];
         let mut af_index = vec![];
         for acc in definition.acc {
@@ -877,6 +906,7 @@ impl ProcessedSymbol {
                     with_length = 1;
                     let cmp_id = *state.component_to_instance.get(&symbol_name).unwrap();
                     signal_type = context.tmp_database.signal_info[cmp_id].get(&name).cloned();
+                    signal_name = Some(name.clone());
                     signal = Some(name);
                 }
             }
@@ -899,7 +929,8 @@ impl ProcessedSymbol {
             name: symbol_name,
             before_signal: bf_index,
             signal: signal_location,
-            signal_type
+            signal_type,
+            signal_name,
         }
     }
 
@@ -907,8 +938,12 @@ impl ProcessedSymbol {
         self,
         id: String,
         args: ArgData,
-        state: &State,
+        state: &mut State,
     ) -> InstructionPointer {
+        let signal_trace_name = match &self.signal_name {
+            Some(name) => state.trace_name(name),
+            None => None,
+        };
         let data = if let Option::Some(signal) = self.signal {
             let dest_type = AddressType::SubcmpSignal {
                 is_parallel: *state.component_to_parallel.get(&self.name).unwrap(),
@@ -924,6 +959,7 @@ impl ProcessedSymbol {
                 dest_is_output: false,
                 dest_address_type: dest_type,
                 dest: signal,
+                trace_name: signal_trace_name,
             }
         } else {
             let address = compute_full_address(state, self.symbol, self.before_signal);
@@ -936,12 +972,14 @@ impl ProcessedSymbol {
                 dest_is_output: self.signal_type.map_or(false, |t| t == SignalType::Output),
                 dest_address_type: xtype,
                 dest: LocationRule::Indexed { location: address, template_header: None },
+                trace_name: signal_trace_name,
             }
         };
         CallBucket {
             is_parallel: state.is_parallel,
             line: self.line,
             message_id: self.message_id,
+            trace_name: state.trace_name(&id),
             symbol: id,
             argument_types: args.argument_data,
             arguments: args.arguments,
@@ -951,7 +989,11 @@ impl ProcessedSymbol {
         .allocate()
     }
 
-    fn into_store(self, src: InstructionPointer, state: &State) -> InstructionPointer {
+    fn into_store(self, src: InstructionPointer, state: &mut State) -> InstructionPointer {
+        let trace_name = match &self.signal_name {
+            Some(name) => state.trace_name(name),
+            None => None,
+        };
         if let Option::Some(signal) = self.signal {
             let dest_type = AddressType::SubcmpSignal {
                 is_parallel: *state.component_to_parallel.get(&self.name).unwrap(),
@@ -971,6 +1013,7 @@ impl ProcessedSymbol {
                 dest_is_output: false,
                 dest_address_type: dest_type,
                 is_parallel: state.is_parallel,
+                trace_name,
             }
             .allocate()
         } else {
@@ -988,6 +1031,7 @@ impl ProcessedSymbol {
                 dest: LocationRule::Indexed { location: address, template_header: None },
                 context: InstrContext { size: self.length },
                 is_parallel: state.is_parallel,
+                trace_name,
             }
             .allocate()
         }
@@ -1173,7 +1217,8 @@ pub struct CodeInfo<'a> {
     pub functions: &'a HashMap<String, Vec<Length>>,
     pub field_tracker: FieldTracker,
     pub component_to_parallel: HashMap<String, bool>,
-    pub string_table: HashMap<String, usize>
+    pub string_table: HashMap<String, usize>,
+    pub trace_flag: bool,
 }
 
 pub struct CodeOutput {
@@ -1196,6 +1241,7 @@ pub fn translate_code(body: Statement, code_info: CodeInfo) -> CodeOutput {
         code_info.component_to_parallel,
     );
     state.string_table = code_info.string_table;
+    state.trace_flag = code_info.trace_flag;
     initialize_components(&mut state, code_info.components);
     initialize_signals(&mut state, code_info.signals);
     initialize_constants(&mut state, code_info.constants);
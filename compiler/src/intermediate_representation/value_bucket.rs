@@ -49,26 +49,20 @@ impl ToString for ValueBucket {
 }
 
 impl WriteWasm for ValueBucket {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
-        if producer.needs_comments() {
-            instructions.push(";; value bucket".to_string());
-	}
         match &self.parse_as {
             ValueType::U32 => {
-                instructions.push(set_constant(&self.value.to_string()));
+                instructions.push(set_constant(self.value as i32));
             }
             ValueType::BigInt => {
                 let mut const_pos = self.value;
-                const_pos *= (producer.get_size_32_bit() + 2) * 4;
+                const_pos *= producer.get_size_32_bits_in_memory() * 4;
                 const_pos += producer.get_constant_numbers_start();
-                instructions.push(set_constant(&const_pos.to_string()));
+                instructions.push(set_constant(const_pos as i32));
             }
         }
-        if producer.needs_comments() {
-            instructions.push(";; end of value bucket".to_string());
-	}
         instructions
     }
 }
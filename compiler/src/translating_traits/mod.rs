@@ -1,5 +1,6 @@
 use code_producers::c_elements::*;
 use code_producers::wasm_elements::*;
+use code_producers::wasm_elements::wasm_binary::WasmModule;
 use std::io::Write;
 
 pub trait WriteC {
@@ -19,11 +20,9 @@ pub trait WriteC {
 }
 
 pub trait WriteWasm {
-    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String>;
-    fn write_wasm<T: Write>(&self, writer: &mut T, producer: &WASMProducer) -> Result<(), ()> {
+    fn produce_wasm(&self, producer: &WASMProducer) -> Vec<WasmInstruction>;
+    fn write_wasm(&self, module: &mut WasmModule, producer: &WASMProducer) -> Result<(), String> {
         let wasm_instructions = self.produce_wasm(producer);
-        let code = wasm_code_generator::merge_code(wasm_instructions);
-        writer.write_all(code.as_bytes()).map_err(|_| {})?;
-        writer.flush().map_err(|_| {})
+        module.add_code(&wasm_instructions)
     }
 }
@@ -13,12 +13,13 @@ use super::environment_utils::{
 use program_structure::constants::UsefulConstants;
 
 use super::execution_data::analysis::Analysis;
+use super::function_cache::FunctionCache;
 use super::execution_data::{ExecutedProgram, ExecutedTemplate, NodePointer};
 use super::{
     ast::*, ArithmeticError, FileID, ProgramArchive, Report, ReportCode, ReportCollection
 };
 use circom_algebra::num_bigint::BigInt;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 type AExpr = ArithmeticExpressionGen<String>;
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -32,14 +33,22 @@ struct RuntimeInformation {
     pub analysis: Analysis,
     pub public_inputs: Vec<String>,
     pub constants: UsefulConstants,
-    pub call_trace: Vec<String>,
+    pub call_trace: Vec<CallFrame>,
+    pub pending_component: Option<String>,
     pub current_file: FileID,
     pub runtime_errors: ReportCollection,
     pub environment: ExecutionEnvironment,
     pub exec_program: ExecutedProgram,
+    pub function_cache: FunctionCache,
+    pub limits: ExecutionLimits,
+    pub steps: usize,
+    pub active_loops: Vec<Meta>,
+    pub log_compile_time: bool,
+    pub signal_asserts: SignalAssertMode,
+    pub reported_asserts: HashSet<(FileID, usize)>,
 }
 impl RuntimeInformation {
-    pub fn new(current_file: FileID, id_max: usize, prime: &String) -> RuntimeInformation {
+    pub fn new(current_file: FileID, id_max: usize, prime: &String, limits: ExecutionLimits) -> RuntimeInformation {
         RuntimeInformation {
             current_file,
             block_type: BlockType::Known,
@@ -47,9 +56,45 @@ impl RuntimeInformation {
             public_inputs: vec![],
             constants: UsefulConstants::new(prime),
             call_trace: Vec::new(),
+            pending_component: Option::None,
             runtime_errors: ReportCollection::new(),
             environment: ExecutionEnvironment::new(),
             exec_program: ExecutedProgram::new(prime),
+            function_cache: FunctionCache::new(),
+            log_compile_time: false,
+            signal_asserts: SignalAssertMode::Ignore,
+            reported_asserts: HashSet::new(),
+            limits,
+            steps: 0,
+            active_loops: Vec::new(),
+        }
+    }
+}
+
+// A function call or template instantiation in execution. Runtime reports
+// show the frames to explain how the failing code was reached, and the
+// arguments are only formatted when a report is built
+struct CallFrame {
+    pub id: String,
+    pub parameters: Vec<String>,
+    pub arguments: Vec<AExpressionSlice>,
+    pub component: Option<String>,
+    pub meta: Meta,
+}
+impl CallFrame {
+    pub fn description(&self) -> String {
+        let mut parameters = Vec::new();
+        for (name, value) in self.parameters.iter().zip(&self.arguments) {
+            let mut value = value.to_string();
+            if value.len() > 64 {
+                value.truncate(61);
+                value.push_str("...");
+            }
+            parameters.push(format!("{}={}", name, value));
+        }
+        match &self.component {
+            Option::Some(path) => format!("{} = {}({})", path, self.id, parameters.join(", ")),
+            Option::None => format!("{}({})", self.id, parameters.join(", ")),
         }
     }
 }
@@ -73,6 +118,31 @@ impl Default for FoldedValue {
     }
 }
 
+// Bounds the work done while executing the program, so that an unbounded
+// recursion is reported instead of crashing the compiler. The number of
+// statements is only bounded when it is requested, to stop loops that never end
+#[derive(Copy, Clone)]
+pub struct ExecutionLimits {
+    pub max_steps: Option<usize>,
+    pub max_recursion_depth: usize,
+}
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        ExecutionLimits { max_steps: None, max_recursion_depth: 1000 }
+    }
+}
+
+// How the asserts whose condition depends on the value of signals are treated.
+// They are only checked by the witness generator, so the proofs do not ensure
+// them unless they are turned into constraints
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum SignalAssertMode {
+    Ignore,
+    Warn,
+    Error,
+    Constrain,
+}
+
 enum ExecutionError {
     NonQuadraticConstraint,
     FalseAssert,
@@ -81,9 +151,14 @@ enum ExecutionError {
 pub fn constraint_execution(
     program_archive: &ProgramArchive,
     flag_verbose: bool, 
-    prime: &String,) -> Result<(ExecutedProgram, ReportCollection), ReportCollection> {    let main_file_id = program_archive.get_file_id_main();
-    let mut runtime_information = RuntimeInformation::new(*main_file_id, program_archive.id_max, prime);
+    prime: &String,
+    limits: ExecutionLimits,
+    log_compile_time: bool,
+    signal_asserts: SignalAssertMode,) -> Result<(ExecutedProgram, ReportCollection), ReportCollection> {    let main_file_id = program_archive.get_file_id_main();
+    let mut runtime_information = RuntimeInformation::new(*main_file_id, program_archive.id_max, prime, limits);
     runtime_information.public_inputs = program_archive.get_public_inputs_main_component().clone();
+    runtime_information.log_compile_time = log_compile_time;
+    runtime_information.signal_asserts = signal_asserts;
     let folded_value_result = execute_expression(
         program_archive.get_main_expression(),
         program_archive,
@@ -94,6 +169,9 @@ pub fn constraint_execution(
         Result::Err(_) => Result::Err(runtime_information.runtime_errors),
         Result::Ok(folded_value) => {
             debug_assert!(FoldedValue::valid_node_pointer(&folded_value));
+            if flag_verbose {
+                runtime_information.function_cache.print_statistics();
+            }
             Result::Ok((runtime_information.exec_program, runtime_information.runtime_errors))
         }
     }
@@ -107,7 +185,8 @@ pub fn execute_constant_expression(
     prime: &String,
 ) -> Result<BigInt, ReportCollection> {
     let current_file = expression.get_meta().get_file_id();
-    let mut runtime_information = RuntimeInformation::new(current_file, program_archive.id_max, prime);
+    let mut runtime_information =
+        RuntimeInformation::new(current_file, program_archive.id_max, prime, ExecutionLimits::default());
     runtime_information.environment = environment;
     let folded_value_result =
         execute_expression(expression, program_archive, &mut runtime_information, flag_verbose);
@@ -135,6 +214,18 @@ fn execute_statement(
     use Statement::*;
     let id = stmt.get_meta().elem_id;
     Analysis::reached(&mut runtime.analysis, id);
+    if let Some(max_steps) = runtime.limits.max_steps {
+        runtime.steps += 1;
+        if runtime.steps > max_steps {
+            let meta = runtime.active_loops.last().unwrap_or(stmt.get_meta()).clone();
+            let report = Report::error(
+                format!("Execution budget of {} steps exceeded", max_steps),
+                ReportCode::ExecutionLimitExceeded,
+            );
+            add_limit_report(report, &meta, runtime, "use --max-exec-steps to raise the budget");
+            return Result::Err(());
+        }
+    }
     let res = match stmt {
         InitializationBlock { initializations, .. } => {
             let possible_fold = execute_sequence_of_statements(
@@ -190,6 +281,12 @@ fn execute_statement(
         }
         Substitution { meta, var, access, op, rhe, .. } => {
             let access_information = treat_accessing(meta, access, program_archive, runtime, flag_verbose)?;
+            let is_component = ExecutionEnvironment::has_component(&runtime.environment, var);
+            runtime.pending_component = if is_component && access_information.signal_access.is_none() {
+                Option::Some(create_symbol(var, &access_information))
+            } else {
+                Option::None
+            };
             let r_folded = execute_expression(rhe, program_archive, runtime, flag_verbose)?;
             let possible_constraint =
                 perform_assign(meta, var, &access_information, r_folded, actual_node, runtime)?;
@@ -209,7 +306,7 @@ fn execute_statement(
                     let symbol = AExpr::Signal { symbol: constrained.left };
                     let expr = AExpr::sub(&symbol, &constrained.right, &p);
                     let ctr = AExpr::transform_expression_to_constraint_form(expr, &p).unwrap();
-                    node.add_constraint(ctr);
+                    node.add_constraint(ctr, meta);
                 }
             }
             Option::None
@@ -237,7 +334,7 @@ fn execute_statement(
             )
             .unwrap();
             if let Option::Some(node) = actual_node {
-                node.add_constraint(constraint_expression);
+                node.add_constraint(constraint_expression, meta);
             }
             Option::None
         }
@@ -264,33 +361,12 @@ fn execute_statement(
             )?;
             possible_return
         }
-        While { cond, stmt, .. } => loop {
-            let (returned, condition_result) = execute_conditional_statement(
-                cond,
-                stmt,
-                Option::None,
-                program_archive,
-                runtime,
-                actual_node,
-                flag_verbose
-            )?;
-            if returned.is_some() {
-                break returned;
-            } else if condition_result.is_none() {
-                let (returned, _) = execute_conditional_statement(
-                    cond,
-                    stmt,
-                    None,
-                    program_archive,
-                    runtime,
-                    actual_node,
-                    flag_verbose
-                )?;
-                break returned;
-            } else if !condition_result.unwrap() {
-                break returned;
-            }
-        },
+        While { meta, cond, stmt, .. } => {
+            runtime.active_loops.push(meta.clone());
+            let returned = execute_while(cond, stmt, program_archive, runtime, actual_node, flag_verbose)?;
+            runtime.active_loops.pop();
+            returned
+        }
         Block { stmts, .. } => {
             ExecutionEnvironment::add_variable_block(&mut runtime.environment);
             let return_value =
@@ -299,23 +375,20 @@ fn execute_statement(
             return_value
         }
         LogCall { args, .. } => {
-            if flag_verbose{
+            if flag_verbose || runtime.log_compile_time {
+                let mut line = match current_component(&runtime.call_trace) {
+                    Some(component) => format!("[{}] ", component),
+                    None => String::new(),
+                };
                 for arglog in args {
-                    if let LogArgument::LogExp(arg) = arglog{
-                        let f_result = execute_expression(arg, program_archive, runtime, flag_verbose)?;
-                        let arith = safe_unwrap_to_single_arithmetic_expression(f_result, line!());
-                        if AExpr::is_number(&arith){
-                            print!("{}", arith);
-                        }
-                        else{
-                            print!("Unknown")
+                    match arglog {
+                        LogArgument::LogExp(arg) => {
+                            line.push_str(&log_value(arg, program_archive, runtime, flag_verbose))
                         }
-                    }
-                    else if let LogArgument::LogStr(s) = arglog {
-                            print!("{}",s);
+                        LogArgument::LogStr(s) => line.push_str(s),
                     }
                 }
-                println!("");
+                println!("{}", line);
             }
             Option::None
         }
@@ -325,6 +398,10 @@ fn execute_statement(
             let possible_bool = AExpr::get_boolean_equivalence(&arith, runtime.constants.get_p());
             let result = match possible_bool {
                 Some(b) if !b => Err(ExecutionError::FalseAssert),
+                None if runtime.signal_asserts != SignalAssertMode::Ignore => {
+                    execute_signal_dependent_assert(arg, meta, program_archive, runtime, actual_node, flag_verbose)?;
+                    Ok(None)
+                }
                 _ => Ok(None),
             };
             treat_result_with_execution_error(
@@ -461,34 +538,60 @@ fn execute_expression(
                 FoldedValue { arithmetic_slice, ..FoldedValue::default() }
             }
         }
-        Call { id, args, .. } => {
+        Call { meta, id, args, .. } => {
+            if runtime.call_trace.len() >= runtime.limits.max_recursion_depth {
+                let report = Report::error(
+                    format!(
+                        "Maximum recursion depth of {} exceeded when calling {}",
+                        runtime.limits.max_recursion_depth, id
+                    ),
+                    ReportCode::ExecutionLimitExceeded,
+                );
+                add_limit_report(report, meta, runtime, "use --max-recursion-depth to raise the limit");
+                return Result::Err(());
+            }
             let mut arg_values = Vec::new();
             for arg_expression in args.iter() {
                 let f_arg = execute_expression(arg_expression, program_archive, runtime, flag_verbose)?;
                 arg_values.push(safe_unwrap_to_arithmetic_slice(f_arg, line!()));
             }
-            let new_environment = prepare_environment_for_call(id, &arg_values, program_archive);
-            let previous_environment = std::mem::replace(&mut runtime.environment, new_environment);
-            let previous_block_type = std::mem::replace(&mut runtime.block_type, BlockType::Known);
-
-            let new_file_id = if program_archive.contains_function(id) {
-                program_archive.get_function_data(id).get_file_id()
+            let cache_key = if program_archive.contains_function(id) {
+                runtime.function_cache.key(id, &arg_values, program_archive)
             } else {
-                program_archive.get_template_data(id).get_file_id()
+                Option::None
             };
-            let previous_id = std::mem::replace(&mut runtime.current_file, new_file_id);
-
-            runtime.call_trace.push(id.clone());
-            let folded_result = if program_archive.contains_function(id) {
-                execute_function_call(id, program_archive, runtime, flag_verbose)?
+            let cached = cache_key.and_then(|key| runtime.function_cache.get(key, id, &arg_values));
+            if let Option::Some(value) = cached {
+                FoldedValue { arithmetic_slice: Option::Some(value), ..FoldedValue::default() }
             } else {
-                execute_template_call(id, &arg_values, program_archive, runtime, flag_verbose)?
-            };
-            runtime.environment = previous_environment;
-            runtime.current_file = previous_id;
-            runtime.block_type = previous_block_type;
-            runtime.call_trace.pop();
-            folded_result
+                let new_environment = prepare_environment_for_call(id, &arg_values, program_archive);
+                let previous_environment = std::mem::replace(&mut runtime.environment, new_environment);
+                let previous_block_type = std::mem::replace(&mut runtime.block_type, BlockType::Known);
+
+                let new_file_id = if program_archive.contains_function(id) {
+                    program_archive.get_function_data(id).get_file_id()
+                } else {
+                    program_archive.get_template_data(id).get_file_id()
+                };
+                let previous_id = std::mem::replace(&mut runtime.current_file, new_file_id);
+
+                let frame = create_call_frame(meta, id, &arg_values, program_archive, runtime);
+                runtime.call_trace.push(frame);
+                let folded_result = if program_archive.contains_function(id) {
+                    execute_function_call(id, program_archive, runtime, flag_verbose)?
+                } else {
+                    execute_template_call(id, &arg_values, program_archive, runtime, flag_verbose)?
+                };
+                runtime.environment = previous_environment;
+                runtime.current_file = previous_id;
+                runtime.block_type = previous_block_type;
+                runtime.call_trace.pop();
+                if let Option::Some(key) = cache_key {
+                    let value = folded_result.arithmetic_slice.clone().unwrap();
+                    runtime.function_cache.insert(key, id, &arg_values, value);
+                }
+                folded_result
+            }
         }
     };
     let expr_id = expr.get_meta().elem_id;
@@ -756,6 +859,43 @@ fn execute_conditional_statement(
     }
 }
 
+fn execute_while(
+    condition: &Expression,
+    body: &Statement,
+    program_archive: &ProgramArchive,
+    runtime: &mut RuntimeInformation,
+    actual_node: &mut Option<ExecutedTemplate>,
+    flag_verbose: bool,
+) -> Result<Option<FoldedValue>, ()> {
+    loop {
+        let (returned, condition_result) = execute_conditional_statement(
+            condition,
+            body,
+            Option::None,
+            program_archive,
+            runtime,
+            actual_node,
+            flag_verbose
+        )?;
+        if returned.is_some() {
+            return Result::Ok(returned);
+        } else if condition_result.is_none() {
+            let (returned, _) = execute_conditional_statement(
+                condition,
+                body,
+                None,
+                program_archive,
+                runtime,
+                actual_node,
+                flag_verbose
+            )?;
+            return Result::Ok(returned);
+        } else if !condition_result.unwrap() {
+            return Result::Ok(returned);
+        }
+    }
+}
+
 fn execute_sequence_of_statements(
     stmts: &[Statement],
     program_archive: &ProgramArchive,
@@ -1001,6 +1141,146 @@ fn prepare_environment_for_call(
     environment
 }
 
+fn create_call_frame(
+    meta: &Meta,
+    id: &str,
+    arg_values: &[AExpressionSlice],
+    program_archive: &ProgramArchive,
+    runtime: &mut RuntimeInformation,
+) -> CallFrame {
+    let (parameters, component) = if program_archive.contains_function(id) {
+        (program_archive.get_function_data(id).get_name_of_params(), Option::None)
+    } else {
+        let name = runtime.pending_component.take().unwrap_or_else(|| "main".to_string());
+        let path = match current_component(&runtime.call_trace) {
+            Option::Some(parent) => format!("{}.{}", parent, name),
+            Option::None => name,
+        };
+        (program_archive.get_template_data(id).get_name_of_params(), Option::Some(path))
+    };
+    CallFrame {
+        id: id.to_string(),
+        parameters: parameters.clone(),
+        arguments: arg_values.to_vec(),
+        component,
+        meta: meta.clone(),
+    }
+}
+
+// Handles an assert whose condition is not known while generating the
+// constraints. A conjunction of quadratic equalities in a template is replaced
+// by its constraints in the constrain mode, any other one is reported
+fn execute_signal_dependent_assert(
+    arg: &Expression,
+    meta: &Meta,
+    program_archive: &ProgramArchive,
+    runtime: &mut RuntimeInformation,
+    actual_node: &mut Option<ExecutedTemplate>,
+    flag_verbose: bool,
+) -> Result<(), ()> {
+    let mode = runtime.signal_asserts;
+    let mut reason = None;
+    if mode == SignalAssertMode::Constrain {
+        match (actual_node.as_mut(), equalities_of(arg)) {
+            (None, _) => reason = Some("it is not in a template"),
+            (_, None) => reason = Some("only equalities and conjunctions of equalities can be"),
+            (Some(_), _) if runtime.block_type == BlockType::Unknown => {
+                reason = Some("it depends on a condition over signals")
+            }
+            (Some(node), Some(equalities)) => {
+                let mut constraints = Vec::new();
+                for (lhe, rhe) in &equalities {
+                    let f_left = execute_expression(lhe, program_archive, runtime, flag_verbose)?;
+                    let f_right = execute_expression(rhe, program_archive, runtime, flag_verbose)?;
+                    let arith_left = safe_unwrap_to_single_arithmetic_expression(f_left, line!());
+                    let arith_right = safe_unwrap_to_single_arithmetic_expression(f_right, line!());
+                    let difference = AExpr::sub(&arith_left, &arith_right, runtime.constants.get_p());
+                    if difference.is_nonquadratic() {
+                        break;
+                    }
+                    match AExpr::transform_expression_to_constraint_form(difference, runtime.constants.get_p()) {
+                        Some(constraint) => constraints.push(constraint),
+                        None => break,
+                    }
+                }
+                if constraints.len() == equalities.len() {
+                    for constraint in constraints {
+                        node.add_constraint(constraint, meta);
+                    }
+                    return Result::Ok(());
+                }
+                reason = Some("its equalities are not quadratic");
+            }
+        }
+    }
+
+    let message = "Assert depends on the value of signals, it is checked by the witness generator but not enforced by the constraints".to_string();
+    let mut report = if mode == SignalAssertMode::Error {
+        Report::error(message, ReportCode::SignalDependentAssert)
+    } else if runtime.reported_asserts.insert((meta.get_file_id(), meta.location.start)) {
+        Report::warning(message, ReportCode::SignalDependentAssert)
+    } else {
+        return Result::Ok(());
+    };
+    let note = match reason {
+        Some(reason) => format!("It can not be replaced by constraints: {}", reason),
+        None => "Use === to constrain the condition, or --signal-asserts constrain to replace its quadratic equalities by constraints".to_string(),
+    };
+    report.add_note(note);
+    add_report_to_runtime(report, meta, &mut runtime.runtime_errors, &runtime.call_trace);
+    if mode == SignalAssertMode::Error {
+        Result::Err(())
+    } else {
+        Result::Ok(())
+    }
+}
+
+// Sides of the equalities of a condition that is an equality or a conjunction
+// of them
+fn equalities_of(condition: &Expression) -> Option<Vec<(&Expression, &Expression)>> {
+    use program_structure::ast::ExpressionInfixOpcode::{BoolAnd, Eq};
+    match condition {
+        Expression::InfixOp { infix_op: Eq, lhe, rhe, .. } => Some(vec![(lhe.as_ref(), rhe.as_ref())]),
+        Expression::InfixOp { infix_op: BoolAnd, lhe, rhe, .. } => {
+            let mut equalities = equalities_of(lhe)?;
+            equalities.append(&mut equalities_of(rhe)?);
+            Some(equalities)
+        }
+        _ => None,
+    }
+}
+
+// Value of a log argument if it is known while generating the constraints.
+// The errors found evaluating it are discarded, the witness generator will
+// report them if the log is reached
+fn log_value(
+    arg: &Expression,
+    program_archive: &ProgramArchive,
+    runtime: &mut RuntimeInformation,
+    flag_verbose: bool,
+) -> String {
+    const UNKNOWN: &str = "<signal-dependent>";
+    let reported = runtime.runtime_errors.len();
+    match execute_expression(arg, program_archive, runtime, flag_verbose) {
+        Result::Ok(f_result) => {
+            let arith = safe_unwrap_to_single_arithmetic_expression(f_result, line!());
+            if AExpr::is_number(&arith) {
+                arith.to_string()
+            } else {
+                UNKNOWN.to_string()
+            }
+        }
+        Result::Err(()) => {
+            runtime.runtime_errors.truncate(reported);
+            UNKNOWN.to_string()
+        }
+    }
+}
+
+fn current_component(call_trace: &[CallFrame]) -> Option<&String> {
+    call_trace.iter().rev().find_map(|frame| frame.component.as_ref())
+}
+
 fn execute_function_call(
     id: &str,
     program_archive: &ProgramArchive,
@@ -1292,7 +1572,7 @@ fn treat_result_with_arithmetic_error<C>(
     arithmetic_error: Result<C, ArithmeticError>,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) -> Result<C, ()> {
     use ReportCode::RuntimeError;
     match arithmetic_error {
@@ -1316,7 +1596,7 @@ fn treat_result_with_memory_error_void(
     memory_error: Result<(), MemoryError>,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) -> Result<(), ()> {
     use ReportCode::RuntimeError;
     match memory_error {
@@ -1357,7 +1637,7 @@ fn treat_result_with_memory_error<C>(
     memory_error: Result<C, MemoryError>,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) -> Result<C, ()> {
     use ReportCode::RuntimeError;
     match memory_error {
@@ -1392,7 +1672,7 @@ fn treat_result_with_environment_error<C>(
     environment_error: Result<C, ExecutionEnvironmentError>,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) -> Result<C, ()> {
     use ReportCode::*;
     match environment_error {
@@ -1413,7 +1693,7 @@ fn treat_result_with_execution_error<C>(
     execution_error: Result<C, ExecutionError>,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) -> Result<C, ()> {
     use ExecutionError::*;
     match execution_error {
@@ -1434,22 +1714,182 @@ fn treat_result_with_execution_error<C>(
     }
 }
 
+fn add_limit_report(report: Report, meta: &Meta, runtime: &mut RuntimeInformation, hint: &str) {
+    let mut report = report;
+    report.add_note(hint.to_string());
+    add_report_to_runtime(report, meta, &mut runtime.runtime_errors, &runtime.call_trace);
+}
+
 fn add_report_to_runtime(
     report: Report,
     meta: &Meta,
     runtime_errors: &mut ReportCollection,
-    call_trace: &[String],
+    call_trace: &[CallFrame],
 ) {
     let mut report = report;
     report.add_primary(meta.location.clone(), meta.get_file_id(), "found here".to_string());
+    if let Option::Some(component) = current_component(call_trace) {
+        report.add_note(format!("in component {}", component));
+    }
 
     let mut trace = "call trace:\n".to_string();
     let mut spacing = "".to_string();
-    for call in call_trace.iter() {
-        let msg = format!("{}->{}\n", spacing, call);
-        trace.push_str(msg.as_str());
-        spacing.push_str(" ");
+    let mut i = 0;
+    while i < call_trace.len() {
+        // Of a chain of recursive calls only the first and the last are shown
+        let frame = &call_trace[i];
+        let repeated = call_trace[i..].iter().take_while(|call| call.id == frame.id).count();
+        let mut shown = vec![frame];
+        if repeated > 1 {
+            shown.push(&call_trace[i + repeated - 1]);
+        }
+        for (j, frame) in shown.into_iter().enumerate() {
+            if j == 1 && repeated > 2 {
+                trace.push_str(&format!("{}... {} recursive calls to {}\n", spacing, repeated - 2, frame.id));
+            }
+            trace.push_str(&format!("{}->{}\n", spacing, frame.description()));
+            spacing.push(' ');
+            let label = match &frame.component {
+                Option::Some(path) => format!("{} instantiated here", path),
+                Option::None => format!("{} called here", frame.id),
+            };
+            report.add_secondary(frame.meta.location.clone(), frame.meta.get_file_id(), Option::Some(label));
+        }
+        i += repeated;
     }
     report.add_note(trace);
     runtime_errors.push(report);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_source(name: &str, src: &str) -> ProgramArchive {
+        let dir = std::env::temp_dir().join(format!("circom_execute_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("circuit.circom");
+        std::fs::write(&file, src).unwrap();
+        let (mut program, _) = parser::run_parser(file.to_str().unwrap().to_string(), env!("CARGO_PKG_VERSION"), false, &[])
+            .ok()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(type_analysis::check_types::check_types(&mut program).is_ok());
+        program
+    }
+
+    fn execute_source(name: &str, src: &str, limits: ExecutionLimits) -> Result<(ExecutedProgram, ReportCollection), ReportCollection> {
+        let program = parse_source(name, src);
+        constraint_execution(&program, false, &"bn128".to_string(), limits, false, SignalAssertMode::Ignore)
+    }
+
+    const LONG_LOOP: &str = "pragma circom 2.0.0;
+function sum(n) {
+    var acc = 0;
+    for (var i = 0; i < n; i++) {
+        acc += i;
+    }
+    return acc;
+}
+template Main() {
+    signal output out;
+    out <== sum(20000);
+}
+component main = Main();
+";
+
+    #[test]
+    fn step_budget_is_opt_in() {
+        assert!(execute_source("unbounded", LONG_LOOP, ExecutionLimits::default()).is_ok());
+        let limits = ExecutionLimits { max_steps: Some(1000), ..ExecutionLimits::default() };
+        let reports = execute_source("bounded", LONG_LOOP, limits).err().unwrap();
+        assert!(reports.iter().any(|r| r.is_error()));
+    }
+
+    #[test]
+    fn cached_calls_return_the_value_of_the_call() {
+        let src = "pragma circom 2.0.0;
+function f(n, a) {
+    var r[2];
+    for (var i = 0; i < n; i++) {
+        r[0] += a[i] * i;
+    }
+    r[1] = n;
+    return r;
+}
+template Main() {
+    signal output out;
+    var r[2] = f(1, [1]);
+    out <== r[1];
+}
+component main = Main();
+";
+        let program = parse_source("cache", src);
+        let limits = ExecutionLimits { max_steps: Some(1000000), ..ExecutionLimits::default() };
+        let mut runtime = RuntimeInformation::new(*program.get_file_id_main(), program.id_max, &"bn128".to_string(), limits);
+        let meta = || Meta::new(0, 0);
+        let number = |value: u64| Expression::Number(meta(), BigInt::from(value));
+        let call = |size: u64| Expression::Call {
+            meta: meta(),
+            id: "f".to_string(),
+            args: vec![number(3), Expression::UniformArray { meta: meta(), value: Box::new(number(5)), dimension: Box::new(number(size)) }],
+        };
+        // each execution of f takes steps, while a cached call takes none
+        let mut execute = |expression: &Expression| {
+            let steps = runtime.steps;
+            let value = execute_expression(expression, &program, &mut runtime, false).ok().unwrap();
+            (value, runtime.steps - steps)
+        };
+        let (uncached, steps) = execute(&call(3));
+        assert!(steps > 0);
+        let (cached, steps) = execute(&call(3));
+        assert_eq!(steps, 0);
+        assert!(uncached.node_pointer.is_none() && cached.node_pointer.is_none());
+        assert!(uncached.arithmetic_slice == cached.arithmetic_slice);
+        assert_eq!(cached.arithmetic_slice.unwrap().to_string(), AExpressionSlice::new_array(vec![2], vec![AExpr::Number { value: BigInt::from(15) }, AExpr::Number { value: BigInt::from(3) }]).to_string());
+        // calls with large arguments are always executed
+        assert!(execute(&call(1000)).1 > 0);
+        assert!(execute(&call(1000)).1 > 0);
+    }
+
+    #[test]
+    fn frames_format_their_arguments_when_described() {
+        let number = |value: u64| AExpr::Number { value: BigInt::from(value) };
+        let frame = CallFrame {
+            id: "f".to_string(),
+            parameters: vec!["a".to_string(), "b".to_string()],
+            arguments: vec![AExpressionSlice::new(&number(3)), AExpressionSlice::new_with_route(&[40], &number(12345))],
+            component: Option::None,
+            meta: Meta::new(0, 0),
+        };
+        let description = frame.description();
+        assert!(description.starts_with("f(a=3, b="));
+        assert!(description.ends_with("...)"));
+        assert_eq!(description.len(), "f(a=3, b=)".len() + 64);
+        let frame = CallFrame { component: Option::Some("main.c".to_string()), arguments: vec![], ..frame };
+        assert_eq!(frame.description(), "main.c = f()");
+    }
+
+    #[test]
+    fn origins_are_only_built_when_requested() {
+        let src = "pragma circom 2.0.0;
+template Main() {
+    signal input a;
+    signal output c;
+    c <== a * a;
+}
+component main = Main();
+";
+        for flag_origins in [false, true].iter() {
+            let program = parse_source("origins", src);
+            let (executed, _) = constraint_execution(&program, false, &"bn128".to_string(), ExecutionLimits::default(), false, SignalAssertMode::Ignore)
+                .ok()
+                .unwrap();
+            let (dag, _, _) = executed.export(program, false, *flag_origins).ok().unwrap();
+            let main = dag.get_main().unwrap();
+            assert_eq!(main.constraints().len(), 1);
+            assert_eq!(main.constraint_origins().len(), if *flag_origins { 1 } else { 0 });
+            assert_eq!(main.constraints()[0].origins().is_empty(), !*flag_origins);
+        }
+    }
+}
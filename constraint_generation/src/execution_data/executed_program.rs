@@ -69,7 +69,7 @@ impl ExecutedProgram {
         node_index
     }
 
-    pub fn export(mut self, mut program: ProgramArchive, flag_verbose: bool) -> ExportResult {
+    pub fn export(mut self, mut program: ProgramArchive, flag_verbose: bool, flag_origins: bool) -> ExportResult {
         use super::executed_template::templates_in_mixed_arrays;
         fn merge_mixed(org: Vec<bool>, new: Vec<bool>) -> Vec<bool> {
             let mut result = Vec::with_capacity(org.len());
@@ -92,7 +92,7 @@ impl ExecutedProgram {
         }
 
         for exe in &mut self.model {
-            exe.insert_in_dag(&mut dag);
+            exe.insert_in_dag(&mut dag, &program.file_library, flag_origins);
         }
 
         for exe in self.model {
@@ -1,9 +1,12 @@
 use super::type_definitions::*;
 use circom_algebra::algebra::ArithmeticExpression;
 use compiler::hir::very_concrete_program::*;
+use constraint_writers::origin_writer::ConstraintOrigin;
+use constraint_writers::sym_json_writer::{ParameterDeclaration, SignalDeclaration, SignalKind};
 use dag::DAG;
 use num_bigint::BigInt;
-use program_structure::ast::{SignalType, Statement};
+use program_structure::ast::{Meta, SignalType, Statement};
+use program_structure::file_definition::{FileID, FileLibrary, FileLocation};
 use std::collections::{HashMap, HashSet};
 
 struct Connexion {
@@ -24,6 +27,7 @@ pub struct ExecutedTemplate {
     pub intermediates: SignalCollector,
     pub ordered_signals: Vec<String>,
     pub constraints: Vec<Constraint>,
+    pub constraint_locations: Vec<(FileID, FileLocation)>,
     pub components: ComponentCollector,
     pub number_of_components: usize,
     pub public_inputs: HashSet<String>,
@@ -59,6 +63,7 @@ impl ExecutedTemplate {
             intermediates: SignalCollector::new(),
             ordered_signals: Vec::new(),
             constraints: Vec::new(),
+            constraint_locations: Vec::new(),
             components: ComponentCollector::new(),
             number_of_components: 0,
             connexions: Vec::new(),
@@ -113,8 +118,9 @@ impl ExecutedTemplate {
         self.number_of_components += dimensions.iter().fold(1, |p, c| p * (*c));
     }
 
-    pub fn add_constraint(&mut self, constraint: Constraint) {
+    pub fn add_constraint(&mut self, constraint: Constraint, meta: &Meta) {
         self.constraints.push(constraint);
+        self.constraint_locations.push((meta.get_file_id(), meta.file_location()));
     }
 
     pub fn template_name(&self) -> &String {
@@ -137,14 +143,16 @@ impl ExecutedTemplate {
         &self.intermediates
     }
 
-    pub fn insert_in_dag(&mut self, dag: &mut DAG) {
+    pub fn insert_in_dag(&mut self, dag: &mut DAG, files: &FileLibrary, flag_origins: bool) {
+        let mut declarations = vec![];
         let parameters = {
             let mut parameters = vec![];
-            for (_, data) in self.parameter_instances.clone() {
-                let (_, values) = data.destruct();
+            for (name, data) in self.parameter_instances.clone() {
+                let (dimensions, values) = data.destruct();
                 for value in as_big_int(values) {
                     parameters.push(value);
                 }
+                declarations.push(ParameterDeclaration { name, dimensions });
             }
             parameters
         }; // repeated code from function build_arguments in export_to_circuit
@@ -156,35 +164,34 @@ impl ExecutedTemplate {
             self.is_parallel,
             self.is_custom_gate
         );
+        for declaration in declarations {
+            dag.add_parameter_declaration(declaration);
+        }
         self.build_signals(dag);
         self.build_connexions(dag);
-        self.build_constraints(dag);
+        self.build_constraints(dag, files, flag_origins);
     }
 
     fn build_signals(&self, dag: &mut DAG) {
         for (name, dim) in self.outputs() {
-            let state = State { name: name.clone(), dim: 0 };
             let config = SignalConfig { signal_type: 1, dimensions: dim, is_public: false };
-            generate_symbols(dag, state, &config);
+            build_signal(dag, name, &config);
         }
         for (name, dim) in self.inputs() {
             if self.public_inputs.contains(name) {
-                let state = State { name: name.clone(), dim: 0 };
                 let config = SignalConfig { signal_type: 0, dimensions: dim, is_public: true };
-                generate_symbols(dag, state, &config);
+                build_signal(dag, name, &config);
             }
         }
         for (name, dim) in self.inputs() {
             if !self.public_inputs.contains(name) {
-                let state = State { name: name.clone(), dim: 0 };
                 let config = SignalConfig { signal_type: 0, dimensions: dim, is_public: false };
-                generate_symbols(dag, state, &config);
+                build_signal(dag, name, &config);
             }
         }
         for (name, dim) in self.intermediates() {
-            let state = State { name: name.clone(), dim: 0 };
             let config = SignalConfig { signal_type: 2, dimensions: dim, is_public: false };
-            generate_symbols(dag, state, &config);
+            build_signal(dag, name, &config);
         }
     }
     fn build_connexions(&mut self, dag: &mut DAG) {
@@ -211,11 +218,25 @@ impl ExecutedTemplate {
         self.has_parallel_sub_cmp = dag.nodes[dag.main_id()].has_parallel_sub_cmp();
         dag.set_number_of_subcomponents_indexes(self.number_of_components);
     }
-    fn build_constraints(&self, dag: &mut DAG) {
-        for c in &self.constraints {
+    fn build_constraints(&self, dag: &mut DAG, files: &FileLibrary, flag_origins: bool) {
+        for (c, (file_id, location)) in self.constraints.iter().zip(&self.constraint_locations) {
             let correspondence = dag.get_main().unwrap().correspondence();
             let cc = Constraint::apply_correspondence(c, correspondence);
-            dag.add_constraint(cc);
+            let origin = if flag_origins {
+                Some(ConstraintOrigin {
+                    file_id: *file_id,
+                    start: location.start,
+                    end: location.end,
+                    location: format!(
+                        "{}:{}",
+                        files.get_name(*file_id).unwrap().trim_matches('"'),
+                        files.get_line(location.start, *file_id).unwrap()
+                    ),
+                })
+            } else {
+                None
+            };
+            dag.add_constraint(cc, origin);
         }
     }
     pub fn export_to_circuit(self, instances: &[TemplateInstance]) -> TemplateInstance {
@@ -260,7 +281,8 @@ impl ExecutedTemplate {
             arguments
         }
 
-        let header = format!("{}_{}", self.template_name, instances.len());
+        // namespaced templates (lib.Mux) must give valid identifiers in the generated code
+        let header = format!("{}_{}", self.template_name.replace('.', "_"), instances.len());
         let clusters = build_clusters(&self, instances);
         let triggers = build_triggers(instances, self.connexions);
         let components = build_components(self.components);
@@ -329,6 +351,20 @@ struct State {
     name: String,
     dim: usize,
 }
+fn build_signal(dag: &mut DAG, name: &str, config: &SignalConfig) {
+    let kind = match config.signal_type {
+        0 => SignalKind::Input,
+        1 => SignalKind::Output,
+        _ => SignalKind::Intermediate,
+    };
+    dag.add_signal_declaration(SignalDeclaration {
+        name: name.to_string(),
+        kind,
+        is_public: config.is_public,
+        dimensions: config.dimensions.to_vec(),
+    });
+    generate_symbols(dag, State { name: name.to_string(), dim: 0 }, config);
+}
 fn generate_symbols(dag: &mut DAG, state: State, config: &SignalConfig) {
     if state.dim == config.dimensions.len() {
         if config.signal_type == 0 {
@@ -0,0 +1,179 @@
+use super::environment_utils::slice_types::{AExpressionSlice, ArithmeticExpression};
+use ansi_term::Colour;
+use program_structure::ast::{Access, Expression, Statement};
+use program_structure::program_archive::ProgramArchive;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+// Functions cannot access signals or components (see
+// functions_free_of_template_elements), so a call whose arguments are all
+// known numbers always produces the same result. The results of such calls
+// are stored by a hash of the function name and argument values, and the
+// arguments are only compared and copied when the hash is found or a result is
+// stored. Functions that log, or call a function that logs, are always
+// executed so that their output does not change.
+pub type CallKey = u64;
+
+// Calls with more argument values than this are not cached, since hashing and
+// comparing them may cost as much as the call itself
+const MAX_CACHED_ARGUMENT_VALUES: usize = 256;
+// The oldest results are dropped once the cache holds this many
+const MAX_CACHED_RESULTS: usize = 4096;
+
+struct CachedCall {
+    id: String,
+    arguments: Vec<AExpressionSlice>,
+    result: AExpressionSlice,
+}
+
+#[derive(Default)]
+pub struct FunctionCache {
+    results: HashMap<CallKey, Vec<CachedCall>>,
+    insertion_order: VecDeque<CallKey>,
+    logging: HashMap<String, bool>,
+    hits: BTreeMap<String, usize>,
+    misses: BTreeMap<String, usize>,
+}
+
+impl FunctionCache {
+    pub fn new() -> FunctionCache {
+        FunctionCache::default()
+    }
+
+    // Returns None if the call can not be cached
+    pub fn key(
+        &mut self,
+        id: &str,
+        arg_values: &[AExpressionSlice],
+        program_archive: &ProgramArchive,
+    ) -> Option<CallKey> {
+        let size: usize = arg_values.iter().map(|arg| arg.values().len()).sum();
+        if size > MAX_CACHED_ARGUMENT_VALUES || self.logs(id, program_archive) {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        for arg in arg_values {
+            arg.route().hash(&mut hasher);
+            for value in arg.values() {
+                if let ArithmeticExpression::Number { value } = value {
+                    value.hash(&mut hasher);
+                } else {
+                    return None;
+                }
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    pub fn get(&mut self, key: CallKey, id: &str, arg_values: &[AExpressionSlice]) -> Option<AExpressionSlice> {
+        let result = self
+            .results
+            .get(&key)
+            .and_then(|calls| calls.iter().find(|call| call.id == id && call.arguments == arg_values))
+            .map(|call| call.result.clone());
+        let counter = if result.is_some() { &mut self.hits } else { &mut self.misses };
+        *counter.entry(id.to_string()).or_insert(0) += 1;
+        result
+    }
+
+    pub fn insert(&mut self, key: CallKey, id: &str, arg_values: &[AExpressionSlice], result: AExpressionSlice) {
+        if self.insertion_order.len() == MAX_CACHED_RESULTS {
+            let oldest = self.insertion_order.pop_front().unwrap();
+            let calls = self.results.get_mut(&oldest).unwrap();
+            calls.remove(0);
+            if calls.is_empty() {
+                self.results.remove(&oldest);
+            }
+        }
+        let call = CachedCall { id: id.to_string(), arguments: arg_values.to_vec(), result };
+        self.results.entry(key).or_default().push(call);
+        self.insertion_order.push_back(key);
+    }
+
+    pub fn print_statistics(&self) {
+        let hits: usize = self.hits.values().sum();
+        let misses: usize = self.misses.values().sum();
+        println!(
+            "{}: {} hits, {} evaluations",
+            Colour::Green.paint("function cache"),
+            hits,
+            misses
+        );
+        for (id, hits) in &self.hits {
+            let misses = self.misses.get(id).cloned().unwrap_or(0);
+            println!("    {}: {} hits, {} evaluations", id, hits, misses);
+        }
+    }
+
+    fn logs(&mut self, id: &str, program_archive: &ProgramArchive) -> bool {
+        if let Some(logs) = self.logging.get(id) {
+            return *logs;
+        }
+        let logs = function_logs(id, program_archive, &mut HashSet::new());
+        self.logging.insert(id.to_string(), logs);
+        logs
+    }
+}
+
+fn function_logs(id: &str, program_archive: &ProgramArchive, visited: &mut HashSet<String>) -> bool {
+    if !program_archive.contains_function(id) || !visited.insert(id.to_string()) {
+        return false;
+    }
+    let body = program_archive.get_function_data(id).get_body();
+    statement_logs(body, program_archive, visited)
+}
+
+fn statement_logs(stmt: &Statement, program_archive: &ProgramArchive, visited: &mut HashSet<String>) -> bool {
+    use Statement::*;
+    let mut check = |e: &Expression| expression_logs(e, program_archive, visited);
+    match stmt {
+        LogCall { .. } => true,
+        IfThenElse { cond, if_case, else_case, .. } => {
+            check(cond)
+                || statement_logs(if_case, program_archive, visited)
+                || else_case.as_ref().is_some_and(|s| statement_logs(s, program_archive, visited))
+        }
+        While { cond, stmt, .. } => check(cond) || statement_logs(stmt, program_archive, visited),
+        Return { value, .. } => check(value),
+        InitializationBlock { initializations, .. } => {
+            initializations.iter().any(|s| statement_logs(s, program_archive, visited))
+        }
+        Declaration { dimensions, .. } => dimensions.iter().any(check),
+        Substitution { access, rhe, .. } => {
+            access_logs(access, program_archive, visited)
+                || expression_logs(rhe, program_archive, visited)
+        }
+        ConstraintEquality { lhe, rhe, .. } => check(lhe) || check(rhe),
+        Block { stmts, .. } => stmts.iter().any(|s| statement_logs(s, program_archive, visited)),
+        Assert { arg, .. } => check(arg),
+    }
+}
+
+fn expression_logs(expr: &Expression, program_archive: &ProgramArchive, visited: &mut HashSet<String>) -> bool {
+    use Expression::*;
+    let mut check = |e: &Expression| expression_logs(e, program_archive, visited);
+    match expr {
+        InfixOp { lhe, rhe, .. } => check(lhe) || check(rhe),
+        PrefixOp { rhe, .. } => check(rhe),
+        InlineSwitchOp { cond, if_true, if_false, .. } => {
+            check(cond) || check(if_true) || check(if_false)
+        }
+        Variable { access, .. } => access_logs(access, program_archive, visited),
+        Number(..) => false,
+        Call { id, args, .. } => {
+            args.iter().any(|a| expression_logs(a, program_archive, visited))
+                || function_logs(id, program_archive, visited)
+        }
+        ArrayInLine { values, .. } => values.iter().any(check),
+        UniformArray { value, dimension, .. } => check(value) || check(dimension),
+    }
+}
+
+fn access_logs(access: &[Access], program_archive: &ProgramArchive, visited: &mut HashSet<String>) -> bool {
+    access.iter().any(|acc| match acc {
+        Access::ArrayAccess(index) => expression_logs(index, program_archive, visited),
+        Access::ComponentAccess(_) => false,
+    })
+}
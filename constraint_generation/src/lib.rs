@@ -5,6 +5,7 @@ mod compute_constants;
 mod environment_utils;
 mod execute;
 mod execution_data;
+mod function_cache;
 
 use ansi_term::Colour;
 use circom_algebra::algebra::{ArithmeticError, ArithmeticExpression};
@@ -12,6 +13,8 @@ use compiler::hir::very_concrete_program::VCP;
 use constraint_list::ConstraintList;
 use constraint_writers::ConstraintExporter;
 use dag::DAG;
+use execute::ExecutionLimits;
+pub use execute::SignalAssertMode;
 use execution_data::executed_program::ExportResult;
 use execution_data::ExecutedProgram;
 use program_structure::ast::{self};
@@ -24,12 +27,18 @@ use std::rc::Rc;
 pub struct BuildConfig {
     pub no_rounds: usize,
     pub flag_json_sub: bool,
+    pub json_substitutions: String,
+    pub flag_origins: bool,
     pub flag_s: bool,
     pub flag_f: bool,
     pub flag_p: bool,
     pub flag_verbose: bool,
     pub flag_old_heuristics: bool,
     pub inspect_constraints: bool,
+    pub max_exec_steps: Option<usize>,
+    pub max_recursion_depth: usize,
+    pub log_compile_time: bool,
+    pub signal_asserts: SignalAssertMode,
     pub prime: String,
 }
 
@@ -37,11 +46,15 @@ pub type ConstraintWriter = Box<dyn ConstraintExporter>;
 type BuildResponse = Result<(ConstraintWriter, VCP), ()>;
 pub fn build_circuit(program: ProgramArchive, config: BuildConfig) -> BuildResponse {
     let files = program.file_library.clone();
-    let (exe, warnings) = instantiation(&program, config.flag_verbose, &config.prime).map_err(|r| {
+    let limits = ExecutionLimits {
+        max_steps: config.max_exec_steps,
+        max_recursion_depth: config.max_recursion_depth,
+    };
+    let (exe, warnings) = instantiation(&program, &config, limits).map_err(|r| {
         Report::print_reports(&r, &files);
     })?;
     Report::print_reports(&warnings, &files);
-    let (mut dag, mut vcp, warnings) = export(exe, program, config.flag_verbose).map_err(|r| {
+    let (mut dag, mut vcp, warnings) = export(exe, program, config.flag_verbose, config.flag_origins).map_err(|r| {
         Report::print_reports(&r, &files);
     })?;
     if config.inspect_constraints {
@@ -57,8 +70,15 @@ pub fn build_circuit(program: ProgramArchive, config: BuildConfig) -> BuildRespo
 }
 
 type InstantiationResponse = Result<(ExecutedProgram, ReportCollection), ReportCollection>;
-fn instantiation(program: &ProgramArchive, flag_verbose: bool, prime: &String) -> InstantiationResponse {
-    let execution_result = execute::constraint_execution(&program, flag_verbose, prime);
+fn instantiation(program: &ProgramArchive, config: &BuildConfig, limits: ExecutionLimits) -> InstantiationResponse {
+    let execution_result = execute::constraint_execution(
+        program,
+        config.flag_verbose,
+        &config.prime,
+        limits,
+        config.log_compile_time,
+        config.signal_asserts,
+    );
     match execution_result {
         Ok((program_exe, warnings)) => {
             let no_nodes = program_exe.number_of_nodes();
@@ -71,8 +91,8 @@ fn instantiation(program: &ProgramArchive, flag_verbose: bool, prime: &String) -
     }
 }
 
-fn export(exe: ExecutedProgram, program: ProgramArchive, flag_verbose: bool) -> ExportResult {
-    let exported = exe.export(program, flag_verbose);
+fn export(exe: ExecutedProgram, program: ProgramArchive, flag_verbose: bool, flag_origins: bool) -> ExportResult {
+    let exported = exe.export(program, flag_verbose, flag_origins);
     exported
 }
 
@@ -87,6 +107,7 @@ fn simplification_process(vcp: &mut VCP, dag: DAG, config: &BuildConfig) -> Cons
         flag_s: config.flag_s,
         parallel_flag: config.flag_p,
         port_substitution: config.flag_json_sub,
+        json_substitutions: config.json_substitutions.clone(),
         no_rounds: config.no_rounds,
         flag_old_heuristics: config.flag_old_heuristics,
         prime : config.prime.clone(),
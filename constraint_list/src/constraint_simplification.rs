@@ -6,8 +6,6 @@ use constraint_writers::json_writer::SubstitutionJSON;
 use std::collections::{HashMap, HashSet, LinkedList, BTreeSet};
 use std::sync::Arc;
 
-const SUB_LOG: &str = "./log_substitution.json";
-
 fn log_substitutions(substitutions: &LinkedList<S>, writer: &mut Option<SubstitutionJSON>) {
     use super::json_porting::port_substitution;
     if let Some(w) = writer {
@@ -158,7 +156,9 @@ fn eq_cluster_simplification(
         let mut subs = LinkedList::new();
         let (mut remains, mut min_remains) = (BTreeSet::new(), None);
         let (mut remove, mut min_remove) = (HashSet::new(), None);
+        let mut origins = Vec::new();
         for c in cluster.constraints {
+            origins.extend_from_slice(c.origins());
             for signal in C::take_cloned_signals_ordered(&c) {
                 if HashSet::contains(&forbidden, &signal) {
                     BTreeSet::insert(&mut remains, signal);
@@ -170,6 +170,9 @@ fn eq_cluster_simplification(
             }
         }
 
+        origins.sort_unstable();
+        origins.dedup();
+
         let rh_signal = if let Some(signal) = min_remains {
             BTreeSet::remove(&mut remains, &signal);
             signal
@@ -183,7 +186,8 @@ fn eq_cluster_simplification(
             let l = A::Signal { symbol: signal };
             let r = A::Signal { symbol: rh_signal };
             let expr = A::sub(&l, &r, field);
-            let c = A::transform_expression_to_constraint_form(expr, field).unwrap();
+            let mut c = A::transform_expression_to_constraint_form(expr, field).unwrap();
+            C::set_origins(&mut c, origins.clone());
             LinkedList::push_back(&mut cons, c);
         }
 
@@ -402,12 +406,13 @@ fn build_relevant_set(
     deletes: &SEncoded,
 ) {
     fn unwrapped_signal(map: &SEncoded, signal: usize) -> Option<usize> {
-        let f = |e: &A| {
-            if let A::Signal { symbol } = e {
+        let f = |s: &S| match s.to().iter().next() {
+            Some((symbol, value))
+                if s.to().len() == 1 && *symbol != S::constant_coefficient() && *value == BigInt::from(1) =>
+            {
                 Some(*symbol)
-            } else {
-                None
             }
+            _ => None,
         };
         SEncoded::get(map, &signal).map_or(None, f)
     }
@@ -444,7 +449,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap) {
     use std::time::SystemTime;
 
     let mut substitution_log =
-        if smp.port_substitution { Some(SubstitutionJSON::new(SUB_LOG).unwrap()) } else { None };
+        if smp.port_substitution { Some(SubstitutionJSON::new(&smp.json_substitutions).unwrap()) } else { None };
     let apply_linear = !smp.flag_s;
     let use_old_heuristics = smp.flag_old_heuristics;
     let field = smp.field.clone();
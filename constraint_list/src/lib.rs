@@ -3,11 +3,14 @@ use std::collections::{HashMap, HashSet, LinkedList};
 use circom_algebra::constraint_storage::ConstraintStorage;
 use circom_algebra::num_bigint::BigInt;
 use constraint_writers::debug_writer::DebugWriter;
+use constraint_writers::origin_writer::ConstraintOrigin;
+use constraint_writers::sym_json_writer::{ParameterDeclaration, SignalDeclaration};
 use constraint_writers::ConstraintExporter;
 
 mod constraint_simplification;
 mod json_porting;
 mod non_linear_utils;
+mod origin_porting;
 mod r1cs_porting;
 mod state_utils;
 mod sym_porting;
@@ -17,7 +20,7 @@ type C = circom_algebra::algebra::Constraint<usize>;
 type S = circom_algebra::algebra::Substitution<usize>;
 type A = circom_algebra::algebra::ArithmeticExpression<usize>;
 type SignalMap = HashMap<usize, usize>;
-type SEncoded = HashMap<usize, A>;
+type SEncoded = HashMap<usize, S>;
 type SFrames = LinkedList<SEncoded>;
 
 pub struct SignalInfo {
@@ -28,9 +31,13 @@ pub struct EncodingNode {
     pub id: usize,
     pub name: String,
     pub parameters: Vec<BigInt>,
+    pub parameter_declarations: Vec<ParameterDeclaration>,
     pub signals: Vec<SignalInfo>,
+    pub signal_declarations: Vec<SignalDeclaration>,
     pub ordered_signals: Vec<usize>,
     pub non_linear: LinkedList<C>,
+    pub constraint_origins: Vec<ConstraintOrigin>,
+    pub local_constraints_offset: usize,
     pub is_custom_gate: bool,
 }
 
@@ -38,6 +45,7 @@ pub struct EncodingEdge {
     pub goes_to: usize,
     pub path: String,
     pub offset: usize,
+    pub constraint_offset: usize,
 }
 
 pub struct DAGEncoding {
@@ -67,6 +75,7 @@ pub struct EncodingIterator<'a> {
     pub node_id: usize,
     pub path: String,
     pub offset: usize,
+    pub constraint_offset: usize,
     pub signals: Vec<SignalInfo>,
     pub non_linear: LinkedList<C>,
 }
@@ -76,6 +85,7 @@ impl<'a> EncodingIterator<'a> {
         let iter = EncodingIterator {
             encoding,
             offset: 0,
+            constraint_offset: 0,
             non_linear: LinkedList::new(),
             path: "main".to_string(),
             signals: Vec::new(),
@@ -88,6 +98,7 @@ impl<'a> EncodingIterator<'a> {
         let iter = EncodingIterator {
             encoding: iterator.encoding,
             offset: iterator.offset + edge.offset,
+            constraint_offset: iterator.constraint_offset + edge.constraint_offset,
             node_id: edge.goes_to,
             path: format!("{}.{}", iterator.path, edge.path),
             non_linear: LinkedList::new(),
@@ -125,6 +136,7 @@ pub struct Simplifier {
     pub flag_s: bool,
     pub flag_old_heuristics: bool,
     pub port_substitution: bool,
+    pub json_substitutions: String,
 }
 impl Simplifier {
     pub fn simplify_constraints(mut self) -> ConstraintList {
@@ -174,6 +186,18 @@ impl ConstraintExporter for ConstraintList {
     fn sym(&self, out: &str) -> Result<(), ()> {
         sym_porting::port_sym(self, out)
     }
+
+    fn sym_json(&self, out: &str) -> Result<(), ()> {
+        sym_porting::port_sym_json(self, out)
+    }
+
+    fn origins(&self, out: &str) -> Result<(), ()> {
+        origin_porting::port_origins(self, out)
+    }
+
+    fn number_of_constraints(&self) -> usize {
+        self.constraints.get_ids().len()
+    }
 }
 
 impl ConstraintList {
@@ -0,0 +1,45 @@
+use super::{ConstraintList, EncodingIterator};
+use constraint_writers::origin_writer::{OriginElem, OriginFile};
+use std::collections::{HashMap, HashSet};
+
+type OriginSources = HashMap<usize, (usize, usize, String)>;
+
+pub fn port_origins(list: &ConstraintList, file_name: &str) -> Result<(), ()> {
+    let mut used = HashSet::new();
+    for c_id in list.constraints.get_ids() {
+        let constraint = list.constraints.read_constraint(c_id).unwrap();
+        for origin in constraint.origins() {
+            used.insert(*origin);
+        }
+    }
+    let mut sources = OriginSources::new();
+    let iter = EncodingIterator::new(&list.dag_encoding);
+    origin_iteration(iter, &used, &mut sources);
+
+    let mut dot_origins = OriginFile::new(file_name)?;
+    for (written, c_id) in list.constraints.get_ids().into_iter().enumerate() {
+        let constraint = list.constraints.read_constraint(c_id).unwrap();
+        for origin in constraint.origins() {
+            if let Some((node_id, local, path)) = sources.get(origin) {
+                let origin = &list.dag_encoding.nodes[*node_id].constraint_origins[*local];
+                let elem = OriginElem { constraint: written, origin, component: path };
+                OriginFile::write_origin_elem(&mut dot_origins, elem)?;
+            }
+        }
+    }
+    OriginFile::close(dot_origins)
+}
+
+fn origin_iteration(iter: EncodingIterator, used: &HashSet<usize>, sources: &mut OriginSources) {
+    let node = &iter.encoding.nodes[iter.node_id];
+    let base = iter.constraint_offset + node.local_constraints_offset;
+    for local in 0..node.constraint_origins.len() {
+        if used.contains(&(base + local)) {
+            sources.insert(base + local, (iter.node_id, local, iter.path.clone()));
+        }
+    }
+    for edge in EncodingIterator::edges(&iter) {
+        let next = EncodingIterator::next(&iter, edge);
+        origin_iteration(next, used, sources);
+    }
+}
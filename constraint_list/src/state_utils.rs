@@ -14,6 +14,8 @@ pub fn clear_encoding_iterator(iterator: &mut EncodingIterator) {
 pub fn build_encoding_iterator(mut iterator: EncodingIterator) -> EncodingIterator {
     let encoding = iterator.encoding;
     let offset = iterator.offset;
+    let origin_offset =
+        iterator.constraint_offset + encoding.nodes[iterator.node_id].local_constraints_offset;
     let node_id = iterator.node_id;
     let path = iterator.path;
     let mut non_linear = LinkedList::new();
@@ -25,7 +27,8 @@ pub fn build_encoding_iterator(mut iterator: EncodingIterator) -> EncodingIterat
     }
 
     for constraint in &encoding.nodes[node_id].non_linear {
-        let constraint = C::apply_offset(constraint, offset);
+        let mut constraint = C::apply_offset(constraint, offset);
+        C::apply_origin_offset(&mut constraint, origin_offset);
         LinkedList::push_back(&mut non_linear, constraint);
     }
     iterator.path = path;
@@ -1,6 +1,8 @@
-use super::{ConstraintList, EncodingIterator, IteratorSignal, SignalMap};
+use super::{ConstraintList, DAGEncoding, EncodingIterator, IteratorSignal, SignalMap};
 use circom_algebra::num_traits::AsPrimitive;
+use constraint_writers::sym_json_writer::{self, SymComponent, SymSignal};
 use constraint_writers::sym_writer::*;
+use std::collections::HashMap;
 
 pub fn port_sym(list: &ConstraintList, file_name: &str) -> Result<(), ()> {
     let iter = EncodingIterator::new(&list.dag_encoding);
@@ -34,3 +36,47 @@ pub fn signal_iteration(
     }
     Ok(())
 }
+
+pub fn port_sym_json(list: &ConstraintList, file_name: &str) -> Result<(), ()> {
+    let iter = EncodingIterator::new(&list.dag_encoding);
+    let main = build_component(&list.dag_encoding, &iter, "main", &list.signal_map);
+    sym_json_writer::write_sym_json(file_name, &main)
+}
+
+fn build_component<'a>(
+    encoding: &'a DAGEncoding,
+    iter: &EncodingIterator,
+    name: &str,
+    map: &SignalMap,
+) -> SymComponent<'a> {
+    let node = &encoding.nodes[iter.node_id];
+    let ids: HashMap<&str, usize> =
+        node.signals.iter().map(|signal| (signal.name.as_str(), signal.id)).collect();
+    let mut signals = Vec::new();
+    for declaration in &node.signal_declarations {
+        let witness = declaration
+            .element_names()
+            .iter()
+            .map(|name| {
+                let original = ids.get(name.as_str()).unwrap() + iter.offset;
+                map.get(&original).map_or(-1, |witness| witness.as_())
+            })
+            .collect();
+        signals.push(SymSignal { declaration, witness });
+    }
+    let mut components = Vec::new();
+    for edge in EncodingIterator::edges(iter) {
+        let next = EncodingIterator::next(iter, edge);
+        components.push(build_component(encoding, &next, &edge.path, map));
+    }
+    SymComponent {
+        name: name.to_string(),
+        path: iter.path.clone(),
+        instance: &node.name,
+        parameter_declarations: &node.parameter_declarations,
+        parameters: node.parameters.iter().map(|value| value.to_string()).collect(),
+        node_id: iter.node_id,
+        signals,
+        components,
+    }
+}
@@ -1,11 +1,18 @@
 pub mod debug_writer;
 pub mod json_writer;
 pub mod log_writer;
+pub mod origin_writer;
+pub mod r1cs_reader;
 pub mod r1cs_writer;
+pub mod sym_json_writer;
+pub mod sym_reader;
 pub mod sym_writer;
 
 pub trait ConstraintExporter {
     fn r1cs(&self, out: &str) -> Result<(), ()>;
     fn json_constraints(&self, writer: &debug_writer::DebugWriter) -> Result<(), ()>;
     fn sym(&self, out: &str) -> Result<(), ()>;
+    fn sym_json(&self, out: &str) -> Result<(), ()>;
+    fn origins(&self, out: &str) -> Result<(), ()>;
+    fn number_of_constraints(&self) -> usize;
 }
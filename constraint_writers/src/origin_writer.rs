@@ -0,0 +1,59 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Clone, Default)]
+pub struct ConstraintOrigin {
+    pub file_id: usize,
+    pub start: usize,
+    pub end: usize,
+    pub location: String,
+}
+
+pub struct OriginElem<'a> {
+    pub constraint: usize,
+    pub origin: &'a ConstraintOrigin,
+    pub component: &'a str,
+}
+impl<'a> ToString for OriginElem<'a> {
+    fn to_string(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.constraint,
+            self.origin.file_id,
+            self.origin.start,
+            self.origin.end,
+            csv_field(&self.origin.location),
+            csv_field(self.component)
+        )
+    }
+}
+
+// Paths may contain commas, the fields that have them are quoted as in RFC 4180
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub struct OriginFile {
+    writer: BufWriter<File>,
+}
+
+impl OriginFile {
+    pub fn new(file: &str) -> Result<OriginFile, ()> {
+        let file = File::create(file).map_err(|_err| {})?;
+        let writer = BufWriter::new(file);
+        Result::Ok(OriginFile { writer })
+    }
+
+    pub fn write_origin_elem(origins: &mut OriginFile, elem: OriginElem) -> Result<(), ()> {
+        origins.writer.write_all(elem.to_string().as_bytes()).map_err(|_err| {})?;
+        origins.writer.write_all(b"\n").map_err(|_err| {})
+    }
+
+    pub fn close(mut origins: OriginFile) -> Result<(), ()> {
+        origins.writer.flush().map_err(|_err| {})
+    }
+}
@@ -0,0 +1,88 @@
+use super::r1cs_writer::{HeaderData, CONSTRAINT_TYPE, HEADER_TYPE, MAGIC};
+pub use circom_algebra::num_bigint::BigInt;
+use circom_algebra::num_bigint::Sign;
+use std::collections::HashMap;
+
+// Linear combination as pairs (wire, coefficient)
+pub type LinearCombination = Vec<(usize, BigInt)>;
+pub type R1CSConstraint = [LinearCombination; 3];
+
+pub struct R1CSFile {
+    pub header: HeaderData,
+    pub constraints: Vec<R1CSConstraint>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8], ()> {
+        if self.position + size > self.bytes.len() {
+            return Result::Err(());
+        }
+        let taken = &self.bytes[self.position..self.position + size];
+        self.position += size;
+        Result::Ok(taken)
+    }
+
+    fn number(&mut self, size: usize) -> Result<usize, ()> {
+        let bytes = self.take(size)?;
+        Result::Ok(bytes.iter().rev().fold(0, |value, byte| (value << 8) | *byte as usize))
+    }
+
+    fn big_number(&mut self, size: usize) -> Result<BigInt, ()> {
+        Result::Ok(BigInt::from_bytes_le(Sign::Plus, self.take(size)?))
+    }
+}
+
+// Reads the header and the constraints of a file written by R1CSWriter. The
+// other sections are skipped
+pub fn read_r1cs(file: &str) -> Result<R1CSFile, ()> {
+    let bytes = std::fs::read(file).map_err(|_err| {})?;
+    let mut reader = Reader { bytes: &bytes, position: 0 };
+    if reader.take(4)? != MAGIC {
+        return Result::Err(());
+    }
+    let _version = reader.number(4)?;
+    let number_of_sections = reader.number(4)?;
+    let mut sections = HashMap::new();
+    for _ in 0..number_of_sections {
+        let section_type = reader.take(4)?;
+        let size = reader.number(8)?;
+        let start = reader.position;
+        reader.take(size)?;
+        sections.insert(section_type, (start, size));
+    }
+
+    let (start, size) = *sections.get(HEADER_TYPE).ok_or(())?;
+    let mut header_reader = Reader { bytes: &bytes[start..start + size], position: 0 };
+    let field_size = header_reader.number(4)?;
+    let header = HeaderData {
+        field: header_reader.big_number(field_size)?,
+        total_wires: header_reader.number(4)?,
+        public_outputs: header_reader.number(4)?,
+        public_inputs: header_reader.number(4)?,
+        private_inputs: header_reader.number(4)?,
+        number_of_labels: header_reader.number(8)?,
+        number_of_constraints: header_reader.number(4)?,
+    };
+
+    let (start, size) = *sections.get(CONSTRAINT_TYPE).ok_or(())?;
+    let mut constraint_reader = Reader { bytes: &bytes[start..start + size], position: 0 };
+    let mut constraints = Vec::with_capacity(header.number_of_constraints);
+    for _ in 0..header.number_of_constraints {
+        let mut constraint: R1CSConstraint = Default::default();
+        for linear_combination in constraint.iter_mut() {
+            let non_zero_factors = constraint_reader.number(4)?;
+            for _ in 0..non_zero_factors {
+                let wire = constraint_reader.number(4)?;
+                let factor = constraint_reader.big_number(field_size)?;
+                linear_combination.push((wire, factor));
+            }
+        }
+        constraints.push(constraint);
+    }
+    Result::Ok(R1CSFile { header, constraints })
+}
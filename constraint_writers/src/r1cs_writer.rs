@@ -4,11 +4,11 @@ use std::fs::File;
 use std::io::{BufWriter, Seek, SeekFrom, Write};
 
 const SECTIONS: u8 = 5;
-const MAGIC: &[u8] = b"r1cs";
+pub(crate) const MAGIC: &[u8] = b"r1cs";
 const VERSION: &[u8] = &[1, 0, 0, 0];
 const NUMBER_OF_SECTIONS: &[u8] = &[SECTIONS, 0, 0, 0];
-const HEADER_TYPE: &[u8] = &[1, 0, 0, 0];
-const CONSTRAINT_TYPE: &[u8] = &[2, 0, 0, 0];
+pub(crate) const HEADER_TYPE: &[u8] = &[1, 0, 0, 0];
+pub(crate) const CONSTRAINT_TYPE: &[u8] = &[2, 0, 0, 0];
 const WIRE2LABEL_TYPE: &[u8] = &[3, 0, 0, 0];
 const CUSTOM_GATES_USED_TYPE: &[u8] = &[4, 0, 0, 0];
 const CUSTOM_GATES_APPLIED_TYPE: &[u8] = &[5, 0, 0, 0];
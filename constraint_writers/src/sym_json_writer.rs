@@ -0,0 +1,129 @@
+use json::JsonValue;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SignalKind {
+    Input,
+    Output,
+    Intermediate,
+}
+impl ToString for SignalKind {
+    fn to_string(&self) -> String {
+        match self {
+            SignalKind::Input => "input".to_string(),
+            SignalKind::Output => "output".to_string(),
+            SignalKind::Intermediate => "intermediate".to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SignalDeclaration {
+    pub name: String,
+    pub kind: SignalKind,
+    pub is_public: bool,
+    pub dimensions: Vec<usize>,
+}
+impl SignalDeclaration {
+    // Names of the elements of the signal in row-major order: s[0][0], s[0][1], ...
+    pub fn element_names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        for dimension in &self.dimensions {
+            let mut next = Vec::with_capacity(names.len() * dimension);
+            for name in &names {
+                for index in 0..*dimension {
+                    next.push(format!("{}[{}]", name, index));
+                }
+            }
+            names = next;
+        }
+        names
+    }
+}
+
+// A parameter of a template instance, whose values are given in row-major order
+#[derive(Clone)]
+pub struct ParameterDeclaration {
+    pub name: String,
+    pub dimensions: Vec<usize>,
+}
+
+// Witness index of every element of a signal, -1 if it was removed
+pub struct SymSignal<'a> {
+    pub declaration: &'a SignalDeclaration,
+    pub witness: Vec<i64>,
+}
+
+pub struct SymComponent<'a> {
+    pub name: String,
+    pub path: String,
+    // Name of the template instance, as in Multiplier(3)
+    pub instance: &'a str,
+    pub parameter_declarations: &'a [ParameterDeclaration],
+    // Values of all the parameters, in the order of their declarations
+    pub parameters: Vec<String>,
+    pub node_id: usize,
+    pub signals: Vec<SymSignal<'a>>,
+    pub components: Vec<SymComponent<'a>>,
+}
+impl<'a> SymComponent<'a> {
+    fn to_json(&self) -> JsonValue {
+        let mut signals = JsonValue::new_array();
+        for signal in &self.signals {
+            let declaration = signal.declaration;
+            let mut value = JsonValue::new_object();
+            value["name"] = declaration.name.clone().into();
+            value["kind"] = declaration.kind.to_string().into();
+            if declaration.kind == SignalKind::Input {
+                value["public"] = declaration.is_public.into();
+            }
+            value["dimensions"] = declaration.dimensions.clone().into();
+            value["witness"] = signal.witness.clone().into();
+            signals.push(value).unwrap();
+        }
+        let mut parameters = JsonValue::new_array();
+        let mut values = self.parameters.iter().cloned();
+        for declaration in self.parameter_declarations {
+            let mut value = JsonValue::new_object();
+            value["name"] = declaration.name.clone().into();
+            value["value"] = parameter_value(&declaration.dimensions, &mut values);
+            parameters.push(value).unwrap();
+        }
+        let mut components = JsonValue::new_array();
+        for component in &self.components {
+            components.push(component.to_json()).unwrap();
+        }
+        let mut value = JsonValue::new_object();
+        value["name"] = self.name.clone().into();
+        value["path"] = self.path.clone().into();
+        value["template"] = self.instance.split('(').next().unwrap().into();
+        value["parameters"] = parameters;
+        value["node_id"] = self.node_id.into();
+        value["signals"] = signals;
+        value["components"] = components;
+        value
+    }
+}
+
+// A single value, or nested arrays with the dimensions of the parameter
+fn parameter_value(dimensions: &[usize], values: &mut dyn Iterator<Item = String>) -> JsonValue {
+    match dimensions.split_first() {
+        None => values.next().unwrap_or_default().into(),
+        Some((length, inner)) => {
+            let mut array = JsonValue::new_array();
+            for _ in 0..*length {
+                array.push(parameter_value(inner, values)).unwrap();
+            }
+            array
+        }
+    }
+}
+
+pub fn write_sym_json(file: &str, main: &SymComponent) -> Result<(), ()> {
+    let file = File::create(file).map_err(|_err| {})?;
+    let mut writer = BufWriter::new(file);
+    main.to_json().write_pretty(&mut writer, 1).map_err(|_err| {})?;
+    writer.write_all(b"\n").map_err(|_err| {})?;
+    writer.flush().map_err(|_err| {})
+}
@@ -0,0 +1,38 @@
+use super::sym_writer::SymElem;
+use std::collections::HashMap;
+
+impl std::str::FromStr for SymElem {
+    type Err = ();
+    fn from_str(line: &str) -> Result<SymElem, ()> {
+        let fields: Vec<&str> = line.splitn(4, ',').collect();
+        if fields.len() != 4 {
+            return Result::Err(());
+        }
+        Result::Ok(SymElem {
+            original: fields[0].parse().map_err(|_err| {})?,
+            witness: fields[1].parse().map_err(|_err| {})?,
+            node_id: fields[2].parse().map_err(|_err| {})?,
+            symbol: fields[3].to_string(),
+        })
+    }
+}
+
+pub fn read_sym(file: &str) -> Result<Vec<SymElem>, ()> {
+    let contents = std::fs::read_to_string(file).map_err(|_err| {})?;
+    contents.lines().filter(|line| !line.trim().is_empty()).map(|line| line.parse()).collect()
+}
+
+// Template of every node of the component tree written by write_sym_json
+pub fn read_sym_json_templates(file: &str) -> Result<HashMap<i64, String>, ()> {
+    let contents = std::fs::read_to_string(file).map_err(|_err| {})?;
+    let tree = json::parse(&contents).map_err(|_err| {})?;
+    let mut templates = HashMap::new();
+    let mut pending = vec![&tree];
+    while let Some(component) = pending.pop() {
+        let node_id = component["node_id"].as_i64().ok_or(())?;
+        let template = component["template"].as_str().ok_or(())?;
+        templates.insert(node_id, template.to_string());
+        pending.extend(component["components"].members());
+    }
+    Result::Ok(templates)
+}
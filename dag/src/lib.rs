@@ -1,12 +1,15 @@
 mod constraint_correctness_analysis;
 mod json_porting;
 mod map_to_constraint_list;
+mod origin_porting;
 mod r1cs_porting;
 mod sym_porting;
 mod witness_producer;
 use circom_algebra::num_bigint::BigInt;
 use constraint_list::ConstraintList;
 use constraint_writers::debug_writer::DebugWriter;
+use constraint_writers::origin_writer::ConstraintOrigin;
+use constraint_writers::sym_json_writer::{ParameterDeclaration, SignalDeclaration};
 use constraint_writers::ConstraintExporter;
 use program_structure::constants::UsefulConstants;
 use program_structure::error_definition::ReportCollection;
@@ -23,6 +26,7 @@ pub struct Tree<'a> {
     pub field: BigInt,
     pub path: String,
     pub offset: usize,
+    pub constraint_offset: usize,
     pub node_id: usize,
     pub signals: Vec<usize>,
     pub forbidden: HashSet<usize>,
@@ -37,6 +41,7 @@ impl<'a> Tree<'a> {
         let root = dag.get_main().unwrap();
         let node_id = dag.main_id();
         let offset = dag.get_entry().unwrap().in_number;
+        let constraint_offset = dag.get_entry().unwrap().in_constraint_number;
         let path = dag.get_entry().unwrap().label.clone();
         let constraints = root.constraints.clone();
         let mut id_to_name = HashMap::new();
@@ -50,7 +55,18 @@ impl<'a> Tree<'a> {
             }
         }
         signals.sort();
-        Tree { field, dag, path, offset, node_id, signals, forbidden, id_to_name, constraints }
+        Tree {
+            field,
+            dag,
+            path,
+            offset,
+            constraint_offset,
+            node_id,
+            signals,
+            forbidden,
+            id_to_name,
+            constraints,
+        }
     }
 
     pub fn go_to_subtree(current: &'a Tree, edge: &Edge) -> Tree<'a> {
@@ -60,6 +76,7 @@ impl<'a> Tree<'a> {
         let node = &current.dag.nodes[node_id];
         let path = format!("{}.{}", current.path, edge.label);
         let offset = current.offset + edge.in_number;
+        let constraint_offset = current.constraint_offset + edge.in_constraint_number;
         let mut id_to_name = HashMap::new();
         let forbidden = HashSet::with_capacity(0);
         let mut signals: Vec<_> = Vec::new();
@@ -76,12 +93,27 @@ impl<'a> Tree<'a> {
             .filter(|c| !c.is_empty())
             .map(|c| Constraint::apply_offset(c, offset))
             .collect();
-        Tree { field, dag, path, offset, node_id, signals, forbidden, id_to_name, constraints }
+        Tree {
+            field,
+            dag,
+            path,
+            offset,
+            constraint_offset,
+            node_id,
+            signals,
+            forbidden,
+            id_to_name,
+            constraints,
+        }
     }
 
     pub fn get_edges(tree: &'a Tree) -> &'a Vec<Edge> {
         &tree.dag.adjacency[tree.node_id]
     }
+
+    pub fn origin_offset(tree: &Tree) -> usize {
+        tree.constraint_offset + tree.dag.nodes[tree.node_id].local_constraints_offset()
+    }
 }
 
 #[derive(Default)]
@@ -91,11 +123,12 @@ pub struct Edge {
     in_number: usize,
     out_number: usize,
     in_component_number: usize,
-    out_component_number: usize
+    out_component_number: usize,
+    in_constraint_number: usize,
 }
 impl Edge {
     fn new_entry(id: usize) -> Edge {
-        Edge { label: "main".to_string(), goes_to: id, in_number: 0, out_number: 0, in_component_number: 0, out_component_number: 0  }
+        Edge { label: "main".to_string(), goes_to: id, in_number: 0, out_number: 0, in_component_number: 0, out_component_number: 0, in_constraint_number: 0 }
     }
 
     pub fn get_goes_to(&self) -> usize {
@@ -122,6 +155,10 @@ impl Edge {
         self.out_component_number
     }
 
+    pub fn get_in_constraint(&self) -> usize {
+        self.in_constraint_number
+    }
+
     pub fn reach(&self, with_offset: usize) -> usize {
         with_offset + self.in_number
     }
@@ -136,18 +173,24 @@ pub struct Node {
     entry: Edge,
     template_name: String,
     parameters: Vec<BigInt>,
+    parameter_declarations: Vec<ParameterDeclaration>,
     number_of_signals: usize,
     number_of_components: usize,
+    number_of_constraints: usize,
     intermediates_length: usize,
     public_inputs_length: usize,
     inputs_length: usize,
     outputs_length: usize,
     signal_correspondence: HashMap<String, Signal>,
     ordered_signals: Vec<String>,
+    signal_declarations: Vec<SignalDeclaration>,
     locals: HashSet<usize>,
     forbidden_if_main: HashSet<usize>,
     io_signals: Vec<usize>,
     constraints: Vec<Constraint>,
+    local_constraints: usize,
+    // only built with --origins
+    constraint_origins: Vec<ConstraintOrigin>,
     is_parallel: bool,
     has_parallel_sub_cmp: bool,
     is_custom_gate: bool,
@@ -210,7 +253,21 @@ impl Node {
         self.intermediates_length += 1;
     }
 
-    fn add_constraint(&mut self, constraint: Constraint) {
+    fn add_signal_declaration(&mut self, declaration: SignalDeclaration) {
+        self.signal_declarations.push(declaration);
+    }
+
+    fn add_parameter_declaration(&mut self, declaration: ParameterDeclaration) {
+        self.parameter_declarations.push(declaration);
+    }
+
+    fn add_constraint(&mut self, mut constraint: Constraint, origin: Option<ConstraintOrigin>) {
+        if let Some(origin) = origin {
+            constraint.set_origins(vec![self.constraint_origins.len()]);
+            self.constraint_origins.push(origin);
+        }
+        self.local_constraints += 1;
+        self.number_of_constraints += 1;
         self.constraints.push(constraint)
     }
 
@@ -234,10 +291,31 @@ impl Node {
         &self.signal_correspondence
     }
 
+    pub fn signal_declarations(&self) -> &[SignalDeclaration] {
+        &self.signal_declarations
+    }
+
+    pub fn parameter_declarations(&self) -> &[ParameterDeclaration] {
+        &self.parameter_declarations
+    }
+
+    pub fn template_name(&self) -> &str {
+        &self.template_name
+    }
+
     pub fn constraints(&self) -> &[Constraint] {
         &self.constraints
     }
 
+    pub fn constraint_origins(&self) -> &[ConstraintOrigin] {
+        &self.constraint_origins
+    }
+
+    // the local constraints are numbered after the ones of the subcomponents
+    pub fn local_constraints_offset(&self) -> usize {
+        self.number_of_constraints - self.local_constraints
+    }
+
     pub fn io_signals(&self) -> &Vec<usize> {
         &self.io_signals
     }
@@ -298,6 +376,18 @@ impl ConstraintExporter for DAG {
     fn sym(&self, out: &str) -> Result<(), ()> {
         DAG::generate_sym_output(self, out)
     }
+
+    fn sym_json(&self, out: &str) -> Result<(), ()> {
+        DAG::generate_sym_json_output(self, out)
+    }
+
+    fn origins(&self, out: &str) -> Result<(), ()> {
+        DAG::generate_origins_output(self, out)
+    }
+
+    fn number_of_constraints(&self) -> usize {
+        self.get_main().map_or(0, |main| main.number_of_constraints)
+    }
 }
 
 impl DAG {
@@ -316,12 +406,14 @@ impl DAG {
             let from = self.main_id();
             let in_num = self.nodes[from].number_of_signals;
             let in_component_num = self.nodes[from].number_of_components;
+            let in_constraint_num = self.nodes[from].number_of_constraints;
             let out_num = in_num + self.nodes[to].number_of_signals;
             let out_component_num = in_component_num + self.nodes[to].number_of_components;
             self.nodes[from].number_of_signals += self.nodes[to].number_of_signals;
             self.nodes[from].entry.out_number += self.nodes[to].number_of_signals;
             self.nodes[from].number_of_components += self.nodes[to].number_of_components;
             self.nodes[from].entry.out_component_number += self.nodes[to].number_of_components;
+            self.nodes[from].number_of_constraints += self.nodes[to].number_of_constraints;
             self.nodes[from].has_parallel_sub_cmp |= self.nodes[to].is_parallel;
             let with = Edge {
                 label: label.to_string(),
@@ -330,6 +422,7 @@ impl DAG {
                 out_number: out_num,
                 in_component_number: in_component_num,
                 out_component_number: out_component_num,
+                in_constraint_number: in_constraint_num,
             };
             // add correspondence to current node
             let mut correspondence = std::mem::take(&mut self.nodes[from].signal_correspondence);
@@ -383,9 +476,21 @@ impl DAG {
         }
     }
 
-    pub fn add_constraint(&mut self, constraint: Constraint) {
+    pub fn add_signal_declaration(&mut self, declaration: SignalDeclaration) {
         if let Option::Some(node) = self.get_mut_main() {
-            node.add_constraint(constraint);
+            node.add_signal_declaration(declaration);
+        }
+    }
+
+    pub fn add_parameter_declaration(&mut self, declaration: ParameterDeclaration) {
+        if let Option::Some(node) = self.get_mut_main() {
+            node.add_parameter_declaration(declaration);
+        }
+    }
+
+    pub fn add_constraint(&mut self, constraint: Constraint, origin: Option<ConstraintOrigin>) {
+        if let Option::Some(node) = self.get_mut_main() {
+            node.add_constraint(constraint, origin);
         }
     }
 
@@ -450,6 +555,14 @@ impl DAG {
         sym_porting::write(self, output_file)
     }
 
+    pub fn generate_sym_json_output(&self, output_file: &str) -> Result<(), ()> {
+        sym_porting::write_json(self, output_file)
+    }
+
+    pub fn generate_origins_output(&self, output_file: &str) -> Result<(), ()> {
+        origin_porting::write(self, output_file)
+    }
+
     pub fn generate_json_constraints(&self, debug: &DebugWriter) -> Result<(), ()> {
         json_porting::port_constraints(self, debug)
     }
@@ -512,6 +625,7 @@ pub struct SimplificationFlags {
     pub flag_s: bool,
     pub parallel_flag: bool,
     pub port_substitution: bool,
+    pub json_substitutions: String,
     pub flag_old_heuristics: bool,
     pub prime : String,
 }
@@ -13,7 +13,7 @@ fn map_tree(
     tree: &Tree,
     witness: &mut Vec<usize>,
     c_holder: &mut CHolder,
-    forbidden: &mut HashSet<usize>
+    forbidden: &mut HashSet<usize>,
 ) -> usize {
     let mut no_constraints = 0;
 
@@ -24,13 +24,16 @@ fn map_tree(
         }
     }
 
+    let origin_offset = Tree::origin_offset(tree);
     for constraint in &tree.constraints {
+        let mut mapped = constraint.clone();
+        Constraint::apply_origin_offset(&mut mapped, origin_offset);
         if Constraint::is_constant_equality(constraint) {
-            LinkedList::push_back(&mut c_holder.constant_equalities, constraint.clone());
+            LinkedList::push_back(&mut c_holder.constant_equalities, mapped);
         } else if Constraint::is_equality(constraint, &tree.field) {
-            LinkedList::push_back(&mut c_holder.equalities, constraint.clone());
+            LinkedList::push_back(&mut c_holder.equalities, mapped);
         } else if Constraint::is_linear(constraint) {
-            LinkedList::push_back(&mut c_holder.linear, constraint.clone());
+            LinkedList::push_back(&mut c_holder.linear, mapped);
         } else {
             no_constraints += 1;
         }
@@ -71,6 +74,7 @@ fn produce_encoding(
 fn map_node_to_encoding(id: usize, node: Node) -> EncodingNode {
     let mut signals = Vec::new();
     let mut ordered_signals = Vec::new();
+    let local_constraints_offset = node.local_constraints_offset();
     let locals = node.locals;
     let mut non_linear = LinkedList::new();
     for c in node.constraints {
@@ -96,15 +100,24 @@ fn map_node_to_encoding(id: usize, node: Node) -> EncodingNode {
         id,
         name: node.template_name,
         parameters: node.parameters,
+        parameter_declarations: node.parameter_declarations,
         signals,
+        signal_declarations: node.signal_declarations,
         ordered_signals,
         non_linear,
+        constraint_origins: node.constraint_origins,
+        local_constraints_offset,
         is_custom_gate: node.is_custom_gate,
     }
 }
 
 fn map_edge_to_encoding(edge: Edge) -> EncodingEdge {
-    EncodingEdge { goes_to: edge.goes_to, path: edge.label, offset: edge.in_number }
+    EncodingEdge {
+        goes_to: edge.goes_to,
+        path: edge.label,
+        offset: edge.in_number,
+        constraint_offset: edge.in_constraint_number,
+    }
 }
 
 pub fn map(dag: DAG, flags: SimplificationFlags) -> ConstraintList {
@@ -120,9 +133,15 @@ pub fn map(dag: DAG, flags: SimplificationFlags) -> ConstraintList {
     let mut forbidden = dag.get_main().unwrap().forbidden_if_main.clone();
     let mut c_holder = CHolder::default();
     let mut signal_map = vec![0];
-    let no_constraints = map_tree(&Tree::new(&dag), &mut signal_map, &mut c_holder, &mut forbidden);
+    let no_constraints = map_tree(
+        &Tree::new(&dag),
+        &mut signal_map,
+        &mut c_holder,
+        &mut forbidden,
+    );
     let max_signal = Vec::len(&signal_map);
-    let name_encoding = produce_encoding(no_constraints, init_id, dag.nodes, dag.adjacency);
+    let name_encoding =
+        produce_encoding(no_constraints, init_id, dag.nodes, dag.adjacency);
     let _dur = now.elapsed().unwrap().as_millis();
     // println!("End of dag to list mapping: {} ms", dur);
     Simplifier {
@@ -141,6 +160,7 @@ pub fn map(dag: DAG, flags: SimplificationFlags) -> ConstraintList {
         parallel_flag: flags.parallel_flag,
         flag_old_heuristics: flags.flag_old_heuristics,
         port_substitution: flags.port_substitution,
+        json_substitutions: flags.json_substitutions,
     }
     .simplify_constraints()
 }
@@ -0,0 +1,27 @@
+use super::{Tree, DAG};
+use constraint_writers::origin_writer::{OriginElem, OriginFile};
+
+pub fn write(dag: &DAG, file_name: &str) -> Result<(), ()> {
+    let tree = Tree::new(dag);
+    let mut dot_origins = OriginFile::new(file_name)?;
+    let mut written = 0;
+    visit_tree(&tree, &mut dot_origins, &mut written)?;
+    OriginFile::close(dot_origins)
+}
+
+fn visit_tree(tree: &Tree, dot_origins: &mut OriginFile, written: &mut usize) -> Result<(), ()> {
+    let node = &tree.dag.nodes[tree.node_id];
+    for constraint in &tree.constraints {
+        for origin in constraint.origins() {
+            let origin = &node.constraint_origins()[*origin];
+            let elem = OriginElem { constraint: *written, origin, component: &tree.path };
+            OriginFile::write_origin_elem(dot_origins, elem)?;
+        }
+        *written += 1;
+    }
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        visit_tree(&subtree, dot_origins, written)?;
+    }
+    Ok(())
+}
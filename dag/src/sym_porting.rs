@@ -1,5 +1,6 @@
 use super::{Tree, DAG};
 use circom_algebra::num_traits::AsPrimitive;
+use constraint_writers::sym_json_writer::{self, SymComponent, SymSignal};
 use constraint_writers::sym_writer::*;
 use std::collections::HashMap;
 
@@ -27,3 +28,38 @@ fn visit_tree(tree: &Tree, dot_sym: &mut SymFile) -> Result<(), ()> {
     }
     Ok(())
 }
+
+// Without simplification every signal keeps its original index as witness
+pub fn write_json(dag: &DAG, file_name: &str) -> Result<(), ()> {
+    let tree = Tree::new(dag);
+    let main = build_component(dag, &tree, "main");
+    sym_json_writer::write_sym_json(file_name, &main)
+}
+
+fn build_component<'a>(dag: &'a DAG, tree: &Tree, name: &str) -> SymComponent<'a> {
+    let node = &dag.nodes[tree.node_id];
+    let mut signals = Vec::new();
+    for declaration in node.signal_declarations() {
+        let witness = declaration
+            .element_names()
+            .iter()
+            .map(|name| (node.correspondence().get(name).unwrap() + tree.offset).as_())
+            .collect();
+        signals.push(SymSignal { declaration, witness });
+    }
+    let mut components = Vec::new();
+    for edge in Tree::get_edges(tree) {
+        let subtree = Tree::go_to_subtree(tree, edge);
+        components.push(build_component(dag, &subtree, edge.get_label()));
+    }
+    SymComponent {
+        name: name.to_string(),
+        path: tree.path.clone(),
+        instance: node.template_name(),
+        parameter_declarations: node.parameter_declarations(),
+        parameters: node.parameters().iter().map(|value| value.to_string()).collect(),
+        node_id: tree.node_id,
+        signals,
+        components,
+    }
+}
@@ -1,5 +1,7 @@
 use super::errors::FileOsError;
 use program_structure::error_definition::Report;
+use program_structure::file_definition::FileID;
+use program_structure::program_merger::{FileNamespace, NamespaceInfo};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
@@ -7,25 +9,41 @@ pub struct FileStack {
     current_location: PathBuf,
     black_paths: HashSet<PathBuf>,
     stack: Vec<PathBuf>,
+    // name and root directory of the dependencies declared in circom.toml
+    dependencies: Vec<(String, PathBuf)>,
 }
 
 impl FileStack {
-    pub fn new(src: PathBuf) -> FileStack {
+    pub fn new(src: PathBuf, dependencies: Vec<(String, PathBuf)>) -> FileStack {
         let mut location = src.clone();
         location.pop();
-        FileStack { current_location: location, black_paths: HashSet::new(), stack: vec![src] }
+        FileStack {
+            current_location: location,
+            black_paths: HashSet::new(),
+            stack: vec![src],
+            dependencies,
+        }
     }
 
-    pub fn add_include(f_stack: &mut FileStack, path: String) -> Result<(), Report> {
+    // An include is a path relative to the including file or, if there is no
+    // such file, a path inside a dependency: "dependency/path/to/file"
+    pub fn add_include(f_stack: &mut FileStack, path: String) -> Result<PathBuf, Report> {
         let mut crr = f_stack.current_location.clone();
         crr.push(path.clone());
         let path = std::fs::canonicalize(crr)
+            .or_else(|_| FileStack::resolve_in_dependency(f_stack, &path))
             .map_err(|_| FileOsError { path: path.clone() })
             .map_err(|e| FileOsError::produce_report(e))?;
         if !f_stack.black_paths.contains(&path) {
-            f_stack.stack.push(path);
+            f_stack.stack.push(path.clone());
         }
-        Ok(())
+        Ok(path)
+    }
+
+    fn resolve_in_dependency(f_stack: &FileStack, path: &str) -> Result<PathBuf, ()> {
+        let (name, rest) = path.split_once('/').ok_or(())?;
+        let (_, root) = f_stack.dependencies.iter().find(|(dep, _)| dep == name).ok_or(())?;
+        std::fs::canonicalize(root.join(rest)).map_err(|_| {})
     }
 
     pub fn take_next(f_stack: &mut FileStack) -> Option<PathBuf> {
@@ -46,6 +64,74 @@ impl FileStack {
     }
 }
 
+// Include edges of the program, used to give every file its namespace
+#[derive(Default)]
+pub struct NamespaceGraph {
+    files: HashMap<PathBuf, FileID>,
+    // includer, included file and alias of the include
+    edges: Vec<(FileID, PathBuf, Option<String>)>,
+}
+
+impl NamespaceGraph {
+    pub fn new() -> NamespaceGraph {
+        NamespaceGraph::default()
+    }
+
+    pub fn add_file(&mut self, path: PathBuf, file_id: FileID) {
+        self.files.insert(path, file_id);
+    }
+
+    pub fn add_edge(&mut self, from: FileID, to: PathBuf, alias: Option<String>) {
+        self.edges.push((from, to, alias));
+    }
+
+    // The root file is in the global namespace. A file included with an alias
+    // gets the namespace of the includer extended with the alias, and a plain
+    // include keeps the namespace of the includer. A file reached both ways
+    // stays in the global namespace if any of the paths leads there, so that
+    // plain includes keep working; otherwise the first namespace found wins.
+    pub fn build_namespaces(&self, root: FileID) -> NamespaceInfo {
+        let mut prefixes: HashMap<FileID, Option<String>> = HashMap::new();
+        prefixes.insert(root, None);
+        let mut pending = vec![root];
+        while let Some(from) = pending.pop() {
+            let from_prefix = prefixes.get(&from).unwrap().clone();
+            for (includer, path, alias) in &self.edges {
+                if *includer != from {
+                    continue;
+                }
+                let to = *self.files.get(path).unwrap();
+                let candidate = match alias {
+                    Some(alias) => Some(FileNamespace::qualify(&from_prefix, alias)),
+                    None => from_prefix.clone(),
+                };
+                let update = match prefixes.get(&to) {
+                    None => true,
+                    Some(Some(_)) => candidate.is_none(),
+                    Some(None) => false,
+                };
+                if update {
+                    prefixes.insert(to, candidate);
+                    pending.push(to);
+                }
+            }
+        }
+        let mut namespaces = NamespaceInfo::new();
+        for (file_id, prefix) in &prefixes {
+            let namespace = namespaces.entry(*file_id).or_default();
+            namespace.prefix = prefix.clone();
+        }
+        for (includer, path, alias) in &self.edges {
+            if let Some(alias) = alias {
+                let to = self.files.get(path).unwrap();
+                let prefix = prefixes.get(to).cloned().unwrap_or(None);
+                namespaces.entry(*includer).or_default().aliases.insert(alias.clone(), prefix);
+            }
+        }
+        namespaces
+    }
+}
+
 pub struct IncludesNode {
     pub path: PathBuf,
     pub custom_gates_pragma: bool,
@@ -75,16 +161,9 @@ impl IncludesGraph {
         }
     }
 
-    pub fn add_edge(&mut self, path: String) -> Result<(), Report> {
-        let mut crr = self.nodes.last().unwrap().path.clone();
-        crr.pop();
-        crr.push(path.clone());
-        let path = std::fs::canonicalize(crr)
-            .map_err(|_| FileOsError { path: path })
-            .map_err(|e| FileOsError::produce_report(e))?;
+    pub fn add_edge(&mut self, path: PathBuf) {
         let edges = self.adjacency.entry(path).or_insert(vec![]);
         edges.push(self.nodes.len() - 1);
-        Ok(())
     }
 
     pub fn get_problematic_paths(&self) -> Vec<Vec<PathBuf>> {
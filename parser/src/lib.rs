@@ -9,44 +9,88 @@ lalrpop_mod!(pub lang);
 
 pub mod errors;
 pub mod include_logic;
+pub mod manifest;
 pub mod parser_logic;
-use include_logic::{FileStack, IncludesGraph};
+use include_logic::{FileStack, IncludesGraph, NamespaceGraph};
+use manifest::Manifest;
+use program_structure::ast::build_main_component;
 use program_structure::error_code::ReportCode;
 use program_structure::error_definition::{Report, ReportCollection};
 use program_structure::file_definition::{FileLibrary};
 use program_structure::program_archive::ProgramArchive;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 pub type Version = (usize, usize, usize);
 
+// Main component given in the command line instead of the one in the files.
+// Several of them are given in batch compilations
+#[derive(Clone, Default)]
+pub struct MainOverride {
+    pub call: Option<String>,
+    pub public: Option<Vec<String>>,
+}
+
 pub fn run_parser(
     file: String,
-    version: &str
+    version: &str,
+    update_lock: bool,
+    main_overrides: &[MainOverride],
 ) -> Result<(ProgramArchive, ReportCollection), (FileLibrary, ReportCollection)> {
     let mut file_library = FileLibrary::new();
     let mut definitions = Vec::new();
     let mut main_components = Vec::new();
-    let mut file_stack = FileStack::new(PathBuf::from(file));
+    let manifest = Manifest::find(&PathBuf::from(&file))
+        .map_err(|e| (file_library.clone(), vec![e]))?;
+    if let Some(manifest) = &manifest {
+        manifest.check_lock(update_lock).map_err(|e| (file_library.clone(), vec![e]))?;
+    }
+    let dependencies = manifest.as_ref().map_or(vec![], |m| m.dependency_roots());
+    let mut file_stack = FileStack::new(PathBuf::from(file), dependencies);
     let mut includes_graph = IncludesGraph::new();
+    let mut namespace_graph = NamespaceGraph::new();
+    let mut root_file = None;
     let mut warnings = Vec::new();
 
     while let Some(crr_file) = FileStack::take_next(&mut file_stack) {
         let (path, src) = open_file(crr_file.clone())
             .map_err(|e| (file_library.clone(), vec![e]))?;
         let file_id = file_library.add_file(path.clone(), src.clone());
+        root_file.get_or_insert(file_id);
+        namespace_graph.add_file(crr_file.clone(), file_id);
         let program = parser_logic::parse_file(&src, file_id)
             .map_err(|e| (file_library.clone(), vec![e]))?;
         if let Some(main) = program.main_component {
             main_components.push((file_id, main));
         }
-        includes_graph.add_node(crr_file, program.custom_gates, program.custom_gates_declared);
+        includes_graph.add_node(crr_file.clone(), program.custom_gates, program.custom_gates_declared);
         let includes = program.includes;
         definitions.push((file_id, program.definitions));
+        let mut aliases = HashSet::new();
         for include in includes {
-            FileStack::add_include(&mut file_stack, include.clone())
+            let included = FileStack::add_include(&mut file_stack, include.path.clone())
+                .map_err(|e| (file_library.clone(), vec![e]))?;
+            includes_graph.add_edge(included.clone());
+            if let Some(alias) = &include.alias {
+                if !aliases.insert(alias.clone()) {
+                    let mut report = Report::error(
+                        format!("Namespace {} declared twice", alias),
+                        ReportCode::NamespaceDeclaredTwice,
+                    );
+                    report.add_primary(
+                        include.meta.file_location(),
+                        file_id,
+                        format!("{} is already in use", alias),
+                    );
+                    return Err((file_library.clone(), vec![report]));
+                }
+            }
+            namespace_graph.add_edge(file_id, included, include.alias);
+        }
+        if let Some(manifest) = &manifest {
+            manifest.check_version(&crr_file, program.compiler_version)
                 .map_err(|e| (file_library.clone(), vec![e]))?;
-            includes_graph.add_edge(include).map_err(|e| (file_library.clone(), vec![e]))?;
         }
         warnings.append(
             &mut check_number_version(
@@ -64,10 +108,27 @@ pub fn run_parser(
         }
     }
 
+    let mut overridden_mains = Vec::new();
+    for main_override in main_overrides {
+        if let Some(call) = &main_override.call {
+            let file_id = file_library.add_file("--main".to_string(), call.clone());
+            let expression = parser_logic::parse_main_call(call, file_id)
+                .map_err(|e| (file_library.clone(), vec![e]))?;
+            let public = main_override.public.clone().unwrap_or_default();
+            overridden_mains.push((file_id, build_main_component(public, expression)));
+        } else if let (Some(public), [(_, main)]) = (&main_override.public, main_components.as_mut_slice()) {
+            main.0 = public.clone();
+        }
+    }
+    let overridden_ids: Vec<_> = overridden_mains.iter().map(|(file_id, _)| *file_id).collect();
+    if !overridden_mains.is_empty() {
+        main_components = overridden_mains;
+    }
+
     if main_components.len() == 0 {
         let report = errors::NoMainError::produce_report();
         Err((file_library, vec![report]))
-    } else if main_components.len() > 1 {
+    } else if main_components.len() > 1 && overridden_ids.is_empty() {
         let report = errors::MultipleMainError::produce_report();
         Err((file_library, vec![report]))
     } else {
@@ -84,9 +145,15 @@ pub fn run_parser(
         if errors.len() > 0 {
             Err((file_library, errors))
         } else {
-            let (main_id, main_component) = main_components.pop().unwrap();
+            let mut namespaces = namespace_graph.build_namespaces(root_file.unwrap());
+            // The main components given in the command line see the names of the input file
+            if let Some(root_namespace) = namespaces.get(&root_file.unwrap()).cloned() {
+                for file_id in overridden_ids {
+                    namespaces.insert(file_id, root_namespace.clone());
+                }
+            }
             let result_program_archive =
-                ProgramArchive::new(file_library, main_id, main_component, definitions);
+                ProgramArchive::new(file_library, main_components, definitions, namespaces);
             match result_program_archive {
                 Err((lib, rep)) => {
                     Err((lib, rep))
@@ -110,8 +177,22 @@ fn open_file(path: PathBuf) -> Result<(String, String), Report> /* path, src */
 }
 
 fn parse_number_version(version: &str) -> Version {
+    read_version(version).unwrap()
+}
+
+pub(crate) fn read_version(version: &str) -> Option<Version> {
     let version_splitted: Vec<&str> = version.split(".").collect();
-    (usize::from_str(version_splitted[0]).unwrap(), usize::from_str(version_splitted[1]).unwrap(), usize::from_str(version_splitted[2]).unwrap())
+    if version_splitted.len() != 3 {
+        return None;
+    }
+    Some((usize::from_str(version_splitted[0]).ok()?, usize::from_str(version_splitted[1]).ok()?, usize::from_str(version_splitted[2]).ok()?))
+}
+
+// A file that requires required_version can be compiled by version_compiler
+pub(crate) fn is_compatible_version(required_version: Version, version_compiler: Version) -> bool {
+    required_version.0 == version_compiler.0
+    && (required_version.1 < version_compiler.1 ||
+     (required_version.1 == version_compiler.1 && required_version.2 <= version_compiler.2))
 }
 
 fn check_number_version(
@@ -121,9 +202,7 @@ fn check_number_version(
 ) -> Result<ReportCollection, Report> {
     use errors::{CompilerVersionError, NoCompilerVersionWarning};
     if let Some(required_version) = version_file {
-        if required_version.0 == version_compiler.0 
-        && (required_version.1 < version_compiler.1 ||  
-         (required_version.1 == version_compiler.1 && required_version.2 <= version_compiler.2)) {
+        if is_compatible_version(required_version, version_compiler) {
             Ok(vec![])
         } else {
             let report = CompilerVersionError::produce_report(
@@ -0,0 +1,369 @@
+use super::{is_compatible_version, read_version};
+use program_structure::ast::Version;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::Report;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+// An optional circom.toml next to the main file (or in any directory above it)
+// declares the dependencies of the project as local directories:
+//
+//     [dependencies]
+//     circomlib = { path = "vendor/circomlib/circuits", version = "2.0.0" }
+//     gadgets = "../gadgets"
+//
+// include "circomlib/poseidon.circom" is then resolved inside the directory of
+// circomlib when it is not a path relative to the including file. The content
+// hash of every dependency is recorded in circom.lock.
+
+pub const MANIFEST_FILE: &str = "circom.toml";
+pub const LOCK_FILE: &str = "circom.lock";
+
+pub struct Dependency {
+    pub name: String,
+    pub root: PathBuf,
+    pub path: String,
+    pub version: Option<Version>,
+}
+
+pub struct Manifest {
+    pub file: PathBuf,
+    pub dependencies: Vec<Dependency>,
+}
+
+fn manifest_error(msg: String) -> Report {
+    Report::error(msg, ReportCode::ManifestError)
+}
+
+impl Manifest {
+    // Looks for circom.toml in the directory of the main file and its ancestors
+    pub fn find(main_file: &Path) -> Result<Option<Manifest>, Report> {
+        let main_file = std::fs::canonicalize(main_file).unwrap_or_else(|_| main_file.to_path_buf());
+        let mut directory = main_file.parent();
+        while let Some(dir) = directory {
+            let candidate = dir.join(MANIFEST_FILE);
+            if candidate.is_file() {
+                return Manifest::load(&candidate).map(Some);
+            }
+            directory = dir.parent();
+        }
+        Ok(None)
+    }
+
+    pub fn load(file: &Path) -> Result<Manifest, Report> {
+        let src = std::fs::read_to_string(file)
+            .map_err(|_| manifest_error(format!("Could not open file {}", file.display())))?;
+        let manifest: Value = src
+            .parse()
+            .map_err(|e| manifest_error(format!("Invalid manifest {}: {}", file.display(), e)))?;
+        let entries = match manifest.get("dependencies") {
+            Some(Value::Table(entries)) => entries.clone(),
+            Some(_) => return Err(manifest_error(format!("Invalid manifest {}: dependencies must be a table", file.display()))),
+            None => Default::default(),
+        };
+        let base = file.parent().unwrap();
+        let mut dependencies = Vec::new();
+        for (name, entry) in entries {
+            let field = |key: &str, value: &Value| match value {
+                Value::String(value) => Ok(value.clone()),
+                _ => Err(format!("Field {} of dependency {} must be a string", key, name)),
+            };
+            let (path, version) = match &entry {
+                Value::String(path) => (path.clone(), None),
+                Value::Table(fields) => {
+                    let mut path = None;
+                    let mut version = None;
+                    for (key, value) in fields {
+                        match key.as_str() {
+                            "path" => path = Some(field(key, value).map_err(manifest_error)?),
+                            "version" => version = Some(field(key, value).map_err(manifest_error)?),
+                            _ => {
+                                return Err(manifest_error(format!(
+                                    "Unknown field {} in dependency {}",
+                                    key, name
+                                )))
+                            }
+                        }
+                    }
+                    match path {
+                        Some(path) => (path, version),
+                        None => {
+                            return Err(manifest_error(format!(
+                                "Dependency {} does not declare a path",
+                                name
+                            )))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(manifest_error(format!(
+                        "Dependency {} must be a path or a table",
+                        name
+                    )))
+                }
+            };
+            let root = std::fs::canonicalize(base.join(&path)).map_err(|_| {
+                manifest_error(format!("Could not find the directory {} of dependency {}", path, name))
+            })?;
+            let version = match version {
+                Some(text) => Some(read_version(&text).ok_or_else(|| {
+                    manifest_error(format!("Invalid version {} in dependency {}", text, name))
+                })?),
+                None => None,
+            };
+            dependencies.push(Dependency { name, root, path, version });
+        }
+        Ok(Manifest { file: file.to_path_buf(), dependencies })
+    }
+
+    pub fn dependency_roots(&self) -> Vec<(String, PathBuf)> {
+        self.dependencies.iter().map(|d| (d.name.clone(), d.root.clone())).collect()
+    }
+
+    fn dependency_of(&self, file: &Path) -> Option<&Dependency> {
+        self.dependencies.iter().find(|d| file.starts_with(&d.root))
+    }
+
+    // Checks the pragma circom of a file of a dependency against the version
+    // declared for it, as the compiler checks it against its own version
+    pub fn check_version(&self, file: &Path, version: Option<Version>) -> Result<(), Report> {
+        let dependency = match self.dependency_of(file) {
+            Some(dependency) => dependency,
+            None => return Ok(()),
+        };
+        match (dependency.version, version) {
+            (Some(declared), Some(version)) if !is_compatible_version(version, declared) => {
+                Err(Report::error(
+                    format!(
+                        "File {} of dependency {} requires circom {}.{}.{}, which is not compatible with the version {}.{}.{} declared in {}",
+                        file.display(),
+                        dependency.name,
+                        version.0,
+                        version.1,
+                        version.2,
+                        declared.0,
+                        declared.1,
+                        declared.2,
+                        self.file.display()
+                    ),
+                    ReportCode::DependencyVersionError,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Compares the content of the dependencies with circom.lock, writing the
+    // file if it does not exist, a dependency is new or removed, or update is set
+    pub fn check_lock(&self, update: bool) -> Result<(), Report> {
+        let lock_file = self.file.with_file_name(LOCK_FILE);
+        let mut locked = BTreeMap::new();
+        if lock_file.is_file() {
+            let src = std::fs::read_to_string(&lock_file)
+                .map_err(|_| manifest_error(format!("Could not open file {}", lock_file.display())))?;
+            let lock: LockFile = toml::from_str(&src)
+                .map_err(|e| manifest_error(format!("Invalid lock file {}: {}", lock_file.display(), e)))?;
+            locked = lock.dependencies;
+        }
+        let mut changed = locked.len() != self.dependencies.len();
+        let mut current = BTreeMap::new();
+        for dependency in &self.dependencies {
+            let hash = hash_directory(&dependency.root)
+                .map_err(|_| manifest_error(format!("Could not read dependency {}", dependency.name)))?;
+            match locked.get(&dependency.name) {
+                // A dependency that points to another directory must still
+                // have the recorded content
+                Some(entry) => {
+                    if entry.hash != hash {
+                        if !update {
+                            return Err(manifest_error(format!(
+                                "The content of dependency {} does not match {}. Review the changes and run with --update-lock to record them",
+                                dependency.name,
+                                lock_file.display()
+                            )));
+                        }
+                        changed = true;
+                    }
+                    if entry.path != dependency.path {
+                        changed = true;
+                    }
+                }
+                None => changed = true,
+            }
+            current.insert(dependency.name.clone(), LockedDependency { path: dependency.path.clone(), hash });
+        }
+        if changed {
+            let lock = toml::to_string(&LockFile { dependencies: current })
+                .map_err(|_| manifest_error(format!("Could not write file {}", lock_file.display())))?;
+            let src = format!("# This file is generated by circom. It records the content of the dependencies in circom.toml.\n\n{}", lock);
+            std::fs::write(&lock_file, src)
+                .map_err(|_| manifest_error(format!("Could not write file {}", lock_file.display())))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockFile {
+    #[serde(default)]
+    dependencies: BTreeMap<String, LockedDependency>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockedDependency {
+    path: String,
+    hash: String,
+}
+
+// sha256 of the .circom files of a directory, with their relative paths
+fn hash_directory(root: &Path) -> Result<String, ()> {
+    fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ()> {
+        for entry in std::fs::read_dir(dir).map_err(|_| {})? {
+            let path = entry.map_err(|_| {})?.path();
+            if path.is_dir() {
+                collect(&path, files)?;
+            } else if path.extension().map_or(false, |ext| ext == "circom") {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    collect(root, &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(root).unwrap();
+        hasher.input(relative.to_string_lossy().as_bytes());
+        hasher.input(&[0]);
+        hasher.input(&std::fs::read(&file).map_err(|_| {})?);
+        hasher.input(&[0]);
+    }
+    let digest = hasher.result();
+    let hex: Vec<String> = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("sha256:{}", hex.join("")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str, manifest: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("circom_manifest_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("lib/a.circom"), "pragma circom 2.0.0;\n").unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        dir
+    }
+
+    fn load(dir: &Path) -> Manifest {
+        match Manifest::load(&dir.join(MANIFEST_FILE)) {
+            Ok(manifest) => manifest,
+            Err(report) => panic!("{}", report.get_message()),
+        }
+    }
+
+    fn load_error(name: &str, manifest: &str) -> String {
+        let dir = project(name, manifest);
+        let result = Manifest::load(&dir.join(MANIFEST_FILE));
+        std::fs::remove_dir_all(&dir).unwrap();
+        match result {
+            Ok(_) => panic!("{} was accepted", manifest),
+            Err(report) => report.get_message().clone(),
+        }
+    }
+
+    #[test]
+    fn version_constraints() {
+        let dir = project("versions", "[dependencies]\nlib = { path = \"lib\", version = \"2.0.3\" }\n");
+        let manifest = load(&dir);
+        let file = manifest.dependencies[0].root.join("a.circom");
+        assert!(manifest.check_version(&file, Some((2, 0, 3))).is_ok());
+        assert!(manifest.check_version(&file, Some((2, 0, 1))).is_ok());
+        assert!(manifest.check_version(&file, Some((2, 1, 0))).is_err());
+        assert!(manifest.check_version(&file, Some((1, 0, 0))).is_err());
+        assert!(manifest.check_version(&file, None).is_ok());
+        assert!(manifest.check_version(&dir.join("main.circom"), Some((3, 0, 0))).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn manifest_entries() {
+        let dir = project("entries", "# comment\n[dependencies]\nlib = { path = \"lib\", version = \"2.0.0\" } # comment\nother = \".\"\n");
+        let manifest = load(&dir);
+        assert_eq!(manifest.dependencies.len(), 2);
+        let lib = manifest.dependencies.iter().find(|d| d.name == "lib").unwrap();
+        assert_eq!(lib.path, "lib");
+        assert!(lib.version == Some((2, 0, 0)));
+        let other = manifest.dependencies.iter().find(|d| d.name == "other").unwrap();
+        assert!(other.version.is_none());
+        assert!(other.root == std::fs::canonicalize(&dir).unwrap());
+        assert!(matches!(Manifest::find(&lib.root.join("a.circom")), Ok(Some(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_manifests() {
+        assert!(load_error("syntax", "[dependencies]\nlib = { path = \"lib\"\n").starts_with("Invalid manifest"));
+        assert!(load_error("section", "dependencies = \"lib\"\n").contains("dependencies must be a table"));
+        assert!(load_error("entry", "[dependencies]\nlib = 2\n").contains("must be a path or a table"));
+        assert!(load_error("field", "[dependencies]\nlib = { path = \"lib\", hash = \"x\" }\n").contains("Unknown field hash"));
+        assert!(load_error("string", "[dependencies]\nlib = { path = [\"lib\"] }\n").contains("must be a string"));
+        assert!(load_error("path", "[dependencies]\nlib = { version = \"2.0.0\" }\n").contains("does not declare a path"));
+        assert!(load_error("directory", "[dependencies]\nlib = \"missing\"\n").contains("Could not find the directory"));
+        assert!(load_error("version", "[dependencies]\nlib = { path = \"lib\", version = \"^2.0\" }\n").contains("Invalid version"));
+    }
+
+    #[test]
+    fn lock_mismatches() {
+        let dir = project("lock", "[dependencies]\nlib = \"lib\"\n");
+        let lock_file = dir.join(LOCK_FILE);
+        let manifest = load(&dir);
+        assert!(manifest.check_lock(false).is_ok());
+        let written = std::fs::read_to_string(&lock_file).unwrap();
+        assert!(manifest.check_lock(false).is_ok());
+        assert_eq!(std::fs::read_to_string(&lock_file).unwrap(), written);
+
+        std::fs::write(dir.join("lib/a.circom"), "pragma circom 2.0.1;\n").unwrap();
+        let error = manifest.check_lock(false).err().unwrap();
+        assert!(error.get_message().contains("--update-lock"));
+        assert_eq!(std::fs::read_to_string(&lock_file).unwrap(), written);
+        assert!(manifest.check_lock(true).is_ok());
+        assert!(std::fs::read_to_string(&lock_file).unwrap() != written);
+        assert!(manifest.check_lock(false).is_ok());
+
+        // A dependency that moved with the same content is recorded again
+        // without --update-lock, but not one that points to other content
+        let moved = load(&dir);
+        let moved = Manifest {
+            file: moved.file,
+            dependencies: moved.dependencies.into_iter().map(|d| Dependency { path: "./lib".to_string(), ..d }).collect(),
+        };
+        assert!(moved.check_lock(false).is_ok());
+        assert!(std::fs::read_to_string(&lock_file).unwrap().contains("./lib"));
+        std::fs::create_dir_all(dir.join("other")).unwrap();
+        std::fs::write(dir.join("other/a.circom"), "pragma circom 2.0.2;\n").unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), "[dependencies]\nlib = \"other\"\n").unwrap();
+        let repointed = load(&dir);
+        let written = std::fs::read_to_string(&lock_file).unwrap();
+        let error = repointed.check_lock(false).err().unwrap();
+        assert!(error.get_message().contains("--update-lock"));
+        assert_eq!(std::fs::read_to_string(&lock_file).unwrap(), written);
+        assert!(repointed.check_lock(true).is_ok());
+        assert!(repointed.check_lock(false).is_ok());
+
+        // Removed dependencies are dropped without --update-lock
+        std::fs::write(dir.join(MANIFEST_FILE), "").unwrap();
+        assert!(load(&dir).check_lock(false).is_ok());
+        assert!(!std::fs::read_to_string(&lock_file).unwrap().contains("lib"));
+
+        std::fs::write(&lock_file, "[dependencies]\nlib = { path = \"lib\" }\n").unwrap();
+        let error = manifest.check_lock(false).err().unwrap();
+        assert!(error.get_message().starts_with("Invalid lock file"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
 use super::errors::{ParsingError, UnclosedCommentError};
 use super::lang;
-use program_structure::ast::AST;
+use lalrpop_util::ParseError;
+use program_structure::ast::{Expression, AST};
+use std::fmt::Debug;
 use program_structure::error_definition::Report;
 use program_structure::file_definition::FileID;
 
@@ -85,26 +87,41 @@ pub fn preprocess(expr: &str, file_id: FileID) -> Result<String, Report> {
 }
 
 pub fn parse_file(src: &str, file_id: FileID) -> Result<AST, Report> {
-    use lalrpop_util::ParseError::*;
     lang::ParseAstParser::new()
         .parse(&preprocess(src, file_id)?)
-        .map_err(|parse_error| match parse_error {
-            InvalidToken { location } => ParsingError {
-                file_id,
-                msg: format!("{:?}", parse_error),
-                location: location..location,
-            },
-            UnrecognizedToken { ref token, .. } => ParsingError {
-                file_id,
-                msg: format!("{:?}", parse_error),
-                location: token.0..token.2,
-            },
-            ExtraToken { ref token } => ParsingError {
-                file_id,
-                msg: format!("{:?}", parse_error),
-                location: token.0..token.2,
-            },
-            _ => ParsingError { file_id, msg: format!("{:?}", parse_error), location: 0..0 },
-        })
-        .map_err(|parsing_error| ParsingError::produce_report(parsing_error))
+        .map_err(|parse_error| produce_parsing_report(parse_error, file_id))
+}
+
+// Parses the main component given in the command line, which replaces the
+// main component of the project
+pub fn parse_main_call(src: &str, file_id: FileID) -> Result<Expression, Report> {
+    lang::ParseExpressionParser::new()
+        .parse(&preprocess(src, file_id)?)
+        .map_err(|parse_error| produce_parsing_report(parse_error, file_id))
+}
+
+fn produce_parsing_report<T: Debug, E: Debug>(
+    parse_error: ParseError<usize, T, E>,
+    file_id: FileID,
+) -> Report {
+    use lalrpop_util::ParseError::*;
+    let parsing_error = match parse_error {
+        InvalidToken { location } => ParsingError {
+            file_id,
+            msg: format!("{:?}", parse_error),
+            location: location..location,
+        },
+        UnrecognizedToken { ref token, .. } => ParsingError {
+            file_id,
+            msg: format!("{:?}", parse_error),
+            location: token.0..token.2,
+        },
+        ExtraToken { ref token } => ParsingError {
+            file_id,
+            msg: format!("{:?}", parse_error),
+            location: token.0..token.2,
+        },
+        _ => ParsingError { file_id, msg: format!("{:?}", parse_error), location: 0..0 },
+    };
+    ParsingError::produce_report(parsing_error)
 }
@@ -14,6 +14,18 @@ pub fn build_main_component(public: Vec<String>, call: Expression) -> MainCompon
 
 pub type Version = (usize, usize, usize);
 
+// An include directive. Files included with an alias (include "file" as alias)
+// keep their definitions in their own namespace, referenced as alias.Name
+#[derive(Clone)]
+pub struct Include {
+    pub meta: Meta,
+    pub path: String,
+    pub alias: Option<String>,
+}
+pub fn build_include(meta: Meta, path: String, alias: Option<String>) -> Include {
+    Include { meta, path, alias }
+}
+
 
 #[derive(Clone)]
 pub struct Meta {
@@ -82,7 +94,7 @@ pub struct AST {
     pub compiler_version: Option<Version>,
     pub custom_gates: bool,
     pub custom_gates_declared: bool,
-    pub includes: Vec<String>,
+    pub includes: Vec<Include>,
     pub definitions: Vec<Definition>,
     pub main_component: Option<MainComponent>,
 }
@@ -91,7 +103,7 @@ impl AST {
         meta: Meta,
         compiler_version: Option<Version>,
         custom_gates: bool,
-        includes: Vec<String>,
+        includes: Vec<Include>,
         definitions: Vec<Definition>,
         main_component: Option<MainComponent>,
     ) -> AST {
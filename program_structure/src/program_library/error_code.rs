@@ -33,6 +33,10 @@ pub enum ReportCode {
     NonExistentSymbol,
     NoMainFoundInProject,
     NoCompilerVersionWarning,
+    NamespaceDeclaredTwice,
+    UnknownNamespace,
+    ManifestError,
+    DependencyVersionError,
     MultipleMainInComponent,
     TemplateCallAsArgument,
     TemplateWrongNumberOfArguments,
@@ -59,6 +63,8 @@ pub enum ReportCode {
     MustBeSingleArithmetic,
     ExpectedDimDiffGotDim(usize, usize),
     RuntimeError,
+    ExecutionLimitExceeded,
+    SignalDependentAssert,
     UnknownTemplate,
     NonQuadratic,
     NonConstantArrayLength,
@@ -67,7 +73,7 @@ pub enum ReportCode {
     UnconstrainedSignal,
     OneConstraintIntermediate,
     NoOutputInInstance,
-    ErrorWat2Wasm,
+    ErrorWasmEncoding,
     CustomGateIntermediateSignalWarning,
     CustomGateConstraintError,
     CustomGateSubComponentError,
@@ -83,6 +89,9 @@ impl fmt::Display for ReportCode {
             MultipleMainInComponent => "P1002",
             CompilerVersionError => "P1003",
             NoCompilerVersionWarning => "P1004",
+            NamespaceDeclaredTwice => "P1005",
+            ManifestError => "P1006",
+            DependencyVersionError => "P1007",
             WrongTypesInAssignOperation => "T2000",
             UndefinedFunction => "T2001",
             UndefinedTemplate => "T2002",
@@ -131,7 +140,10 @@ impl fmt::Display for ReportCode {
             InvalidPartialArray => "T2043",
             MustBeSingleArithmetic => "T2044",
             ExpectedDimDiffGotDim(..) => "T2045",
+            UnknownNamespace => "T2047",
             RuntimeError => "T3001",
+            ExecutionLimitExceeded => "T3002",
+            SignalDependentAssert => "T3003",
             UnknownDimension => "T20460",
             UnknownTemplate => "T20461",
             NonQuadratic => "T20462",
@@ -142,7 +154,7 @@ impl fmt::Display for ReportCode {
             UnconstrainedSignal => "CA01",
             OneConstraintIntermediate => "CA02",
             NoOutputInInstance => "CA03",
-            ErrorWat2Wasm => "W01",
+            ErrorWasmEncoding => "W01",
             CustomGateIntermediateSignalWarning => "CG01",
             CustomGateConstraintError => "CG02",
             CustomGateSubComponentError => "CG03",
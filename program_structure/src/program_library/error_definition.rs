@@ -124,7 +124,7 @@ impl Report {
     fn get_category(&self) -> &MessageCategory {
         &self.category
     }
-    fn get_message(&self) -> &String {
+    pub fn get_message(&self) -> &String {
         &self.error_message
     }
     fn get_code(&self) -> &ReportCode {
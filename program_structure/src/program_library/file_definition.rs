@@ -31,6 +31,12 @@ impl FileLibrary {
             None => None,
         }
     }
+    pub fn get_name(&self, file_id: FileID) -> Option<&FilePath> {
+        match self.files.get(file_id) {
+            Some(file) => Some(file.name()),
+            None => None,
+        }
+    }
     pub fn to_storage(&self) -> &FileStorage {
         &self.get_files()
     }
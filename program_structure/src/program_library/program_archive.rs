@@ -1,7 +1,7 @@
 use super::ast::{Definition, Expression, MainComponent};
 use super::file_definition::{FileID, FileLibrary};
 use super::function_data::{FunctionData, FunctionInfo};
-use super::program_merger::Merger;
+use super::program_merger::{self, Merger, NamespaceInfo};
 use super::template_data::{TemplateData, TemplateInfo};
 use crate::abstract_syntax_tree::ast::FillMeta;
 use std::collections::HashSet;
@@ -20,21 +20,28 @@ pub struct ProgramArchive {
     pub template_keys: HashSet<String>,
     pub public_inputs: Vec<String>,
     pub initial_template_call: Expression,
+    // Every main component of a batch compilation, the selected one is also
+    // stored in file_id_main, public_inputs and initial_template_call
+    pub main_components: Vec<(FileID, MainComponent)>,
 }
 impl ProgramArchive {
     pub fn new(
         file_library: FileLibrary,
-        file_id_main: FileID,
-        main_component: MainComponent,
+        main_components: Vec<(FileID, MainComponent)>,
         program_contents: Contents,
+        namespaces: NamespaceInfo,
     ) -> Result<ProgramArchive, (FileLibrary, Vec<Report>)> {
         let mut merger = Merger::new();
         let mut reports = vec![];
         for (file_id, definitions) in program_contents {
-            if let Err(mut errs) = merger.add_definitions(file_id, definitions) {
+            let namespace = namespaces.get(&file_id).cloned().unwrap_or_default();
+            if let Err(mut errs) = merger.add_definitions(file_id, &namespace, definitions) {
                 reports.append(&mut errs);
             }
         }
+        if let Err(mut errs) = merger.resolve_namespaces(&namespaces) {
+            reports.append(&mut errs);
+        }
         let (mut fresh_id, functions, templates) = merger.decompose();
         let mut function_keys = HashSet::new();
         let mut template_keys = HashSet::new();
@@ -44,8 +51,14 @@ impl ProgramArchive {
         for key in templates.keys() {
             template_keys.insert(key.clone());
         }
-        let (public_inputs, mut initial_template_call) = main_component;
-        initial_template_call.fill(file_id_main, &mut fresh_id);
+        let names: HashSet<String> = function_keys.union(&template_keys).cloned().collect();
+        let mut main_components = main_components;
+        for (file_id, (_, call)) in main_components.iter_mut() {
+            call.fill(*file_id, &mut fresh_id);
+            let main_namespace = namespaces.get(file_id).cloned().unwrap_or_default();
+            program_merger::resolve_expression(call, &main_namespace, &names, &mut reports);
+        }
+        let (file_id_main, (public_inputs, initial_template_call)) = main_components[0].clone();
         if reports.is_empty() {
             Ok(ProgramArchive {
                 id_max: fresh_id,
@@ -57,6 +70,7 @@ impl ProgramArchive {
                 initial_template_call,
                 function_keys,
                 template_keys,
+                main_components,
             })
         } else {
             Err((file_library, reports))
@@ -127,6 +141,15 @@ impl ProgramArchive {
     pub fn get_main_expression(&self) -> &Expression {
         &self.initial_template_call
     }
+    pub fn get_main_components(&self) -> &[(FileID, MainComponent)] {
+        &self.main_components
+    }
+    pub fn select_main_component(&mut self, index: usize) {
+        let (file_id, (public_inputs, call)) = self.main_components[index].clone();
+        self.file_id_main = file_id;
+        self.public_inputs = public_inputs;
+        self.initial_template_call = call;
+    }
     // FileLibrary functions
     pub fn get_file_library(&self) -> &FileLibrary {
         &self.file_library
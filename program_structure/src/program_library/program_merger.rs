@@ -1,9 +1,31 @@
-use super::ast::Definition;
+use super::ast::{Definition, Expression, LogArgument, Statement, Access};
 use super::error_code::ReportCode;
 use super::error_definition::Report;
 use super::file_definition::FileID;
 use super::function_data::{FunctionData, FunctionInfo};
 use super::template_data::{TemplateData, TemplateInfo};
+use std::collections::{HashMap, HashSet};
+
+// The namespace of a file. Definitions of files included with an alias get
+// the prefix of their namespace (lib.Mux), so that templates and functions
+// of different files keep distinct identities; files in the global namespace
+// (prefix None) keep their plain names.
+#[derive(Clone, Default)]
+pub struct FileNamespace {
+    pub prefix: Option<String>,
+    // alias declared in the file -> prefix of the included file
+    pub aliases: HashMap<String, Option<String>>,
+}
+pub type NamespaceInfo = HashMap<FileID, FileNamespace>;
+
+impl FileNamespace {
+    pub fn qualify(prefix: &Option<String>, name: &str) -> String {
+        match prefix {
+            Some(prefix) => format!("{}.{}", prefix, name),
+            None => name.to_string(),
+        }
+    }
+}
 
 pub struct Merger {
     fresh_id: usize,
@@ -25,11 +47,17 @@ impl Merger {
         Merger::default()
     }
 
-    pub fn add_definitions(&mut self, file_id: FileID, definitions: Vec<Definition>)  -> Result<(), Vec<Report>> {
+    pub fn add_definitions(
+        &mut self,
+        file_id: FileID,
+        namespace: &FileNamespace,
+        definitions: Vec<Definition>,
+    ) -> Result<(), Vec<Report>> {
         let mut reports = vec![];
         for definition in definitions {
             let (name, meta) = match definition {
                 Definition::Template { name, args, arg_location, body, meta, parallel, is_custom_gate } => {
+                    let name = FileNamespace::qualify(&namespace.prefix, &name);
                     if self.contains_function(&name) || self.contains_template(&name) {
                         (Option::Some(name), meta)
                     } else {
@@ -49,6 +77,7 @@ impl Merger {
                     }
                 }
                 Definition::Function { name, body, args, arg_location, meta } => {
+                    let name = FileNamespace::qualify(&namespace.prefix, &name);
                     if self.contains_function(&name) || self.contains_template(&name) {
                         (Option::Some(name), meta)
                     } else {
@@ -81,6 +110,28 @@ impl Merger {
         }
         if reports.is_empty() { Ok(()) } else { Err(reports) }
     }
+
+    // Rewrites the calls in the bodies of every template and function to the
+    // names the callees were stored with: alias.Name is looked up in the
+    // aliases of the file and an unqualified Name refers first to the
+    // namespace of the file and then to the global one.
+    pub fn resolve_namespaces(&mut self, namespaces: &NamespaceInfo) -> Result<(), Vec<Report>> {
+        let mut names = HashSet::new();
+        names.extend(self.function_info.keys().cloned());
+        names.extend(self.template_info.keys().cloned());
+        let global = FileNamespace::default();
+        let mut reports = vec![];
+        for data in self.function_info.values_mut() {
+            let namespace = namespaces.get(&data.get_file_id()).unwrap_or(&global);
+            resolve_statement(data.get_mut_body(), namespace, &names, &mut reports);
+        }
+        for data in self.template_info.values_mut() {
+            let namespace = namespaces.get(&data.get_file_id()).unwrap_or(&global);
+            resolve_statement(data.get_mut_body(), namespace, &names, &mut reports);
+        }
+        if reports.is_empty() { Ok(()) } else { Err(reports) }
+    }
+
     pub fn contains_function(&self, function_name: &str) -> bool {
         self.get_function_info().contains_key(function_name)
     }
@@ -106,3 +157,138 @@ impl Merger {
         (self.fresh_id, self.function_info, self.template_info)
     }
 }
+
+fn resolve_call_id(
+    id: &str,
+    namespace: &FileNamespace,
+    names: &HashSet<String>,
+) -> Result<String, String> {
+    if let Some((alias, name)) = id.split_once('.') {
+        match namespace.aliases.get(alias) {
+            Some(prefix) => Ok(FileNamespace::qualify(prefix, name)),
+            None => Err(alias.to_string()),
+        }
+    } else {
+        let local = FileNamespace::qualify(&namespace.prefix, id);
+        if names.contains(&local) { Ok(local) } else { Ok(id.to_string()) }
+    }
+}
+
+pub fn resolve_expression(
+    expression: &mut Expression,
+    namespace: &FileNamespace,
+    names: &HashSet<String>,
+    reports: &mut Vec<Report>,
+) {
+    use Expression::*;
+    match expression {
+        InfixOp { lhe, rhe, .. } => {
+            resolve_expression(lhe, namespace, names, reports);
+            resolve_expression(rhe, namespace, names, reports);
+        }
+        PrefixOp { rhe, .. } => resolve_expression(rhe, namespace, names, reports),
+        InlineSwitchOp { cond, if_true, if_false, .. } => {
+            resolve_expression(cond, namespace, names, reports);
+            resolve_expression(if_true, namespace, names, reports);
+            resolve_expression(if_false, namespace, names, reports);
+        }
+        Variable { access, .. } => resolve_access(access, namespace, names, reports),
+        Number(..) => {}
+        Call { meta, id, args } => {
+            match resolve_call_id(id, namespace, names) {
+                Ok(resolved) => *id = resolved,
+                Err(alias) => {
+                    let mut report = Report::error(
+                        format!("Unknown namespace {}", alias),
+                        ReportCode::UnknownNamespace,
+                    );
+                    report.add_primary(
+                        meta.file_location(),
+                        meta.get_file_id(),
+                        format!("no file is included as {}", alias),
+                    );
+                    reports.push(report);
+                }
+            }
+            for arg in args {
+                resolve_expression(arg, namespace, names, reports);
+            }
+        }
+        ArrayInLine { values, .. } => {
+            for value in values {
+                resolve_expression(value, namespace, names, reports);
+            }
+        }
+        UniformArray { value, dimension, .. } => {
+            resolve_expression(value, namespace, names, reports);
+            resolve_expression(dimension, namespace, names, reports);
+        }
+    }
+}
+
+fn resolve_access(
+    access: &mut Vec<Access>,
+    namespace: &FileNamespace,
+    names: &HashSet<String>,
+    reports: &mut Vec<Report>,
+) {
+    for acc in access {
+        if let Access::ArrayAccess(index) = acc {
+            resolve_expression(index, namespace, names, reports);
+        }
+    }
+}
+
+fn resolve_statement(
+    statement: &mut Statement,
+    namespace: &FileNamespace,
+    names: &HashSet<String>,
+    reports: &mut Vec<Report>,
+) {
+    use Statement::*;
+    match statement {
+        IfThenElse { cond, if_case, else_case, .. } => {
+            resolve_expression(cond, namespace, names, reports);
+            resolve_statement(if_case, namespace, names, reports);
+            if let Some(else_case) = else_case {
+                resolve_statement(else_case, namespace, names, reports);
+            }
+        }
+        While { cond, stmt, .. } => {
+            resolve_expression(cond, namespace, names, reports);
+            resolve_statement(stmt, namespace, names, reports);
+        }
+        Return { value, .. } => resolve_expression(value, namespace, names, reports),
+        InitializationBlock { initializations, .. } => {
+            for initialization in initializations {
+                resolve_statement(initialization, namespace, names, reports);
+            }
+        }
+        Declaration { dimensions, .. } => {
+            for dimension in dimensions {
+                resolve_expression(dimension, namespace, names, reports);
+            }
+        }
+        Substitution { access, rhe, .. } => {
+            resolve_access(access, namespace, names, reports);
+            resolve_expression(rhe, namespace, names, reports);
+        }
+        ConstraintEquality { lhe, rhe, .. } => {
+            resolve_expression(lhe, namespace, names, reports);
+            resolve_expression(rhe, namespace, names, reports);
+        }
+        LogCall { args, .. } => {
+            for arg in args {
+                if let LogArgument::LogExp(arg) = arg {
+                    resolve_expression(arg, namespace, names, reports);
+                }
+            }
+        }
+        Block { stmts, .. } => {
+            for stmt in stmts {
+                resolve_statement(stmt, namespace, names, reports);
+            }
+        }
+        Assert { arg, .. } => resolve_expression(arg, namespace, names, reports),
+    }
+}
@@ -227,6 +227,9 @@ impl<C: Clone> MemorySlice<C> {
     pub fn route(&self) -> &[SliceCapacity] {
         &self.route
     }
+    pub fn values(&self) -> &[C] {
+        &self.values
+    }
     pub fn is_single(&self) -> bool {
         self.route.is_empty()
     }
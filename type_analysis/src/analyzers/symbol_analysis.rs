@@ -32,8 +32,10 @@ pub fn check_naming_correctness(program_archive: &ProgramArchive) -> Result<(),
         );
         instances.push(instance);
     }
-    if let Err(mut r) = analyze_main(program_archive) {
-        reports.append(&mut r);
+    for (_, (signals, call)) in program_archive.get_main_components() {
+        if let Err(mut r) = analyze_main(program_archive, call, signals) {
+            reports.append(&mut r);
+        }
     }
     for (file_id, param_location, params_names, body) in instances {
         let res = analyze_symbols(
@@ -55,9 +57,7 @@ pub fn check_naming_correctness(program_archive: &ProgramArchive) -> Result<(),
     }
 }
 
-fn analyze_main(program: &ProgramArchive) -> Result<(), Vec<Report>> {
-    let call = program.get_main_expression();
-    let signals = program.get_public_inputs_main_component();
+fn analyze_main(program: &ProgramArchive, call: &Expression, signals: &[String]) -> Result<(), Vec<Report>> {
     let template_info = program.get_templates();
     let function_info = program.get_functions();
 
@@ -70,20 +70,22 @@ pub fn type_check(program_archive: &ProgramArchive) -> Result<OutInfo, ReportCol
         environment: TypingEnvironment::new(),
         return_type: Option::None,
     };
-    let initial_expression = program_archive.get_main_expression();
-    let type_analysis_response =
-        type_expression(initial_expression, program_archive, &mut analysis_information);
-    let first_type = if let Result::Ok(t) = type_analysis_response {
-        t
-    } else {
-        return Result::Err(analysis_information.reports);
-    };
-    if !first_type.is_template() {
-        add_report(
-            ReportCode::WrongTypesInAssignOperation,
-            initial_expression.get_meta(),
-            &mut analysis_information.reports,
-        );
+    for (file_id, (_, initial_expression)) in program_archive.get_main_components() {
+        analysis_information.file_id = *file_id;
+        let type_analysis_response =
+            type_expression(initial_expression, program_archive, &mut analysis_information);
+        let first_type = if let Result::Ok(t) = type_analysis_response {
+            t
+        } else {
+            return Result::Err(analysis_information.reports);
+        };
+        if !first_type.is_template() {
+            add_report(
+                ReportCode::WrongTypesInAssignOperation,
+                initial_expression.get_meta(),
+                &mut analysis_information.reports,
+            );
+        }
     }
     if analysis_information.reports.is_empty() {
         Result::Ok(OutInfo { reached: analysis_information.reached })
@@ -0,0 +1,201 @@
+use crate::files;
+use circom_algebra::num_bigint::BigInt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+// The witness generators produced by circom for a circuit. A backend is
+// unavailable when its toolchain is missing (node for the wasm generator,
+// make and a C++ compiler for the C one, which uses the portable field
+// arithmetic so that it builds without nasm and gmp); the harness then skips
+// it. The compiler has no witness generator of its own: the execution of the
+// templates only computes the constraints, so there is no in-process backend
+// to compare with.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Backend {
+    Wasm,
+    C,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Wasm => "wasm",
+            Backend::C => "c",
+        }
+    }
+}
+
+pub enum Outcome {
+    Witness(Vec<BigInt>),
+    Rejected(String),
+}
+
+pub struct Artifacts {
+    pub name: String,
+    pub output: PathBuf,
+}
+
+impl Artifacts {
+    pub fn r1cs_file(&self) -> PathBuf {
+        self.output.join(format!("{}.r1cs", self.name))
+    }
+    pub fn sym_file(&self) -> PathBuf {
+        self.output.join(format!("{}.sym", self.name))
+    }
+    fn files(&self) -> Vec<PathBuf> {
+        vec![self.r1cs_file(), self.sym_file(), self.js_folder(), self.c_folder()]
+    }
+    fn js_folder(&self) -> PathBuf {
+        self.output.join(format!("{}_js", self.name))
+    }
+    fn c_folder(&self) -> PathBuf {
+        self.output.join(format!("{}_cpp", self.name))
+    }
+}
+
+fn failure_message(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() { stdout } else { stderr };
+    message.trim().lines().last().unwrap_or("").to_string()
+}
+
+// Everything circom printed, to show its diagnostics
+fn diagnostics(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    format!("{}\n{}", stdout.trim(), stderr.trim()).trim().to_string()
+}
+
+pub fn compile(
+    circom: &str,
+    circuit: &Path,
+    output: &Path,
+    prime: &str,
+) -> Result<Artifacts, String> {
+    let name = circuit.file_stem().unwrap().to_str().unwrap().to_string();
+    let artifacts = Artifacts { name, output: output.to_path_buf() };
+    // circom does not always exit with an error when the compilation fails, so
+    // the files of a previous run must not be taken for the result of this one
+    for file in artifacts.files() {
+        let removed = if file.is_dir() { std::fs::remove_dir_all(&file) } else { std::fs::remove_file(&file) };
+        if removed.is_err() && file.exists() {
+            return Result::Err(format!("could not remove {}", file.display()));
+        }
+    }
+    let result = Command::new(circom)
+        .arg(circuit)
+        .args(["--r1cs", "--sym", "--wasm", "--c", "--portable", "--prime", prime, "-o"])
+        .arg(output)
+        .output()
+        .map_err(|err| format!("could not run {}: {}", circom, err))?;
+    let failed = !result.status.success()
+        || String::from_utf8_lossy(&result.stderr).contains("previous errors")
+        || artifacts.files().iter().any(|file| !file.exists());
+    if failed {
+        return Result::Err(format!("compilation failed:\n{}", diagnostics(&result)));
+    }
+    Result::Ok(artifacts)
+}
+
+// Builds the C witness generator; the wasm one needs no build step
+pub fn prepare(backend: Backend, artifacts: &Artifacts) -> Result<(), String> {
+    match backend {
+        Backend::Wasm => {
+            let result = Command::new("node")
+                .arg("--version")
+                .output()
+                .map_err(|err| format!("node is not available: {}", err))?;
+            if result.status.success() {
+                Result::Ok(())
+            } else {
+                Result::Err("node is not available".to_string())
+            }
+        }
+        Backend::C => {
+            let result = Command::new("make")
+                .arg("-C")
+                .arg(artifacts.c_folder())
+                .output()
+                .map_err(|err| format!("make is not available: {}", err))?;
+            if result.status.success() {
+                Result::Ok(())
+            } else {
+                Result::Err(format!("build failed: {}", failure_message(&result)))
+            }
+        }
+    }
+}
+
+pub fn run(
+    backend: Backend,
+    artifacts: &Artifacts,
+    input: &str,
+    witness: &str,
+) -> Result<Outcome, String> {
+    let _ = std::fs::remove_file(witness);
+    let mut command = match backend {
+        Backend::Wasm => {
+            let folder = artifacts.js_folder();
+            let mut command = Command::new("node");
+            command
+                .arg(folder.join("generate_witness.js"))
+                .arg(folder.join(format!("{}.wasm", artifacts.name)));
+            command
+        }
+        Backend::C => Command::new(artifacts.c_folder().join(&artifacts.name)),
+    };
+    let result = command
+        .arg(input)
+        .arg(witness)
+        .output()
+        .map_err(|err| format!("could not run the {} witness generator: {}", backend.name(), err))?;
+    if !result.status.success() || !Path::new(witness).is_file() {
+        return Result::Ok(Outcome::Rejected(failure_message(&result)));
+    }
+    match files::read_wtns(witness) {
+        Result::Ok(values) => Result::Ok(Outcome::Witness(values)),
+        Result::Err(_) => Result::Err(format!("invalid witness file {}", witness)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("witness_diff_backends_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(folder.join("top_js")).unwrap();
+        std::fs::create_dir_all(folder.join("top_cpp")).unwrap();
+        std::fs::write(folder.join("top.r1cs"), "r1cs").unwrap();
+        std::fs::write(folder.join("top.sym"), "sym").unwrap();
+        folder
+    }
+
+    // sh stands for circom: it runs the circuit file as a script
+    fn compile_with(folder: &Path, script: &str) -> Result<Artifacts, String> {
+        let circuit = folder.join("top.circom");
+        std::fs::write(&circuit, script).unwrap();
+        compile("sh", &circuit, folder, "bn128")
+    }
+
+    #[test]
+    fn failed_compilations_do_not_reuse_previous_artifacts() {
+        let folder = output_folder("errors");
+        let script = "echo 'error[T3001]: Non quadratic constraints are not allowed!' >&2\necho 'previous errors were found' >&2\n";
+        let message = compile_with(&folder, script).err().unwrap();
+        assert!(message.contains("Non quadratic constraints are not allowed!"));
+        assert!(!folder.join("top.r1cs").exists());
+        assert!(!folder.join("top_js").exists());
+        std::fs::remove_dir_all(&folder).unwrap();
+
+        let folder = output_folder("missing");
+        let script = "echo 'Everything went okay'\necho r1cs > \"$(dirname \"$0\")/top.r1cs\"\n";
+        let message = compile_with(&folder, script).err().unwrap();
+        assert!(message.contains("Everything went okay"));
+        assert!(folder.join("top.r1cs").is_file());
+        assert!(!folder.join("top.sym").exists());
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+}
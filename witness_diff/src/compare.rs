@@ -0,0 +1,132 @@
+use crate::files::{LinearCombination, R1CSFile, SymFile};
+use circom_algebra::modular_arithmetic;
+use circom_algebra::num_bigint::BigInt;
+
+// Maximum number of differences listed per pair of backends
+const MAX_REPORTED: usize = 20;
+
+fn evaluate(lc: &LinearCombination, witness: &[BigInt], prime: &BigInt) -> BigInt {
+    let mut result = BigInt::from(0);
+    for (wire, coefficient) in lc {
+        let value = modular_arithmetic::mul(coefficient, &witness[*wire], prime);
+        result = modular_arithmetic::add(&result, &value, prime);
+    }
+    result
+}
+
+fn describe(lc: &LinearCombination, sym: &SymFile) -> String {
+    let names: Vec<String> = lc.iter().map(|(wire, _)| sym.witness_name(*wire)).collect();
+    names.join(", ")
+}
+
+// Evaluates every constraint A*B = C of the simplified system over the
+// witness and returns a description of the ones that do not hold.
+pub fn check_constraints(witness: &[BigInt], r1cs: &R1CSFile, sym: &SymFile) -> Vec<String> {
+    let mut errors = Vec::new();
+    if witness.len() != r1cs.header.total_wires {
+        errors.push(format!(
+            "the witness has {} values but the constraint system has {} wires",
+            witness.len(),
+            r1cs.header.total_wires
+        ));
+        return errors;
+    }
+    if witness[0] != BigInt::from(1) {
+        errors.push("the first value of the witness is not 1".to_string());
+    }
+    let prime = &r1cs.header.field;
+    for (index, constraint) in r1cs.constraints.iter().enumerate() {
+        let a = evaluate(&constraint[0], witness, prime);
+        let b = evaluate(&constraint[1], witness, prime);
+        let c = evaluate(&constraint[2], witness, prime);
+        if modular_arithmetic::mul(&a, &b, prime) != c {
+            let mut signals = Vec::new();
+            signals.push(describe(&constraint[0], sym));
+            signals.push(describe(&constraint[1], sym));
+            signals.push(describe(&constraint[2], sym));
+            signals.retain(|s| !s.is_empty());
+            errors.push(format!("constraint {} does not hold (signals: {})", index, signals.join("; ")));
+        }
+    }
+    errors
+}
+
+// Compares two witnesses value by value, naming the positions with the sym file
+pub fn compare_witnesses(
+    left: &[BigInt],
+    right: &[BigInt],
+    sym: &SymFile,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    if left.len() != right.len() {
+        differences.push(format!("witness lengths differ: {} and {}", left.len(), right.len()));
+    }
+    let mut found = 0;
+    for (index, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+        if l != r {
+            found += 1;
+            if found <= MAX_REPORTED {
+                differences.push(format!("{} (witness {}): {} != {}", sym.witness_name(index), index, l, r));
+            }
+        }
+    }
+    if found > MAX_REPORTED {
+        differences.push(format!("... and {} more", found - MAX_REPORTED));
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constraint_writers::r1cs_writer::HeaderData;
+    use std::collections::HashMap;
+
+    // main.c <== main.a * main.b over the field of 101 elements
+    fn multiplier() -> (R1CSFile, SymFile) {
+        let one = || BigInt::from(1);
+        let header = HeaderData {
+            field: BigInt::from(101),
+            total_wires: 4,
+            public_outputs: 1,
+            public_inputs: 0,
+            private_inputs: 2,
+            number_of_labels: 3,
+            number_of_constraints: 1,
+        };
+        let constraints = vec![[vec![(2, one())], vec![(3, one())], vec![(1, one())]]];
+        let mut witness_names = HashMap::new();
+        for (wire, name) in [(1, "main.c"), (2, "main.a"), (3, "main.b")].iter() {
+            witness_names.insert(*wire, name.to_string());
+        }
+        (R1CSFile { header, constraints }, SymFile { entries: Vec::new(), witness_names })
+    }
+
+    fn witness(values: &[u64]) -> Vec<BigInt> {
+        values.iter().map(|value| BigInt::from(*value)).collect()
+    }
+
+    #[test]
+    fn constraints_are_checked_in_the_field() {
+        let (r1cs, sym) = multiplier();
+        assert!(check_constraints(&witness(&[1, 20, 10, 2]), &r1cs, &sym).is_empty());
+        assert!(check_constraints(&witness(&[1, 100, 11, 55]), &r1cs, &sym).is_empty());
+        let errors = check_constraints(&witness(&[1, 21, 10, 2]), &r1cs, &sym);
+        assert_eq!(errors, vec!["constraint 0 does not hold (signals: main.a; main.b; main.c)".to_string()]);
+        let errors = check_constraints(&witness(&[1, 20, 10]), &r1cs, &sym);
+        assert_eq!(errors, vec!["the witness has 3 values but the constraint system has 4 wires".to_string()]);
+    }
+
+    #[test]
+    fn differences_are_named_and_bounded() {
+        let (_, sym) = multiplier();
+        let differences = compare_witnesses(&witness(&[1, 20, 10, 2]), &witness(&[1, 20, 11, 2]), &sym);
+        assert_eq!(differences, vec!["main.a (witness 2): 10 != 11".to_string()]);
+        let left = witness(&[0; 30]);
+        let right = witness(&[1; 31]);
+        let differences = compare_witnesses(&left, &right, &sym);
+        assert_eq!(differences[0], "witness lengths differ: 30 and 31");
+        assert_eq!(differences.len(), MAX_REPORTED + 2);
+        assert_eq!(differences.last().unwrap(), "... and 10 more");
+    }
+}
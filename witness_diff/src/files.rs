@@ -0,0 +1,212 @@
+use circom_algebra::num_bigint::{BigInt, Sign};
+use constraint_writers::sym_reader;
+use std::collections::HashMap;
+use std::fs;
+
+// Readers for the artifacts written by the witness generators (.wtns) and by
+// circom (the substitutions of --jsons), and views of the .r1cs and .sym files
+// read by constraint_writers.
+
+pub use constraint_writers::r1cs_reader::{read_r1cs, LinearCombination, R1CSFile};
+
+const WTNS_MAGIC: &[u8] = b"wtns";
+const HEADER_TYPE: u32 = 1;
+const WTNS_DATA_TYPE: u32 = 2;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Reader<'a> {
+        Reader { data, pos }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], ()> {
+        if self.pos + n > self.data.len() {
+            return Result::Err(());
+        }
+        let slice = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Result::Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, ()> {
+        let b = self.bytes(4)?;
+        Result::Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, ()> {
+        let b = self.bytes(8)?;
+        let mut array = [0; 8];
+        array.copy_from_slice(b);
+        Result::Ok(u64::from_le_bytes(array))
+    }
+
+    fn field(&mut self, n8: usize) -> Result<BigInt, ()> {
+        let b = self.bytes(n8)?;
+        Result::Ok(BigInt::from_bytes_le(Sign::Plus, b))
+    }
+}
+
+// Returns the (type, start, size) of every section of a binary file
+fn sections(data: &[u8], magic: &[u8]) -> Result<Vec<(u32, usize, usize)>, ()> {
+    let mut reader = Reader::new(data, 0);
+    if reader.bytes(4)? != magic {
+        return Result::Err(());
+    }
+    let _version = reader.u32()?;
+    let no_sections = reader.u32()?;
+    let mut sections = Vec::new();
+    for _ in 0..no_sections {
+        let section_type = reader.u32()?;
+        let size = reader.u64()? as usize;
+        sections.push((section_type, reader.pos, size));
+        reader.bytes(size)?;
+    }
+    Result::Ok(sections)
+}
+
+fn find_section(sections: &[(u32, usize, usize)], section_type: u32) -> Result<usize, ()> {
+    sections.iter().find(|s| s.0 == section_type).map(|s| s.1).ok_or(())
+}
+
+pub fn read_wtns(file: &str) -> Result<Vec<BigInt>, ()> {
+    read_wtns_with_prime(file).map(|(_, witness)| witness)
+}
+
+pub fn read_wtns_with_prime(file: &str) -> Result<(BigInt, Vec<BigInt>), ()> {
+    let data = fs::read(file).map_err(|_err| {})?;
+    let sections = sections(&data, WTNS_MAGIC)?;
+    let mut reader = Reader::new(&data, find_section(&sections, HEADER_TYPE)?);
+    let n8 = reader.u32()? as usize;
+    let prime = reader.field(n8)?;
+    let no_witness = reader.u32()?;
+    let mut reader = Reader::new(&data, find_section(&sections, WTNS_DATA_TYPE)?);
+    let mut witness = Vec::new();
+    for _ in 0..no_witness {
+        witness.push(reader.field(n8)?);
+    }
+    Result::Ok((prime, witness))
+}
+
+pub struct SymEntry {
+    pub signal: usize,
+    pub witness: Option<usize>,
+    pub name: String,
+}
+
+pub struct SymFile {
+    pub entries: Vec<SymEntry>,
+    // first name found for each position of the witness
+    pub witness_names: HashMap<usize, String>,
+}
+
+impl SymFile {
+    pub fn witness_name(&self, witness: usize) -> String {
+        if witness == 0 {
+            return "one".to_string();
+        }
+        match self.witness_names.get(&witness) {
+            Some(name) => name.clone(),
+            None => format!("<witness {}>", witness),
+        }
+    }
+}
+
+pub fn read_sym(file: &str) -> Result<SymFile, ()> {
+    let mut entries = Vec::new();
+    let mut witness_names = HashMap::new();
+    for elem in sym_reader::read_sym(file)? {
+        let witness = if elem.witness >= 0 { Some(elem.witness as usize) } else { None };
+        if let Some(witness) = witness {
+            witness_names.entry(witness).or_insert_with(|| elem.symbol.clone());
+        }
+        entries.push(SymEntry { signal: elem.original as usize, witness, name: elem.symbol });
+    }
+    Result::Ok(SymFile { entries, witness_names })
+}
+
+// Linear expression of every signal removed by the simplification in terms of
+// other signals, as written by circom --jsons. The signal 0 is the constant one
+pub type Substitutions = HashMap<usize, LinearCombination>;
+
+pub fn read_substitutions(file: &str) -> Result<Substitutions, ()> {
+    let data = fs::read_to_string(file).map_err(|_err| {})?;
+    let parsed = json::parse(&data).map_err(|_err| {})?;
+    let mut substitutions = HashMap::new();
+    for (from, to) in parsed["substitution"].entries() {
+        let from = from.parse::<usize>().map_err(|_err| {})?;
+        let mut lc = Vec::new();
+        for (signal, coefficient) in to.entries() {
+            let signal = signal.parse::<usize>().map_err(|_err| {})?;
+            let coefficient = coefficient.as_str().ok_or(())?.parse::<BigInt>().map_err(|_err| {})?;
+            lc.push((signal, coefficient));
+        }
+        substitutions.insert(from, lc);
+    }
+    Result::Ok(substitutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: &[u8]) -> String {
+        let file = std::env::temp_dir().join(format!("witness_diff_{}_{}", std::process::id(), name));
+        fs::write(&file, contents).unwrap();
+        file.to_str().unwrap().to_string()
+    }
+
+    fn section(bytes: &mut Vec<u8>, section_type: u32, contents: &[u8]) {
+        bytes.extend_from_slice(&section_type.to_le_bytes());
+        bytes.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(contents);
+    }
+
+    #[test]
+    fn witness_files_are_read() {
+        let mut header = Vec::new();
+        header.extend_from_slice(&8u32.to_le_bytes());
+        header.extend_from_slice(&101u64.to_le_bytes());
+        header.extend_from_slice(&3u32.to_le_bytes());
+        let mut values = Vec::new();
+        for value in [1u64, 100, 7].iter() {
+            values.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut bytes = b"wtns".to_vec();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        section(&mut bytes, HEADER_TYPE, &header);
+        section(&mut bytes, WTNS_DATA_TYPE, &values);
+        let file = temp_file("witness.wtns", &bytes);
+        let (prime, witness) = read_wtns_with_prime(&file).unwrap();
+        assert_eq!(prime, BigInt::from(101));
+        assert_eq!(witness, vec![BigInt::from(1), BigInt::from(100), BigInt::from(7)]);
+        let truncated = temp_file("truncated.wtns", &bytes[..bytes.len() - 1]);
+        assert!(read_wtns(&truncated).is_err());
+        fs::remove_file(file).unwrap();
+        fs::remove_file(truncated).unwrap();
+    }
+
+    #[test]
+    fn sym_files_name_the_witness() {
+        let file = temp_file("circuit.sym", b"1,1,0,main.c\n2,-1,0,main.a\n3,1,1,main.m.out\n");
+        let sym = read_sym(&file).unwrap();
+        assert_eq!(sym.entries.len(), 3);
+        assert_eq!(sym.entries[1].witness, None);
+        assert_eq!(sym.witness_name(0), "one");
+        assert_eq!(sym.witness_name(1), "main.c");
+        assert_eq!(sym.witness_name(2), "<witness 2>");
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn substitutions_are_read() {
+        let file = temp_file("substitutions.json", b"{\"substitution\": {\"4\": {\"0\": \"3\", \"2\": \"100\"}}}");
+        let substitutions = read_substitutions(&file).unwrap();
+        assert_eq!(substitutions[&4], vec![(0, BigInt::from(3)), (2, BigInt::from(100))]);
+        fs::remove_file(file).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+use crate::files::{R1CSFile, SymFile};
+use circom_algebra::num_bigint::{BigInt, Sign};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// The inputs of the main component, in the order the witness generators
+// expect them: one entry per signal name with its flattened values.
+pub type Inputs = Vec<(String, Vec<BigInt>)>;
+
+// Names of the input signals of main, grouped by signal and flattened.
+// Signals 1..=outputs are the outputs of main and the inputs follow them.
+pub fn main_inputs(r1cs: &R1CSFile, sym: &SymFile) -> Vec<(String, usize)> {
+    let header = &r1cs.header;
+    let first = header.public_outputs + 1;
+    let last = header.public_outputs + header.public_inputs + header.private_inputs;
+    let mut inputs: Vec<(String, usize)> = Vec::new();
+    for entry in &sym.entries {
+        if entry.signal < first || entry.signal > last {
+            continue;
+        }
+        let name = entry.name.trim_start_matches("main.");
+        let name = match name.find('[') {
+            Some(pos) => &name[..pos],
+            None => name,
+        };
+        match inputs.last_mut() {
+            Some((last_name, size)) if last_name == name => *size += 1,
+            _ => inputs.push((name.to_string(), 1)),
+        }
+    }
+    inputs
+}
+
+// Random values are biased towards the edge cases where the witness
+// generators have disagreed in the past: zero, one, small integers that
+// exercise shifts and integer division, and the largest field elements.
+fn random_value(rng: &mut StdRng, prime: &BigInt) -> BigInt {
+    match rng.gen_range(0, 6) {
+        0 => BigInt::from(0),
+        1 => BigInt::from(1),
+        2 => BigInt::from(rng.gen_range(0u64, 256)) % prime,
+        // A u64 may not be a field element of goldilocks
+        3 => BigInt::from(rng.gen::<u64>()) % prime,
+        4 => prime - BigInt::from(rng.gen_range(1u64, 256)),
+        _ => {
+            let (_, bytes) = prime.to_bytes_le();
+            let random: Vec<u8> = bytes.iter().map(|_| rng.gen()).collect();
+            BigInt::from_bytes_le(Sign::Plus, &random) % prime
+        }
+    }
+}
+
+pub fn random_inputs(signals: &[(String, usize)], prime: &BigInt, rng: &mut StdRng) -> Inputs {
+    let mut inputs = Vec::new();
+    for (name, size) in signals {
+        let values = (0..*size).map(|_| random_value(rng, prime)).collect();
+        inputs.push((name.clone(), values));
+    }
+    inputs
+}
+
+pub fn read_inputs(file: &str) -> Result<Inputs, ()> {
+    let data = std::fs::read_to_string(file).map_err(|_err| {})?;
+    let parsed = json::parse(&data).map_err(|_err| {})?;
+    let mut inputs = Vec::new();
+    for (name, value) in parsed.entries() {
+        let mut values = Vec::new();
+        flatten(value, &mut values)?;
+        inputs.push((name.to_string(), values));
+    }
+    Result::Ok(inputs)
+}
+
+fn flatten(value: &json::JsonValue, values: &mut Vec<BigInt>) -> Result<(), ()> {
+    if value.is_array() {
+        for member in value.members() {
+            flatten(member, values)?;
+        }
+        Result::Ok(())
+    } else {
+        let text = value.to_string();
+        let number = BigInt::parse_bytes(text.as_bytes(), 10).ok_or(())?;
+        values.push(number);
+        Result::Ok(())
+    }
+}
+
+pub fn write_inputs(inputs: &Inputs, file: &str) -> Result<(), ()> {
+    let mut object = json::JsonValue::new_object();
+    for (name, values) in inputs {
+        let values: Vec<json::JsonValue> =
+            values.iter().map(|v| json::JsonValue::from(v.to_str_radix(10))).collect();
+        object[name.as_str()] = if values.len() == 1 {
+            values[0].clone()
+        } else {
+            json::JsonValue::Array(values)
+        };
+    }
+    std::fs::write(file, object.pretty(2)).map_err(|_err| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::SymEntry;
+    use constraint_writers::r1cs_writer::HeaderData;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    #[test]
+    fn inputs_are_grouped_by_signal() {
+        let header = HeaderData {
+            field: BigInt::from(101),
+            total_wires: 6,
+            public_outputs: 1,
+            public_inputs: 2,
+            private_inputs: 1,
+            number_of_labels: 5,
+            number_of_constraints: 0,
+        };
+        let r1cs = R1CSFile { header, constraints: Vec::new() };
+        let names = ["main.out", "main.in[0]", "main.in[1]", "main.k", "main.x.y"];
+        let entries = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| SymEntry { signal: i + 1, witness: Some(i + 1), name: name.to_string() })
+            .collect();
+        let sym = SymFile { entries, witness_names: HashMap::new() };
+        assert_eq!(main_inputs(&r1cs, &sym), vec![("in".to_string(), 2), ("k".to_string(), 1)]);
+    }
+
+    #[test]
+    fn random_inputs_are_field_elements() {
+        // goldilocks, smaller than some u64 values
+        let prime = BigInt::parse_bytes(b"18446744069414584321", 10).unwrap();
+        let signals = vec![("in".to_string(), 500), ("k".to_string(), 1)];
+        let inputs = random_inputs(&signals, &prime, &mut StdRng::seed_from_u64(7));
+        assert_eq!(inputs.iter().map(|(_, values)| values.len()).collect::<Vec<_>>(), vec![500, 1]);
+        assert!(inputs.iter().flat_map(|(_, values)| values).all(|value| *value >= BigInt::from(0) && *value < prime));
+        assert_eq!(inputs, random_inputs(&signals, &prime, &mut StdRng::seed_from_u64(7)));
+    }
+
+    #[test]
+    fn inputs_are_written_and_read_back() {
+        let file = std::env::temp_dir().join(format!("witness_diff_inputs_{}.json", std::process::id()));
+        let file = file.to_str().unwrap();
+        let inputs = vec![
+            ("in".to_string(), vec![BigInt::from(3), BigInt::from(4)]),
+            ("k".to_string(), vec![BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap()]),
+        ];
+        write_inputs(&inputs, file).unwrap();
+        assert_eq!(read_inputs(file).unwrap(), inputs);
+        std::fs::write(file, "{\"in\": [[1, 2], [\"3\", 4]], \"k\": 5}").unwrap();
+        let read = read_inputs(file).unwrap();
+        assert_eq!(read[0].1, vec![BigInt::from(1), BigInt::from(2), BigInt::from(3), BigInt::from(4)]);
+        std::fs::write(file, "{\"in\": \"x\"}").unwrap();
+        assert!(read_inputs(file).is_err());
+        std::fs::remove_file(file).unwrap();
+    }
+}
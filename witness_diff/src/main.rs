@@ -0,0 +1,313 @@
+mod backends;
+mod compare;
+mod files;
+mod inputs;
+mod recover;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+use ansi_term::Colour;
+use backends::{Artifacts, Backend, Outcome};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use files::{R1CSFile, SymFile};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let matches = view();
+    if let Some(matches) = matches.subcommand_matches("signals") {
+        if recover::dump_signals(matches).is_err() {
+            eprintln!("{}", Colour::Red.paint("previous errors were found"));
+            std::process::exit(1);
+        }
+        return;
+    }
+    match start(&matches) {
+        Result::Err(()) => {
+            eprintln!("{}", Colour::Red.paint("previous errors were found"));
+            std::process::exit(1);
+        }
+        Result::Ok(backends) if backends > 1 => {
+            println!("{}", Colour::Green.paint("Every witness generator agreed"))
+        }
+        Result::Ok(_) => println!("{}", Colour::Green.paint("The witnesses satisfy the constraints")),
+    }
+}
+
+fn error(message: String) {
+    eprintln!("{}", Colour::Red.paint(message));
+}
+
+struct Session {
+    artifacts: Artifacts,
+    backends: Vec<Backend>,
+    r1cs: R1CSFile,
+    sym: SymFile,
+}
+
+// Returns the number of witness generators that were run
+fn start(matches: &ArgMatches) -> Result<usize, ()> {
+    let circuit = Path::new(matches.value_of("input").unwrap()).to_path_buf();
+    if !circuit.is_file() {
+        error("invalid input file".to_string());
+        return Result::Err(());
+    }
+    let output = PathBuf::from(matches.value_of("output").unwrap());
+    std::fs::create_dir_all(&output).map_err(|_err| error("invalid output path".to_string()))?;
+    let circom = match matches.value_of("circom") {
+        Some(circom) => circom.to_string(),
+        None => default_circom(),
+    };
+    let prime = matches.value_of("prime").unwrap();
+
+    let artifacts = backends::compile(&circom, &circuit, &output, prime).map_err(error)?;
+    let r1cs_file = artifacts.r1cs_file();
+    let sym_file = artifacts.sym_file();
+    let r1cs = files::read_r1cs(r1cs_file.to_str().unwrap())
+        .map_err(|_err| error(format!("could not read {}", r1cs_file.display())))?;
+    let sym = files::read_sym(sym_file.to_str().unwrap())
+        .map_err(|_err| error(format!("could not read {}", sym_file.display())))?;
+
+    let selected = selected_backends(matches);
+    let mut available = Vec::new();
+    for backend in &selected {
+        match backends::prepare(*backend, &artifacts) {
+            Result::Ok(()) => available.push(*backend),
+            Result::Err(reason) => {
+                println!("{} {}: {}", Colour::Yellow.paint("Skipping backend"), backend.name(), reason)
+            }
+        }
+    }
+    if available.is_empty() {
+        error("no witness generator could be run".to_string());
+        return Result::Err(());
+    }
+    // A skipped backend leaves nothing to compare with, which must not pass
+    // for an agreement. Running a single one on purpose only checks the constraints
+    if available.len() < 2 {
+        let message = format!(
+            "only the {} witness generator can be run, its witnesses are only checked against the constraints",
+            available[0].name()
+        );
+        if available.len() < selected.len() {
+            error(message);
+            return Result::Err(());
+        }
+        println!("{}", Colour::Yellow.paint(message));
+    }
+    let number_of_backends = available.len();
+    let session = Session { artifacts, backends: available, r1cs, sym };
+
+    let mut divergent_runs = 0;
+    if let Some(file) = matches.value_of("inputs") {
+        let inputs = inputs::read_inputs(file)
+            .map_err(|_err| error(format!("could not read the inputs in {}", file)))?;
+        if !run(&session, 0, &inputs)? {
+            divergent_runs += 1;
+        }
+    } else {
+        let runs = matches.value_of("runs").unwrap().parse::<usize>()
+            .map_err(|_err| error("invalid number of runs".to_string()))?;
+        let seed = matches.value_of("seed").unwrap().parse::<u64>()
+            .map_err(|_err| error("invalid seed".to_string()))?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let signals = inputs::main_inputs(&session.r1cs, &session.sym);
+        for index in 0..runs {
+            let inputs = inputs::random_inputs(&signals, &session.r1cs.header.field, &mut rng);
+            if !run(&session, index, &inputs)? {
+                divergent_runs += 1;
+            }
+        }
+    }
+    if divergent_runs > 0 {
+        error(format!("{} run(s) diverged", divergent_runs));
+        Result::Err(())
+    } else {
+        Result::Ok(number_of_backends)
+    }
+}
+
+// Runs every backend on the given inputs and reports the divergences.
+// Returns whether all the backends agreed.
+fn run(session: &Session, index: usize, inputs: &inputs::Inputs) -> Result<bool, ()> {
+    let output = &session.artifacts.output;
+    let input_file = output.join(format!("diff_input_{}.json", index));
+    let input_file = input_file.to_str().unwrap();
+    inputs::write_inputs(inputs, input_file)
+        .map_err(|_err| error(format!("could not write {}", input_file)))?;
+
+    let mut outcomes = Vec::new();
+    for backend in &session.backends {
+        let witness_file = output.join(format!("diff_{}_{}.wtns", backend.name(), index));
+        let outcome = backends::run(*backend, &session.artifacts, input_file, witness_file.to_str().unwrap())
+            .map_err(error)?;
+        outcomes.push((*backend, outcome));
+    }
+
+    let mut report = Vec::new();
+    for (backend, outcome) in &outcomes {
+        match outcome {
+            Outcome::Rejected(reason) => {
+                report.push(format!("{} rejected the inputs: {}", backend.name(), reason));
+            }
+            Outcome::Witness(witness) => {
+                for failure in compare::check_constraints(witness, &session.r1cs, &session.sym) {
+                    report.push(format!("{}: {}", backend.name(), failure));
+                }
+            }
+        }
+    }
+    let all_rejected = outcomes.iter().all(|(_, o)| matches!(o, Outcome::Rejected(_)));
+    let mut diverged = !report.is_empty() && !all_rejected;
+    for i in 0..outcomes.len() {
+        for j in i + 1..outcomes.len() {
+            if let (Outcome::Witness(left), Outcome::Witness(right)) = (&outcomes[i].1, &outcomes[j].1) {
+                let differences = compare::compare_witnesses(left, right, &session.sym);
+                if !differences.is_empty() {
+                    diverged = true;
+                    report.push(format!("{} and {} disagree:", outcomes[i].0.name(), outcomes[j].0.name()));
+                    report.extend(differences.into_iter().map(|d| format!("  {}", d)));
+                }
+            }
+        }
+    }
+
+    if diverged {
+        println!("{} {}", Colour::Red.paint("Divergence with inputs"), input_file);
+    } else if all_rejected {
+        println!("{} {}", Colour::Yellow.paint("Inputs rejected by every backend:"), input_file);
+    } else if session.backends.len() == 1 {
+        println!("{} {}", Colour::Green.paint("Constraints hold with inputs"), input_file);
+    } else {
+        println!("{} {}", Colour::Green.paint("Witnesses agree with inputs"), input_file);
+    }
+    if diverged {
+        for line in &report {
+            println!("  {}", line);
+        }
+    }
+    Result::Ok(!diverged)
+}
+
+fn selected_backends(matches: &ArgMatches) -> Vec<Backend> {
+    let mut backends = Vec::new();
+    if !matches.is_present("no_wasm") {
+        backends.push(Backend::Wasm);
+    }
+    if !matches.is_present("no_c") {
+        backends.push(Backend::C);
+    }
+    backends
+}
+
+// The circom binary built alongside this tool, or the one in the PATH
+fn default_circom() -> String {
+    if let Result::Ok(exe) = std::env::current_exe() {
+        let sibling = exe.with_file_name("circom");
+        if sibling.is_file() {
+            return sibling.to_str().unwrap().to_string();
+        }
+    }
+    "circom".to_string()
+}
+
+fn view() -> ArgMatches<'static> {
+    App::new("circom witness diff")
+        .version(VERSION)
+        .author("IDEN3")
+        .about("Compares the witnesses computed by the C and wasm witness generators of a circuit")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(
+            Arg::with_name("input")
+                .multiple(false)
+                .required(true)
+                .help("Path to a circuit with a main component"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .default_value("./witness_diff")
+                .help("Path to the directory where the compiled circuit and the witnesses are written"),
+        )
+        .arg(
+            Arg::with_name("inputs")
+                .short("i")
+                .long("inputs")
+                .takes_value(true)
+                .help("Input json file to use instead of random inputs"),
+        )
+        .arg(
+            Arg::with_name("runs")
+                .long("runs")
+                .takes_value(true)
+                .default_value("10")
+                .help("Number of random inputs to try"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0")
+                .help("Seed used to generate the random inputs"),
+        )
+        .arg(
+            Arg::with_name("prime")
+                .short("p")
+                .long("prime")
+                .takes_value(true)
+                .default_value("bn128")
+                .help("To choose the prime number to use to generate the circuit. Receives the name of the curve (bn128, bls12381, goldilocks)"),
+        )
+        .arg(
+            Arg::with_name("circom")
+                .long("circom")
+                .takes_value(true)
+                .help("Path to the circom binary used to compile the circuit"),
+        )
+        .arg(
+            Arg::with_name("no_wasm")
+                .long("no-wasm")
+                .takes_value(false)
+                .help("Does not run the wasm witness generator"),
+        )
+        .arg(
+            Arg::with_name("no_c")
+                .long("no-c")
+                .takes_value(false)
+                .help("Does not run the C witness generator"),
+        )
+        .subcommand(
+            SubCommand::with_name("signals")
+                .about("Prints the value of every signal of a witness, including the ones removed by the simplification")
+                .arg(
+                    Arg::with_name("sym")
+                        .long("sym")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Sym file of the circuit"),
+                )
+                .arg(
+                    Arg::with_name("witness")
+                        .long("wtns")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Witness computed for the circuit"),
+                )
+                .arg(
+                    Arg::with_name("substitutions")
+                        .long("substitutions")
+                        .takes_value(true)
+                        .help("Substitutions written by circom with --jsons, to recover the removed signals"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .takes_value(true)
+                        .help("Json file where the values of the signals are written"),
+                ),
+        )
+        .get_matches()
+}
@@ -0,0 +1,127 @@
+use crate::files::{self, Substitutions, SymFile};
+use ansi_term::Colour;
+use circom_algebra::modular_arithmetic;
+use circom_algebra::num_bigint::BigInt;
+use clap::ArgMatches;
+use std::collections::{HashMap, HashSet};
+
+// Value of a signal by its index before the simplification. None if the
+// signal is not in the witness and no substitution determines it
+type Values = HashMap<usize, Option<BigInt>>;
+
+// Evaluates the substitution of a removed signal after the ones of the signals
+// it depends on. The chains of substitutions can be long, so the dependencies
+// are followed with an explicit stack
+fn recover(signal: usize, substitutions: &Substitutions, prime: &BigInt, values: &mut Values) {
+    let mut stack = vec![signal];
+    let mut visiting = HashSet::new();
+    visiting.insert(signal);
+    while let Some(current) = stack.last().cloned() {
+        if values.contains_key(&current) {
+            stack.pop();
+            continue;
+        }
+        let lc = match substitutions.get(&current) {
+            Some(lc) => lc,
+            None => {
+                values.insert(current, None);
+                stack.pop();
+                continue;
+            }
+        };
+        let pending = lc.iter().map(|(dependency, _)| *dependency).find(|d| !values.contains_key(d));
+        match pending {
+            Some(dependency) if visiting.contains(&dependency) => {
+                // A cyclic substitution determines nothing
+                values.insert(current, None);
+                stack.pop();
+            }
+            Some(dependency) => {
+                visiting.insert(dependency);
+                stack.push(dependency);
+            }
+            None => {
+                let mut value = Some(BigInt::from(0));
+                for (dependency, coefficient) in lc {
+                    value = match (value, &values[dependency]) {
+                        (Some(value), Some(dependency)) => {
+                            let term = modular_arithmetic::mul(coefficient, dependency, prime);
+                            Some(modular_arithmetic::add(&value, &term, prime))
+                        }
+                        _ => None,
+                    };
+                }
+                values.insert(current, value);
+                stack.pop();
+            }
+        }
+    }
+}
+
+// Values of every signal of the sym file: the ones in the witness and the ones
+// removed by the simplification that can be recovered from the substitutions
+pub fn recover_signals(
+    witness: &[BigInt],
+    sym: &SymFile,
+    substitutions: &Substitutions,
+    prime: &BigInt,
+) -> Values {
+    let mut values = HashMap::new();
+    values.insert(0, Some(BigInt::from(1)));
+    for entry in &sym.entries {
+        if let Some(position) = entry.witness {
+            values.insert(entry.signal, witness.get(position).cloned());
+        }
+    }
+    for entry in &sym.entries {
+        recover(entry.signal, substitutions, prime, &mut values);
+    }
+    values
+}
+
+fn error(message: String) {
+    eprintln!("{}", Colour::Red.paint(message));
+}
+
+pub fn dump_signals(matches: &ArgMatches) -> Result<(), ()> {
+    let sym_file = matches.value_of("sym").unwrap();
+    let witness_file = matches.value_of("witness").unwrap();
+    let sym = files::read_sym(sym_file).map_err(|_err| error(format!("could not read {}", sym_file)))?;
+    let (prime, witness) = files::read_wtns_with_prime(witness_file)
+        .map_err(|_err| error(format!("could not read {}", witness_file)))?;
+    let substitutions = match matches.value_of("substitutions") {
+        Some(file) => files::read_substitutions(file).map_err(|_err| error(format!("could not read {}", file)))?,
+        None => HashMap::new(),
+    };
+    let values = recover_signals(&witness, &sym, &substitutions, &prime);
+
+    let mut output = json::JsonValue::new_object();
+    let (mut recovered, mut unknown) = (0, 0);
+    for entry in &sym.entries {
+        let value = &values[&entry.signal];
+        match value {
+            Some(value) if entry.witness.is_some() => println!("{} = {}", entry.name, value),
+            Some(value) => {
+                recovered += 1;
+                println!("{} = {} {}", entry.name, value, Colour::Yellow.paint("(recovered)"))
+            }
+            None => {
+                unknown += 1;
+                println!("{} = {}", entry.name, Colour::Red.paint("unknown"))
+            }
+        }
+        output[entry.name.as_str()] = match value {
+            Some(value) => value.to_string().into(),
+            None => json::JsonValue::Null,
+        };
+    }
+    println!("{} signals, {} recovered from the substitutions, {} unknown", sym.entries.len(), recovered, unknown);
+    if unknown > 0 && substitutions.is_empty() {
+        println!("The substitutions are written by circom with the option --jsons");
+    }
+    if let Some(file) = matches.value_of("json") {
+        std::fs::write(file, output.pretty(1)).map_err(|_err| error(format!("could not write {}", file)))?;
+        println!("{} {}", Colour::Green.paint("Written successfully:"), file);
+    }
+    Result::Ok(())
+}